@@ -3,20 +3,24 @@
 
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, extract_send_result, ActorError, SYSTEM_ACTOR_ADDR,
+    actor_dispatch, actor_error, extract_send_result, ActorError, FIRST_NON_SINGLETON_ADDR,
+    SYSTEM_ACTOR_ADDR,
 };
 
-use fvm_ipld_encoding::tuple::*;
+use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 
+use fvm_shared::sys::SendFlags;
 use fvm_shared::METHOD_CONSTRUCTOR;
 use num_derive::FromPrimitive;
 use num_traits::Zero;
 
-pub use self::state::{Entry, State};
+pub use self::state::{Entry, ScheduledEntry, State};
+pub use self::types::*;
 
 mod state;
 pub mod testing;
+mod types;
 
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(Actor);
@@ -29,15 +33,20 @@ fil_actors_runtime::wasm_trampoline!(Actor);
 pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
     EpochTick = 2,
+    SetGovernor = 3,
+    // Method numbers derived from FRC-0042 standards
+    AddEntryExported = frc42_dispatch::method_hash!("AddEntry"),
+    RemoveEntryExported = frc42_dispatch::method_hash!("RemoveEntry"),
 }
 
-/// Constructor parameters for Cron actor, contains entries
-/// of actors and methods to call on each epoch
-#[derive(Default, Debug, Serialize_tuple, Deserialize_tuple)]
-pub struct ConstructorParams {
-    /// Entries is a set of actors (and corresponding methods) to call during EpochTick.
-    pub entries: Vec<Entry>,
-}
+/// Maximum number of entries that can be scheduled by the governor.
+pub const MAX_SCHEDULED_ENTRIES: usize = 32;
+/// Maximum gas limit for a call to a scheduled entry.
+pub const MAX_SCHEDULED_ENTRY_GAS_LIMIT: u64 = 1_000_000_000;
+/// Maximum total gas limit of all scheduled entries, which may all be due in the same tick.
+/// This is a fifth of the block gas limit, bounding the gas the governor can direct into
+/// the implicit cron message each epoch.
+pub const MAX_SCHEDULED_ENTRIES_TOTAL_GAS_LIMIT: u64 = 2_000_000_000;
 
 /// Cron actor
 pub struct Actor;
@@ -46,7 +55,14 @@ impl Actor {
     /// Constructor for Cron actor
     fn constructor(rt: &impl Runtime, params: ConstructorParams) -> Result<(), ActorError> {
         rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
-        rt.create(&State { entries: params.entries })?;
+        let governor =
+            params.governor.map(|governor| resolve_governor(rt, &governor)).transpose()?;
+        rt.create(&State {
+            entries: params.entries,
+            governor,
+            scheduled_entries: vec![],
+            next_entry_id: 0,
+        })?;
         Ok(())
     }
     /// Executes built-in periodic actions, run at every Epoch.
@@ -72,8 +88,154 @@ impl Actor {
                 );
             }
         }
+
+        let curr_epoch = rt.curr_epoch();
+        let mut called = Vec::new();
+        for entry in st.scheduled_entries.iter().filter(|e| e.next_epoch <= curr_epoch) {
+            // Intentionally ignore any error when calling cron methods
+            let res = extract_send_result(rt.send(
+                &entry.receiver,
+                entry.method_num,
+                None,
+                TokenAmount::zero(),
+                Some(entry.gas_limit),
+                SendFlags::empty(),
+            ));
+            if let Err(e) = res {
+                log::error!(
+                    "cron failed to send scheduled entry {} to {}, send error code {}",
+                    entry.id,
+                    entry.receiver,
+                    e
+                );
+            }
+            called.push(entry.id);
+        }
+
+        if !called.is_empty() {
+            rt.transaction(|st: &mut State, _| {
+                // An entry may have been removed by a call.
+                for entry in st.scheduled_entries.iter_mut().filter(|e| called.contains(&e.id)) {
+                    entry.next_epoch = entry.next_epoch_after(curr_epoch);
+                }
+                Ok(())
+            })?;
+        }
         Ok(())
     }
+
+    /// Schedules an entry to be called every `period` epochs, starting `period` epochs
+    /// from now. Only the governor can add entries, and the receiver may not be a singleton
+    /// built-in actor, whose methods the cron actor may be privileged to call.
+    fn add_entry(rt: &impl Runtime, params: AddEntryParams) -> Result<AddEntryReturn, ActorError> {
+        let st: State = rt.state()?;
+        validate_governor(rt, &st)?;
+
+        if params.method_num == 0 {
+            return Err(actor_error!(illegal_argument, "entry method number must be positive"));
+        }
+        if params.period <= 0 {
+            return Err(actor_error!(
+                illegal_argument,
+                "entry period must be positive, was {}",
+                params.period
+            ));
+        }
+        if params.gas_limit == 0 || params.gas_limit > MAX_SCHEDULED_ENTRY_GAS_LIMIT {
+            return Err(actor_error!(
+                illegal_argument,
+                "entry gas limit {} must be positive and at most {}",
+                params.gas_limit,
+                MAX_SCHEDULED_ENTRY_GAS_LIMIT
+            ));
+        }
+        let receiver = rt.resolve_address(&params.receiver).ok_or_else(|| {
+            actor_error!(illegal_argument, "failed to resolve receiver {}", params.receiver)
+        })?;
+        if receiver < FIRST_NON_SINGLETON_ADDR {
+            return Err(actor_error!(
+                forbidden,
+                "cannot schedule calls to singleton actor {}",
+                params.receiver
+            ));
+        }
+        let receiver = Address::new_id(receiver);
+        rt.get_actor_code_cid(&receiver.id().unwrap())
+            .ok_or_else(|| actor_error!(not_found, "no code for receiver {}", params.receiver))?;
+
+        let next_epoch = rt.curr_epoch() + params.period;
+        rt.transaction(|st: &mut State, _| {
+            if st.scheduled_entries.len() >= MAX_SCHEDULED_ENTRIES {
+                return Err(actor_error!(
+                    forbidden,
+                    "cannot schedule more than {} entries",
+                    MAX_SCHEDULED_ENTRIES
+                ));
+            }
+            let total_gas_limit = st.scheduled_gas_limit() + params.gas_limit;
+            if total_gas_limit > MAX_SCHEDULED_ENTRIES_TOTAL_GAS_LIMIT {
+                return Err(actor_error!(
+                    forbidden,
+                    "total scheduled entry gas limit {} would exceed maximum {}",
+                    total_gas_limit,
+                    MAX_SCHEDULED_ENTRIES_TOTAL_GAS_LIMIT
+                ));
+            }
+
+            let id = st.next_entry_id;
+            st.next_entry_id += 1;
+            st.scheduled_entries.push(ScheduledEntry {
+                id,
+                receiver,
+                method_num: params.method_num,
+                period: params.period,
+                gas_limit: params.gas_limit,
+                next_epoch,
+            });
+            Ok(AddEntryReturn { id })
+        })
+    }
+
+    /// Sets or clears the governor, e.g. to appoint one on a network whose cron actor was
+    /// constructed without one. Only the system actor can set the governor. Entries already
+    /// scheduled are kept.
+    fn set_governor(rt: &impl Runtime, params: SetGovernorParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
+        let governor =
+            params.governor.map(|governor| resolve_governor(rt, &governor)).transpose()?;
+        rt.transaction(|st: &mut State, _| {
+            st.governor = governor;
+            Ok(())
+        })
+    }
+
+    /// Removes a scheduled entry. Only the governor can remove entries.
+    fn remove_entry(rt: &impl Runtime, params: RemoveEntryParams) -> Result<(), ActorError> {
+        rt.transaction(|st: &mut State, rt| {
+            validate_governor(rt, st)?;
+            let idx = st
+                .scheduled_entries
+                .iter()
+                .position(|e| e.id == params.id)
+                .ok_or_else(|| actor_error!(not_found, "no scheduled entry {}", params.id))?;
+            st.scheduled_entries.remove(idx);
+            Ok(())
+        })
+    }
+}
+
+fn resolve_governor(rt: &impl Runtime, governor: &Address) -> Result<Address, ActorError> {
+    rt.resolve_address(governor)
+        .map(Address::new_id)
+        .ok_or_else(|| actor_error!(illegal_argument, "failed to resolve governor {}", governor))
+}
+
+/// Checks that the caller is the governor. A cron actor without a governor, such as one
+/// constructed before governors were added, rejects all changes to its scheduled entries
+/// until the system actor sets one.
+fn validate_governor(rt: &impl Runtime, st: &State) -> Result<(), ActorError> {
+    let governor = st.governor.ok_or_else(|| actor_error!(forbidden, "cron has no governor"))?;
+    rt.validate_immediate_caller_is(std::iter::once(&governor))
 }
 
 impl ActorCode for Actor {
@@ -86,5 +248,8 @@ impl ActorCode for Actor {
     actor_dispatch! {
         Constructor => constructor,
         EpochTick => epoch_tick,
+        SetGovernor => set_governor,
+        AddEntryExported => add_entry,
+        RemoveEntryExported => remove_entry,
     }
 }
@@ -1,15 +1,45 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use fil_actors_runtime::impl_trailing_optional_tuple;
 use fvm_ipld_encoding::tuple::*;
 use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
 use fvm_shared::MethodNum;
 
 /// Cron actor state which holds entries to call during epoch tick
-#[derive(Default, Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+///
+/// The fields added in v15 are omitted from the encoding while they hold their defaults,
+/// so state without a governor or scheduled entries is encoded as before and state written
+/// by earlier versions decodes with no governor and no scheduled entries.
+#[derive(Default, Clone, Debug)]
 pub struct State {
     /// Entries is a set of actors (and corresponding methods) to call during EpochTick.
     pub entries: Vec<Entry>,
+
+    // * Added in v15
+    /// The actor allowed to add and remove scheduled entries (ID address), if any.
+    /// Set at construction, or later by the system actor with SetGovernor (the cron actor is
+    /// constructed at genesis, so an existing network has no governor until one is set).
+    pub governor: Option<Address>,
+    /// Entries added after construction, called periodically during EpochTick.
+    pub scheduled_entries: Vec<ScheduledEntry>,
+    /// The ID of the next scheduled entry to be added.
+    pub next_entry_id: u64,
+}
+
+impl_trailing_optional_tuple!(State {
+    entries;
+    governor,
+    scheduled_entries,
+    next_entry_id,
+});
+
+impl State {
+    /// Returns the sum of the gas limits of all scheduled entries.
+    pub fn scheduled_gas_limit(&self) -> u64 {
+        self.scheduled_entries.iter().map(|e| e.gas_limit).sum()
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
@@ -19,3 +49,31 @@ pub struct Entry {
     /// The method number to call (must accept empty parameters)
     pub method_num: MethodNum,
 }
+
+/// An entry added by the governor, called every `period` epochs with a limited amount of gas.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ScheduledEntry {
+    pub id: u64,
+    /// The actor to call (ID address)
+    pub receiver: Address,
+    /// The method number to call (must accept empty parameters)
+    pub method_num: MethodNum,
+    /// The number of epochs between calls.
+    pub period: ChainEpoch,
+    /// The gas limit for each call.
+    pub gas_limit: u64,
+    /// The epoch at which the entry is next due. If the cron tick for this epoch is
+    /// skipped, the entry is called at the next tick instead.
+    pub next_epoch: ChainEpoch,
+}
+
+impl ScheduledEntry {
+    /// Returns the first epoch after `epoch` at which the entry is due, keeping the
+    /// entry aligned to its period.
+    pub fn next_epoch_after(&self, epoch: ChainEpoch) -> ChainEpoch {
+        if epoch < self.next_epoch {
+            return self.next_epoch;
+        }
+        epoch + self.period - (epoch - self.next_epoch) % self.period
+    }
+}
@@ -1,10 +1,16 @@
-use fil_actors_runtime::MessageAccumulator;
+use std::collections::BTreeSet;
+
+use fil_actors_runtime::{MessageAccumulator, FIRST_NON_SINGLETON_ADDR};
 use fvm_shared::address::Protocol;
 
-use crate::State;
+use crate::{
+    State, MAX_SCHEDULED_ENTRIES, MAX_SCHEDULED_ENTRIES_TOTAL_GAS_LIMIT,
+    MAX_SCHEDULED_ENTRY_GAS_LIMIT,
+};
 
 pub struct StateSummary {
     pub entry_count: usize,
+    pub scheduled_entry_count: usize,
 }
 
 pub fn check_state_invariants(state: &State) -> (StateSummary, MessageAccumulator) {
@@ -21,5 +27,58 @@ pub fn check_state_invariants(state: &State) -> (StateSummary, MessageAccumulato
         );
     });
 
-    (StateSummary { entry_count: state.entries.len() }, acc)
+    if let Some(governor) = &state.governor {
+        acc.require(
+            governor.protocol() == Protocol::ID,
+            format!("governor address {governor} must be ID protocol"),
+        );
+    }
+
+    acc.require(
+        state.scheduled_entries.len() <= MAX_SCHEDULED_ENTRIES,
+        format!("{} scheduled entries exceeds maximum", state.scheduled_entries.len()),
+    );
+    let mut ids = BTreeSet::new();
+    state.scheduled_entries.iter().for_each(|entry| {
+        let id = entry.id;
+        acc.require(ids.insert(id), format!("scheduled entry {id} is duplicated"));
+        acc.require(
+            id < state.next_entry_id,
+            format!("scheduled entry {id} not less than next entry ID {}", state.next_entry_id),
+        );
+        acc.require(
+            entry.receiver.protocol() == Protocol::ID,
+            format!("scheduled entry {id} receiver address {} must be ID protocol", entry.receiver),
+        );
+        acc.require(
+            entry.receiver.id().map_or(true, |receiver| receiver >= FIRST_NON_SINGLETON_ADDR),
+            format!("scheduled entry {id} receiver {} is a singleton actor", entry.receiver),
+        );
+        acc.require(
+            entry.method_num > 0,
+            format!("scheduled entry {id} has invalid method number {}", entry.method_num),
+        );
+        acc.require(
+            entry.period > 0,
+            format!("scheduled entry {id} has invalid period {}", entry.period),
+        );
+        acc.require(
+            entry.gas_limit > 0 && entry.gas_limit <= MAX_SCHEDULED_ENTRY_GAS_LIMIT,
+            format!("scheduled entry {id} has invalid gas limit {}", entry.gas_limit),
+        );
+    });
+    let total_gas_limit: u128 =
+        state.scheduled_entries.iter().map(|entry| entry.gas_limit as u128).sum();
+    acc.require(
+        total_gas_limit <= MAX_SCHEDULED_ENTRIES_TOTAL_GAS_LIMIT as u128,
+        format!("total scheduled entry gas limit {total_gas_limit} exceeds maximum"),
+    );
+
+    (
+        StateSummary {
+            entry_count: state.entries.len(),
+            scheduled_entry_count: state.scheduled_entries.len(),
+        },
+        acc,
+    )
 }
@@ -0,0 +1,55 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fil_actors_runtime::impl_trailing_optional_tuple;
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::MethodNum;
+
+use crate::Entry;
+
+/// Constructor parameters for Cron actor, contains entries
+/// of actors and methods to call on each epoch
+#[derive(Default, Debug)]
+pub struct ConstructorParams {
+    /// Entries is a set of actors (and corresponding methods) to call during EpochTick.
+    pub entries: Vec<Entry>,
+    /// The actor allowed to add and remove scheduled entries, if any.
+    /// Omitted from the encoding when `None`, to remain compatible with parameters
+    /// encoded without it.
+    pub governor: Option<Address>,
+}
+
+impl_trailing_optional_tuple!(ConstructorParams { entries; governor });
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct AddEntryParams {
+    /// The actor to call
+    pub receiver: Address,
+    /// The method number to call (must accept empty parameters)
+    pub method_num: MethodNum,
+    /// The number of epochs between calls.
+    pub period: ChainEpoch,
+    /// The gas limit for each call.
+    pub gas_limit: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct AddEntryReturn {
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct RemoveEntryParams {
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct SetGovernorParams {
+    /// The new governor, or `None` to remove the governor.
+    pub governor: Option<Address>,
+}
@@ -4,13 +4,21 @@
 use std::cell::RefCell;
 
 use fil_actor_cron::testing::check_state_invariants;
-use fil_actor_cron::{Actor as CronActor, ConstructorParams, Entry, State};
+use fil_actor_cron::{
+    Actor as CronActor, AddEntryParams, AddEntryReturn, ConstructorParams, Entry, Method,
+    RemoveEntryParams, ScheduledEntry, SetGovernorParams, State, MAX_SCHEDULED_ENTRIES,
+    MAX_SCHEDULED_ENTRIES_TOTAL_GAS_LIMIT, MAX_SCHEDULED_ENTRY_GAS_LIMIT,
+};
 use fil_actors_runtime::test_utils::*;
-use fil_actors_runtime::SYSTEM_ACTOR_ADDR;
+use fil_actors_runtime::{
+    CRON_ACTOR_ADDR, REWARD_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR,
+    SYSTEM_ACTOR_ADDR,
+};
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
+use fvm_shared::sys::SendFlags;
 use num_traits::Zero;
 
 fn check_state(rt: &MockRuntime) {
@@ -31,7 +39,7 @@ fn construct_runtime() -> MockRuntime {
 fn construct_with_empty_entries() {
     let rt = construct_runtime();
 
-    construct_and_verify(&rt, &ConstructorParams { entries: vec![] });
+    construct_and_verify(&rt, &ConstructorParams { entries: vec![], governor: None });
     let state: State = rt.get_state();
 
     assert_eq!(state.entries, vec![]);
@@ -47,7 +55,8 @@ fn construct_with_entries() {
     let entry3 = Entry { receiver: Address::new_id(1003), method_num: 1003 };
     let entry4 = Entry { receiver: Address::new_id(1004), method_num: 1004 };
 
-    let params = ConstructorParams { entries: vec![entry1, entry2, entry3, entry4] };
+    let params =
+        ConstructorParams { entries: vec![entry1, entry2, entry3, entry4], governor: None };
 
     construct_and_verify(&rt, &params);
 
@@ -57,11 +66,32 @@ fn construct_with_entries() {
     check_state(&rt);
 }
 
+#[test]
+fn state_without_scheduled_entries_is_compatible() {
+    let entries = vec![Entry { receiver: Address::new_id(1001), method_num: 1001 }];
+
+    // State written before governors and scheduled entries were added.
+    let legacy = fvm_ipld_encoding::to_vec(&(&entries,)).unwrap();
+    let state: State = fvm_ipld_encoding::from_slice(&legacy).unwrap();
+    assert_eq!(entries, state.entries);
+    assert_eq!(None, state.governor);
+    assert!(state.scheduled_entries.is_empty());
+    assert_eq!(0, state.next_entry_id);
+    assert_eq!(legacy, fvm_ipld_encoding::to_vec(&state).unwrap());
+
+    // A governor, e.g. one appointed by an upgrade migration, survives a round trip.
+    let state = State { governor: Some(Address::new_id(200)), ..state };
+    let encoded = fvm_ipld_encoding::to_vec(&state).unwrap();
+    let decoded: State = fvm_ipld_encoding::from_slice(&encoded).unwrap();
+    assert_eq!(Some(Address::new_id(200)), decoded.governor);
+    assert_eq!(entries, decoded.entries);
+}
+
 #[test]
 fn epoch_tick_with_empty_entries() {
     let rt = construct_runtime();
 
-    construct_and_verify(&rt, &ConstructorParams { entries: vec![] });
+    construct_and_verify(&rt, &ConstructorParams { entries: vec![], governor: None });
     epoch_tick_and_verify(&rt);
 }
 
@@ -76,6 +106,7 @@ fn epoch_tick_with_entries() {
 
     let params = ConstructorParams {
         entries: vec![entry1.clone(), entry2.clone(), entry3.clone(), entry4.clone()],
+        governor: None,
     };
 
     construct_and_verify(&rt, &params);
@@ -117,6 +148,391 @@ fn epoch_tick_with_entries() {
     epoch_tick_and_verify(&rt);
 }
 
+mod scheduled_entries {
+    use super::*;
+
+    const GOVERNOR: u64 = 200;
+    const RECEIVER: u64 = 1001;
+
+    fn construct_with_governor() -> MockRuntime {
+        let rt = construct_runtime();
+        rt.add_id_address(Address::new_bls(&[1; 48]).unwrap(), Address::new_id(GOVERNOR));
+        rt.set_address_actor_type(Address::new_id(RECEIVER), *ACCOUNT_ACTOR_CODE_ID);
+        let params = ConstructorParams {
+            entries: vec![Entry { receiver: Address::new_id(1000), method_num: 1000 }],
+            governor: Some(Address::new_bls(&[1; 48]).unwrap()),
+        };
+        construct_and_verify(&rt, &params);
+        let st: State = rt.get_state();
+        assert_eq!(Some(Address::new_id(GOVERNOR)), st.governor);
+        rt
+    }
+
+    fn add_entry_params(period: i64) -> AddEntryParams {
+        AddEntryParams {
+            receiver: Address::new_id(RECEIVER),
+            method_num: 1 << 25,
+            period,
+            gas_limit: 10_000_000,
+        }
+    }
+
+    fn add_entry(rt: &MockRuntime, params: &AddEntryParams) -> u64 {
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(GOVERNOR));
+        rt.expect_validate_caller_addr(vec![Address::new_id(GOVERNOR)]);
+        let ret: AddEntryReturn = rt
+            .call::<CronActor>(
+                Method::AddEntryExported as u64,
+                IpldBlock::serialize_cbor(params).unwrap(),
+            )
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        check_state(rt);
+        ret.id
+    }
+
+    fn expect_builtin_send(rt: &MockRuntime) {
+        rt.expect_send_simple(
+            Address::new_id(1000),
+            1000,
+            None,
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+    }
+
+    fn expect_scheduled_send(rt: &MockRuntime, params: &AddEntryParams, exit_code: ExitCode) {
+        rt.expect_send(
+            params.receiver,
+            params.method_num,
+            None,
+            TokenAmount::zero(),
+            Some(params.gas_limit),
+            SendFlags::empty(),
+            None,
+            exit_code,
+            None,
+        );
+    }
+
+    fn tick_at(rt: &MockRuntime, epoch: i64) {
+        rt.set_epoch(epoch);
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        epoch_tick_and_verify(rt);
+    }
+
+    #[test]
+    fn entries_fire_on_schedule() {
+        let rt = construct_with_governor();
+        rt.set_epoch(10);
+        let every_3 = add_entry_params(3);
+        let every_5 = AddEntryParams { method_num: (1 << 25) + 1, ..add_entry_params(5) };
+        assert_eq!(0, add_entry(&rt, &every_3));
+        assert_eq!(1, add_entry(&rt, &every_5));
+
+        let st: State = rt.get_state();
+        assert_eq!(
+            vec![
+                ScheduledEntry {
+                    id: 0,
+                    receiver: Address::new_id(RECEIVER),
+                    method_num: 1 << 25,
+                    period: 3,
+                    gas_limit: 10_000_000,
+                    next_epoch: 13,
+                },
+                ScheduledEntry {
+                    id: 1,
+                    receiver: Address::new_id(RECEIVER),
+                    method_num: (1 << 25) + 1,
+                    period: 5,
+                    gas_limit: 10_000_000,
+                    next_epoch: 15,
+                },
+            ],
+            st.scheduled_entries
+        );
+
+        // Nothing is due yet.
+        expect_builtin_send(&rt);
+        tick_at(&rt, 12);
+
+        // A failing call doesn't stop the tick.
+        expect_builtin_send(&rt);
+        expect_scheduled_send(&rt, &every_3, ExitCode::USR_ILLEGAL_STATE);
+        tick_at(&rt, 13);
+
+        expect_builtin_send(&rt);
+        tick_at(&rt, 14);
+
+        expect_builtin_send(&rt);
+        expect_scheduled_send(&rt, &every_5, ExitCode::OK);
+        tick_at(&rt, 15);
+
+        // The tick at epoch 16 is skipped, so the entry due then is called at 17,
+        // keeping its schedule.
+        expect_builtin_send(&rt);
+        expect_scheduled_send(&rt, &every_3, ExitCode::OK);
+        tick_at(&rt, 17);
+        let st: State = rt.get_state();
+        assert_eq!(19, st.scheduled_entries[0].next_epoch);
+        assert_eq!(20, st.scheduled_entries[1].next_epoch);
+
+        // A removed entry isn't called.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(GOVERNOR));
+        rt.expect_validate_caller_addr(vec![Address::new_id(GOVERNOR)]);
+        rt.call::<CronActor>(
+            Method::RemoveEntryExported as u64,
+            IpldBlock::serialize_cbor(&RemoveEntryParams { id: 0 }).unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+
+        expect_builtin_send(&rt);
+        expect_scheduled_send(&rt, &every_5, ExitCode::OK);
+        tick_at(&rt, 20);
+        let st: State = rt.get_state();
+        assert_eq!(1, st.scheduled_entries.len());
+        assert_eq!(25, st.scheduled_entries[0].next_epoch);
+    }
+
+    #[test]
+    fn only_governor_can_change_entries() {
+        let rt = construct_with_governor();
+        let other = Address::new_id(GOVERNOR + 1);
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, other);
+        rt.expect_validate_caller_addr(vec![Address::new_id(GOVERNOR)]);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<CronActor>(
+                Method::AddEntryExported as u64,
+                IpldBlock::serialize_cbor(&add_entry_params(3)).unwrap(),
+            ),
+        );
+        rt.verify();
+
+        let id = add_entry(&rt, &add_entry_params(3));
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, other);
+        rt.expect_validate_caller_addr(vec![Address::new_id(GOVERNOR)]);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<CronActor>(
+                Method::RemoveEntryExported as u64,
+                IpldBlock::serialize_cbor(&RemoveEntryParams { id }).unwrap(),
+            ),
+        );
+        rt.verify();
+
+        // Without a governor, no one can add entries.
+        let rt = construct_runtime();
+        construct_and_verify(&rt, &ConstructorParams::default());
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, other);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<CronActor>(
+                Method::AddEntryExported as u64,
+                IpldBlock::serialize_cbor(&add_entry_params(3)).unwrap(),
+            ),
+        );
+        rt.verify();
+    }
+
+    #[test]
+    fn system_sets_governor() {
+        // A cron actor constructed without a governor, as on an existing network.
+        let rt = construct_runtime();
+        let builtin = Entry { receiver: Address::new_id(1000), method_num: 1000 };
+        construct_and_verify(
+            &rt,
+            &ConstructorParams { entries: vec![builtin.clone()], governor: None },
+        );
+        rt.add_id_address(Address::new_bls(&[1; 48]).unwrap(), Address::new_id(GOVERNOR));
+        rt.set_address_actor_type(Address::new_id(RECEIVER), *ACCOUNT_ACTOR_CODE_ID);
+        let set_governor = |governor: Option<Address>| {
+            rt.call::<CronActor>(
+                Method::SetGovernor as u64,
+                IpldBlock::serialize_cbor(&SetGovernorParams { governor }).unwrap(),
+            )
+        };
+
+        // Only the system actor can set the governor.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(GOVERNOR));
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        expect_abort(ExitCode::USR_FORBIDDEN, set_governor(Some(Address::new_id(GOVERNOR))));
+        rt.verify();
+
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            set_governor(Some(Address::new_bls(&[2; 48]).unwrap())),
+        );
+        rt.verify();
+
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        assert!(set_governor(Some(Address::new_bls(&[1; 48]).unwrap())).unwrap().is_none());
+        rt.verify();
+        let st: State = rt.get_state();
+        assert_eq!(Some(Address::new_id(GOVERNOR)), st.governor);
+        assert_eq!(vec![builtin], st.entries);
+        check_state(&rt);
+
+        // The governor can now schedule entries, which fire and survive its removal.
+        let params = add_entry_params(3);
+        let id = add_entry(&rt, &params);
+        expect_builtin_send(&rt);
+        expect_scheduled_send(&rt, &params, ExitCode::OK);
+        tick_at(&rt, 3);
+
+        rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
+        set_governor(None).unwrap();
+        rt.verify();
+        let st: State = rt.get_state();
+        assert_eq!(None, st.governor);
+        assert_eq!(vec![id], st.scheduled_entries.iter().map(|e| e.id).collect::<Vec<_>>());
+        check_state(&rt);
+    }
+
+    #[test]
+    fn invalid_entries_rejected() {
+        let rt = construct_with_governor();
+        let invalid = [
+            (
+                AddEntryParams { method_num: 0, ..add_entry_params(3) },
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+            ),
+            (add_entry_params(0), ExitCode::USR_ILLEGAL_ARGUMENT),
+            (
+                AddEntryParams { gas_limit: 0, ..add_entry_params(3) },
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+            ),
+            (
+                AddEntryParams { gas_limit: u64::MAX, ..add_entry_params(3) },
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+            ),
+            (
+                AddEntryParams { receiver: Address::new_id(999), ..add_entry_params(3) },
+                ExitCode::USR_NOT_FOUND,
+            ),
+            (
+                AddEntryParams { receiver: STORAGE_POWER_ACTOR_ADDR, ..add_entry_params(3) },
+                ExitCode::USR_FORBIDDEN,
+            ),
+            (
+                AddEntryParams { receiver: STORAGE_MARKET_ACTOR_ADDR, ..add_entry_params(3) },
+                ExitCode::USR_FORBIDDEN,
+            ),
+            (
+                AddEntryParams { receiver: CRON_ACTOR_ADDR, ..add_entry_params(3) },
+                ExitCode::USR_FORBIDDEN,
+            ),
+            (
+                AddEntryParams { receiver: REWARD_ACTOR_ADDR, ..add_entry_params(3) },
+                ExitCode::USR_FORBIDDEN,
+            ),
+        ];
+        for (params, code) in invalid {
+            rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(GOVERNOR));
+            rt.expect_validate_caller_addr(vec![Address::new_id(GOVERNOR)]);
+            expect_abort(
+                code,
+                rt.call::<CronActor>(
+                    Method::AddEntryExported as u64,
+                    IpldBlock::serialize_cbor(&params).unwrap(),
+                ),
+            );
+            rt.verify();
+        }
+
+        for _ in 0..MAX_SCHEDULED_ENTRIES {
+            add_entry(&rt, &add_entry_params(3));
+        }
+        rt.expect_validate_caller_addr(vec![Address::new_id(GOVERNOR)]);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<CronActor>(
+                Method::AddEntryExported as u64,
+                IpldBlock::serialize_cbor(&add_entry_params(3)).unwrap(),
+            ),
+        );
+        rt.verify();
+
+        rt.expect_validate_caller_addr(vec![Address::new_id(GOVERNOR)]);
+        expect_abort(
+            ExitCode::USR_NOT_FOUND,
+            rt.call::<CronActor>(
+                Method::RemoveEntryExported as u64,
+                IpldBlock::serialize_cbor(&RemoveEntryParams { id: 1000 }).unwrap(),
+            ),
+        );
+        rt.verify();
+        check_state(&rt);
+    }
+
+    #[test]
+    fn total_gas_limit_is_bounded() {
+        let rt = construct_with_governor();
+        let large =
+            AddEntryParams { gas_limit: MAX_SCHEDULED_ENTRY_GAS_LIMIT, ..add_entry_params(3) };
+        let count = MAX_SCHEDULED_ENTRIES_TOTAL_GAS_LIMIT / MAX_SCHEDULED_ENTRY_GAS_LIMIT;
+        let mut ids = vec![];
+        for _ in 0..count {
+            ids.push(add_entry(&rt, &large));
+        }
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(GOVERNOR));
+        rt.expect_validate_caller_addr(vec![Address::new_id(GOVERNOR)]);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<CronActor>(
+                Method::AddEntryExported as u64,
+                IpldBlock::serialize_cbor(&AddEntryParams { gas_limit: 1, ..add_entry_params(3) })
+                    .unwrap(),
+            ),
+        );
+        rt.verify();
+
+        // Removing an entry frees its gas for another.
+        rt.expect_validate_caller_addr(vec![Address::new_id(GOVERNOR)]);
+        rt.call::<CronActor>(
+            Method::RemoveEntryExported as u64,
+            IpldBlock::serialize_cbor(&RemoveEntryParams { id: ids[0] }).unwrap(),
+        )
+        .unwrap();
+        rt.verify();
+        add_entry(&rt, &large);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn constructor_params_without_governor_are_compatible() {
+        use fvm_ipld_encoding::tuple::*;
+
+        #[derive(Serialize_tuple)]
+        struct LegacyParams {
+            entries: Vec<Entry>,
+        }
+        let entries = vec![Entry { receiver: Address::new_id(1000), method_num: 1000 }];
+        let legacy = IpldBlock::serialize_cbor(&LegacyParams { entries: entries.clone() }).unwrap();
+        let params = IpldBlock::serialize_cbor(&ConstructorParams {
+            entries: entries.clone(),
+            governor: None,
+        })
+        .unwrap();
+        assert_eq!(legacy, params);
+
+        let decoded: ConstructorParams = legacy.unwrap().deserialize().unwrap();
+        assert_eq!(entries, decoded.entries);
+        assert_eq!(None, decoded.governor);
+    }
+}
+
 fn construct_and_verify(rt: &MockRuntime, params: &ConstructorParams) {
     rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
     rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
@@ -37,6 +37,8 @@ pub enum Method {
     Create = 2,
     Create2 = 3,
     CreateExternal = 4,
+    // Method numbers derived from FRC-0042 standards
+    Create3Exported = frc42_dispatch::method_hash!("Create3"),
 }
 
 /// Compute the a new actor address using the EVM's CREATE rules.
@@ -57,6 +59,24 @@ pub fn compute_address_create2(
     EthAddress(hash_20(rt, &[&[0xff], &from.0[..], salt, &inithash].concat()))
 }
 
+/// The initcode of the proxy contract deployed by CREATE3. When called, the proxy deploys the
+/// initcode it's called with (using CREATE). This is the proxy used by the common CREATE3
+/// libraries (e.g., solady), so CREATE3 addresses match theirs.
+pub const CREATE3_PROXY_INITCODE: [u8; 16] = hex_literal::hex!("67363d3d37363d34f03d5260086018f3");
+
+/// Compute a new actor address using the CREATE3 rules: the address of the first contract created
+/// (with CREATE) by a proxy deployed with CREATE2. Unlike CREATE2, the address only depends on the
+/// deployer and the salt, not on the initcode.
+pub fn compute_address_create3(
+    rt: &impl Runtime,
+    from: &EthAddress,
+    salt: &[u8; 32],
+) -> EthAddress {
+    let proxy = compute_address_create2(rt, from, salt, &CREATE3_PROXY_INITCODE);
+    // Contracts start with a nonce of 1 (EIP-161).
+    compute_address_create(rt, &proxy, 1)
+}
+
 pub fn compute_address_create_external(rt: &impl Runtime, from: &EthAddress) -> EthAddress {
     compute_address_create(rt, from, rt.message().nonce())
 }
@@ -76,6 +96,14 @@ pub struct Create2Params {
     pub salt: [u8; 32],
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct Create3Params {
+    #[serde(with = "strict_bytes")]
+    pub initcode: Vec<u8>,
+    #[serde(with = "strict_bytes")]
+    pub salt: [u8; 32],
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct CreateExternalParams(#[serde(with = "strict_bytes")] pub Vec<u8>);
@@ -90,6 +118,7 @@ pub struct Return {
 pub type CreateReturn = Return;
 pub type Create2Return = Return;
 pub type CreateExternalReturn = Return;
+pub type Create3Return = Return;
 
 impl Return {
     fn from_exec4(exec4: Exec4Return, eth_address: EthAddress) -> Self {
@@ -267,6 +296,22 @@ impl EamActor {
         create_actor(rt, caller_addr, eth_addr, params.initcode)
     }
 
+    /// Create a new contract per the CREATE3 rules, i.e. at an address that depends only on the
+    /// caller's address and the salt (see [`compute_address_create3`]). No proxy contract is
+    /// actually deployed.
+    ///
+    /// Permissions: May be called by the EVM or eth accounts.
+    pub fn create3(rt: &impl Runtime, params: Create3Params) -> Result<Create3Return, ActorError> {
+        rt.validate_immediate_caller_type(&[Type::EVM, Type::EthAccount])?;
+        let caller_addr = resolve_eth_address(rt, rt.message().caller().id().unwrap())?;
+
+        // Compute the CREATE3 address
+        let eth_addr = compute_address_create3(rt, &caller_addr, &params.salt);
+
+        // send to init actor
+        create_actor(rt, caller_addr, eth_addr, params.initcode)
+    }
+
     /// Create a new contract from off-chain.
     ///
     /// When called by an EthAccount, this method will compute the new actor's address according to
@@ -300,6 +345,7 @@ impl ActorCode for EamActor {
         Create => create,
         Create2 => create2,
         CreateExternal => create_external,
+        Create3Exported => create3,
     }
 }
 
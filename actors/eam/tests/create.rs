@@ -1,13 +1,16 @@
 use eam::ext::evm::RESURRECT_METHOD;
 use eam::ext::init::{Exec4Params, Exec4Return, EXEC4_METHOD};
-use eam::{compute_address_create, Create2Params, CreateParams, Return};
+use eam::{
+    compute_address_create, compute_address_create2, compute_address_create3, Create2Params,
+    Create3Params, CreateParams, Return, CREATE3_PROXY_INITCODE,
+};
 use fil_actor_eam as eam;
 use fil_actor_eam::CreateExternalParams;
 use fil_actors_evm_shared::address::EthAddress;
 use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::runtime::Primitives;
 use fil_actors_runtime::test_utils::{
-    expect_empty, MockRuntime, ETHACCOUNT_ACTOR_CODE_ID, EVM_ACTOR_CODE_ID,
+    expect_empty, MockRuntime, ACCOUNT_ACTOR_CODE_ID, ETHACCOUNT_ACTOR_CODE_ID, EVM_ACTOR_CODE_ID,
     PLACEHOLDER_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID,
 };
 use fil_actors_runtime::{INIT_ACTOR_ADDR, SYSTEM_ACTOR_ADDR};
@@ -281,3 +284,155 @@ pub fn construct_and_verify() -> MockRuntime {
 
     rt
 }
+
+fn create3_caller(rt: &MockRuntime) -> (Address, EthAddress) {
+    let id_addr = Address::new_id(110);
+    let eth_addr = EthAddress(hex_literal::hex!("CAFEB0BA00000000000000000000000000000000"));
+    let f4_eth_addr = Address::new_delegated(10, &eth_addr.0).unwrap();
+    rt.set_delegated_address(id_addr.id().unwrap(), f4_eth_addr);
+    rt.set_caller(*EVM_ACTOR_CODE_ID, id_addr);
+    (id_addr, eth_addr)
+}
+
+fn call_create3_method(rt: &MockRuntime, params: &Create3Params) -> Result<Return, ExitCode> {
+    rt.expect_validate_caller_type(vec![Type::EVM, Type::EthAccount]);
+    let result = rt
+        .call::<eam::EamActor>(
+            eam::Method::Create3Exported as u64,
+            IpldBlock::serialize_cbor(params).unwrap(),
+        )
+        .map(|ret| ret.unwrap().deserialize::<Return>().unwrap())
+        .map_err(|e| e.exit_code());
+    rt.verify();
+    result
+}
+
+#[test]
+fn create3_address() {
+    let rt = MockRuntime::default();
+    let deployer = EthAddress(hex_literal::hex!("CAFEB0BA00000000000000000000000000000000"));
+    let salt = [0x42; 32];
+
+    // The address of the first contract deployed by the CREATE2-deployed proxy.
+    let proxy = compute_address_create2(&rt, &deployer, &salt, &CREATE3_PROXY_INITCODE);
+    let expected = compute_address_create(&rt, &proxy, 1);
+    assert_eq!(expected, compute_address_create3(&rt, &deployer, &salt));
+
+    // It depends on the deployer and the salt.
+    assert_ne!(expected, compute_address_create3(&rt, &deployer, &[0x43; 32]));
+    assert_ne!(expected, compute_address_create3(&rt, &EthAddress([0xaa; 20]), &salt));
+}
+
+#[test]
+fn call_create3() {
+    let rt = construct_and_verify();
+    let (_, eth_addr) = create3_caller(&rt);
+
+    let salt = [0x42; 32];
+    let new_eth_addr = compute_address_create3(&rt, &eth_addr, &salt);
+
+    // The address doesn't depend on the initcode.
+    for (i, initcode) in [vec![0xff], vec![0x60, 0x00, 0x60, 0x00, 0xf3]].into_iter().enumerate() {
+        let evm_params = eam::ext::evm::ConstructorParams {
+            creator: eth_addr,
+            initcode: initcode.clone().into(),
+        };
+        let params = Exec4Params {
+            code_cid: *EVM_ACTOR_CODE_ID,
+            constructor_params: RawBytes::serialize(evm_params).unwrap(),
+            subaddress: new_eth_addr.0[..].to_owned().into(),
+        };
+        let new_id = 111 + i as u64;
+        rt.expect_send_simple(
+            INIT_ACTOR_ADDR,
+            EXEC4_METHOD,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+            TokenAmount::from_atto(0),
+            IpldBlock::serialize_cbor(&Exec4Return {
+                id_address: Address::new_id(new_id),
+                robust_address: Address::new_id(0),
+            })
+            .unwrap(),
+            ExitCode::OK,
+        );
+
+        let result = call_create3_method(&rt, &Create3Params { initcode, salt }).unwrap();
+        assert_eq!(
+            result,
+            Return {
+                actor_id: new_id,
+                robust_address: Some(Address::new_id(0)),
+                eth_address: new_eth_addr,
+            }
+        );
+    }
+}
+
+#[test]
+fn call_create3_collisions() {
+    let rt = construct_and_verify();
+    let (_, eth_addr) = create3_caller(&rt);
+
+    let salt = [0x42; 32];
+    let initcode = vec![0xff];
+    let target_id_addr = Address::new_id(111);
+    let target_eth_addr = compute_address_create3(&rt, &eth_addr, &salt);
+    let target_f4_eth_addr = Address::new_delegated(10, &target_eth_addr.0).unwrap();
+    rt.set_delegated_address(target_id_addr.id().unwrap(), target_f4_eth_addr);
+    let params = Create3Params { initcode: initcode.clone(), salt };
+
+    // Can't deploy over an existing EthAccount.
+    rt.set_address_actor_type(target_id_addr, *ETHACCOUNT_ACTOR_CODE_ID);
+    assert_eq!(Err(ExitCode::USR_FORBIDDEN), call_create3_method(&rt, &params));
+
+    // Can't deploy over a live contract (it refuses to be resurrected).
+    let evm_params =
+        eam::ext::evm::ConstructorParams { creator: eth_addr, initcode: initcode.clone().into() };
+    rt.set_address_actor_type(target_id_addr, *EVM_ACTOR_CODE_ID);
+    rt.expect_send_simple(
+        target_id_addr,
+        RESURRECT_METHOD,
+        IpldBlock::serialize_cbor(&evm_params).unwrap(),
+        TokenAmount::from_atto(0),
+        None,
+        ExitCode::USR_FORBIDDEN,
+    );
+    assert_eq!(Err(ExitCode::USR_FORBIDDEN), call_create3_method(&rt, &params));
+
+    // But can deploy over a placeholder (e.g., an address that has already received funds).
+    rt.set_address_actor_type(target_id_addr, *PLACEHOLDER_ACTOR_CODE_ID);
+    let exec4_params = Exec4Params {
+        code_cid: *EVM_ACTOR_CODE_ID,
+        constructor_params: RawBytes::serialize(evm_params).unwrap(),
+        subaddress: target_eth_addr.0[..].to_owned().into(),
+    };
+    rt.expect_send_simple(
+        INIT_ACTOR_ADDR,
+        EXEC4_METHOD,
+        IpldBlock::serialize_cbor(&exec4_params).unwrap(),
+        TokenAmount::from_atto(0),
+        IpldBlock::serialize_cbor(&Exec4Return {
+            id_address: target_id_addr,
+            robust_address: Address::new_id(0),
+        })
+        .unwrap(),
+        ExitCode::OK,
+    );
+    assert_eq!(target_eth_addr, call_create3_method(&rt, &params).unwrap().eth_address);
+}
+
+#[test]
+fn call_create3_caller_types() {
+    let rt = construct_and_verify();
+    create3_caller(&rt);
+
+    // Accounts (without an eth address) can't use CREATE3.
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(112));
+    rt.expect_validate_caller_type(vec![Type::EVM, Type::EthAccount]);
+    let result = rt.call::<eam::EamActor>(
+        eam::Method::Create3Exported as u64,
+        IpldBlock::serialize_cbor(&Create3Params { initcode: vec![0xff], salt: [0; 32] }).unwrap(),
+    );
+    assert_eq!(ExitCode::USR_FORBIDDEN, result.unwrap_err().exit_code());
+    rt.verify();
+}
@@ -2,15 +2,20 @@ pub mod types;
 
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_shared::address::Payload;
+use fvm_shared::crypto::hash::SupportedHashes;
+use fvm_shared::crypto::signature::{SECP_SIG_LEN, SECP_SIG_MESSAGE_HASH_SIZE};
+use fvm_shared::error::ExitCode;
 use fvm_shared::{MethodNum, METHOD_CONSTRUCTOR};
 use num_derive::FromPrimitive;
 
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, ActorError, EAM_ACTOR_ID, FIRST_EXPORTED_METHOD_NUMBER,
-    SYSTEM_ACTOR_ADDR,
+    actor_dispatch, actor_error, ActorDowncast, ActorError, EAM_ACTOR_ID,
+    FIRST_EXPORTED_METHOD_NUMBER, SYSTEM_ACTOR_ADDR,
 };
 
+use crate::types::{AuthenticateMessageParams, AuthenticateMessageReturn};
+
 #[cfg(feature = "fil-actor")]
 fil_actors_runtime::wasm_trampoline!(EthAccountActor);
 
@@ -19,6 +24,7 @@ fil_actors_runtime::wasm_trampoline!(EthAccountActor);
 #[repr(u64)]
 pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
+    AuthenticateMessageExported = frc42_dispatch::method_hash!("AuthenticateMessage"),
 }
 
 /// Ethereum Account actor.
@@ -50,6 +56,58 @@ impl EthAccountActor {
         Ok(())
     }
 
+    /// Authenticates whether the provided signature is valid for the provided message.
+    /// The signature must be a 65 byte secp256k1 signature (`r || s || v`) over the keccak256
+    /// digest of the message, made with the key of this account's Ethereum (f410) address.
+    /// Errors with USR_ILLEGAL_ARGUMENT if the authentication is invalid.
+    pub fn authenticate_message(
+        rt: &impl Runtime,
+        params: AuthenticateMessageParams,
+    ) -> Result<AuthenticateMessageReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+
+        let mut signature: [u8; SECP_SIG_LEN] =
+            params.signature.as_slice().try_into().map_err(|_| {
+                actor_error!(illegal_argument;
+                    "invalid signature length {}, expected {}", params.signature.len(), SECP_SIG_LEN)
+            })?;
+        // Accept both the raw recovery ID and the Ethereum "v" value (27/28).
+        if signature[64] >= 27 {
+            signature[64] -= 27;
+        }
+
+        let hash: [u8; SECP_SIG_MESSAGE_HASH_SIZE] =
+            rt.hash(SupportedHashes::Keccak256, &params.message).try_into().map_err(|_| {
+                actor_error!(assertion_failed; "expected a {} byte digest", SECP_SIG_MESSAGE_HASH_SIZE)
+            })?;
+        let pubkey = rt.recover_secp_public_key(&hash, &signature).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                "failed to authenticate message, signature invalid",
+            )
+        })?;
+
+        // The Ethereum address is the last 20 bytes of the keccak256 digest of the uncompressed
+        // public key (without the 0x04 prefix).
+        let signer = &rt.hash(SupportedHashes::Keccak256, &pubkey[1..])[12..];
+        let eth_address = match rt
+            .lookup_delegated_address(rt.message().receiver().id().unwrap())
+            .map(|a| *a.payload())
+        {
+            Some(Payload::Delegated(da)) if da.namespace() == EAM_ACTOR_ID => da,
+            _ => {
+                return Err(actor_error!(illegal_state;
+                    "EthAccount must have an Ethereum delegated address"));
+            }
+        };
+        if eth_address.subaddress() != signer {
+            return Err(actor_error!(illegal_argument;
+                "failed to authenticate message, signature invalid"));
+        }
+
+        Ok(AuthenticateMessageReturn { authenticated: true })
+    }
+
     // Always succeeds, accepting any transfers.
     pub fn fallback(
         rt: &impl Runtime,
@@ -74,6 +132,7 @@ impl ActorCode for EthAccountActor {
 
     actor_dispatch! {
         Constructor => constructor,
+        AuthenticateMessageExported => authenticate_message,
         _ => fallback,
     }
 }
@@ -6,14 +6,17 @@ use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
 
+use fil_actor_ethaccount::types::{AuthenticateMessageParams, AuthenticateMessageReturn};
 use fil_actor_ethaccount::{EthAccountActor, Method};
+use fvm_shared::crypto::hash::SupportedHashes;
 use fvm_shared::error::ExitCode;
 use fvm_shared::MethodNum;
 
 use fil_actors_runtime::test_utils::{
-    expect_abort_contains_message, ACCOUNT_ACTOR_CODE_ID, SYSTEM_ACTOR_CODE_ID,
+    expect_abort_contains_message, hash, MockRuntime, ACCOUNT_ACTOR_CODE_ID, MARKET_ACTOR_CODE_ID,
+    SYSTEM_ACTOR_CODE_ID,
 };
-use fil_actors_runtime::SYSTEM_ACTOR_ADDR;
+use fil_actors_runtime::{STORAGE_MARKET_ACTOR_ADDR, SYSTEM_ACTOR_ADDR};
 
 #[test]
 fn no_delegated_cant_deploy() {
@@ -46,3 +49,96 @@ fn token_receiver() {
         .unwrap();
     assert!(ret.is_none());
 }
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    hash(SupportedHashes::Keccak256, data).0[..32].try_into().unwrap()
+}
+
+fn eth_address(key: &libsecp256k1::SecretKey) -> [u8; 20] {
+    let pubkey = libsecp256k1::PublicKey::from_secret_key(key).serialize();
+    keccak256(&pubkey[1..])[12..].try_into().unwrap()
+}
+
+fn sign(key: &libsecp256k1::SecretKey, message: &[u8]) -> Vec<u8> {
+    let digest = libsecp256k1::Message::parse(&keccak256(message));
+    let (sig, recovery_id) = libsecp256k1::sign(&digest, key);
+    let mut bytes = sig.serialize().to_vec();
+    bytes.push(recovery_id.serialize());
+    bytes
+}
+
+fn call_authenticate_message(
+    rt: &MockRuntime,
+    signature: Vec<u8>,
+    message: Vec<u8>,
+) -> Result<Option<IpldBlock>, fil_actors_runtime::ActorError> {
+    rt.set_caller(*MARKET_ACTOR_CODE_ID, STORAGE_MARKET_ACTOR_ADDR);
+    rt.expect_validate_caller_any();
+    let ret = rt.call::<EthAccountActor>(
+        Method::AuthenticateMessageExported as MethodNum,
+        IpldBlock::serialize_cbor(&AuthenticateMessageParams { signature, message }).unwrap(),
+    );
+    rt.verify();
+    ret
+}
+
+#[test]
+fn authenticate_message() {
+    let key = libsecp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+    let rt = setup_with_eth_address(&eth_address(&key));
+    let message = b"a deal proposal".to_vec();
+
+    let ret = call_authenticate_message(&rt, sign(&key, &message), message.clone()).unwrap();
+    let ret: AuthenticateMessageReturn = ret.unwrap().deserialize().unwrap();
+    assert!(ret.authenticated);
+
+    // Ethereum style recovery IDs (27/28) are accepted too.
+    let mut signature = sign(&key, &message);
+    signature[64] += 27;
+    let ret = call_authenticate_message(&rt, signature, message).unwrap();
+    let ret: AuthenticateMessageReturn = ret.unwrap().deserialize().unwrap();
+    assert!(ret.authenticated);
+}
+
+#[test]
+fn authenticate_message_wrong_signer() {
+    let key = libsecp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+    let other_key = libsecp256k1::SecretKey::parse(&[0x43; 32]).unwrap();
+    let rt = setup_with_eth_address(&eth_address(&key));
+    let message = b"a deal proposal".to_vec();
+
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "signature invalid",
+        call_authenticate_message(&rt, sign(&other_key, &message), message.clone()),
+    );
+
+    // A signature over a different message recovers a different key.
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "signature invalid",
+        call_authenticate_message(&rt, sign(&key, b"another deal proposal"), message),
+    );
+}
+
+#[test]
+fn authenticate_message_bad_signature() {
+    let key = libsecp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+    let rt = setup_with_eth_address(&eth_address(&key));
+    let message = b"a deal proposal".to_vec();
+
+    let signature = sign(&key, &message);
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "invalid signature length 64",
+        call_authenticate_message(&rt, signature[..64].to_vec(), message.clone()),
+    );
+
+    let mut signature = sign(&key, &message);
+    signature[64] = 4;
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "signature invalid",
+        call_authenticate_message(&rt, signature, message),
+    );
+}
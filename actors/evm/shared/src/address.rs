@@ -5,7 +5,9 @@ use fvm_shared::address::Address;
 use fvm_shared::ActorID;
 
 /// A Filecoin address as represented in the FEVM runtime (also called EVM-form).
-#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy)]
+#[derive(
+    serde::Deserialize, serde::Serialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy,
+)]
 pub struct EthAddress(#[serde(with = "strict_bytes")] pub [u8; 20]);
 
 /// Converts a U256 to an EthAddress by taking the lower 20 bytes.
@@ -28,3 +28,31 @@ pub mod eam {
         pub eth_address: EthAddress,
     }
 }
+
+pub mod power {
+    use fvm_ipld_encoding::tuple::*;
+    use fvm_shared::bigint::bigint_ser;
+    use fvm_shared::sector::StoragePower;
+
+    pub const MINER_RAW_POWER_METHOD: u64 = frc42_dispatch::method_hash!("MinerRawPower");
+    pub const MINER_QUALITY_ADJ_POWER_METHOD: u64 =
+        frc42_dispatch::method_hash!("MinerQualityAdjPower");
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+    pub struct MinerRawPowerReturn {
+        #[serde(with = "bigint_ser")]
+        pub raw_byte_power: StoragePower,
+        pub meets_consensus_minimum: bool,
+    }
+
+    #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+    #[serde(transparent)]
+    pub struct MinerQualityAdjPowerReturn {
+        #[serde(with = "bigint_ser")]
+        pub quality_adj_power: StoragePower,
+    }
+}
+
+pub mod miner {
+    pub const GET_SECTOR_STATUS_METHOD: u64 = frc42_dispatch::method_hash!("GetSectorStatus");
+}
@@ -74,6 +74,14 @@ macro_rules! def_opcodes {
             table
         }
         $(pub const $op: u8 = $code;)*
+
+        /// Returns the mnemonic of the given opcode, or `None` if it's undefined.
+        pub fn name(op: u8) -> Option<&'static str> {
+            match op {
+                $($code => Some(stringify!($op)),)*
+                _ => None,
+            }
+        }
     }
 }
 
@@ -163,6 +171,8 @@ pub mod opcodes {
         0x59: MSIZE,
         0x5a: GAS,
         0x5b: JUMPDEST,
+        0x5c: TLOAD,
+        0x5d: TSTORE,
         0x5e: MCOPY,
         0x5F: PUSH0,
         0x60: PUSH1,
@@ -257,10 +267,18 @@ impl<'r, 'a, RT: Runtime + 'r> Machine<'r, 'a, RT> {
     }
 
     pub fn execute(mut self) -> Result<Output, ActorError> {
+        #[cfg(feature = "tracing")]
+        let _frame = super::tracer::Frame::enter();
+
         while self.pc < self.bytecode.len() {
+            #[cfg(not(feature = "tracing"))]
+            let result = self.step();
+            #[cfg(feature = "tracing")]
+            let result = super::tracer::step(&mut self);
+
             // This is faster than the question mark operator, and speed counts here.
             #[allow(clippy::question_mark)]
-            if let Err(e) = self.step() {
+            if let Err(e) = result {
                 return Err(e.wrap(format!("ABORT(pc={})", self.pc)));
             }
         }
@@ -400,6 +418,8 @@ mod tests {
             MSTORE8,
             SLOAD,
             SSTORE,
+            TLOAD,
+            TSTORE,
             LOG0,
             LOG1,
             LOG2,
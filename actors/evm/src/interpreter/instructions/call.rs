@@ -261,7 +261,7 @@ pub fn call_generic<RT: Runtime>(
                         },
                     }
                 }
-                CallKind::DelegateCall => match get_contract_type(system.rt, &dst) {
+                CallKind::DelegateCall => match get_contract_type(system, &dst) {
                     ContractType::EVM(dst_addr) => {
                         // If we're calling an actual EVM actor, get its code.
                         if let Some(code) = get_evm_bytecode_cid(system, &dst_addr)? {
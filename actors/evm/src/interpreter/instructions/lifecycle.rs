@@ -178,8 +178,13 @@ pub fn selfdestruct(
         },
     )?;
 
-    // Now mark ourselves as deleted.
-    system.mark_selfdestructed();
+    // Now mark ourselves as deleted. Since EIP-6780, contracts are only deleted if they
+    // self-destruct in the same transaction that created them.
+    if system.rt.network_version() < crate::EIP_6780_NETWORK_VERSION
+        || system.created_in_current_transaction()
+    {
+        system.mark_selfdestructed();
+    }
 
     // And "return".
     //
@@ -628,6 +633,74 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_selfdestruct_eip6780_existing_contract() {
+        // after EIP-6780, contracts created in an earlier transaction only send away their funds.
+        let beneficiary = EthAddress::from_id(1001);
+        let fil_beneficiary = FilAddress::new_id(1001);
+
+        evm_unit_test! {
+            (rt) {
+                rt.network_version = crate::EIP_6780_NETWORK_VERSION;
+                rt.set_balance(TokenAmount::from_atto(1_000_000));
+                rt.set_origin(fil_beneficiary);
+
+                rt.expect_send(
+                    fil_beneficiary,
+                    METHOD_SEND,
+                    None,
+                    TokenAmount::from_atto(1_000_000),
+                    None,
+                    SendFlags::empty(),
+                    None,
+                    ExitCode::OK,
+                    None,
+                );
+            }
+            (m) {
+                SELFDESTRUCT;
+            }
+            m.system.creation = Some(crate::Tombstone { origin: 1001, nonce: 1 });
+            m.state.stack.push(beneficiary.as_evm_word()).unwrap();
+            m.step().expect("execution step failed");
+            assert!(m.system.tombstone.is_none());
+        }
+    }
+
+    #[test]
+    fn test_selfdestruct_eip6780_same_transaction() {
+        // after EIP-6780, contracts created in the current transaction are still deleted.
+        let beneficiary = EthAddress::from_id(1001);
+        let fil_beneficiary = FilAddress::new_id(1001);
+
+        evm_unit_test! {
+            (rt) {
+                rt.network_version = crate::EIP_6780_NETWORK_VERSION;
+                rt.set_balance(TokenAmount::from_atto(1_000_000));
+                rt.set_origin(fil_beneficiary);
+
+                rt.expect_send(
+                    fil_beneficiary,
+                    METHOD_SEND,
+                    None,
+                    TokenAmount::from_atto(1_000_000),
+                    None,
+                    SendFlags::empty(),
+                    None,
+                    ExitCode::OK,
+                    None,
+                );
+            }
+            (m) {
+                SELFDESTRUCT;
+            }
+            m.system.creation = Some(crate::Tombstone { origin: 1001, nonce: 0 });
+            m.state.stack.push(beneficiary.as_evm_word()).unwrap();
+            m.step().expect("execution step failed");
+            assert_eq!(m.system.tombstone, Some(crate::Tombstone { origin: 1001, nonce: 0 }));
+        }
+    }
+
     #[test]
     fn test_selfdestruct_fail() {
         // tests the outcome of selfdestruct
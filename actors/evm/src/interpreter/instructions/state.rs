@@ -1,6 +1,5 @@
 use fil_actors_evm_shared::{address::EthAddress, uints::U256};
 use fil_actors_runtime::ActorError;
-use fvm_shared::address::Address;
 
 use {
     crate::interpreter::{ExecutionState, System},
@@ -10,15 +9,13 @@ use {
 #[inline]
 pub fn balance(
     _state: &mut ExecutionState,
-    system: &System<impl Runtime>,
+    system: &mut System<impl Runtime>,
     actor: U256,
 ) -> Result<U256, ActorError> {
     let addr: EthAddress = actor.into();
-    let addr: Address = addr.into();
 
     let balance = system
-        .rt
-        .resolve_address(&addr)
+        .resolve_actor_id(&addr)
         .and_then(|id| system.rt.actor_balance(id).as_ref().map(U256::from))
         .unwrap_or_default();
 
@@ -41,6 +38,8 @@ mod test {
     use fvm_shared::address::Address;
 
     use crate::evm_unit_test;
+    use crate::interpreter::system::WARM_ACCOUNT_ACCESS_GAS;
+    use crate::EIP_2929_NETWORK_VERSION;
 
     #[test]
     fn balance_basic() {
@@ -120,6 +119,54 @@ mod test {
         };
     }
 
+    #[test]
+    fn balance_warm() {
+        let addr = EthAddress([0xab; 20]);
+        evm_unit_test! {
+            (rt) {
+                rt.add_id_address(addr.into(), Address::new_id(1111));
+                rt.actor_balances.insert(1111, TokenAmount::from_atto(1234));
+                rt.network_version = EIP_2929_NETWORK_VERSION;
+                // only the second, warm, lookup of the address is served from memory
+                rt.expect_gas_charge(WARM_ACCOUNT_ACCESS_GAS);
+            }
+            (m) {
+                BALANCE;
+                BALANCE;
+            }
+
+            for _ in 0..2 {
+                m.state.stack.push(addr.as_evm_word()).unwrap();
+                m.step().expect("execution step failed");
+                assert_eq!(m.state.stack.pop().unwrap(), U256::from(1234));
+            }
+            m.system.rt.verify();
+        };
+    }
+
+    #[test]
+    fn balance_warm_missing_actor() {
+        let addr = EthAddress([0xab; 20]);
+        evm_unit_test! {
+            (rt) {
+                rt.network_version = EIP_2929_NETWORK_VERSION;
+                // an address without an actor is remembered too
+                rt.expect_gas_charge(WARM_ACCOUNT_ACCESS_GAS);
+            }
+            (m) {
+                BALANCE;
+                BALANCE;
+            }
+
+            for _ in 0..2 {
+                m.state.stack.push(addr.as_evm_word()).unwrap();
+                m.step().expect("execution step failed");
+                assert_eq!(m.state.stack.pop().unwrap(), U256::ZERO);
+            }
+            m.system.rt.verify();
+        };
+    }
+
     #[test]
     fn selfbalance_basic() {
         for i in 0..256 {
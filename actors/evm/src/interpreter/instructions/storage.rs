@@ -13,7 +13,10 @@ pub fn sload(
     location: U256,
 ) -> Result<U256, ActorError> {
     // get from storage and place on stack
-    system.get_storage(location)
+    let value = system.get_storage(location)?;
+    #[cfg(feature = "tracing")]
+    crate::interpreter::tracer::record_storage(location, value);
+    Ok(value)
 }
 
 #[inline]
@@ -27,14 +30,43 @@ pub fn sstore(
         return Err(ActorError::read_only("store called while read-only".into()));
     }
 
+    #[cfg(feature = "tracing")]
+    crate::interpreter::tracer::record_storage(key, value);
     system.set_storage(key, value)
 }
 
+#[inline]
+pub fn tload(
+    _state: &mut ExecutionState,
+    system: &mut System<impl Runtime>,
+    location: U256,
+) -> Result<U256, ActorError> {
+    // get from transient storage and place on stack
+    system.get_transient_storage(location)
+}
+
+#[inline]
+pub fn tstore(
+    _state: &mut ExecutionState,
+    system: &mut System<impl Runtime>,
+    key: U256,
+    value: U256,
+) -> Result<(), ActorError> {
+    if system.readonly {
+        return Err(ActorError::read_only("transient store called while read-only".into()));
+    }
+
+    system.set_transient_storage(key, value)
+}
+
 #[cfg(test)]
 mod tests {
     use fil_actors_evm_shared::uints::U256;
+    use fil_actors_runtime::test_utils::MockRuntime;
 
     use crate::evm_unit_test;
+    use crate::interpreter::system::WARM_STORAGE_ACCESS_GAS;
+    use crate::EIP_2929_NETWORK_VERSION;
 
     #[test]
     fn test_sload() {
@@ -82,4 +114,239 @@ mod tests {
             assert_eq!(m.system.get_storage(U256::from(0)).unwrap(), U256::from(0x42));
         };
     }
+
+    #[test]
+    fn test_sload_warm() {
+        evm_unit_test! {
+            (rt) {
+                rt.network_version = EIP_2929_NETWORK_VERSION;
+                // only the second, warm, access is served from memory
+                rt.expect_gas_charge(WARM_STORAGE_ACCESS_GAS);
+            }
+            (m) {
+                SLOAD;
+                SLOAD;
+            }
+            // the first access is cold, the second warm
+            m.state.stack.push(U256::from(7)).unwrap();
+            m.step().expect("execution step failed");
+            assert_eq!(m.state.stack.pop().unwrap(), U256::from(0));
+            m.state.stack.push(U256::from(7)).unwrap();
+            m.step().expect("execution step failed");
+            assert_eq!(m.state.stack.pop().unwrap(), U256::from(0));
+            m.system.rt.verify();
+        };
+    }
+
+    #[test]
+    fn test_sstore_warm_unchanged() {
+        use fil_actors_runtime::runtime::Runtime;
+
+        evm_unit_test! {
+            (rt) {
+                rt.network_version = EIP_2929_NETWORK_VERSION;
+                rt.expect_gas_charge(WARM_STORAGE_ACCESS_GAS);
+            }
+            (m) {
+                SSTORE;
+                SSTORE;
+            }
+            // storing the current value of a warm slot doesn't touch the state
+            m.state.stack.push(U256::from(0x42)).unwrap();
+            m.state.stack.push(U256::from(1)).unwrap();
+            m.step().expect("execution step failed");
+            m.system.flush().unwrap();
+            let root = m.system.rt.get_state_root().unwrap();
+
+            m.state.stack.push(U256::from(0x42)).unwrap();
+            m.state.stack.push(U256::from(1)).unwrap();
+            m.step().expect("execution step failed");
+            m.system.flush().unwrap();
+            assert_eq!(root, m.system.rt.get_state_root().unwrap());
+            m.system.rt.verify();
+        };
+    }
+
+    #[test]
+    fn test_warm_storage() {
+        evm_unit_test! {
+            (rt) {
+                rt.network_version = EIP_2929_NETWORK_VERSION;
+                // the slot is read when pre-warmed, then served from memory
+                rt.expect_gas_charge(WARM_STORAGE_ACCESS_GAS);
+            }
+            (m) {
+                SLOAD;
+            }
+            m.system.warm_storage(&[U256::from(3), U256::from(3)]).unwrap();
+            m.state.stack.push(U256::from(3)).unwrap();
+            m.step().expect("execution step failed");
+            assert_eq!(m.state.stack.pop().unwrap(), U256::from(0));
+            m.system.rt.verify();
+        };
+    }
+
+    /// Replaces the contract's state as a re-entrant call would, incrementing the nonce and
+    /// optionally writing a storage slot.
+    fn reenter(rt: &MockRuntime, slot: Option<(U256, U256)>) {
+        use crate::interpreter::system::{StateKamt, KAMT_CONFIG};
+        use crate::state::State;
+        use cid::multihash::Code;
+        use fil_actors_runtime::runtime::Runtime;
+        use fvm_ipld_encoding::CborStore;
+
+        let mut state: State = rt.store.get_cbor(&rt.get_state_root().unwrap()).unwrap().unwrap();
+        state.nonce += 1;
+        if let Some((key, value)) = slot {
+            let mut slots =
+                StateKamt::load_with_config(&state.contract_state, &rt.store, KAMT_CONFIG.clone())
+                    .unwrap();
+            slots.set(key, value).unwrap();
+            state.contract_state = slots.flush().unwrap();
+        }
+        let root = rt.store.put_cbor(&state, Code::Blake2b256).unwrap();
+        rt.set_state_root(&root).unwrap();
+    }
+
+    #[test]
+    fn test_sload_warm_after_reentry() {
+        evm_unit_test! {
+            (rt) {
+                rt.network_version = EIP_2929_NETWORK_VERSION;
+                // only the read following the unmodifying re-entrant call is warm
+                rt.expect_gas_charge(WARM_STORAGE_ACCESS_GAS);
+            }
+            (m) {
+                SLOAD;
+            }
+            m.system.set_storage(U256::from(5), U256::from(0x42)).unwrap();
+            m.system.flush().unwrap();
+
+            // A re-entrant call that doesn't modify storage keeps the cached value.
+            reenter(m.system.rt, None);
+            m.system.reload().unwrap();
+            m.state.stack.push(U256::from(5)).unwrap();
+            m.step().expect("execution step failed");
+            assert_eq!(m.state.stack.pop().unwrap(), U256::from(0x42));
+
+            // A re-entrant call that modifies storage makes the slot cold again.
+            m.pc = 0;
+            reenter(m.system.rt, Some((U256::from(5), U256::from(0x43))));
+            m.system.reload().unwrap();
+            m.state.stack.push(U256::from(5)).unwrap();
+            m.step().expect("execution step failed");
+            assert_eq!(m.state.stack.pop().unwrap(), U256::from(0x43));
+            m.system.rt.verify();
+        };
+    }
+
+    #[test]
+    fn test_tload() {
+        // happy path
+        evm_unit_test! {
+            (m) {
+                TLOAD;
+            }
+            m.system.set_transient_storage(U256::from(0), U256::from(0x42)).unwrap();
+            m.state.stack.push(U256::from(0)).unwrap();
+            let result = m.step();
+            assert!(result.is_ok(), "execution step failed");
+            assert_eq!(m.state.stack.len(), 1);
+            assert_eq!(m.state.stack.pop().unwrap(), U256::from(0x42));
+        };
+    }
+
+    #[test]
+    fn test_tload_oob() {
+        // oob access -- it is a zero
+        evm_unit_test! {
+            (m) {
+                TLOAD;
+            }
+            m.state.stack.push(U256::from(1234)).unwrap();
+            let result = m.step();
+            assert!(result.is_ok(), "execution step failed");
+            assert_eq!(m.state.stack.len(), 1);
+            assert_eq!(m.state.stack.pop().unwrap(), U256::from(0));
+        };
+    }
+
+    #[test]
+    fn test_tstore() {
+        evm_unit_test! {
+            (m) {
+                TSTORE;
+            }
+
+            m.state.stack.push(U256::from(0x42)).unwrap();
+            m.state.stack.push(U256::from(0)).unwrap();
+            let result = m.step();
+            assert!(result.is_ok(), "execution step failed");
+            assert_eq!(m.state.stack.len(), 0);
+            assert_eq!(m.system.get_transient_storage(U256::from(0)).unwrap(), U256::from(0x42));
+            // transient storage is separate from persistent storage
+            assert_eq!(m.system.get_storage(U256::from(0)).unwrap(), U256::from(0));
+        };
+    }
+
+    #[test]
+    fn test_tstore_read_only() {
+        evm_unit_test! {
+            (m) {
+                TSTORE;
+            }
+
+            m.system.readonly = true;
+            m.state.stack.push(U256::from(0x42)).unwrap();
+            m.state.stack.push(U256::from(0)).unwrap();
+            let result = m.step();
+            assert!(result.is_err(), "transient store succeeded while read-only");
+            assert_eq!(result.unwrap_err().exit_code(), fvm_shared::error::ExitCode::USR_READ_ONLY);
+            assert_eq!(m.system.get_transient_storage(U256::from(0)).unwrap(), U256::from(0));
+        };
+    }
+
+    #[test]
+    fn test_transient_storage_lifespan() {
+        use crate::interpreter::System;
+        use fil_actors_runtime::test_utils::MockRuntime;
+        use fvm_shared::address::Address;
+
+        let rt = MockRuntime::default();
+        rt.in_call.replace(true);
+        rt.set_origin(Address::new_id(100));
+
+        {
+            let mut system = System::create(&rt).unwrap();
+            system.set_transient_storage(U256::from(1), U256::from(0x42)).unwrap();
+            system.set_storage(U256::from(1), U256::from(0x43)).unwrap();
+            system.flush().unwrap();
+        }
+
+        // A (nested) call within the same transaction observes the transient storage.
+        {
+            let mut system = System::load(&rt).unwrap();
+            assert_eq!(system.get_transient_storage(U256::from(1)).unwrap(), U256::from(0x42));
+            assert_eq!(system.get_storage(U256::from(1)).unwrap(), U256::from(0x43));
+        }
+
+        // A different transaction doesn't.
+        rt.set_origin(Address::new_id(101));
+        {
+            let mut system = System::load(&rt).unwrap();
+            assert_eq!(system.get_transient_storage(U256::from(1)).unwrap(), U256::from(0));
+            assert_eq!(system.get_storage(U256::from(1)).unwrap(), U256::from(0x43));
+
+            // Persistent storage changes don't carry the stale transient storage forward.
+            system.set_storage(U256::from(2), U256::from(0x44)).unwrap();
+            system.flush().unwrap();
+        }
+
+        rt.set_origin(Address::new_id(100));
+        {
+            let mut system = System::load(&rt).unwrap();
+            assert_eq!(system.get_transient_storage(U256::from(1)).unwrap(), U256::from(0));
+            assert_eq!(system.get_storage(U256::from(2)).unwrap(), U256::from(0x44));
+        }
+    }
 }
@@ -6,6 +6,8 @@ mod output;
 mod precompiles;
 mod stack;
 mod system;
+#[cfg(feature = "tracing")]
+pub mod tracer;
 
 #[cfg(test)]
 pub mod test_util;
@@ -17,6 +19,8 @@ pub use {
     system::System,
 };
 
+pub use system::{StateHashAlgorithm, StateKamt, KAMT_CONFIG};
+
 /// The kind of call-like instruction.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CallKind {
@@ -1,8 +1,13 @@
+use crate::ext::{miner, power};
 use crate::{EVM_MAX_RESERVED_METHOD, EVM_WORD_SIZE};
 use fil_actors_evm_shared::uints::U256;
 use fil_actors_runtime::runtime::Runtime;
+use fil_actors_runtime::{actor_error, deserialize_block, ActorError, STORAGE_POWER_ACTOR_ID};
 use fvm_ipld_encoding::ipld_block::IpldBlock;
-use fvm_shared::{address::Address, econ::TokenAmount, sys::SendFlags, METHOD_SEND};
+use fvm_shared::bigint::{BigInt, Sign};
+use fvm_shared::{address::Address, econ::TokenAmount, sys::SendFlags, MethodNum, METHOD_SEND};
+use num_traits::Zero;
+use serde::de::DeserializeOwned;
 
 use crate::interpreter::{CallKind, System};
 
@@ -238,3 +243,92 @@ pub(super) fn get_randomness<RT: Runtime>(
     let randomness = system.rt.get_beacon_randomness(randomness_epoch);
     randomness.map(|r| r.to_vec()).map_err(|_| PrecompileError::InvalidInput)
 }
+
+/// Returns the power of a storage provider, and the status of one of its sectors.
+///
+/// Parameters are encoded according to the solidity ABI, with no function selector:
+///
+/// ```text
+/// u64   miner (actor ID)
+/// u64   sector_number
+/// ```
+///
+/// Returns (also solidity ABI encoded):
+///
+/// ```text
+/// u256  raw_byte_power
+/// u256  quality_adj_power
+/// bool  meets_consensus_minimum
+/// u8    sector_status
+/// ```
+///
+/// for sector_status:
+/// - 0 if the sector doesn't exist or has been terminated
+/// - 1 if the sector is active
+/// - 2 if the sector is faulty
+/// - 3 if the sector has been committed, but not yet proven
+///
+/// Reverts if the actor isn't a miner (or has no power claim).
+pub(super) fn get_miner_power_and_sector_status<RT: Runtime>(
+    system: &mut System<RT>,
+    input: &[u8],
+    ctx: PrecompileContext,
+) -> PrecompileResult {
+    let mut input_params = ValueReader::new(input);
+    let miner: u64 = input_params.read_value()?;
+    let sector_number: u64 = input_params.read_value()?;
+
+    // Query the power actor first: only miners have power claims, so this also makes sure we
+    // don't ask some arbitrary actor about its "sectors".
+    let power_actor = Address::new_id(STORAGE_POWER_ACTOR_ID);
+    let raw_power: power::MinerRawPowerReturn =
+        query_actor(system, &power_actor, power::MINER_RAW_POWER_METHOD, miner, ctx.gas_limit)?;
+    let qa_power: power::MinerQualityAdjPowerReturn = query_actor(
+        system,
+        &power_actor,
+        power::MINER_QUALITY_ADJ_POWER_METHOD,
+        miner,
+        ctx.gas_limit,
+    )?;
+    let sector_status: u8 = query_actor(
+        system,
+        &Address::new_id(miner),
+        miner::GET_SECTOR_STATUS_METHOD,
+        sector_number,
+        ctx.gas_limit,
+    )?;
+
+    let mut output = Vec::with_capacity(4 * EVM_WORD_SIZE);
+    output.extend_from_slice(&power_to_u256(&raw_power.raw_byte_power)?.to_bytes());
+    output.extend_from_slice(&power_to_u256(&qa_power.quality_adj_power)?.to_bytes());
+    output.extend_from_slice(&U256::from(raw_power.meets_consensus_minimum as u8).to_bytes());
+    output.extend_from_slice(&U256::from(sector_status).to_bytes());
+    Ok(output)
+}
+
+fn power_to_u256(power: &BigInt) -> Result<U256, ActorError> {
+    let (sign, bytes) = power.to_bytes_be();
+    if sign == Sign::Minus || bytes.len() > EVM_WORD_SIZE {
+        return Err(actor_error!(illegal_state, "power {} out of range", power));
+    }
+    Ok(U256::from_big_endian(&bytes))
+}
+
+/// Calls a read-only actor method taking a single integer parameter, decoding the return value.
+fn query_actor<RT: Runtime, T: DeserializeOwned>(
+    system: &mut System<RT>,
+    to: &Address,
+    method: MethodNum,
+    param: u64,
+    gas_limit: u64,
+) -> Result<T, ActorError> {
+    let ret = system.send(
+        to,
+        method,
+        IpldBlock::serialize_cbor(&param)?,
+        TokenAmount::zero(),
+        Some(gas_limit),
+        SendFlags::READ_ONLY,
+    )?;
+    deserialize_block(ret)
+}
@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 
 use cid::multihash::Code;
 use fil_actors_evm_shared::{address::EthAddress, uints::U256};
@@ -14,9 +15,9 @@ use fvm_shared::crypto::hash::SupportedHashes;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::{ErrorNumber, ExitCode};
 use fvm_shared::sys::SendFlags;
-use fvm_shared::{MethodNum, Response, IPLD_RAW, METHOD_SEND};
+use fvm_shared::{ActorID, MethodNum, Response, IPLD_RAW, METHOD_SEND};
 
-use crate::state::{State, Tombstone};
+use crate::state::{State, Tombstone, TransientData, TransientDataLifespan};
 use crate::BytecodeHash;
 
 use cid::Cid;
@@ -44,7 +45,8 @@ use fvm_ipld_kamt::{AsHashedKey, Config as KamtConfig, Kamt};
 //
 // The following values have been set by looking at how the charts evolved
 // with the test contract. They might not be the best for other contracts.
-const KAMT_CONFIG: KamtConfig = KamtConfig { min_data_depth: 0, bit_width: 5, max_array_width: 1 };
+pub const KAMT_CONFIG: KamtConfig =
+    KamtConfig { min_data_depth: 0, bit_width: 5, max_array_width: 1 };
 
 pub struct StateHashAlgorithm;
 
@@ -63,6 +65,17 @@ impl AsHashedKey<U256, 32> for StateHashAlgorithm {
 /// being 256 bits long, which we store in a KAMT.
 pub type StateKamt<BS> = Kamt<BS, U256, U256, StateHashAlgorithm>;
 
+/// Gas charged, from `EIP_2929_NETWORK_VERSION`, for reading (or writing the current value to) a
+/// storage slot already accessed by the current invocation, which is served from memory. This is
+/// a small fraction of a state tree lookup, which opens at least one block. Cold accesses pay for
+/// the lookup as before.
+pub(crate) const WARM_STORAGE_ACCESS_GAS: i64 = 1000;
+
+/// Gas charged, from `EIP_2929_NETWORK_VERSION`, for resolving an address already resolved by the
+/// current invocation, which is served from memory. This is a small fraction of the address lookup
+/// syscall. Cold accesses pay for the syscall as before.
+pub(crate) const WARM_ACCOUNT_ACCESS_GAS: i64 = 1000;
+
 /// Maximum allowed EVM bytecode size.
 /// The contract code size limit is 24kB.
 const MAX_CODE_SIZE: usize = 24 << 10;
@@ -91,6 +104,20 @@ pub struct System<'r, RT: Runtime> {
     bytecode: Option<EvmBytecode>,
     /// The contract's EVM storage slots.
     slots: StateKamt<RT::Blockstore>,
+    /// The contract's EVM transient storage slots (EIP-1153).
+    transient_slots: StateKamt<RT::Blockstore>,
+    /// The lifespan of the transient storage slots. This is "none" if the contract has no
+    /// transient storage in the current transaction.
+    transient_data_lifespan: Option<TransientDataLifespan>,
+    /// Storage slots accessed by the current invocation (EIP-2929), along with their current
+    /// values. The slots are forgotten, and must be re-read from the state tree, if a re-entrant
+    /// call modifies the contract storage.
+    slot_values: BTreeMap<U256, U256>,
+    /// The root of the contract storage when the state was last loaded or saved.
+    storage_root: Option<Cid>,
+    /// Addresses resolved by the current invocation (EIP-2929), along with their actor IDs. An
+    /// address without an actor is forgotten on any send, which may create the actor.
+    warm_addresses: BTreeMap<EthAddress, Option<ActorID>>,
     /// The contracts "nonce" (incremented when creating new actors).
     pub(crate) nonce: u64,
     /// The last saved state root. None if the current state hasn't been saved yet.
@@ -103,6 +130,9 @@ pub struct System<'r, RT: Runtime> {
     /// This is "some" if the actor is currently a "zombie". I.e., it has selfdestructed, but the
     /// current message is still executing. `System` cannot load a contracts state with a
     pub(crate) tombstone: Option<Tombstone>,
+
+    /// The transaction that created this contract, if known (see [`State::creation`]).
+    pub(crate) creation: Option<Tombstone>,
 }
 
 impl<'r, RT: Runtime> System<'r, RT> {
@@ -113,13 +143,19 @@ impl<'r, RT: Runtime> System<'r, RT> {
         let store = rt.store().clone();
         Self {
             rt,
-            slots: StateKamt::new_with_config(store, KAMT_CONFIG.clone()),
+            slots: StateKamt::new_with_config(store.clone(), KAMT_CONFIG.clone()),
+            transient_slots: StateKamt::new_with_config(store, KAMT_CONFIG.clone()),
+            transient_data_lifespan: None,
+            slot_values: BTreeMap::new(),
+            storage_root: None,
+            warm_addresses: BTreeMap::new(),
             nonce: 1,
             saved_state_root: None,
             bytecode: None,
             readonly,
             randomness: None,
             tombstone: None,
+            creation: None,
         }
     }
 
@@ -178,16 +214,38 @@ impl<'r, RT: Runtime> System<'r, RT> {
 
         let read_only = rt.read_only();
 
+        // Transient storage is only visible within the transaction that wrote it.
+        let (transient_slots, transient_data_lifespan) = match state.transient_data {
+            Some(TransientData { transient_data_state, transient_data_lifespan })
+                if transient_data_lifespan == crate::current_transient_data_lifespan(rt) =>
+            {
+                let slots = StateKamt::load_with_config(
+                    &transient_data_state,
+                    store.clone(),
+                    KAMT_CONFIG.clone(),
+                )
+                .context_code(ExitCode::USR_ILLEGAL_STATE, "state not in blockstore")?;
+                (slots, Some(transient_data_lifespan))
+            }
+            _ => (StateKamt::new_with_config(store.clone(), KAMT_CONFIG.clone()), None),
+        };
+
         Ok(Self {
             rt,
             slots: StateKamt::load_with_config(&state.contract_state, store, KAMT_CONFIG.clone())
                 .context_code(ExitCode::USR_ILLEGAL_STATE, "state not in blockstore")?,
+            transient_slots,
+            transient_data_lifespan,
+            slot_values: BTreeMap::new(),
+            storage_root: Some(state.contract_state),
+            warm_addresses: BTreeMap::new(),
             nonce: state.nonce,
             saved_state_root: Some(state_root),
             bytecode: Some(EvmBytecode::new(state.bytecode, state.bytecode_hash)),
             readonly: read_only,
             randomness: None,
             tombstone: state.tombstone,
+            creation: state.creation,
         })
     }
 
@@ -198,7 +256,9 @@ impl<'r, RT: Runtime> System<'r, RT> {
 
     /// Transfers funds to the receiver. This doesn't bother saving/reloading state.
     pub fn transfer(&mut self, to: &Address, value: TokenAmount) -> Result<(), ActorError> {
-        extract_send_result(self.rt.send_simple(to, METHOD_SEND, None, value))?;
+        let result = self.rt.send_simple(to, METHOD_SEND, None, value);
+        self.forget_missing_actors();
+        extract_send_result(result)?;
         Ok(())
     }
 
@@ -245,6 +305,7 @@ impl<'r, RT: Runtime> System<'r, RT> {
     ) -> Result<Result<Response, ErrorNumber>, ActorError> {
         self.flush()?;
         let result = self.rt.send(to, method, params, value, gas_limit, send_flags);
+        self.forget_missing_actors();
 
         // Reload on success, and only on success.
         match &result {
@@ -270,6 +331,20 @@ impl<'r, RT: Runtime> System<'r, RT> {
             // set empty bytecode hashes
             None => self.set_bytecode(&[])?,
         };
+        let transient_data = match self.transient_data_lifespan {
+            Some(transient_data_lifespan) => Some(TransientData {
+                transient_data_state: self.transient_slots.flush().context_code(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    "failed to flush contract transient storage",
+                )?,
+                transient_data_lifespan,
+            }),
+            None => None,
+        };
+        let contract_state = self
+            .slots
+            .flush()
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to flush contract state")?;
         let new_root = self
             .rt
             .store()
@@ -277,12 +352,11 @@ impl<'r, RT: Runtime> System<'r, RT> {
                 &State {
                     bytecode: cid,
                     bytecode_hash: evm_hash,
-                    contract_state: self.slots.flush().context_code(
-                        ExitCode::USR_ILLEGAL_STATE,
-                        "failed to flush contract state",
-                    )?,
+                    contract_state,
+                    transient_data,
                     nonce: self.nonce,
                     tombstone: self.tombstone,
+                    creation: self.creation,
                 },
                 Code::Blake2b256,
             )
@@ -290,6 +364,7 @@ impl<'r, RT: Runtime> System<'r, RT> {
 
         self.rt.set_state_root(&new_root)?;
         self.saved_state_root = Some(new_root);
+        self.storage_root = Some(contract_state);
         Ok(())
     }
 
@@ -314,10 +389,28 @@ impl<'r, RT: Runtime> System<'r, RT> {
         self.slots
             .set_root(&state.contract_state)
             .context_code(ExitCode::USR_ILLEGAL_STATE, "state not in blockstore")?;
+        // We always flush before calling out, and the transient data written by the current
+        // transaction can't be removed by nested calls, so any transient data we find here belongs
+        // to the current transaction.
+        if let Some(TransientData { transient_data_state, transient_data_lifespan }) =
+            state.transient_data
+        {
+            self.transient_slots
+                .set_root(&transient_data_state)
+                .context_code(ExitCode::USR_ILLEGAL_STATE, "state not in blockstore")?;
+            self.transient_data_lifespan = Some(transient_data_lifespan);
+        }
+        // Our storage may have been modified by a re-entrant call, in which case the slots must be
+        // re-read.
+        if self.storage_root != Some(state.contract_state) {
+            self.slot_values.clear();
+            self.storage_root = Some(state.contract_state);
+        }
         self.nonce = state.nonce;
         self.saved_state_root = Some(root);
         self.bytecode = Some(EvmBytecode::new(state.bytecode, state.bytecode_hash));
         self.tombstone = state.tombstone;
+        self.creation = state.creation;
         Ok(())
     }
 
@@ -357,16 +450,29 @@ impl<'r, RT: Runtime> System<'r, RT> {
 
     /// Get value of a storage key.
     pub fn get_storage(&mut self, key: U256) -> Result<U256, ActorError> {
-        Ok(self
+        if self.warm_path() {
+            if let Some(value) = self.slot_values.get(&key) {
+                self.rt.charge_gas("OnEvmWarmStorageAccess", WARM_STORAGE_ACCESS_GAS);
+                return Ok(*value);
+            }
+        }
+        let value = self
             .slots
             .get(&key)
             .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to clear storage slot")?
             .cloned()
-            .unwrap_or_default())
+            .unwrap_or_default();
+        self.slot_values.insert(key, value);
+        Ok(value)
     }
 
     /// Set value of a storage key.
     pub fn set_storage(&mut self, key: U256, value: U256) -> Result<(), ActorError> {
+        if self.warm_path() && self.slot_values.get(&key) == Some(&value) {
+            self.rt.charge_gas("OnEvmWarmStorageAccess", WARM_STORAGE_ACCESS_GAS);
+            return Ok(());
+        }
+
         let changed = if value.is_zero() {
             self.slots
                 .delete(&key)
@@ -378,6 +484,7 @@ impl<'r, RT: Runtime> System<'r, RT> {
                 .map(|v| v != Some(value))
                 .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to update storage slot")?
         };
+        self.slot_values.insert(key, value);
 
         if changed {
             self.saved_state_root = None; // dirty.
@@ -385,6 +492,72 @@ impl<'r, RT: Runtime> System<'r, RT> {
         Ok(())
     }
 
+    /// Pre-warm storage slots (e.g., from an EIP-2930 access list), so that subsequent accesses
+    /// take the warm path. Each slot is read from the state tree when it is pre-warmed.
+    pub fn warm_storage(&mut self, keys: &[U256]) -> Result<(), ActorError> {
+        for key in keys {
+            if !self.slot_values.contains_key(key) {
+                self.get_storage(*key)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve an Ethereum address to an actor ID, if the actor exists.
+    pub fn resolve_actor_id(&mut self, addr: &EthAddress) -> Option<ActorID> {
+        if self.warm_path() {
+            if let Some(id) = self.warm_addresses.get(addr) {
+                self.rt.charge_gas("OnEvmWarmAccountAccess", WARM_ACCOUNT_ACCESS_GAS);
+                return *id;
+            }
+        }
+        let id = self.rt.resolve_address(&(*addr).into());
+        self.warm_addresses.insert(*addr, id);
+        id
+    }
+
+    /// Whether warm storage slots and addresses are served from memory, from the network version
+    /// at which warm accesses became cheaper than cold ones.
+    fn warm_path(&self) -> bool {
+        self.rt.network_version() >= crate::EIP_2929_NETWORK_VERSION
+    }
+
+    /// Forgets warm addresses without an actor, after a send which may have created it.
+    fn forget_missing_actors(&mut self) {
+        self.warm_addresses.retain(|_, id| id.is_some());
+    }
+
+    /// Get value of a transient storage key (EIP-1153).
+    pub fn get_transient_storage(&mut self, key: U256) -> Result<U256, ActorError> {
+        Ok(self
+            .transient_slots
+            .get(&key)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to get transient storage slot")?
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// Set value of a transient storage key (EIP-1153).
+    pub fn set_transient_storage(&mut self, key: U256, value: U256) -> Result<(), ActorError> {
+        let changed = if value.is_zero() {
+            self.transient_slots.delete(&key).map(|v| v.is_some()).context_code(
+                ExitCode::USR_ILLEGAL_STATE,
+                "failed to clear transient storage slot",
+            )?
+        } else {
+            self.transient_slots.set(key, value).map(|v| v != Some(value)).context_code(
+                ExitCode::USR_ILLEGAL_STATE,
+                "failed to update transient storage slot",
+            )?
+        };
+
+        if changed {
+            self.transient_data_lifespan = Some(crate::current_transient_data_lifespan(self.rt));
+            self.saved_state_root = None; // dirty.
+        };
+        Ok(())
+    }
+
     /// Resolve the address to the ethereum equivalent, if possible.
     ///
     /// - Eth f4 maps directly to an Eth address.
@@ -440,6 +613,11 @@ impl<'r, RT: Runtime> System<'r, RT> {
         }
     }
 
+    /// Returns true if this contract was created (or resurrected) in the current transaction.
+    pub fn created_in_current_transaction(&self) -> bool {
+        self.creation == Some(crate::current_tombstone(self.rt))
+    }
+
     /// Mark ourselves as "selfdestructed".
     pub fn mark_selfdestructed(&mut self) {
         self.saved_state_root = None;
@@ -0,0 +1,185 @@
+//! An execution tracer for the EVM interpreter, for debugging contracts when running the actors
+//! natively (e.g., in the test VM). This module only exists with the `tracing` feature, which
+//! must not be enabled when building the Wasm bundle.
+//!
+//! Traces are collected per-thread: call [`start`] before applying a message and [`finish`] to
+//! take the recorded steps. Nested invocations of EVM contracts (including re-entrant calls into
+//! the same contract) are recorded in the same trace, with an increasing `depth`.
+
+use std::cell::RefCell;
+
+use fil_actors_evm_shared::{address::EthAddress, uints::U256};
+use fil_actors_runtime::runtime::Runtime;
+use fil_actors_runtime::ActorError;
+
+use super::execution::Machine;
+
+thread_local! {
+    static TRACE: RefCell<Option<Trace>> = RefCell::new(None);
+}
+
+#[derive(Default)]
+struct Trace {
+    /// The number of currently executing EVM frames.
+    depth: usize,
+    steps: Vec<StructLog>,
+}
+
+/// A single step (instruction) executed by the interpreter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructLog {
+    /// The program counter before executing the instruction.
+    pub pc: usize,
+    /// The opcode of the instruction.
+    pub op: u8,
+    /// The gas available before executing the instruction.
+    pub gas: u64,
+    /// The gas charged while executing the instruction (including any nested calls).
+    pub gas_cost: u64,
+    /// The EVM call depth, starting at 1 for the outermost contract invocation.
+    pub depth: usize,
+    /// The EVM address of the contract executing the instruction.
+    pub address: EthAddress,
+    /// The stack before executing the instruction, bottom first.
+    pub stack: Vec<U256>,
+    /// The size of the memory (in bytes) before executing the instruction.
+    pub memory_size: usize,
+    /// The storage slots read (SLOAD) or written (SSTORE) by the instruction.
+    pub storage: Vec<(U256, U256)>,
+    /// The error, if the instruction failed.
+    pub error: Option<String>,
+}
+
+/// Starts tracing EVM execution on the current thread, discarding any previous trace.
+pub fn start() {
+    TRACE.with(|t| *t.borrow_mut() = Some(Trace::default()));
+}
+
+/// Stops tracing EVM execution on the current thread, returning the recorded steps.
+pub fn finish() -> Vec<StructLog> {
+    TRACE.with(|t| t.borrow_mut().take().map(|t| t.steps).unwrap_or_default())
+}
+
+/// Returns true if EVM execution is currently being traced on this thread.
+pub fn is_active() -> bool {
+    TRACE.with(|t| t.borrow().is_some())
+}
+
+/// An executing EVM frame. Tracks the call depth while alive.
+pub(crate) struct Frame(());
+
+impl Frame {
+    pub(crate) fn enter() -> Self {
+        with_trace(|t| t.depth += 1);
+        Frame(())
+    }
+}
+
+impl Drop for Frame {
+    fn drop(&mut self) {
+        with_trace(|t| t.depth = t.depth.saturating_sub(1));
+    }
+}
+
+/// Executes a single step of the machine, recording it if tracing is active.
+pub(crate) fn step<RT: Runtime>(m: &mut Machine<RT>) -> Result<(), ActorError> {
+    if !is_active() {
+        return m.step();
+    }
+
+    let gas = m.system.rt.gas_available();
+    let index = with_trace(|t| {
+        t.steps.push(StructLog {
+            pc: m.pc,
+            op: m.bytecode[m.pc],
+            gas,
+            gas_cost: 0,
+            depth: t.depth,
+            address: m.state.receiver,
+            stack: m.state.stack.as_slice().to_vec(),
+            memory_size: m.state.memory.len(),
+            storage: Vec::new(),
+            error: None,
+        });
+        t.steps.len() - 1
+    });
+
+    let result = m.step();
+
+    let gas_cost = gas.saturating_sub(m.system.rt.gas_available());
+    with_trace(|t| {
+        if let Some(log) = index.and_then(|i| t.steps.get_mut(i)) {
+            log.gas_cost = gas_cost;
+            log.error = result.as_ref().err().map(|e| e.msg().to_owned());
+        }
+    });
+    result
+}
+
+/// Records a storage slot accessed by the instruction currently being executed.
+pub(crate) fn record_storage(key: U256, value: U256) {
+    with_trace(|t| {
+        if let Some(log) = t.steps.last_mut() {
+            log.storage.push((key, value));
+        }
+    });
+}
+
+fn with_trace<T>(f: impl FnOnce(&mut Trace) -> T) -> Option<T> {
+    TRACE.with(|t| t.borrow_mut().as_mut().map(f))
+}
+
+#[cfg(test)]
+mod tests {
+    use fil_actors_evm_shared::uints::U256;
+
+    use crate::evm_unit_test;
+    use crate::interpreter::opcodes::{PUSH1, SLOAD};
+
+    #[test]
+    fn test_trace_steps() {
+        evm_unit_test! {
+            (rt) {
+                rt.expect_gas_available(1000);
+                rt.expect_gas_available(997);
+                rt.expect_gas_available(997);
+                rt.expect_gas_available(900);
+            }
+            (m) {
+                PUSH1;
+                0x42;
+                SLOAD;
+            }
+            super::start();
+            let _frame = super::Frame::enter();
+            super::step(&mut m).unwrap();
+            super::step(&mut m).unwrap();
+            let steps = super::finish();
+
+            assert_eq!(steps.len(), 2);
+            assert_eq!((steps[0].pc, steps[0].op, steps[0].depth), (0, PUSH1, 1));
+            assert_eq!((steps[0].gas, steps[0].gas_cost), (1000, 3));
+            assert!(steps[0].stack.is_empty());
+            assert!(steps[0].storage.is_empty());
+
+            assert_eq!((steps[1].pc, steps[1].op, steps[1].depth), (2, SLOAD, 1));
+            assert_eq!((steps[1].gas, steps[1].gas_cost), (997, 97));
+            assert_eq!(steps[1].stack, vec![U256::from(0x42)]);
+            assert_eq!(steps[1].storage, vec![(U256::from(0x42), U256::zero())]);
+        };
+    }
+
+    #[test]
+    fn test_trace_inactive() {
+        evm_unit_test! {
+            (m) {
+                PUSH1;
+                0x42;
+            }
+            // No gas expectations; nothing should be recorded.
+            super::step(&mut m).unwrap();
+            assert!(!super::is_active());
+            assert!(super::finish().is_empty());
+        };
+    }
+}
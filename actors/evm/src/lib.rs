@@ -5,10 +5,11 @@ use fil_actors_runtime::{
 };
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
-use fvm_ipld_encoding::{BytesSer, DAG_CBOR};
+use fvm_ipld_encoding::{BytesSer, RawBytes, DAG_CBOR};
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
+use fvm_shared::version::NetworkVersion;
 
 use crate::interpreter::Outcome;
 use crate::interpreter::{execute, Bytecode, ExecutionState, System};
@@ -23,6 +24,7 @@ pub use types::*;
 #[doc(hidden)]
 pub mod ext;
 pub mod interpreter;
+mod proof;
 pub(crate) mod reader;
 mod state;
 mod types;
@@ -41,12 +43,23 @@ pub const EVM_CONTRACT_ILLEGAL_MEMORY_ACCESS: ExitCode = ExitCode::new(38);
 pub const EVM_CONTRACT_BAD_JUMPDEST: ExitCode = ExitCode::new(39);
 pub const EVM_CONTRACT_SELFDESTRUCT_FAILED: ExitCode = ExitCode::new(40);
 
+/// The network version from which SELFDESTRUCT only deletes contracts created in the same
+/// transaction (EIP-6780).
+pub const EIP_6780_NETWORK_VERSION: NetworkVersion = NetworkVersion::new(24);
+
+/// The network version from which storage slots and addresses already accessed in an invocation
+/// are served from memory, at a lower cost than the first (cold) access (EIP-2929).
+pub const EIP_2929_NETWORK_VERSION: NetworkVersion = NetworkVersion::new(24);
+
 const EVM_MAX_RESERVED_METHOD: u64 = 1023;
 pub const NATIVE_METHOD_SIGNATURE: &str = "handle_filecoin_method(uint64,uint64,bytes)";
 pub const NATIVE_METHOD_SELECTOR: [u8; 4] = [0x86, 0x8e, 0x10, 0xc4];
 
 const EVM_WORD_SIZE: usize = 32;
 
+/// The maximum number of storage keys that may be proven in a single `GetStorageAtWithProofs` call.
+pub const MAX_STORAGE_PROOF_KEYS: usize = 256;
+
 #[test]
 fn test_method_selector() {
     // We could just _generate_ this method selector with a proc macro, but this is easier.
@@ -66,6 +79,7 @@ pub enum Method {
     GetStorageAt = 5,
     InvokeContractDelegate = 6,
     InvokeContract = frc42_dispatch::method_hash!("InvokeEVM"),
+    GetStorageAtWithProofsExported = frc42_dispatch::method_hash!("GetStorageAtWithProofs"),
 }
 
 pub struct EvmContractActor;
@@ -75,6 +89,14 @@ pub(crate) fn current_tombstone(rt: &impl Runtime) -> Tombstone {
     Tombstone { origin: rt.message().origin().id().unwrap(), nonce: rt.message().nonce() }
 }
 
+/// Returns the transient data lifespan for the currently executing message.
+pub(crate) fn current_transient_data_lifespan(rt: &impl Runtime) -> TransientDataLifespan {
+    TransientDataLifespan {
+        origin: rt.message().origin().id().unwrap(),
+        nonce: rt.message().nonce(),
+    }
+}
+
 /// Returns true if the contract is "dead". A contract is dead if:
 ///
 /// 1. It has a tombstone.
@@ -104,6 +126,9 @@ fn initialize_evm_contract(
     caller: EthAddress,
     initcode: Vec<u8>,
 ) -> Result<(), ActorError> {
+    // Remember the transaction that created us (EIP-6780).
+    system.creation = Some(current_tombstone(system.rt));
+
     // Lookup our Ethereum address.
     let receiver_fil_addr = system.rt.message().receiver();
     let receiver_eth_addr = system.resolve_ethereum_address(&receiver_fil_addr).context_code(
@@ -255,6 +280,18 @@ impl EvmContractActor {
             None => return Ok(InvokeContractReturn { output_data: Vec::new() }),
         };
 
+        if let Some(access_list) = &params.access_list {
+            let receiver =
+                system.resolve_ethereum_address(&system.rt.message().receiver()).unwrap();
+            for item in access_list {
+                if item.address == receiver {
+                    system.warm_storage(&item.storage_keys)?;
+                } else {
+                    system.resolve_actor_id(&item.address);
+                }
+            }
+        }
+
         let received_value = system.rt.message().value_received();
         let caller = system.resolve_ethereum_address(&system.rt.message().caller()).unwrap();
         let data = invoke_contract_inner(
@@ -281,7 +318,10 @@ impl EvmContractActor {
         }
         let params = args.unwrap_or(IpldBlock { codec: 0, data: vec![] });
         let input = handle_filecoin_method_input(method, params.codec, params.data.as_slice());
-        let output = Self::invoke_contract(rt, InvokeContractParams { input_data: input })?;
+        let output = Self::invoke_contract(
+            rt,
+            InvokeContractParams { input_data: input, access_list: None },
+        )?;
         handle_filecoin_method_output(&output.output_data)
     }
 
@@ -333,6 +373,45 @@ impl EvmContractActor {
 
         Ok(GetStorageAtReturn { storage: val })
     }
+
+    /// Returns the values of many storage slots, along with Merkle proofs of their values against
+    /// the contract's storage root.
+    pub fn storage_at_with_proofs(
+        rt: &impl Runtime,
+        params: GetStorageAtWithProofsParams,
+    ) -> Result<GetStorageAtWithProofsReturn, ActorError> {
+        // Any caller can read (and prove) the storage; it is public state anyways.
+        rt.validate_immediate_caller_accept_any()?;
+
+        if params.storage_keys.len() > MAX_STORAGE_PROOF_KEYS {
+            return Err(actor_error!(
+                illegal_argument,
+                "too many storage keys {} > {}",
+                params.storage_keys.len(),
+                MAX_STORAGE_PROOF_KEYS
+            ));
+        }
+
+        let state: State = rt.state()?;
+        if is_dead(rt, &state) {
+            return Err(actor_error!(not_found; "contract has self-destructed"));
+        }
+
+        // Return the state block itself, so that verifiers can link the storage root to the
+        // actor's head in the state tree.
+        let state_block = rt
+            .store()
+            .get(&rt.get_state_root()?)
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to load state")?
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "state not in blockstore")?;
+        let storage_proofs =
+            proof::storage_proofs(rt.store(), &state.contract_state, &params.storage_keys)?;
+        Ok(GetStorageAtWithProofsReturn {
+            state: RawBytes::new(state_block),
+            storage_root: state.contract_state,
+            storage_proofs,
+        })
+    }
 }
 
 /// Format "filecoin_native_method" input parameters.
@@ -423,6 +502,7 @@ impl ActorCode for EvmContractActor {
         GetBytecode => bytecode,
         GetBytecodeHash => bytecode_hash,
         GetStorageAt => storage_at,
+        GetStorageAtWithProofsExported => storage_at_with_proofs,
         InvokeContractDelegate => invoke_contract_delegate,
         Resurrect => resurrect,
         _ => handle_filecoin_method,
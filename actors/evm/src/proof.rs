@@ -0,0 +1,79 @@
+//! Merkle proofs of EVM storage slots, so that off-chain verifiers can check slot values against a
+//! contract's storage root (like Ethereum's `eth_getProof`).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use cid::Cid;
+use fil_actors_evm_shared::uints::U256;
+use fil_actors_runtime::{ActorError, AsActorError};
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::error::ExitCode;
+
+use crate::interpreter::{StateKamt, KAMT_CONFIG};
+use crate::StorageProof;
+
+/// A read-only blockstore that records the blocks loaded through it.
+struct RecordingBlockstore<'a, BS> {
+    inner: &'a BS,
+    /// Blocks already loaded from the inner blockstore, so each is only loaded once.
+    cache: RefCell<HashMap<Cid, Vec<u8>>>,
+    /// The blocks loaded since the recording was last taken, in order.
+    recorded: RefCell<Vec<RawBytes>>,
+}
+
+impl<'a, BS: Blockstore> RecordingBlockstore<'a, BS> {
+    fn new(inner: &'a BS) -> Self {
+        Self { inner, cache: Default::default(), recorded: Default::default() }
+    }
+
+    fn take_recorded(&self) -> Vec<RawBytes> {
+        self.recorded.take()
+    }
+}
+
+impl<'a, BS: Blockstore> Blockstore for RecordingBlockstore<'a, BS> {
+    fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        let cached = self.cache.borrow().get(k).cloned();
+        let block = match cached {
+            Some(block) => block,
+            None => match self.inner.get(k)? {
+                Some(block) => {
+                    self.cache.borrow_mut().insert(*k, block.clone());
+                    block
+                }
+                None => return Ok(None),
+            },
+        };
+        self.recorded.borrow_mut().push(RawBytes::new(block.clone()));
+        Ok(Some(block))
+    }
+
+    fn put_keyed(&self, _k: &Cid, _block: &[u8]) -> anyhow::Result<()> {
+        Err(anyhow::anyhow!("cannot write to a recording blockstore"))
+    }
+}
+
+/// Looks up each key in the storage KAMT with the given root, returning its value along with the
+/// KAMT nodes on the path from the root to the key. For absent keys, the path proves the absence.
+pub(crate) fn storage_proofs<BS: Blockstore>(
+    store: &BS,
+    root: &Cid,
+    keys: &[U256],
+) -> Result<Vec<StorageProof>, ActorError> {
+    let recorder = RecordingBlockstore::new(store);
+    keys.iter()
+        .map(|key| {
+            // Load the KAMT afresh for every key, so that every node on its path is recorded.
+            let slots = StateKamt::load_with_config(root, &recorder, KAMT_CONFIG.clone())
+                .context_code(ExitCode::USR_ILLEGAL_STATE, "state not in blockstore")?;
+            let value = slots
+                .get(key)
+                .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to get storage slot")?
+                .cloned()
+                .unwrap_or_default();
+            Ok(StorageProof { key: *key, value, proof: recorder.take_recorded() })
+        })
+        .collect()
+}
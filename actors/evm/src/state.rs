@@ -4,6 +4,7 @@ use fil_actors_evm_shared::uints::U256;
 use fvm_shared::ActorID;
 
 use cid::Cid;
+use fil_actors_runtime::impl_trailing_optional_tuple;
 use fvm_ipld_encoding::strict_bytes;
 use fvm_ipld_encoding::tuple::*;
 use serde::{Deserialize, Serialize};
@@ -17,6 +18,28 @@ pub struct Tombstone {
     pub nonce: u64,
 }
 
+/// The lifespan of a contract's transient storage (EIP-1153). Transient storage is scoped to the
+/// top-level transaction, identified by the message origin and nonce.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct TransientDataLifespan {
+    /// The message origin of the transaction that wrote the transient data.
+    pub origin: ActorID,
+    /// The message nonce of the transaction that wrote the transient data.
+    pub nonce: u64,
+}
+
+/// A contract's transient storage (EIP-1153), along with the transaction it belongs to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize_tuple, Deserialize_tuple)]
+pub struct TransientData {
+    /// The transient storage dictionary.
+    ///
+    /// KAMT<U256, U256>
+    pub transient_data_state: Cid,
+
+    /// The top-level transaction in which this transient data is valid.
+    pub transient_data_lifespan: TransientDataLifespan,
+}
+
 /// A Keccak256 digest of EVM bytecode.
 #[derive(Deserialize, Serialize, Clone, Copy, Eq, PartialEq)]
 #[serde(transparent)]
@@ -91,7 +114,10 @@ impl TryFrom<&[u8]> for BytecodeHash {
 
 /// Data stored by an EVM contract.
 /// This runs on the fvm-evm-runtime actor code cid.
-#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+///
+/// The fields added in v15 are omitted from the encoding while they're empty, so state written
+/// by earlier versions of this actor decodes without a migration.
+#[derive(Debug)]
 pub struct State {
     /// The EVM contract bytecode resulting from calling the
     /// initialization code by the constructor.
@@ -117,7 +143,9 @@ pub struct State {
     /// Specifically:
     ///
     /// 1. On SELFDESTRUCT, they mark themselves as "deleted" (by setting a tombstone with the
-    ///    current origin/nonce), send away all funds, and return immediately.
+    ///    current origin/nonce), send away all funds, and return immediately. Since EIP-6780, this
+    ///    only happens if the contract was created in the current transaction (see `creation`);
+    ///    otherwise SELFDESTRUCT only sends away all funds.
     /// 2. For the rest of the current transaction (as long as the tombstone's origin/nonce matches
     ///    the currently executing top-level transaction) , the contract continues to behave
     ///    normally.
@@ -127,13 +155,79 @@ pub struct State {
     ///
     /// See https://github.com/filecoin-project/ref-fvm/issues/1174 for some context.
     pub tombstone: Option<Tombstone>,
+
+    /// The EVM contract transient storage (EIP-1153), if any.
+    ///
+    /// Transient storage only lives for the duration of the top-level transaction that wrote it.
+    /// It's kept in the state so nested calls back into this contract observe it (and so it's
+    /// reverted along with the rest of the state on failure), but it's ignored (and eventually
+    /// overwritten) once its lifespan no longer matches the currently executing transaction.
+    // * Added in v15
+    pub transient_data: Option<TransientData>,
+
+    /// The origin and nonce of the top-level transaction that created (or resurrected) this
+    /// contract, in the same form as the tombstone. Omitted from the encoding when "none", and
+    /// decoded as "none" for contracts created by earlier versions of this actor. Such contracts
+    /// weren't created in the current transaction, so SELFDESTRUCT never deletes them.
+    // * Added in v15
+    pub creation: Option<Tombstone>,
 }
 
+impl_trailing_optional_tuple!(State {
+    bytecode,
+    bytecode_hash,
+    contract_state,
+    nonce,
+    tombstone;
+    transient_data,
+    creation,
+});
+
 #[cfg(test)]
 mod test {
+    use cid::Cid;
     use fvm_ipld_encoding::{from_slice, to_vec, BytesDe};
 
-    use crate::BytecodeHash;
+    use crate::{BytecodeHash, State, Tombstone, TransientData, TransientDataLifespan};
+
+    #[test]
+    fn test_state_without_transient_data_is_compatible() {
+        let tombstone = Some(Tombstone { origin: 100, nonce: 3 });
+        // State written before transient storage was added.
+        let legacy =
+            to_vec(&(Cid::default(), BytecodeHash::EMPTY, Cid::default(), 7u64, tombstone))
+                .unwrap();
+        let state: State = from_slice(&legacy).unwrap();
+        assert_eq!(7, state.nonce);
+        assert_eq!(tombstone, state.tombstone);
+        assert_eq!(None, state.transient_data);
+        assert_eq!(legacy, to_vec(&state).unwrap());
+
+        let transient_data = Some(TransientData {
+            transient_data_state: Cid::default(),
+            transient_data_lifespan: TransientDataLifespan { origin: 100, nonce: 4 },
+        });
+        let state = State { transient_data, ..state };
+        let decoded: State = from_slice(&to_vec(&state).unwrap()).unwrap();
+        assert_eq!(transient_data, decoded.transient_data);
+    }
+
+    #[test]
+    fn test_state_without_creation_is_compatible() {
+        // State written before the creating transaction was recorded.
+        let legacy =
+            to_vec(&(Cid::default(), BytecodeHash::EMPTY, Cid::default(), 0u64, ())).unwrap();
+        let state: State = from_slice(&legacy).unwrap();
+        assert_eq!(None, state.creation);
+        assert_eq!(legacy, to_vec(&state).unwrap());
+
+        // The creation is encoded after the (absent) transient data.
+        let creation = Some(Tombstone { origin: 100, nonce: 1 });
+        let state = State { creation, ..state };
+        let decoded: State = from_slice(&to_vec(&state).unwrap()).unwrap();
+        assert_eq!(None, decoded.transient_data);
+        assert_eq!(creation, decoded.creation);
+    }
 
     #[test]
     fn test_bytecode_hash_serde() {
@@ -1,10 +1,14 @@
+use std::fmt;
+
 use cid::Cid;
 use fil_actors_evm_shared::address::EthAddress;
 use fil_actors_evm_shared::uints::U256;
 use fvm_ipld_encoding::strict_bytes;
 use fvm_ipld_encoding::tuple::*;
-use fvm_ipld_encoding::RawBytes;
+use fvm_ipld_encoding::{BytesDe, BytesSer, RawBytes};
 use fvm_shared::econ::TokenAmount;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ConstructorParams {
@@ -16,11 +20,74 @@ pub struct ConstructorParams {
 
 pub type ResurrectParams = ConstructorParams;
 
-#[derive(Default, Serialize_tuple, Deserialize_tuple)]
-#[serde(transparent)]
+/// Parameters for invoking a contract.
+///
+/// For compatibility with callers that don't know about access lists, these are encoded as just
+/// the input data (a byte string) when there is no access list, and as a tuple of the input data
+/// and access list otherwise.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
 pub struct InvokeContractParams {
-    #[serde(with = "strict_bytes")]
     pub input_data: Vec<u8>,
+    /// An optional EIP-2930 access list. Storage keys listed for the invoked contract itself are
+    /// pre-warmed, as are the listed addresses.
+    pub access_list: Option<Vec<AccessListItem>>,
+}
+
+/// An entry in an EIP-2930 access list.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct AccessListItem {
+    pub address: EthAddress,
+    pub storage_keys: Vec<U256>,
+}
+
+impl Serialize for InvokeContractParams {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match &self.access_list {
+            None => BytesSer(&self.input_data).serialize(serializer),
+            Some(access_list) => (BytesSer(&self.input_data), access_list).serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for InvokeContractParams {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ParamsVisitor;
+
+        impl<'de> Visitor<'de> for ParamsVisitor {
+            type Value = InvokeContractParams;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("input bytes, or a tuple of input bytes and an access list")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Ok(InvokeContractParams { input_data: v.to_vec(), access_list: None })
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(InvokeContractParams { input_data: v, access_list: None })
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let BytesDe(input_data) =
+                    seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let access_list =
+                    seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(3, &self));
+                }
+                Ok(InvokeContractParams { input_data, access_list: Some(access_list) })
+            }
+        }
+
+        deserializer.deserialize_any(ParamsVisitor)
+    }
 }
 
 #[derive(Serialize_tuple, Deserialize_tuple)]
@@ -42,6 +109,32 @@ pub struct GetStorageAtReturn {
     pub storage: U256,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetStorageAtWithProofsParams {
+    pub storage_keys: Vec<U256>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct GetStorageAtWithProofsReturn {
+    /// The actor's state block (DAG-CBOR), whose CID is the actor's head in the state tree.
+    /// Verifiers must check it against the head, then check that its `contract_state` is
+    /// `storage_root`.
+    pub state: RawBytes,
+    /// The root of the contract's storage KAMT.
+    pub storage_root: Cid,
+    /// A proof for each of the requested storage keys, in order.
+    pub storage_proofs: Vec<StorageProof>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct StorageProof {
+    pub key: U256,
+    /// The value of the storage slot (zero if unset).
+    pub value: U256,
+    /// The KAMT nodes (DAG-CBOR blocks) on the path from the storage root to the key, root first.
+    pub proof: Vec<RawBytes>,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct DelegateCallParams {
     pub code: Cid,
@@ -0,0 +1,139 @@
+mod asm;
+mod util;
+
+use fil_actor_evm as evm;
+use fil_actor_evm::{AccessListItem, InvokeContractParams};
+use fil_actors_evm_shared::address::EthAddress;
+use fil_actors_evm_shared::uints::U256;
+use fil_actors_runtime::test_utils::MockRuntime;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::{BytesDe, BytesSer};
+
+// Gas charged for a storage slot already accessed by the invocation.
+const WARM_STORAGE_ACCESS_GAS: i64 = 1000;
+// Gas charged for an address already resolved by the invocation.
+const WARM_ACCOUNT_ACCESS_GAS: i64 = 1000;
+
+fn sload_contract() -> Vec<u8> {
+    asm::new_contract(
+        "sload",
+        "",
+        r#"
+push1 0x01
+sload
+push1 0x01
+sload
+add
+push1 0x00
+mstore
+push1 0x20
+push1 0x00
+return
+"#,
+    )
+    .unwrap()
+}
+
+fn balance_contract() -> Vec<u8> {
+    asm::new_contract(
+        "balance",
+        "",
+        r#"
+push20 0xabababababababababababababababababababab
+balance
+push1 0x00
+mstore
+push1 0x20
+push1 0x00
+return
+"#,
+    )
+    .unwrap()
+}
+
+fn invoke(rt: &MockRuntime, params: &InvokeContractParams) -> U256 {
+    rt.expect_validate_caller_any();
+    let BytesDe(result) = rt
+        .call::<evm::EvmContractActor>(
+            evm::Method::InvokeContract as u64,
+            IpldBlock::serialize_cbor(params).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    U256::from_big_endian(&result)
+}
+
+#[test]
+fn test_invoke_without_access_list() {
+    let mut rt = util::construct_and_verify(sload_contract());
+    rt.network_version = evm::EIP_2929_NETWORK_VERSION;
+    // The second load of the slot is warm.
+    rt.expect_gas_charge(WARM_STORAGE_ACCESS_GAS);
+    assert_eq!(invoke(&rt, &InvokeContractParams::default()), U256::zero());
+}
+
+#[test]
+fn test_invoke_before_eip_2929() {
+    let rt = util::construct_and_verify(sload_contract());
+    // No gas is charged by the actor for warm accesses.
+    assert_eq!(invoke(&rt, &InvokeContractParams::default()), U256::zero());
+}
+
+#[test]
+fn test_invoke_with_access_list() {
+    let mut rt = util::construct_and_verify(sload_contract());
+    rt.network_version = evm::EIP_2929_NETWORK_VERSION;
+    let params = InvokeContractParams {
+        input_data: vec![],
+        access_list: Some(vec![
+            AccessListItem {
+                address: EthAddress(util::CONTRACT_ADDRESS),
+                storage_keys: vec![U256::from(1)],
+            },
+            // Other contracts are warmed, but their storage keys are ignored.
+            AccessListItem { address: EthAddress([0xab; 20]), storage_keys: vec![U256::from(2)] },
+        ]),
+    };
+    // The slot and address are read when pre-warmed by the access list, and both loads are warm.
+    rt.expect_gas_charge(WARM_STORAGE_ACCESS_GAS);
+    rt.expect_gas_charge(WARM_STORAGE_ACCESS_GAS);
+    assert_eq!(invoke(&rt, &params), U256::zero());
+}
+
+#[test]
+fn test_invoke_with_access_list_missing_actor() {
+    let mut rt = util::construct_and_verify(balance_contract());
+    rt.network_version = evm::EIP_2929_NETWORK_VERSION;
+    let params = InvokeContractParams {
+        input_data: vec![],
+        access_list: Some(vec![AccessListItem {
+            address: EthAddress([0xab; 20]),
+            storage_keys: vec![],
+        }]),
+    };
+    // The address has no actor, but stays warm.
+    rt.expect_gas_charge(WARM_ACCOUNT_ACCESS_GAS);
+    assert_eq!(invoke(&rt, &params), U256::zero());
+}
+
+#[test]
+fn test_invoke_params_encoding() {
+    // Without an access list, the params are encoded as just the input bytes.
+    let params = InvokeContractParams { input_data: vec![1, 2, 3], access_list: None };
+    let encoded = IpldBlock::serialize_cbor(&params).unwrap().unwrap();
+    assert_eq!(encoded, IpldBlock::serialize_cbor(&BytesSer(&[1, 2, 3])).unwrap().unwrap());
+    assert_eq!(params, encoded.deserialize().unwrap());
+
+    let params = InvokeContractParams {
+        input_data: vec![1, 2, 3],
+        access_list: Some(vec![AccessListItem {
+            address: EthAddress([0xab; 20]),
+            storage_keys: vec![U256::from(1), U256::MAX],
+        }]),
+    };
+    let encoded = IpldBlock::serialize_cbor(&params).unwrap().unwrap();
+    assert_eq!(params, encoded.deserialize().unwrap());
+}
@@ -2,11 +2,16 @@ mod asm;
 
 use cid::Cid;
 use fil_actor_evm as evm;
+use fil_actor_evm::interpreter::{StateKamt, KAMT_CONFIG};
 use fil_actors_evm_shared::uints::U256;
+use fil_actors_runtime::runtime::Runtime;
 use fil_actors_runtime::test_utils::*;
 use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_blockstore::{Block, MemoryBlockstore};
 use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::DAG_CBOR;
 use fvm_shared::address::Address;
+use multihash::{Code, MultihashDigest};
 
 mod util;
 
@@ -160,6 +165,83 @@ sstore";
     rt.verify();
 }
 
+#[test]
+fn basic_get_storage_at_with_proofs() {
+    let init_code = {
+        // Initialize a few storage entries, enough to build a multi-level KAMT.
+        let mut init = String::new();
+        for i in 0..8u8 {
+            init += &format!("push1 0x{:02x}\npush1 0x{:02x}\nsstore\n", i + 1, i);
+        }
+        init += "push2 0xfffa\npush2 0x8965\nsstore";
+        asm::new_contract("get_storage_at_with_proofs", &init, "return").unwrap()
+    };
+
+    let rt = util::construct_and_verify(init_code);
+    rt.reset();
+
+    // Any caller may request proofs.
+    rt.expect_validate_caller_any();
+    rt.caller.replace(Address::new_id(1234));
+    let keys: Vec<U256> = vec![0x8965.into(), 3.into(), 7.into(), 0xaaaa.into()];
+    let ret: evm::GetStorageAtWithProofsReturn = rt
+        .call::<evm::EvmContractActor>(
+            evm::Method::GetStorageAtWithProofsExported as u64,
+            IpldBlock::serialize_cbor(&evm::GetStorageAtWithProofsParams {
+                storage_keys: keys.clone(),
+            })
+            .unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+
+    // The returned state block is the actor's head, and links to the storage root.
+    let head = Cid::new_v1(DAG_CBOR, Code::Blake2b256.digest(ret.state.bytes()));
+    assert_eq!(rt.get_state_root().unwrap(), head);
+    let state: evm::State = fvm_ipld_encoding::from_slice(ret.state.bytes()).unwrap();
+    assert_eq!(state.contract_state, ret.storage_root);
+    assert_eq!(
+        vec![U256::from(0xfffa), U256::from(4), U256::from(8), U256::zero()],
+        ret.storage_proofs.iter().map(|p| p.value).collect::<Vec<_>>()
+    );
+
+    assert!(ret.storage_proofs.iter().any(|p| p.proof.len() > 1), "expected a multi-level KAMT");
+
+    // Each proof alone must be enough to look up the key from the storage root.
+    for (key, proof) in keys.iter().zip(ret.storage_proofs) {
+        assert_eq!(*key, proof.key);
+        let store = MemoryBlockstore::new();
+        for block in &proof.proof {
+            store.put(Code::Blake2b256, &Block::new(DAG_CBOR, block.bytes())).unwrap();
+        }
+        let slots = StateKamt::load_with_config(&ret.storage_root, &store, KAMT_CONFIG).unwrap();
+        assert_eq!(proof.value, slots.get(key).unwrap().cloned().unwrap_or_default());
+    }
+}
+
+#[test]
+fn get_storage_at_with_proofs_too_many_keys() {
+    let init_code = asm::new_contract("get_storage_at_with_proofs_too_many_keys", "", "").unwrap();
+    let rt = util::construct_and_verify(init_code);
+    rt.reset();
+
+    rt.expect_validate_caller_any();
+    let keys: Vec<U256> = (0..=evm::MAX_STORAGE_PROOF_KEYS as u64).map(U256::from).collect();
+    expect_abort_contains_message(
+        fvm_shared::error::ExitCode::USR_ILLEGAL_ARGUMENT,
+        "too many storage keys",
+        rt.call::<evm::EvmContractActor>(
+            evm::Method::GetStorageAtWithProofsExported as u64,
+            IpldBlock::serialize_cbor(&evm::GetStorageAtWithProofsParams { storage_keys: keys })
+                .unwrap(),
+        ),
+    );
+    rt.verify();
+}
+
 #[test]
 fn test_push_last_byte() {
     // 60 01 # len
@@ -3,9 +3,17 @@ mod asm;
 use fil_actors_evm_shared::{address::EthAddress, uints::U256};
 use fil_actors_runtime::{
     test_utils::{new_bls_addr, MockRuntime},
-    EAM_ACTOR_ID,
+    EAM_ACTOR_ID, STORAGE_POWER_ACTOR_ADDR,
 };
-use fvm_shared::{address::Address as FILAddress, econ::TokenAmount, error::ExitCode, METHOD_SEND};
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::bigint::bigint_ser::BigIntSer;
+use fvm_shared::sector::StoragePower;
+use fvm_shared::sys::SendFlags;
+use fvm_shared::{
+    address::Address as FILAddress, econ::TokenAmount, error::ExitCode, MethodNum, METHOD_SEND,
+};
+use num_traits::Zero;
+use serde::Serialize;
 
 mod util;
 
@@ -338,3 +346,114 @@ fn test_precompile_failure() {
     assert_eq!(&[1u8], result.as_slice());
     rt.reset();
 }
+
+#[test]
+fn test_precompile_miner_power_and_sector_status() {
+    let rt = util::construct_and_verify(tester_bytecode());
+
+    let miner = FILAddress::new_id(1000);
+    let sector_number = 42u64;
+    let input = [id_to_vec(&miner), U256::from(sector_number).to_bytes().to_vec()].concat();
+
+    fn expect_query<P: Serialize, R: Serialize>(
+        rt: &MockRuntime,
+        to: FILAddress,
+        method: MethodNum,
+        params: P,
+        ret: R,
+        exit_code: ExitCode,
+    ) {
+        rt.expect_send(
+            to,
+            method,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+            TokenAmount::zero(),
+            Some(0),
+            SendFlags::READ_ONLY,
+            IpldBlock::serialize_cbor(&ret).unwrap(),
+            exit_code,
+            None,
+        );
+    }
+
+    {
+        // An active sector of a miner meeting the consensus minimum.
+        expect_query(
+            &rt,
+            STORAGE_POWER_ACTOR_ADDR,
+            frc42_dispatch::method_hash!("MinerRawPower"),
+            miner.id().unwrap(),
+            (BigIntSer(&StoragePower::from(1u64 << 40)), true),
+            ExitCode::OK,
+        );
+        expect_query(
+            &rt,
+            STORAGE_POWER_ACTOR_ADDR,
+            frc42_dispatch::method_hash!("MinerQualityAdjPower"),
+            miner.id().unwrap(),
+            BigIntSer(&StoragePower::from(10u64 << 40)),
+            ExitCode::OK,
+        );
+        expect_query(
+            &rt,
+            miner,
+            frc42_dispatch::method_hash!("GetSectorStatus"),
+            sector_number,
+            1u8,
+            ExitCode::OK,
+        );
+
+        let expected = [
+            U256::from(1u64 << 40).to_bytes(),
+            U256::from(10u64 << 40).to_bytes(),
+            U256::ONE.to_bytes(),
+            U256::ONE.to_bytes(),
+        ]
+        .concat();
+        let test = PrecompileTest {
+            precompile_address: NativePrecompile::GetMinerPowerAndSectorStatus.eth_address(),
+            output_size: 128,
+            expected_exit_code: PrecompileExit::Success,
+            gas_avaliable: 10_000_000_000,
+            call_op: util::PrecompileCallOpcode::StaticCall,
+            input: input.clone(),
+            expected_return: expected,
+        };
+        test.run_test(&rt);
+    }
+    {
+        // Not a miner: the power actor has no claim, so the precompile reverts without asking the
+        // actor about its sectors.
+        expect_query(
+            &rt,
+            STORAGE_POWER_ACTOR_ADDR,
+            frc42_dispatch::method_hash!("MinerRawPower"),
+            miner.id().unwrap(),
+            (),
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+        );
+        let test = PrecompileTest {
+            precompile_address: NativePrecompile::GetMinerPowerAndSectorStatus.eth_address(),
+            output_size: 128,
+            expected_exit_code: PrecompileExit::Reverted,
+            gas_avaliable: 10_000_000_000,
+            call_op: util::PrecompileCallOpcode::StaticCall,
+            input: input.clone(),
+            expected_return: vec![],
+        };
+        test.run_test(&rt);
+    }
+    {
+        // Truncated input.
+        let test = PrecompileTest {
+            precompile_address: NativePrecompile::GetMinerPowerAndSectorStatus.eth_address(),
+            output_size: 128,
+            expected_exit_code: PrecompileExit::Reverted,
+            gas_avaliable: 10_000_000_000,
+            call_op: util::PrecompileCallOpcode::StaticCall,
+            input: vec![0xff; 32],
+            expected_return: vec![],
+        };
+        test.run_test(&rt);
+    }
+}
@@ -1,9 +1,11 @@
 use fil_actor_evm::{
-    EvmContractActor, Method, ResurrectParams, State, Tombstone, EVM_CONTRACT_SELFDESTRUCT_FAILED,
+    EvmContractActor, Method, ResurrectParams, State, Tombstone, EIP_6780_NETWORK_VERSION,
+    EVM_CONTRACT_SELFDESTRUCT_FAILED,
 };
 use fil_actors_evm_shared::{address::EthAddress, uints::U256};
 use fil_actors_runtime::{test_utils::*, EAM_ACTOR_ADDR, INIT_ACTOR_ADDR};
 use fvm_ipld_encoding::{ipld_block::IpldBlock, BytesSer, RawBytes};
+use fvm_shared::version::NetworkVersion;
 use fvm_shared::{
     address::Address,
     econ::TokenAmount,
@@ -13,6 +15,7 @@ use fvm_shared::{
 };
 use num_traits::Zero;
 
+mod asm;
 mod util;
 
 #[test]
@@ -94,6 +97,73 @@ fn test_selfdestruct() {
     rt.verify();
 }
 
+#[test]
+fn test_selfdestruct_eip6780() {
+    let bytecode = hex::decode(include_str!("contracts/selfdestruct.hex")).unwrap();
+
+    let contract = Address::new_id(100);
+    let beneficiary = Address::new_id(1001);
+
+    let token_amount = TokenAmount::from_whole(2);
+
+    let mut rt = util::init_construct_and_verify(bytecode, |rt| {
+        rt.actor_code_cids.borrow_mut().insert(contract, *EVM_ACTOR_CODE_ID);
+        rt.set_origin(contract);
+    });
+    rt.network_version = EIP_6780_NETWORK_VERSION;
+    let state: State = rt.get_state();
+    assert_eq!(state.creation, Some(Tombstone { origin: 100, nonce: 0 }));
+
+    let returnone_params = hex::decode("901717d1").unwrap();
+    let selfdestruct_params = hex::decode("35f46994").unwrap();
+
+    // Self-destruct in a later transaction.
+    rt.set_origin(beneficiary);
+    rt.set_balance(token_amount.clone());
+    rt.expect_send_simple(beneficiary, METHOD_SEND, None, token_amount, None, ExitCode::OK);
+    assert!(util::invoke_contract(&rt, &selfdestruct_params).is_empty());
+    rt.verify();
+
+    // The funds are gone, but the contract isn't.
+    let state: State = rt.get_state();
+    assert_eq!(state.tombstone, None);
+    assert_eq!(state.creation, Some(Tombstone { origin: 100, nonce: 0 }));
+
+    // Including in subsequent transactions.
+    rt.set_origin(Address::new_id(1002));
+    assert_eq!(U256::from_big_endian(&util::invoke_contract(&rt, &returnone_params)), U256::ONE);
+    rt.verify();
+}
+
+#[test]
+fn test_selfdestruct_eip6780_same_transaction() {
+    let bytecode = hex::decode(include_str!("contracts/selfdestruct.hex")).unwrap();
+
+    let contract = Address::new_id(100);
+    let beneficiary = Address::new_id(1001);
+
+    let mut rt = util::init_construct_and_verify(bytecode, |rt| {
+        rt.actor_code_cids.borrow_mut().insert(contract, *EVM_ACTOR_CODE_ID);
+        rt.set_origin(contract);
+    });
+    rt.network_version = EIP_6780_NETWORK_VERSION;
+
+    let returnone_params = hex::decode("901717d1").unwrap();
+    let selfdestruct_params = hex::decode("35f46994").unwrap();
+
+    // Self-destruct in the transaction that created the contract.
+    rt.expect_send_simple(beneficiary, METHOD_SEND, None, TokenAmount::zero(), None, ExitCode::OK);
+    assert!(util::invoke_contract(&rt, &selfdestruct_params).is_empty());
+    let state: State = rt.get_state();
+    assert_eq!(state.tombstone, Some(Tombstone { origin: 100, nonce: 0 }));
+    rt.verify();
+
+    // The contract is gone once the transaction ends.
+    rt.set_origin(beneficiary);
+    assert!(util::invoke_contract(&rt, &returnone_params).is_empty());
+    rt.verify();
+}
+
 #[test]
 fn test_selfdestruct_missing_beneficiary() {
     let bytecode = hex::decode(include_str!("contracts/selfdestruct.hex")).unwrap();
@@ -135,3 +205,151 @@ fn test_selfdestruct_missing_beneficiary() {
     assert_eq!(state.tombstone, None);
     rt.verify();
 }
+
+// The scenarios below are ported from the Ethereum execution-spec-tests
+// (`tests/cancun/eip6780_selfdestruct/test_selfdestruct.py`), and run at network versions before
+// and after EIP-6780 was enabled.
+
+/// Sets storage slot 0 to 1 on construction. When called with no input, returns slot 0. Otherwise,
+/// self-destructs, sending its funds to the beneficiary in the first word of the input.
+fn storage_selfdestruct_contract() -> Vec<u8> {
+    let init = "push1 0x01\npush1 0x00\nsstore";
+    let body = r#"
+calldatasize
+iszero
+%push(get_value)
+jumpi
+push1 0x00
+calldataload
+selfdestruct
+get_value:
+    jumpdest
+    push1 0x00
+    sload
+    %return_stack_word()
+"#;
+    asm::new_contract("storage_selfdestruct", init, body).unwrap()
+}
+
+struct SelfdestructCase {
+    name: &'static str,
+    network_version: NetworkVersion,
+    /// Whether the contract self-destructs in the transaction that created it.
+    same_transaction: bool,
+    /// The number of times the contract self-destructs in that transaction.
+    selfdestruct_calls: usize,
+    /// Whether the contract (with its storage) is deleted once the transaction ends.
+    deleted: bool,
+}
+
+#[test]
+fn test_selfdestruct_execution_spec_cases() {
+    let pre_6780 = NetworkVersion::V23;
+    let cases = [
+        SelfdestructCase {
+            name: "create_selfdestruct_same_tx",
+            network_version: EIP_6780_NETWORK_VERSION,
+            same_transaction: true,
+            selfdestruct_calls: 1,
+            deleted: true,
+        },
+        SelfdestructCase {
+            name: "create_selfdestruct_same_tx (called twice)",
+            network_version: EIP_6780_NETWORK_VERSION,
+            same_transaction: true,
+            selfdestruct_calls: 2,
+            deleted: true,
+        },
+        SelfdestructCase {
+            name: "selfdestruct_pre_existing",
+            network_version: EIP_6780_NETWORK_VERSION,
+            same_transaction: false,
+            selfdestruct_calls: 1,
+            deleted: false,
+        },
+        SelfdestructCase {
+            name: "selfdestruct_pre_existing (called twice)",
+            network_version: EIP_6780_NETWORK_VERSION,
+            same_transaction: false,
+            selfdestruct_calls: 2,
+            deleted: false,
+        },
+        SelfdestructCase {
+            name: "create_selfdestruct_same_tx (before EIP-6780)",
+            network_version: pre_6780,
+            same_transaction: true,
+            selfdestruct_calls: 1,
+            deleted: true,
+        },
+        SelfdestructCase {
+            name: "selfdestruct_pre_existing (before EIP-6780)",
+            network_version: pre_6780,
+            same_transaction: false,
+            selfdestruct_calls: 1,
+            deleted: true,
+        },
+    ];
+
+    let creator = Address::new_id(100);
+    let beneficiary = Address::new_id(1001);
+    let mut selfdestruct_params = vec![0u8; 12];
+    selfdestruct_params.extend_from_slice(&EthAddress::from_id(1001).0);
+
+    for case in cases {
+        let mut rt = util::init_construct_and_verify(storage_selfdestruct_contract(), |rt| {
+            rt.set_origin(creator);
+        });
+        rt.network_version = case.network_version;
+        if !case.same_transaction {
+            rt.set_origin(Address::new_id(1002));
+        }
+
+        let balance = TokenAmount::from_whole(2);
+        rt.set_balance(balance.clone());
+        for i in 0..case.selfdestruct_calls {
+            // The funds are sent away on every call, so later calls send nothing.
+            let value = if i == 0 { balance.clone() } else { TokenAmount::zero() };
+            rt.expect_send_simple(beneficiary, METHOD_SEND, None, value, None, ExitCode::OK);
+            assert!(util::invoke_contract(&rt, &selfdestruct_params).is_empty(), "{}", case.name);
+            rt.verify();
+        }
+
+        // In a later transaction, the contract is either gone or still has its storage.
+        rt.set_origin(Address::new_id(1003));
+        let state: State = rt.get_state();
+        assert_eq!(state.tombstone.is_some(), case.deleted, "{}", case.name);
+        let value = util::invoke_contract(&rt, &[]);
+        if case.deleted {
+            assert!(value.is_empty(), "{}", case.name);
+        } else {
+            assert_eq!(U256::from_big_endian(&value), U256::ONE, "{}", case.name);
+        }
+        rt.verify();
+    }
+}
+
+#[test]
+fn test_self_destructing_initcode() {
+    // A contract that self-destructs in its initcode is never created, before and after EIP-6780.
+    let init = "push20 0xff000000000000000000000000000000000003e9\nselfdestruct";
+    let initcode = asm::new_contract("self_destructing_initcode", init, "").unwrap();
+    let beneficiary = Address::new_id(1001);
+
+    let rt = util::init_construct_and_verify(initcode, |rt| {
+        rt.set_origin(Address::new_id(100));
+        rt.expect_send_simple(
+            beneficiary,
+            METHOD_SEND,
+            None,
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+    });
+    let state: State = rt.get_state();
+    assert_eq!(state.tombstone, Some(Tombstone { origin: 100, nonce: 0 }));
+
+    rt.set_origin(Address::new_id(1002));
+    assert!(util::invoke_contract(&rt, &[]).is_empty());
+    rt.verify();
+}
@@ -112,7 +112,7 @@ pub enum NativePrecompile {
     ResolveAddress = 1,
     LookupDelegatedAddress = 2,
     CallActor = 3,
-    GetActorTypeDISABLED = 4,
+    GetMinerPowerAndSectorStatus = 4,
     CallActorId = 5,
     GetRandomness = 6,
 }
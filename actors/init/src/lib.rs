@@ -6,13 +6,16 @@ use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 
 use fil_actors_runtime::{
-    actor_dispatch, actor_error, extract_send_result, ActorContext, ActorError, AsActorError,
-    EAM_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
+    actor_dispatch, actor_error, deserialize_block, extract_send_result, ActorContext, ActorError,
+    AsActorError, EAM_ACTOR_ADDR, SYSTEM_ACTOR_ADDR,
 };
 use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
+use fvm_shared::sys::SendFlags;
 use fvm_shared::{ActorID, METHOD_CONSTRUCTOR};
 use num_derive::FromPrimitive;
+use num_traits::Zero;
 
 pub use self::state::State;
 pub use self::types::*;
@@ -31,8 +34,14 @@ pub enum Method {
     Constructor = METHOD_CONSTRUCTOR,
     Exec = 2,
     Exec4 = 3,
+    // Method numbers derived from FRC-0042 standards
+    ResolveAddressExported = frc42_dispatch::method_hash!("ResolveAddress"),
+    LookupAddressesExported = frc42_dispatch::method_hash!("LookupAddresses"),
 }
 
+/// Account actor method returning the key address of the account.
+const PUBKEY_ADDRESS_METHOD: u64 = 2;
+
 /// Init actor
 pub struct Actor;
 
@@ -162,6 +171,52 @@ impl Actor {
 
         Ok(Exec4Return { id_address: Address::new_id(id_address), robust_address })
     }
+
+    /// Resolves an address to the actor ID it maps to.
+    /// ID addresses are returned as-is, whether or not an actor exists at that ID.
+    pub fn resolve_address(
+        rt: &impl Runtime,
+        params: ResolveAddressParams,
+    ) -> Result<ResolveAddressReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let id = st.resolve_address(rt.store(), &params.address)?.map(|addr| addr.id().unwrap());
+        Ok(ResolveAddressReturn { id })
+    }
+
+    /// Lists the addresses of an actor other than its ID address: the key address of an
+    /// account, and the delegated address of an actor that has one.
+    /// The robust address assigned by `Exec` or `Exec4` is not indexed by ID and is not listed:
+    /// it is only available as the `robust_address` in the return value of the call that
+    /// created the actor.
+    /// Returns no addresses if there is no actor with the ID.
+    pub fn lookup_addresses(
+        rt: &impl Runtime,
+        params: LookupAddressesParams,
+    ) -> Result<LookupAddressesReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let mut addresses = Vec::new();
+        let code = match rt.get_actor_code_cid(&params.id) {
+            Some(code) => code,
+            None => return Ok(LookupAddressesReturn { addresses }),
+        };
+        if rt.resolve_builtin_actor_type(&code) == Some(Type::Account) {
+            let ret = extract_send_result(rt.send(
+                &Address::new_id(params.id),
+                PUBKEY_ADDRESS_METHOD,
+                None,
+                TokenAmount::zero(),
+                None,
+                SendFlags::READ_ONLY,
+            ))
+            .context("account failed to return its key address")?;
+            addresses.push(deserialize_block(ret)?);
+        }
+        if let Some(delegated) = rt.lookup_delegated_address(params.id) {
+            addresses.push(delegated);
+        }
+        Ok(LookupAddressesReturn { addresses })
+    }
 }
 
 impl ActorCode for Actor {
@@ -175,13 +230,15 @@ impl ActorCode for Actor {
         Constructor => constructor,
         Exec => exec,
         Exec4 => exec4,
+        ResolveAddressExported => resolve_address,
+        LookupAddressesExported => lookup_addresses,
     }
 }
 
 fn can_exec(rt: &impl Runtime, caller: &Cid, exec: &Cid) -> bool {
     rt.resolve_builtin_actor_type(exec)
         .map(|typ| match typ {
-            Type::Multisig | Type::PaymentChannel => true,
+            Type::Multisig | Type::PaymentChannel | Type::MultiKey | Type::Passkey => true,
             Type::Miner if rt.resolve_builtin_actor_type(caller) == Some(Type::Power) => true,
             _ => false,
         })
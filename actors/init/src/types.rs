@@ -5,6 +5,7 @@ use cid::Cid;
 use fvm_ipld_encoding::tuple::*;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
+use fvm_shared::ActorID;
 
 /// Init actor Constructor parameters
 #[derive(Serialize_tuple, Deserialize_tuple)]
@@ -38,3 +39,34 @@ pub struct Exec4Params {
 
 /// Init actor Exec4 Return value
 pub type Exec4Return = ExecReturn;
+
+/// Init actor ResolveAddress Params
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ResolveAddressParams {
+    pub address: Address,
+}
+
+/// Init actor ResolveAddress Return value
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct ResolveAddressReturn {
+    /// The actor ID the address maps to, if any
+    pub id: Option<ActorID>,
+}
+
+/// Init actor LookupAddresses Params
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct LookupAddressesParams {
+    pub id: ActorID,
+}
+
+/// Init actor LookupAddresses Return value
+#[derive(Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct LookupAddressesReturn {
+    /// The key address of an account, followed by the delegated address of the actor, if any.
+    /// The robust address is only returned by Exec.
+    pub addresses: Vec<Address>,
+}
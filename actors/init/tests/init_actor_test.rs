@@ -7,7 +7,8 @@ use cid::Cid;
 use fil_actor_init::testing::check_state_invariants;
 use fil_actor_init::{
     Actor as InitActor, ConstructorParams, Exec4Params, Exec4Return, ExecParams, ExecReturn,
-    Method, State,
+    LookupAddressesParams, LookupAddressesReturn, Method, ResolveAddressParams,
+    ResolveAddressReturn, State,
 };
 use fil_actors_runtime::runtime::Runtime;
 use fil_actors_runtime::{test_utils::*, EAM_ACTOR_ADDR, EAM_ACTOR_ID};
@@ -19,10 +20,13 @@ use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
+use fvm_shared::sys::SendFlags;
 use fvm_shared::{ActorID, HAMT_BIT_WIDTH, METHOD_CONSTRUCTOR};
 use num_traits::Zero;
 use serde::Serialize;
 
+const PUBKEY_ADDRESS_METHOD: u64 = 2;
+
 fn check_state(rt: &MockRuntime) {
     let (_, acc) = check_state_invariants(&rt.get_state(), rt.store());
     acc.assert_empty();
@@ -48,6 +52,11 @@ fn abort_cant_call_exec() {
 
     let err = exec_and_verify(&rt, *POWER_ACTOR_CODE_ID, &"").expect_err("Exec should have failed");
     assert_eq!(err.exit_code(), ExitCode::USR_FORBIDDEN);
+
+    // Accounts are only created by the system, for key addresses.
+    let err =
+        exec_and_verify(&rt, *ACCOUNT_ACTOR_CODE_ID, &"").expect_err("Exec should have failed");
+    assert_eq!(err.exit_code(), ExitCode::USR_FORBIDDEN);
     check_state(&rt);
 }
 
@@ -411,6 +420,76 @@ fn call_exec4_placeholder() {
     assert_eq!(expected_id_addr, resolved_id, "f4 address not assigned to the right actor");
 }
 
+#[test]
+fn resolve_address() {
+    let rt = construct_runtime();
+    construct_and_verify(&rt);
+
+    let robust = Address::new_actor(b"robust");
+    let f4_addr = Address::new_delegated(EAM_ACTOR_ID, b"foobar").unwrap();
+    let id = {
+        let mut state: State = rt.get_state();
+        let (id, _) = state.map_addresses_to_id(rt.store(), &robust, Some(&f4_addr)).unwrap();
+        rt.replace_state(&state);
+        id
+    };
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(1234));
+    assert_eq!(Some(id), resolve_and_verify(&rt, robust));
+    assert_eq!(Some(id), resolve_and_verify(&rt, f4_addr));
+    // ID addresses pass through.
+    assert_eq!(Some(5555), resolve_and_verify(&rt, Address::new_id(5555)));
+    assert_eq!(None, resolve_and_verify(&rt, Address::new_actor(b"unknown")));
+    check_state(&rt);
+}
+
+#[test]
+fn lookup_addresses_by_id() {
+    let rt = construct_runtime();
+    construct_and_verify(&rt);
+
+    let f4_addr = Address::new_delegated(EAM_ACTOR_ID, b"foobar").unwrap();
+    let key_addr = Address::new_bls(&[1; 48]).unwrap();
+    let (id, key_id, plain_id) = {
+        let mut state: State = rt.get_state();
+        let (id, _) = state
+            .map_addresses_to_id(rt.store(), &Address::new_actor(b"robust"), Some(&f4_addr))
+            .unwrap();
+        // As mapped by the VM when it implicitly creates an account.
+        let (key_id, _) = state.map_addresses_to_id(rt.store(), &key_addr, None).unwrap();
+        let (plain_id, _) =
+            state.map_addresses_to_id(rt.store(), &Address::new_actor(b"plain"), None).unwrap();
+        rt.replace_state(&state);
+        (id, key_id, plain_id)
+    };
+    rt.set_address_actor_type(Address::new_id(id), *EVM_ACTOR_CODE_ID);
+    rt.set_delegated_address(id, f4_addr);
+    rt.set_address_actor_type(Address::new_id(key_id), *ACCOUNT_ACTOR_CODE_ID);
+    rt.set_address_actor_type(Address::new_id(plain_id), *MULTISIG_ACTOR_CODE_ID);
+
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(1234));
+    assert_eq!(vec![f4_addr], lookup_and_verify(&rt, id).addresses);
+
+    // The key address of an account is fetched from the account.
+    rt.expect_send(
+        Address::new_id(key_id),
+        PUBKEY_ADDRESS_METHOD,
+        None,
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+        IpldBlock::serialize_cbor(&key_addr).unwrap(),
+        ExitCode::OK,
+        None,
+    );
+    assert_eq!(vec![key_addr], lookup_and_verify(&rt, key_id).addresses);
+
+    // Robust addresses are not indexed by ID.
+    assert!(lookup_and_verify(&rt, plain_id).addresses.is_empty());
+    assert!(lookup_and_verify(&rt, 5555).addresses.is_empty());
+    check_state(&rt);
+}
+
 fn construct_and_verify(rt: &MockRuntime) {
     rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
     rt.expect_validate_caller_addr(vec![SYSTEM_ACTOR_ADDR]);
@@ -480,3 +559,33 @@ where
     check_state(rt);
     ret.and_then(|v| v.unwrap().deserialize().map_err(|e| e.into()))
 }
+
+fn resolve_and_verify(rt: &MockRuntime, address: Address) -> Option<ActorID> {
+    rt.expect_validate_caller_any();
+    let ret: ResolveAddressReturn = rt
+        .call::<InitActor>(
+            Method::ResolveAddressExported as u64,
+            IpldBlock::serialize_cbor(&ResolveAddressParams { address }).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret.id
+}
+
+fn lookup_and_verify(rt: &MockRuntime, id: ActorID) -> LookupAddressesReturn {
+    rt.expect_validate_caller_any();
+    let ret = rt
+        .call::<InitActor>(
+            Method::LookupAddressesExported as u64,
+            IpldBlock::serialize_cbor(&LookupAddressesParams { id }).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret
+}
@@ -2,15 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use cid::{Cid, Version};
+use fil_actors_runtime::impl_trailing_optional_tuple;
 use fvm_ipld_encoding::tuple::*;
 use fvm_ipld_encoding::BytesSer;
 use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::commcid::{FIL_COMMITMENT_UNSEALED, SHA2_256_TRUNC254_PADDED};
 use fvm_shared::crypto::signature::Signature;
+use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::piece::PaddedPieceSize;
 use fvm_shared::sector::SectorNumber;
+use fvm_shared::ActorID;
 use libipld_core::ipld::Ipld;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::convert::{TryFrom, TryInto};
@@ -127,11 +130,140 @@ impl DealProposal {
     }
 }
 
-/// ClientDealProposal is a DealProposal signed by a client
+/// Revised terms of an activated deal whose term has been extended,
+/// superseding the end epoch and price of its proposal.
+/// The revised price applies from `repriced_epoch`, when the deal was last extended.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct DealExtension {
+    pub end_epoch: ChainEpoch,
+    pub storage_price_per_epoch: TokenAmount,
+    pub repriced_epoch: ChainEpoch,
+    /// Total storage fee for the epochs before `repriced_epoch`.
+    pub prior_storage_fee: TokenAmount,
+}
+
+impl DealExtension {
+    pub fn total_storage_fee(&self) -> TokenAmount {
+        &self.prior_storage_fee
+            + &self.storage_price_per_epoch * (self.end_epoch - self.repriced_epoch)
+    }
+
+    /// Returns the proposal with the revised end epoch and price.
+    /// Payments computed from the revised proposal are correct only for epochs
+    /// from `repriced_epoch`.
+    pub fn apply(&self, proposal: &DealProposal) -> DealProposal {
+        DealProposal {
+            end_epoch: self.end_epoch,
+            storage_price_per_epoch: self.storage_price_per_epoch.clone(),
+            ..proposal.clone()
+        }
+    }
+}
+
+/// ClientDealProposal is a DealProposal signed by a client
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ClientDealProposal {
     pub proposal: DealProposal,
     pub client_signature: Signature,
+    /// Whether the client signed EIP-712 typed data (see `authenticate_client_message`).
+    // * Added in v15
+    pub eip712_signature: bool,
+}
+
+impl_trailing_optional_tuple!(ClientDealProposal { proposal, client_signature; eip712_signature });
+
+/// A DealProposal whose storage fee is paid in an FRC-46 token rather than FIL.
+/// The storage price is denominated in the token. Collateral is always FIL.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct TokenDealProposal {
+    pub proposal: DealProposal,
+    pub payment_token: Address,
+}
+
+/// ClientTokenDealProposal is a TokenDealProposal signed by a client
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientTokenDealProposal {
+    pub proposal: TokenDealProposal,
+    pub client_signature: Signature,
+    /// Whether the client signed EIP-712 typed data (see `authenticate_client_message`).
+    pub eip712_signature: bool,
+}
+
+impl_trailing_optional_tuple!(ClientTokenDealProposal { proposal, client_signature; eip712_signature });
+
+/// A deal proposal signed once by a client, which each of up to `replicas` of the allowed
+/// providers may publish as a separate deal storing a replica of the piece on the same terms.
+/// The price and collateral apply to each replica, so the client commits to at most
+/// `replicas` times the cost of a single deal.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ReplicatedDealProposal {
+    pub piece_cid: Cid,
+    pub piece_size: PaddedPieceSize,
+    pub verified_deal: bool,
+    pub client: Address,
+    /// Providers which may publish a replica, each at most once.
+    pub providers: Vec<Address>,
+    /// Maximum number of replicas which may be published.
+    pub replicas: u64,
+
+    /// Arbitrary client chosen label to apply to each replica deal
+    pub label: Label,
+
+    pub start_epoch: ChainEpoch,
+    pub end_epoch: ChainEpoch,
+    pub storage_price_per_epoch: TokenAmount,
+
+    pub provider_collateral: TokenAmount,
+    pub client_collateral: TokenAmount,
+}
+
+impl ReplicatedDealProposal {
+    /// Returns the proposal for the replica deal published by a provider.
+    pub fn replica(&self, provider: Address) -> DealProposal {
+        DealProposal {
+            piece_cid: self.piece_cid,
+            piece_size: self.piece_size,
+            verified_deal: self.verified_deal,
+            client: self.client,
+            provider,
+            label: self.label.clone(),
+            start_epoch: self.start_epoch,
+            end_epoch: self.end_epoch,
+            storage_price_per_epoch: self.storage_price_per_epoch.clone(),
+            provider_collateral: self.provider_collateral.clone(),
+            client_collateral: self.client_collateral.clone(),
+        }
+    }
+}
+
+/// ClientReplicatedDealProposal is a ReplicatedDealProposal signed by a client
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientReplicatedDealProposal {
+    pub proposal: ReplicatedDealProposal,
+    pub client_signature: Signature,
+    /// Whether the client signed EIP-712 typed data (see `authenticate_client_message`).
+    pub eip712_signature: bool,
+}
+
+impl_trailing_optional_tuple!(ClientReplicatedDealProposal { proposal, client_signature; eip712_signature });
+
+/// The replica deals published from a replicated deal proposal.
+/// A provider's claim on a replica is kept after its deal is removed,
+/// so a replica cannot be published again. The record is removed at the proposal's
+/// start epoch, after which no replica can be published.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ReplicaDeals {
+    /// Maximum number of replicas, from the proposal.
+    pub replicas: u64,
+    /// Start epoch of the replica deals, from the proposal.
+    pub start_epoch: ChainEpoch,
+    pub deals: Vec<ReplicaDeal>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ReplicaDeal {
+    pub provider: ActorID,
+    pub deal_id: DealID,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Copy, Serialize_tuple, Deserialize_tuple)]
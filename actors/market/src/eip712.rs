@@ -0,0 +1,219 @@
+//! EIP-712 typed-data encoding of deal proposals.
+//!
+//! Ethereum wallets will only sign structured data they can display to the user, not raw CBOR.
+//! Clients with an Ethereum (f410) address may therefore sign a deal proposal, or another
+//! message a client signs to the market, as EIP-712 typed data instead, with the following
+//! domain and types:
+//!
+//! ```text
+//! EIP712Domain(string name,string version,uint256 chainId)
+//! DealProposal(string pieceCid,uint64 pieceSize,bool verifiedDeal,bytes client,bytes provider,
+//!     string label,bytes labelBytes,int64 startEpoch,int64 endEpoch,uint256 storagePricePerEpoch,
+//!     uint256 providerCollateral,uint256 clientCollateral)
+//! TokenDealProposal(DealProposal proposal,bytes paymentToken)
+//! ReplicatedDealProposal(string pieceCid,uint64 pieceSize,bool verifiedDeal,bytes client,
+//!     bytes[] providers,uint64 replicas,string label,bytes labelBytes,int64 startEpoch,
+//!     int64 endEpoch,uint256 storagePricePerEpoch,uint256 providerCollateral,
+//!     uint256 clientCollateral)
+//! DealExtension(uint64 dealId,int64 endEpoch,uint256 storagePricePerEpoch,int64 expiration)
+//! DealCancellation(uint64 dealId,int64 cancelEpoch,uint64 claimId,int64 expiration)
+//! ```
+//!
+//! The piece CID is its canonical (base32) string form, addresses are their byte representation,
+//! and token amounts are in attoFIL. A string label is encoded in `label` (with empty
+//! `labelBytes`) and a bytes label in `labelBytes` (with empty `label`).
+
+use fil_actors_runtime::runtime::Primitives;
+use fil_actors_runtime::{actor_error, ActorError};
+use fvm_shared::bigint::Sign;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::hash::SupportedHashes;
+use fvm_shared::econ::TokenAmount;
+
+use crate::{
+    DealCancellationProposal, DealExtensionProposal, DealProposal, Label, ReplicatedDealProposal,
+    TokenDealProposal,
+};
+
+/// The EIP-712 domain name for signed deal proposals.
+pub const EIP712_DOMAIN_NAME: &str = "Filecoin Storage Market";
+/// The EIP-712 domain version for signed deal proposals.
+pub const EIP712_DOMAIN_VERSION: &str = "1";
+
+const EIP712_DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId)";
+const DEAL_PROPOSAL_TYPE: &str = "DealProposal(string pieceCid,uint64 pieceSize,\
+bool verifiedDeal,bytes client,bytes provider,string label,bytes labelBytes,int64 startEpoch,\
+int64 endEpoch,uint256 storagePricePerEpoch,uint256 providerCollateral,uint256 clientCollateral)";
+const TOKEN_DEAL_PROPOSAL_TYPE: &str =
+    "TokenDealProposal(DealProposal proposal,bytes paymentToken)";
+const REPLICATED_DEAL_PROPOSAL_TYPE: &str = "ReplicatedDealProposal(string pieceCid,\
+uint64 pieceSize,bool verifiedDeal,bytes client,bytes[] providers,uint64 replicas,string label,\
+bytes labelBytes,int64 startEpoch,int64 endEpoch,uint256 storagePricePerEpoch,\
+uint256 providerCollateral,uint256 clientCollateral)";
+const DEAL_EXTENSION_TYPE: &str =
+    "DealExtension(uint64 dealId,int64 endEpoch,uint256 storagePricePerEpoch,int64 expiration)";
+const DEAL_CANCELLATION_TYPE: &str =
+    "DealCancellation(uint64 dealId,int64 cancelEpoch,uint64 claimId,int64 expiration)";
+
+/// Returns the message an Ethereum account signs (after hashing with keccak256) when signing a
+/// deal proposal as EIP-712 typed data: `0x19 0x01 || domainSeparator || hashStruct(proposal)`.
+pub fn deal_proposal_eip712_message(
+    rt: &(impl Primitives + ?Sized),
+    chain_id: u64,
+    proposal: &DealProposal,
+) -> Result<Vec<u8>, ActorError> {
+    Ok(eip712_message(rt, chain_id, &hash_deal_proposal(rt, proposal)?))
+}
+
+/// Returns the message an Ethereum account signs when signing a token deal proposal as EIP-712
+/// typed data.
+pub fn token_deal_proposal_eip712_message(
+    rt: &(impl Primitives + ?Sized),
+    chain_id: u64,
+    proposal: &TokenDealProposal,
+) -> Result<Vec<u8>, ActorError> {
+    // A referenced struct type is appended to the encoding of the referencing type.
+    let typ = [TOKEN_DEAL_PROPOSAL_TYPE, DEAL_PROPOSAL_TYPE].concat();
+    let mut data = Vec::with_capacity(3 * 32);
+    data.extend_from_slice(&keccak(rt, typ.as_bytes()));
+    data.extend_from_slice(&hash_deal_proposal(rt, &proposal.proposal)?);
+    data.extend_from_slice(&keccak(rt, &proposal.payment_token.to_bytes()));
+    Ok(eip712_message(rt, chain_id, &keccak(rt, &data)))
+}
+
+/// Returns the message an Ethereum account signs when signing a replicated deal proposal as
+/// EIP-712 typed data.
+pub fn replicated_deal_proposal_eip712_message(
+    rt: &(impl Primitives + ?Sized),
+    chain_id: u64,
+    proposal: &ReplicatedDealProposal,
+) -> Result<Vec<u8>, ActorError> {
+    let (label, label_bytes) = label_fields(&proposal.label);
+    // An array is encoded as the hash of the concatenated encodings of its elements.
+    let providers: Vec<u8> =
+        proposal.providers.iter().flat_map(|p| keccak(rt, &p.to_bytes())).collect();
+
+    let mut data = Vec::with_capacity(14 * 32);
+    data.extend_from_slice(&keccak(rt, REPLICATED_DEAL_PROPOSAL_TYPE.as_bytes()));
+    data.extend_from_slice(&keccak(rt, proposal.piece_cid.to_string().as_bytes()));
+    data.extend_from_slice(&uint_word(proposal.piece_size.0));
+    data.extend_from_slice(&uint_word(proposal.verified_deal as u64));
+    data.extend_from_slice(&keccak(rt, &proposal.client.to_bytes()));
+    data.extend_from_slice(&keccak(rt, &providers));
+    data.extend_from_slice(&uint_word(proposal.replicas));
+    data.extend_from_slice(&keccak(rt, label));
+    data.extend_from_slice(&keccak(rt, label_bytes));
+    data.extend_from_slice(&int_word(proposal.start_epoch));
+    data.extend_from_slice(&int_word(proposal.end_epoch));
+    data.extend_from_slice(&token_word(&proposal.storage_price_per_epoch, "storage price")?);
+    data.extend_from_slice(&token_word(&proposal.provider_collateral, "provider collateral")?);
+    data.extend_from_slice(&token_word(&proposal.client_collateral, "client collateral")?);
+    Ok(eip712_message(rt, chain_id, &keccak(rt, &data)))
+}
+
+/// Returns the message an Ethereum account signs when signing a deal extension proposal as
+/// EIP-712 typed data.
+pub fn deal_extension_eip712_message(
+    rt: &(impl Primitives + ?Sized),
+    chain_id: u64,
+    extension: &DealExtensionProposal,
+) -> Result<Vec<u8>, ActorError> {
+    let mut data = Vec::with_capacity(5 * 32);
+    data.extend_from_slice(&keccak(rt, DEAL_EXTENSION_TYPE.as_bytes()));
+    data.extend_from_slice(&uint_word(extension.deal_id));
+    data.extend_from_slice(&int_word(extension.end_epoch));
+    data.extend_from_slice(&token_word(&extension.storage_price_per_epoch, "storage price")?);
+    data.extend_from_slice(&int_word(extension.expiration));
+    Ok(eip712_message(rt, chain_id, &keccak(rt, &data)))
+}
+
+/// Returns the message an Ethereum account signs when signing a deal cancellation proposal as
+/// EIP-712 typed data.
+pub fn deal_cancellation_eip712_message(
+    rt: &(impl Primitives + ?Sized),
+    chain_id: u64,
+    cancellation: &DealCancellationProposal,
+) -> Vec<u8> {
+    let mut data = Vec::with_capacity(5 * 32);
+    data.extend_from_slice(&keccak(rt, DEAL_CANCELLATION_TYPE.as_bytes()));
+    data.extend_from_slice(&uint_word(cancellation.deal_id));
+    data.extend_from_slice(&int_word(cancellation.cancel_epoch));
+    data.extend_from_slice(&uint_word(cancellation.claim_id));
+    data.extend_from_slice(&int_word(cancellation.expiration));
+    eip712_message(rt, chain_id, &keccak(rt, &data))
+}
+
+// Encodes `0x19 0x01 || domainSeparator || hashStruct(message)`.
+fn eip712_message(rt: &(impl Primitives + ?Sized), chain_id: u64, hash: &[u8; 32]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(66);
+    message.extend_from_slice(&[0x19, 0x01]);
+    message.extend_from_slice(&domain_separator(rt, chain_id));
+    message.extend_from_slice(hash);
+    message
+}
+
+fn domain_separator(rt: &(impl Primitives + ?Sized), chain_id: u64) -> [u8; 32] {
+    let mut data = Vec::with_capacity(4 * 32);
+    data.extend_from_slice(&keccak(rt, EIP712_DOMAIN_TYPE.as_bytes()));
+    data.extend_from_slice(&keccak(rt, EIP712_DOMAIN_NAME.as_bytes()));
+    data.extend_from_slice(&keccak(rt, EIP712_DOMAIN_VERSION.as_bytes()));
+    data.extend_from_slice(&uint_word(chain_id));
+    keccak(rt, &data)
+}
+
+fn hash_deal_proposal(
+    rt: &(impl Primitives + ?Sized),
+    proposal: &DealProposal,
+) -> Result<[u8; 32], ActorError> {
+    let (label, label_bytes) = label_fields(&proposal.label);
+
+    let mut data = Vec::with_capacity(13 * 32);
+    data.extend_from_slice(&keccak(rt, DEAL_PROPOSAL_TYPE.as_bytes()));
+    data.extend_from_slice(&keccak(rt, proposal.piece_cid.to_string().as_bytes()));
+    data.extend_from_slice(&uint_word(proposal.piece_size.0));
+    data.extend_from_slice(&uint_word(proposal.verified_deal as u64));
+    data.extend_from_slice(&keccak(rt, &proposal.client.to_bytes()));
+    data.extend_from_slice(&keccak(rt, &proposal.provider.to_bytes()));
+    data.extend_from_slice(&keccak(rt, label));
+    data.extend_from_slice(&keccak(rt, label_bytes));
+    data.extend_from_slice(&int_word(proposal.start_epoch));
+    data.extend_from_slice(&int_word(proposal.end_epoch));
+    data.extend_from_slice(&token_word(&proposal.storage_price_per_epoch, "storage price")?);
+    data.extend_from_slice(&token_word(&proposal.provider_collateral, "provider collateral")?);
+    data.extend_from_slice(&token_word(&proposal.client_collateral, "client collateral")?);
+    Ok(keccak(rt, &data))
+}
+
+fn label_fields(label: &Label) -> (&[u8], &[u8]) {
+    match label {
+        Label::String(s) => (s.as_bytes(), &[]),
+        Label::Bytes(b) => (&[], b),
+    }
+}
+
+fn keccak(rt: &(impl Primitives + ?Sized), data: &[u8]) -> [u8; 32] {
+    rt.hash(SupportedHashes::Keccak256, data).try_into().expect("keccak256 digest must be 32 bytes")
+}
+
+fn uint_word(v: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&v.to_be_bytes());
+    word
+}
+
+/// Encodes a signed integer as a two's complement 256bit word.
+fn int_word(v: ChainEpoch) -> [u8; 32] {
+    let mut word = if v < 0 { [0xff; 32] } else { [0u8; 32] };
+    word[24..].copy_from_slice(&v.to_be_bytes());
+    word
+}
+
+fn token_word(v: &TokenAmount, name: &str) -> Result<[u8; 32], ActorError> {
+    let (sign, bytes) = v.atto().to_bytes_be();
+    if sign == Sign::Minus || bytes.len() > 32 {
+        return Err(actor_error!(illegal_argument, "{} {} is not a valid uint256", name, v));
+    }
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(word)
+}
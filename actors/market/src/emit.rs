@@ -48,6 +48,21 @@ pub fn deal_terminated(
     )
 }
 
+/// Indicates a deal has been cancelled by agreement of its client and provider.
+pub fn deal_cancelled(
+    rt: &impl Runtime,
+    deal_id: DealID,
+    client: ActorID,
+    provider: ActorID,
+) -> Result<(), ActorError> {
+    rt.emit_event(
+        &EventBuilder::new()
+            .typ("deal-cancelled")
+            .with_parties(deal_id, client, provider)
+            .build()?,
+    )
+}
+
 /// Indicates a deal has been completed successfully.
 pub fn deal_completed(
     rt: &impl Runtime,
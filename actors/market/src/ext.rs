@@ -36,6 +36,14 @@ pub mod miner {
         frc42_dispatch::method_hash!("IsControllingAddress");
     pub const SECTOR_CONTENT_CHANGED: MethodNum =
         frc42_dispatch::method_hash!("SectorContentChanged");
+    pub const GET_SECTOR_EXPIRATION_EXPORTED: MethodNum =
+        frc42_dispatch::method_hash!("GetSectorExpiration");
+    pub const GET_SECTOR_STATUS_EXPORTED: MethodNum =
+        frc42_dispatch::method_hash!("GetSectorStatus");
+    pub const SECTOR_DEALS_CANCELLED: MethodNum = 37;
+
+    /// The status of a sector which is proven and not faulty.
+    pub const SECTOR_STATUS_ACTIVE: u8 = 1;
 
     #[derive(Serialize_tuple, Deserialize_tuple)]
     pub struct GetControlAddressesReturnParams {
@@ -56,6 +64,43 @@ pub mod miner {
         pub address: Address,
     }
 
+    #[derive(Serialize_tuple, Deserialize_tuple)]
+    #[serde(transparent)]
+    pub struct GetSectorExpirationParams {
+        pub sector_number: SectorNumber,
+    }
+
+    #[derive(Serialize_tuple, Deserialize_tuple)]
+    #[serde(transparent)]
+    pub struct GetSectorExpirationReturn {
+        pub expiration: ChainEpoch,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+    #[serde(transparent)]
+    pub struct GetSectorStatusParams {
+        pub sector_number: SectorNumber,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+    #[serde(transparent)]
+    pub struct GetSectorStatusReturn {
+        pub status: u8,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+    #[serde(transparent)]
+    pub struct SectorDealsCancelledParams {
+        pub sectors: Vec<SectorDealsCancellation>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+    pub struct SectorDealsCancellation {
+        pub sector_number: SectorNumber,
+        pub deal_space: u64,
+        pub verified_deal_space: u64,
+    }
+
     #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
     #[serde(transparent)]
     pub struct SectorContentChangedParams {
@@ -101,11 +146,45 @@ pub mod verifreg {
     use fil_actors_runtime::BatchReturn;
     use fvm_shared::clock::ChainEpoch;
     use fvm_shared::piece::PaddedPieceSize;
+    use fvm_shared::sector::SectorNumber;
     use fvm_shared::ActorID;
 
+    pub const GET_CLAIMS_METHOD: u64 = 10;
+    pub const CANCEL_CLAIMS_METHOD: u64 = 13;
+
     pub type AllocationID = u64;
     pub type ClaimID = u64;
 
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+    pub struct Claim {
+        pub provider: ActorID,
+        pub client: ActorID,
+        pub data: Cid,
+        pub size: PaddedPieceSize,
+        pub term_min: ChainEpoch,
+        pub term_max: ChainEpoch,
+        pub term_start: ChainEpoch,
+        pub sector: SectorNumber,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+    pub struct GetClaimsParams {
+        pub provider: ActorID,
+        pub claim_ids: Vec<ClaimID>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+    pub struct GetClaimsReturn {
+        pub batch_info: BatchReturn,
+        pub claims: Vec<Claim>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+    pub struct CancelClaimsParams {
+        pub provider: ActorID,
+        pub claim_ids: Vec<ClaimID>,
+    }
+
     #[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
     pub struct AllocationRequest {
         pub provider: ActorID,
@@ -145,6 +224,10 @@ pub mod datacap {
     pub const TRANSFER_FROM_METHOD: u64 = frc42_dispatch::method_hash!("TransferFrom");
 }
 
+pub mod token {
+    pub const TRANSFER_METHOD: u64 = frc42_dispatch::method_hash!("Transfer");
+}
+
 pub mod reward {
     pub const THIS_EPOCH_REWARD_METHOD: u64 = 3;
 }
@@ -1,21 +1,26 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::cmp::min;
+use std::cmp::{max, min};
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 use cid::multihash::{Code, MultihashGeneric};
 use cid::Cid;
 use fil_actors_runtime::reward::ThisEpochRewardReturn;
-use frc46_token::token::types::{BalanceReturn, TransferFromParams, TransferFromReturn};
+use frc46_token::receiver::{FRC46TokenReceived, FRC46_TOKEN_TYPE};
+use frc46_token::token::types::{
+    BalanceReturn, TransferFromParams, TransferFromReturn, TransferParams,
+};
+use fvm_actor_utils::receiver::UniversalReceiverParams;
 use fvm_ipld_bitfield::BitField;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::{RawBytes, DAG_CBOR};
 use fvm_ipld_hamt::BytesKey;
-use fvm_shared::address::Address;
+use fvm_shared::address::{Address, Payload};
 use fvm_shared::bigint::BigInt;
 use fvm_shared::clock::{ChainEpoch, EPOCH_UNDEFINED};
+use fvm_shared::crypto::signature::Signature;
 use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
@@ -28,20 +33,23 @@ use log::{info, warn};
 use num_derive::FromPrimitive;
 use num_traits::Zero;
 
-use fil_actors_runtime::cbor::{deserialize, serialize};
+use fil_actors_runtime::cbor::{deserialize, serialize, serialize_vec};
 use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::runtime::{ActorCode, Policy, Runtime};
 use fil_actors_runtime::{
     actor_dispatch, actor_error, deserialize_block, ActorContext, ActorDowncast, ActorError,
-    AsActorError, BURNT_FUNDS_ACTOR_ADDR, CRON_ACTOR_ADDR, DATACAP_TOKEN_ACTOR_ADDR,
+    AsActorError, BURNT_FUNDS_ACTOR_ADDR, CRON_ACTOR_ADDR, DATACAP_TOKEN_ACTOR_ADDR, EAM_ACTOR_ID,
     REWARD_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, SYSTEM_ACTOR_ADDR, VERIFIED_REGISTRY_ACTOR_ADDR,
 };
-use fil_actors_runtime::{extract_send_result, BatchReturnGen, FIRST_ACTOR_SPECIFIC_EXIT_CODE};
+use fil_actors_runtime::{
+    extract_send_result, BatchReturn, BatchReturnGen, FIRST_ACTOR_SPECIFIC_EXIT_CODE,
+};
 
 use crate::balance_table::BalanceTable;
-use crate::ext::verifreg::{AllocationID, AllocationRequest};
+use crate::ext::verifreg::{AllocationID, AllocationRequest, ClaimID};
 
 pub use self::deal::*;
+pub use self::eip712::*;
 use self::policy::*;
 pub use self::state::*;
 pub use self::types::*;
@@ -54,6 +62,7 @@ pub mod policy;
 pub mod testing;
 
 mod deal;
+mod eip712;
 mod emit;
 mod state;
 mod types;
@@ -98,12 +107,36 @@ pub enum Method {
     GetDealActivationExported = frc42_dispatch::method_hash!("GetDealActivation"),
     GetDealSectorExported = frc42_dispatch::method_hash!("GetDealSector"),
     SettleDealPaymentsExported = frc42_dispatch::method_hash!("SettleDealPayments"),
+    ExtendDealTermsExported = frc42_dispatch::method_hash!("ExtendDealTerms"),
+    PublishTokenDealsExported = frc42_dispatch::method_hash!("PublishTokenDeals"),
+    WithdrawTokenBalanceExported = frc42_dispatch::method_hash!("WithdrawTokenBalance"),
+    GetTokenBalanceExported = frc42_dispatch::method_hash!("GetTokenBalance"),
+    GetDealPaymentTokenExported = frc42_dispatch::method_hash!("GetDealPaymentToken"),
+    CancelDealsExported = frc42_dispatch::method_hash!("CancelDeals"),
+    GetClientDealsExported = frc42_dispatch::method_hash!("GetClientDeals"),
+    GetProviderDealsExported = frc42_dispatch::method_hash!("GetProviderDeals"),
+    GetClientDealSummariesExported = frc42_dispatch::method_hash!("GetClientDealSummaries"),
+    GetProviderDealSummariesExported = frc42_dispatch::method_hash!("GetProviderDealSummaries"),
+    PublishReplicaDealsExported = frc42_dispatch::method_hash!("PublishReplicaDeals"),
+    GetReplicaDealsExported = frc42_dispatch::method_hash!("GetReplicaDeals"),
+    UniversalReceiverHook = frc42_dispatch::method_hash!("Receive"),
     SectorContentChangedExported = ext::miner::SECTOR_CONTENT_CHANGED,
 }
 
+/// Maximum number of deals listed by a single call to list a client's or provider's deals.
+pub const MAX_LIST_DEALS_LIMIT: u64 = 1000;
+
 /// Market Actor
 pub struct Actor;
 
+/// A deal proposal to publish, with the token in which its storage fee is paid (if not FIL),
+/// and the replicated proposal signed by the client (if the deal is a replica).
+struct PublishedDeal {
+    deal: ClientDealProposal,
+    payment_token: Option<Address>,
+    replicated: Option<ReplicatedDealProposal>,
+}
+
 impl Actor {
     pub fn constructor(rt: &impl Runtime) -> Result<(), ActorError> {
         rt.validate_immediate_caller_is(std::iter::once(&SYSTEM_ACTOR_ADDR))?;
@@ -190,18 +223,192 @@ impl Actor {
         Ok(GetBalanceReturn { balance, locked })
     }
 
+    /// Deposits FRC-46 tokens transferred to the market into the balance held in escrow in that
+    /// token. The tokens are credited to the address named by `AddBalanceParams` in the operator
+    /// data, or to the sender if there is none.
+    ///
+    /// The market cannot verify that the caller is a genuine token actor, nor that any tokens
+    /// were transferred: any actor may call this hook and credit balances denominated in itself.
+    /// Balances are kept separately per token (the calling actor's ID), so such a "token" can only
+    /// pay for deals that name it as their payment token. A provider is exposed only if it
+    /// accepts a deal paid in a token it does not trust, and must check a deal's payment token
+    /// (see `GetDealPaymentToken`) before relying on its payments.
+    fn universal_receiver_hook(
+        rt: &impl Runtime,
+        params: UniversalReceiverParams,
+    ) -> Result<(), ActorError> {
+        // Any token may be held in escrow, except the data cap token which is transferred
+        // to the market only to make verified allocations.
+        rt.validate_immediate_caller_accept_any()?;
+        let caller = rt.message().caller();
+        if caller == DATACAP_TOKEN_ACTOR_ADDR {
+            return Err(actor_error!(forbidden, "data cap cannot be held in escrow"));
+        }
+        let token = caller.id().unwrap();
+        let my_id = rt.message().receiver().id().unwrap();
+
+        let tokens_received = validate_tokens_received(&params, my_id)?;
+        if !tokens_received.amount.is_positive() {
+            return Err(actor_error!(
+                illegal_argument,
+                "balance to add must be greater than zero was: {}",
+                tokens_received.amount
+            ));
+        }
+        let beneficiary = if tokens_received.operator_data.is_empty() {
+            Address::new_id(tokens_received.from)
+        } else {
+            let params: AddBalanceParams =
+                deserialize(&tokens_received.operator_data, "add balance params")?;
+            params.provider_or_client
+        };
+        let (nominal, _, _) = escrow_address(rt, &beneficiary)?;
+
+        rt.transaction(|st: &mut State, rt| {
+            st.add_balance_to_token_escrow_table(
+                rt.store(),
+                token,
+                &nominal,
+                &tokens_received.amount,
+            )
+        })
+    }
+
+    /// Attempt to withdraw the specified amount of an FRC-46 token from the balance held in
+    /// escrow. If less than the specified amount is available, yields the entire available balance.
+    fn withdraw_token_balance(
+        rt: &impl Runtime,
+        params: WithdrawTokenBalanceParams,
+    ) -> Result<WithdrawBalanceReturn, ActorError> {
+        if params.amount < TokenAmount::zero() {
+            return Err(actor_error!(illegal_argument, "negative amount: {}", params.amount));
+        }
+        let token = rt.resolve_address(&params.token).ok_or_else(|| {
+            actor_error!(illegal_argument, "failed to resolve token address {}", params.token)
+        })?;
+
+        let (nominal, recipient, approved) = escrow_address(rt, &params.provider_or_client)?;
+        // for providers -> only corresponding owner or worker can withdraw
+        // for clients -> only the client i.e the recipient can withdraw
+        rt.validate_immediate_caller_is(&approved)?;
+
+        let amount_extracted = rt.transaction(|st: &mut State, rt| {
+            st.withdraw_balance_from_token_escrow_table(rt.store(), token, &nominal, &params.amount)
+        })?;
+
+        if amount_extracted.is_positive() {
+            extract_send_result(rt.send_simple(
+                &Address::new_id(token),
+                ext::token::TRANSFER_METHOD,
+                IpldBlock::serialize_cbor(&TransferParams {
+                    to: recipient,
+                    amount: amount_extracted.clone(),
+                    operator_data: RawBytes::default(),
+                })?,
+                TokenAmount::zero(),
+            ))
+            .with_context(|| format!("failed to transfer token {} to {}", token, recipient))?;
+        }
+
+        Ok(WithdrawBalanceReturn { amount_withdrawn: amount_extracted })
+    }
+
+    /// Returns the escrow balance and locked amount of an FRC-46 token for an address.
+    fn get_token_balance(
+        rt: &impl Runtime,
+        params: GetTokenBalanceParams,
+    ) -> Result<GetBalanceReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let token = rt.resolve_address(&params.token).ok_or_else(|| {
+            actor_error!(illegal_argument, "failed to resolve token address {}", params.token)
+        })?;
+        let account = params.account;
+        let nominal = rt.resolve_address(&account).ok_or_else(|| {
+            actor_error!(illegal_argument, "failed to resolve address {}", account)
+        })?;
+        let account = Address::new_id(nominal);
+
+        let store = rt.store();
+        let st: State = rt.state()?;
+        let token_balances = st.get_token_balances(store, token)?;
+        let balances =
+            BalanceTable::from_root(store, &token_balances.escrow_table, "token escrow table")?;
+        let locks =
+            BalanceTable::from_root(store, &token_balances.locked_table, "token locked table")?;
+        let balance = balances.get(&account)?;
+        let locked = locks.get(&account)?;
+
+        Ok(GetBalanceReturn { balance, locked })
+    }
+
     /// Publish a new set of storage deals (not yet included in a sector).
     fn publish_storage_deals(
         rt: &impl Runtime,
         params: PublishStorageDealsParams,
+    ) -> Result<PublishStorageDealsReturn, ActorError> {
+        let deals = params
+            .deals
+            .into_iter()
+            .map(|deal| PublishedDeal { deal, payment_token: None, replicated: None })
+            .collect();
+        Self::publish_deals(rt, deals)
+    }
+
+    /// Publish a new set of storage deals whose storage fees are paid in FRC-46 tokens
+    /// held in escrow, rather than in FIL.
+    fn publish_token_deals(
+        rt: &impl Runtime,
+        params: PublishTokenDealsParams,
+    ) -> Result<PublishStorageDealsReturn, ActorError> {
+        let deals = params
+            .deals
+            .into_iter()
+            .map(|deal| PublishedDeal {
+                deal: ClientDealProposal {
+                    proposal: deal.proposal.proposal,
+                    client_signature: deal.client_signature,
+                    eip712_signature: deal.eip712_signature,
+                },
+                payment_token: Some(deal.proposal.payment_token),
+                replicated: None,
+            })
+            .collect();
+        Self::publish_deals(rt, deals)
+    }
+
+    /// Publish a new set of storage deals, each a replica claimed by the provider from a
+    /// replicated proposal signed by the client.
+    fn publish_replica_deals(
+        rt: &impl Runtime,
+        params: PublishReplicaDealsParams,
+    ) -> Result<PublishStorageDealsReturn, ActorError> {
+        let deals = params
+            .deals
+            .into_iter()
+            .map(|deal| PublishedDeal {
+                deal: ClientDealProposal {
+                    proposal: deal.proposal.replica(params.provider),
+                    client_signature: deal.client_signature,
+                    eip712_signature: deal.eip712_signature,
+                },
+                payment_token: None,
+                replicated: Some(deal.proposal),
+            })
+            .collect();
+        Self::publish_deals(rt, deals)
+    }
+
+    fn publish_deals(
+        rt: &impl Runtime,
+        deals: Vec<PublishedDeal>,
     ) -> Result<PublishStorageDealsReturn, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
-        if params.deals.is_empty() {
+        if deals.is_empty() {
             return Err(actor_error!(illegal_argument, "Empty deals parameter"));
         }
 
         // All deals should have the same provider so get worker once
-        let provider_raw = params.deals[0].proposal.provider;
+        let provider_raw = deals[0].deal.proposal.provider;
         let provider_id = rt.resolve_address(&provider_raw).ok_or_else(|| {
             actor_error!(not_found, "failed to resolve provider address {}", provider_raw)
         })?;
@@ -218,16 +425,7 @@ impl Actor {
         }
 
         let caller = rt.message().caller();
-        let caller_status: ext::miner::IsControllingAddressReturn =
-            deserialize_block(extract_send_result(rt.send_simple(
-                &Address::new_id(provider_id),
-                ext::miner::IS_CONTROLLING_ADDRESS_EXPORTED,
-                IpldBlock::serialize_cbor(&ext::miner::IsControllingAddressParam {
-                    address: caller,
-                })?,
-                TokenAmount::zero(),
-            ))?)?;
-        if !caller_status.is_controlling {
+        if !is_controlling_address(rt, provider_id, caller)? {
             return Err(actor_error!(
                 forbidden,
                 "caller {} is not worker or control address of provider {}",
@@ -236,15 +434,21 @@ impl Actor {
             ));
         }
         // Deals that passed `AuthenticateMessage` and other state-less checks.
-        let mut validity_index: Vec<bool> = Vec::with_capacity(params.deals.len());
+        let mut validity_index: Vec<bool> = Vec::with_capacity(deals.len());
 
         let baseline_power = request_current_baseline_power(rt)?;
         let (network_raw_power, _) = request_current_network_power(rt)?;
 
         // We perform these checks before loading state since the call to `AuthenticateMessage` could recurse
-        for (di, deal) in params.deals.iter().enumerate() {
-            let valid = if let Err(e) = validate_deal(rt, deal, &network_raw_power, &baseline_power)
-            {
+        for (di, deal) in deals.iter().enumerate() {
+            let valid = if let Err(e) = validate_deal(
+                rt,
+                &deal.deal,
+                deal.payment_token.as_ref(),
+                deal.replicated.as_ref(),
+                &network_raw_power,
+                &baseline_power,
+            ) {
                 info!("invalid deal {}: {}", di, e);
                 false
             } else {
@@ -258,13 +462,19 @@ impl Actor {
             proposal: DealProposal,
             serialized_proposal: RawBytes,
             cid: Cid,
+            payment_token: Option<ActorID>,
+            // CID and replica count of the replicated proposal, if the deal is a replica.
+            replica_of: Option<(Cid, u64)>,
         }
 
         // Deals that passed validation.
-        let mut valid_deals: Vec<ValidDeal> = Vec::with_capacity(params.deals.len());
+        let mut valid_deals: Vec<ValidDeal> = Vec::with_capacity(deals.len());
         // CIDs of valid proposals.
         let mut proposal_cid_lookup = BTreeSet::new();
         let mut total_client_lockup: BTreeMap<ActorID, TokenAmount> = BTreeMap::new();
+        // Storage fees to lock for each client, keyed by payment token and client.
+        let mut total_client_token_lockup: BTreeMap<(ActorID, ActorID), TokenAmount> =
+            BTreeMap::new();
         // Client datacap balance remaining after allocations for deals processed so far.
         let mut client_datacap_remaining: BTreeMap<ActorID, TokenAmount> = BTreeMap::new();
         // Verified allocation requests to make for each client, paired with the proposal CID.
@@ -277,7 +487,9 @@ impl Actor {
 
         let state: State = rt.state()?;
 
-        for (di, mut deal) in params.deals.into_iter().enumerate() {
+        for (di, PublishedDeal { mut deal, payment_token, replicated }) in
+            deals.into_iter().enumerate()
+        {
             if !*validity_index.get(di).context_code(
                 ExitCode::USR_ASSERTION_FAILED,
                 "validity index has incorrect length",
@@ -305,10 +517,48 @@ impl Actor {
                 }
             };
 
+            // The datacap token cannot be held in escrow, so cannot pay for storage.
+            let payment_token = match payment_token {
+                None => None,
+                Some(token) => match rt.resolve_address(&token) {
+                    Some(token_id) if Address::new_id(token_id) != DATACAP_TOKEN_ACTOR_ADDR => {
+                        Some(token_id)
+                    }
+                    _ => {
+                        info!("invalid deal {}: cannot pay for storage in token {}", di, token);
+                        continue;
+                    }
+                },
+            };
+
             // drop deals with insufficient lock up to cover costs
             let mut client_lockup =
                 total_client_lockup.get(&client_id).cloned().unwrap_or_default();
-            client_lockup += deal.proposal.client_balance_requirement();
+            let mut client_token_lockup = None;
+            match payment_token {
+                None => client_lockup += deal.proposal.client_balance_requirement(),
+                Some(token) => {
+                    client_lockup += &deal.proposal.client_collateral;
+                    let mut token_lockup = total_client_token_lockup
+                        .get(&(token, client_id))
+                        .cloned()
+                        .unwrap_or_default();
+                    token_lockup += deal.proposal.total_storage_fee();
+                    if !state.token_balance_covered(
+                        rt.store(),
+                        token,
+                        Address::new_id(client_id),
+                        &token_lockup,
+                    )? {
+                        info!(
+                            "invalid deal: {}: insufficient client tokens to cover proposal cost",
+                            di
+                        );
+                        continue;
+                    }
+                    client_token_lockup = Some(((token, client_id), token_lockup));
+                }
+            }
 
             let client_balance_ok =
                 state.balance_covered(rt.store(), Address::new_id(client_id), &client_lockup)?;
@@ -353,6 +603,27 @@ impl Actor {
                 continue;
             }
 
+            // Drop replicas of proposals whose replicas have all been published, or of which
+            // the provider has already published a replica.
+            // A provider can claim a proposal only once per batch, as a duplicate deal.
+            let replica_of = match replicated {
+                None => None,
+                Some(replicated) => {
+                    let rcid = replicated_deal_cid(rt, &replicated)?;
+                    if let Some(published) = state.get_replica_deals(rt.store(), &rcid)? {
+                        if published.deals.len() as u64 >= published.replicas {
+                            info!("invalid deal {}: all replicas already published", di);
+                            continue;
+                        }
+                        if published.deals.iter().any(|d| d.provider == provider_id) {
+                            info!("invalid deal {}: provider already published a replica", di);
+                            continue;
+                        }
+                    }
+                    Some((rcid, replicated.replicas))
+                }
+            };
+
             // Fetch each client's datacap balance and calculate the amount of datacap required for
             // each client's verified deals.
             // Drop any verified deals for which the client has insufficient datacap.
@@ -380,8 +651,17 @@ impl Actor {
 
             total_provider_lockup = provider_lockup;
             total_client_lockup.insert(client_id, client_lockup);
+            if let Some((key, token_lockup)) = client_token_lockup {
+                total_client_token_lockup.insert(key, token_lockup);
+            }
             proposal_cid_lookup.insert(pcid);
-            valid_deals.push(ValidDeal { proposal: deal.proposal, serialized_proposal, cid: pcid });
+            valid_deals.push(ValidDeal {
+                proposal: deal.proposal,
+                serialized_proposal,
+                cid: pcid,
+                payment_token,
+                replica_of,
+            });
             valid_input_bf.set(di as u64)
         }
 
@@ -428,11 +708,17 @@ impl Actor {
             let mut deal_proposals: Vec<(DealID, DealProposal)> = vec![];
             let mut deals_by_epoch: Vec<(ChainEpoch, DealID)> = vec![];
             let mut pending_deal_allocation_ids: Vec<(DealID, AllocationID)> = vec![];
+            let mut deal_payment_tokens: Vec<(DealID, ActorID)> = vec![];
+            let mut replica_deals: Vec<(Cid, u64, ChainEpoch, ReplicaDeal)> = vec![];
 
             // All storage dealProposals will be added in an atomic transaction; this operation will be unrolled if any of them fails.
             // This should only fail on programmer error because all expected invalid conditions should be filtered in the first set of checks.
             for valid_deal in valid_deals.iter() {
-                st.lock_client_and_provider_balances(rt.store(), &valid_deal.proposal)?;
+                st.lock_client_and_provider_balances(
+                    rt.store(),
+                    &valid_deal.proposal,
+                    valid_deal.payment_token,
+                )?;
 
                 // Store the proposal CID in pending deals set.
                 pending_deals.push(valid_deal.cid);
@@ -447,6 +733,19 @@ impl Actor {
                     pending_deal_allocation_ids.push((deal_id, *alloc_id));
                 }
 
+                if let Some(token) = valid_deal.payment_token {
+                    deal_payment_tokens.push((deal_id, token));
+                }
+
+                if let Some((rcid, replicas)) = valid_deal.replica_of {
+                    replica_deals.push((
+                        rcid,
+                        replicas,
+                        valid_deal.proposal.start_epoch,
+                        ReplicaDeal { provider: provider_id, deal_id },
+                    ));
+                }
+
                 // Randomize the first epoch for when the deal will be processed so an attacker isn't able to
                 // schedule too many deals for the same tick.
                 deals_by_epoch.push((
@@ -464,6 +763,8 @@ impl Actor {
             st.put_pending_deals(rt.store(), &pending_deals)?;
             st.put_deal_proposals(rt.store(), &deal_proposals)?;
             st.put_pending_deal_allocation_ids(rt.store(), &pending_deal_allocation_ids)?;
+            st.put_deal_payment_tokens(rt.store(), &deal_payment_tokens)?;
+            st.put_replica_deals(rt.store(), &replica_deals)?;
             st.put_deals_by_epoch(rt.store(), &deals_by_epoch)?;
             Ok(())
         })?;
@@ -809,6 +1110,7 @@ impl Actor {
             )?;
 
             let mut total_slashed = TokenAmount::zero();
+            let mut removed_proposals = Vec::new();
             for id in all_deal_ids {
                 let deal = proposals
                     .get(id)
@@ -834,8 +1136,11 @@ impl Actor {
                     ));
                 }
 
+                // An extended deal is slashed according to its current terms.
+                let deal_terms = st.extended_proposal(rt.store(), id, deal.clone())?;
+
                 // do not slash expired deals
-                if deal.end_epoch <= params.epoch {
+                if deal_terms.end_epoch <= params.epoch {
                     info!("deal {} expired, not slashing", id);
                     continue;
                 }
@@ -861,7 +1166,7 @@ impl Actor {
                 }
 
                 state.slash_epoch = params.epoch;
-                total_slashed += st.process_slashed_deal(rt.store(), &deal, &state)?;
+                total_slashed += st.process_slashed_deal(rt.store(), id, &deal_terms, &state)?;
                 st.remove_completed_deal(rt.store(), id)?;
 
                 emit::deal_terminated(
@@ -870,7 +1175,9 @@ impl Actor {
                     deal.client.id().unwrap(),
                     deal.provider.id().unwrap(),
                 )?;
+                removed_proposals.push((id, deal));
             }
+            st.unindex_party_deals(rt.store(), &removed_proposals)?;
 
             Ok(total_slashed)
         })?;
@@ -896,6 +1203,7 @@ impl Actor {
             let last_cron = st.last_cron;
             let mut provider_deals_to_remove =
                 BTreeMap::<ActorID, BTreeMap<SectorNumber, Vec<DealID>>>::new();
+            let mut removed_proposals: Vec<(DealID, DealProposal)> = Vec::new();
             let mut new_updates_scheduled: BTreeMap<ChainEpoch, Vec<DealID>> = BTreeMap::new();
             let mut epochs_completed: Vec<ChainEpoch> = vec![];
 
@@ -922,6 +1230,7 @@ impl Actor {
                         LoadDealState::Loaded(state) => state,
                         LoadDealState::ProposalExpired(expiration_penalty) => {
                             amount_slashed += expiration_penalty;
+                            removed_proposals.push((deal_id, deal_proposal));
                             continue;
                         }
                         LoadDealState::TooEarly => {
@@ -951,11 +1260,14 @@ impl Actor {
                     // https://github.com/filecoin-project/builtin-actors/issues/1389
                     // handling of legacy deals is still done in cron. we handle such deals here and continue to
                     // reschedule them. eventually, all legacy deals will expire and the below code can be removed.
+                    let deal_terms =
+                        st.extended_proposal(rt.store(), deal_id, deal_proposal.clone())?;
                     let (slash_amount, _payment_amount, completed, remove_deal) = st
                         .process_deal_update(
                             rt.store(),
+                            deal_id,
                             &state,
-                            &deal_proposal,
+                            &deal_terms,
                             &dcid,
                             curr_epoch,
                         )?;
@@ -984,6 +1296,7 @@ impl Actor {
                                 deal_proposal.provider.id().unwrap(),
                             )?;
                         }
+                        removed_proposals.push((deal_id, deal_proposal.clone()));
                     } else {
                         if !slash_amount.is_zero() {
                             return Err(actor_error!(
@@ -1022,7 +1335,9 @@ impl Actor {
             // Remove the provider->sector->deal mappings.
             // The sectors may still have other deals, so we can't remove the sector altogether.
             st.remove_sector_deal_ids(rt.store(), &provider_deals_to_remove)?;
+            st.unindex_party_deals(rt.store(), &removed_proposals)?;
             st.remove_deals_by_epoch(rt.store(), &epochs_completed)?;
+            st.remove_replica_deals_by_epoch(rt.store(), &epochs_completed)?;
             st.put_batch_deals_by_epoch(rt.store(), &new_updates_scheduled)?;
             st.last_cron = rt.curr_epoch();
             Ok(())
@@ -1082,23 +1397,116 @@ impl Actor {
     }
 
     /// Returns the start epoch and duration (in epochs) of a deal proposal.
+    /// The duration reflects any extension of the deal's term.
     fn get_deal_term(
         rt: &impl Runtime,
         params: GetDealTermParams,
     ) -> Result<GetDealTermReturn, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
-        let found = rt.state::<State>()?.get_proposal(rt.store(), params.id)?;
+        let st = rt.state::<State>()?;
+        let found = st.get_proposal(rt.store(), params.id)?;
+        let found = st.extended_proposal(rt.store(), params.id, found)?;
         Ok(GetDealTermReturn { start: found.start_epoch, duration: found.duration() })
     }
 
-    /// Returns the total price that will be paid from the client to the provider for this deal.
+    /// Returns the total price that will be paid from the client to the provider for this deal,
+    /// in the deal's payment token (see `get_deal_payment_token`).
+    /// The price reflects any extension of the deal's term.
     fn get_deal_total_price(
         rt: &impl Runtime,
         params: GetDealTotalPriceParams,
     ) -> Result<GetDealTotalPriceReturn, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
-        let found = rt.state::<State>()?.get_proposal(rt.store(), params.id)?;
-        Ok(GetDealTotalPriceReturn { total_price: found.total_storage_fee() })
+        let st = rt.state::<State>()?;
+        let found = st.get_proposal(rt.store(), params.id)?;
+        let total_price = match st.find_deal_extension(rt.store(), params.id)? {
+            Some(extension) => extension.total_storage_fee(),
+            None => found.total_storage_fee(),
+        };
+        Ok(GetDealTotalPriceReturn { total_price })
+    }
+
+    /// Returns the FRC-46 token in which a deal's storage fee is paid, if not paid in FIL.
+    /// Any actor may act as a token (see `universal_receiver_hook`), so the token should be
+    /// checked before the deal's payments are relied upon.
+    fn get_deal_payment_token(
+        rt: &impl Runtime,
+        params: GetDealPaymentTokenParams,
+    ) -> Result<GetDealPaymentTokenReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st = rt.state::<State>()?;
+        // Check the deal exists.
+        st.get_proposal(rt.store(), params.id)?;
+        let token = st.find_payment_token(rt.store(), params.id)?;
+        Ok(GetDealPaymentTokenReturn { token })
+    }
+
+    /// Lists a page of the IDs of a client's deals, in ID order.
+    /// Callers continue from the returned cursor until it is empty to list all the client's deals.
+    fn get_client_deals(
+        rt: &impl Runtime,
+        params: GetClientDealsParams,
+    ) -> Result<GetClientDealsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st = rt.state::<State>()?;
+        let (client, limit) = resolve_list_deals_params(rt, &params)?;
+        let (deals, next_cursor) =
+            st.list_client_deals(rt.store(), client, params.cursor, limit)?;
+        Ok(ListDealsReturn { deals, next_cursor })
+    }
+
+    /// Lists a page of the IDs of a provider's deals, in ID order.
+    /// Callers continue from the returned cursor until it is empty to list all the provider's deals.
+    fn get_provider_deals(
+        rt: &impl Runtime,
+        params: GetProviderDealsParams,
+    ) -> Result<GetProviderDealsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st = rt.state::<State>()?;
+        let (provider, limit) = resolve_list_deals_params(rt, &params)?;
+        let (deals, next_cursor) =
+            st.list_provider_deals(rt.store(), provider, params.cursor, limit)?;
+        Ok(ListDealsReturn { deals, next_cursor })
+    }
+
+    /// Lists a page of summaries of a client's deals.
+    fn get_client_deal_summaries(
+        rt: &impl Runtime,
+        params: GetClientDealSummariesParams,
+    ) -> Result<GetClientDealSummariesReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st = rt.state::<State>()?;
+        let (client, limit) = resolve_list_deals_params(rt, &params)?;
+        let (deals, next_cursor) =
+            st.list_client_deals(rt.store(), client, params.cursor, limit)?;
+        let deals = summarize_deals(rt, &st, &deals)?;
+        Ok(ListDealSummariesReturn { deals, next_cursor })
+    }
+
+    /// Lists a page of summaries of a provider's deals.
+    fn get_provider_deal_summaries(
+        rt: &impl Runtime,
+        params: GetProviderDealSummariesParams,
+    ) -> Result<GetProviderDealSummariesReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st = rt.state::<State>()?;
+        let (provider, limit) = resolve_list_deals_params(rt, &params)?;
+        let (deals, next_cursor) =
+            st.list_provider_deals(rt.store(), provider, params.cursor, limit)?;
+        let deals = summarize_deals(rt, &st, &deals)?;
+        Ok(ListDealSummariesReturn { deals, next_cursor })
+    }
+
+    /// Returns the replica deals published from a replicated deal proposal.
+    fn get_replica_deals(
+        rt: &impl Runtime,
+        params: GetReplicaDealsParams,
+    ) -> Result<GetReplicaDealsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        st.get_replica_deals(rt.store(), &params.proposal_cid)?.ok_or_else(|| {
+            actor_error!(not_found, "no replicas published for proposal {}", params.proposal_cid)
+        })
     }
 
     /// Returns the client collateral requirement for a deal proposal.
@@ -1232,6 +1640,7 @@ impl Actor {
             let mut new_deal_states: Vec<(DealID, DealState)> = Vec::new();
             let mut provider_deals_to_remove =
                 BTreeMap::<ActorID, BTreeMap<SectorNumber, Vec<DealID>>>::new();
+            let mut removed_proposals: Vec<(DealID, DealProposal)> = Vec::new();
             for deal_id in params.deal_ids.iter() {
                 let deal_proposal = match st.get_proposal(rt.store(), deal_id) {
                     Ok(prop) => prop,
@@ -1275,6 +1684,7 @@ impl Actor {
                     LoadDealState::ProposalExpired(penalty) => {
                         // deal proposal was not activated in time
                         total_slashed += penalty;
+                        removed_proposals.push((deal_id, deal_proposal));
                         batch_gen.add_fail(EX_DEAL_EXPIRED);
                         continue;
                     }
@@ -1291,10 +1701,19 @@ impl Actor {
                     ));
                 }
 
+                let deal_terms =
+                    match st.extended_proposal(rt.store(), deal_id, deal_proposal.clone()) {
+                        Ok(terms) => terms,
+                        Err(e) => {
+                            batch_gen.add_fail(e.exit_code());
+                            continue;
+                        }
+                    };
                 let (_, payment_amount, completed, remove_deal) = match st.process_deal_update(
                     rt.store(),
+                    deal_id,
                     &deal_state,
-                    &deal_proposal,
+                    &deal_terms,
                     &dcid,
                     curr_epoch,
                 ) {
@@ -1322,6 +1741,7 @@ impl Actor {
                             deal_proposal.provider.id().unwrap(),
                         )?;
                     }
+                    removed_proposals.push((deal_id, deal_proposal.clone()));
                 } else {
                     deal_state.last_updated_epoch = curr_epoch;
                     new_deal_states.push((deal_id, deal_state));
@@ -1345,6 +1765,7 @@ impl Actor {
 
             st.put_deal_states(rt.store(), &new_deal_states)?;
             st.remove_sector_deal_ids(rt.store(), &provider_deals_to_remove)?;
+            st.unindex_party_deals(rt.store(), &removed_proposals)?;
             Ok(())
         })?;
 
@@ -1359,6 +1780,261 @@ impl Actor {
 
         Ok(SettleDealPaymentsReturn { results: batch_gen.gen(), settlements })
     }
+
+    /// Extends the terms of activated deals, as agreed by their clients and providers.
+    /// Each extension is signed by the deal's client and must be submitted by the worker
+    /// or a control address of the deal's provider.
+    /// An extension sets a later end epoch for the deal, which must not exceed the expiration
+    /// of the sector holding it, and the price per epoch for the remainder of the deal's term.
+    /// The deal is settled up to the current epoch, and the client's locked storage fee is
+    /// adjusted to cover the remainder of the revised term.
+    /// Extensions succeed or fail independently.
+    fn extend_deal_terms(
+        rt: &impl Runtime,
+        params: ExtendDealTermsParams,
+    ) -> Result<ExtendDealTermsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let caller = rt.message().caller();
+        let curr_epoch = rt.curr_epoch();
+
+        // We perform these checks before loading state for update since the calls to
+        // `AuthenticateMessage` and the providers could recurse.
+        let st: State = rt.state()?;
+        let mut codes = Vec::with_capacity(params.extensions.len());
+        let mut extended_deals = BTreeSet::new();
+        let mut controlling_providers = BTreeMap::new();
+        for extension in &params.extensions {
+            let deal_id = extension.proposal.deal_id;
+            let code = if !extended_deals.insert(deal_id) {
+                info!("invalid extension: deal {} is extended more than once", deal_id);
+                ExitCode::USR_ILLEGAL_ARGUMENT
+            } else if let Err(e) = validate_deal_extension(
+                rt,
+                &st,
+                extension,
+                &caller,
+                curr_epoch,
+                &mut controlling_providers,
+            ) {
+                info!("invalid extension of deal {}: {}", deal_id, e);
+                e.exit_code()
+            } else {
+                ExitCode::OK
+            };
+            codes.push(code);
+        }
+
+        rt.transaction(|st: &mut State, rt| {
+            for (extension, code) in params.extensions.iter().zip(codes.iter_mut()) {
+                if !code.is_success() {
+                    continue;
+                }
+                let extension = &extension.proposal;
+                let deal_id = extension.deal_id;
+                let proposal = st.get_proposal(rt.store(), deal_id)?;
+                let mut deal_state = st
+                    .find_deal_state(rt.store(), deal_id)?
+                    .ok_or_else(|| actor_error!(illegal_state, "no state for deal {}", deal_id))?;
+                let current = st.extended_proposal(rt.store(), deal_id, proposal.clone())?;
+
+                match st.extend_deal_term(
+                    rt.store(),
+                    deal_id,
+                    &proposal,
+                    extension.end_epoch,
+                    &extension.storage_price_per_epoch,
+                    curr_epoch,
+                ) {
+                    Ok(()) => {}
+                    Err(e) if e.exit_code() == ExitCode::USR_INSUFFICIENT_FUNDS => {
+                        info!("invalid extension of deal {}: {}", deal_id, e);
+                        *code = e.exit_code();
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                }
+
+                // Settle the epochs up to the current one at the deal's previous terms.
+                let dcid = deal_cid(rt, &proposal)?;
+                let (_, _, _, remove_deal) = st.process_deal_update(
+                    rt.store(),
+                    deal_id,
+                    &deal_state,
+                    &current,
+                    &dcid,
+                    curr_epoch,
+                )?;
+                if remove_deal {
+                    return Err(actor_error!(
+                        illegal_state,
+                        "extended deal {} completed at settlement",
+                        deal_id
+                    ));
+                }
+                deal_state.last_updated_epoch = curr_epoch;
+                st.put_deal_states(rt.store(), &[(deal_id, deal_state)])?;
+            }
+            Ok(())
+        })?;
+
+        Ok(ExtendDealTermsReturn { results: BatchReturn::of(&codes) })
+    }
+
+    /// Cancels activated deals early, as agreed by their clients and providers.
+    /// Each cancellation is signed by the deal's client and must be submitted by the worker
+    /// or a control address of the deal's provider.
+    /// The deal is settled up to the cancel epoch, after which the client's remaining storage
+    /// fee and both parties' collateral are unlocked without penalty, and the deal is removed.
+    /// The verified registry claim of a cancelled verified deal is removed, and the provider is
+    /// notified of the cancelled deal space so it can reduce the deal weights, and so the power,
+    /// of the sectors holding it. A verified deal can't be cancelled while the sector holding it
+    /// is faulty or not yet proven, since its power can't be reduced then.
+    /// Cancellations succeed or fail independently.
+    fn cancel_deals(
+        rt: &impl Runtime,
+        params: CancelDealsParams,
+    ) -> Result<CancelDealsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let caller = rt.message().caller();
+        let curr_epoch = rt.curr_epoch();
+
+        // We perform these checks before loading state for update since the calls to
+        // `AuthenticateMessage` and the providers could recurse.
+        let st: State = rt.state()?;
+        let mut codes = Vec::with_capacity(params.cancellations.len());
+        let mut cancelled_deals = BTreeSet::new();
+        let mut cancelled_claims = BTreeSet::new();
+        let mut controlling_providers = BTreeMap::new();
+        let mut active_sectors = BTreeMap::new();
+        for cancellation in &params.cancellations {
+            let deal_id = cancellation.proposal.deal_id;
+            let claim_id = cancellation.proposal.claim_id;
+            let code = if !cancelled_deals.insert(deal_id) {
+                info!("invalid cancellation: deal {} is cancelled more than once", deal_id);
+                ExitCode::USR_ILLEGAL_ARGUMENT
+            } else if claim_id != NO_ALLOCATION_ID && !cancelled_claims.insert(claim_id) {
+                info!("invalid cancellation: claim {} is cancelled more than once", claim_id);
+                ExitCode::USR_ILLEGAL_ARGUMENT
+            } else if let Err(e) = validate_deal_cancellation(
+                rt,
+                &st,
+                cancellation,
+                &caller,
+                curr_epoch,
+                &mut controlling_providers,
+                &mut active_sectors,
+            ) {
+                info!("invalid cancellation of deal {}: {}", deal_id, e);
+                e.exit_code()
+            } else {
+                ExitCode::OK
+            };
+            codes.push(code);
+        }
+
+        let mut cancelled_space: BTreeMap<ActorID, BTreeMap<SectorNumber, (u64, u64)>> =
+            BTreeMap::new();
+        let mut provider_claims: BTreeMap<ActorID, Vec<ClaimID>> = BTreeMap::new();
+        let mut cancelled = Vec::new();
+        rt.transaction(|st: &mut State, rt| {
+            let mut provider_deals_to_remove: BTreeMap<
+                ActorID,
+                BTreeMap<SectorNumber, Vec<DealID>>,
+            > = BTreeMap::new();
+            let mut removed_proposals = Vec::new();
+            for (cancellation, code) in params.cancellations.iter().zip(codes.iter()) {
+                if !code.is_success() {
+                    continue;
+                }
+                let cancellation = &cancellation.proposal;
+                let deal_id = cancellation.deal_id;
+                let proposal = st.get_proposal(rt.store(), deal_id)?;
+                let deal_state = st
+                    .find_deal_state(rt.store(), deal_id)?
+                    .ok_or_else(|| actor_error!(illegal_state, "no state for deal {}", deal_id))?;
+                let current = st.extended_proposal(rt.store(), deal_id, proposal.clone())?;
+
+                if deal_state.last_updated_epoch == EPOCH_UNDEFINED {
+                    st.remove_pending_deal(rt.store(), deal_cid(rt, &proposal)?)?;
+                }
+                st.process_deal_cancelled(
+                    rt.store(),
+                    deal_id,
+                    &current,
+                    &deal_state,
+                    cancellation.cancel_epoch,
+                )?;
+                st.remove_completed_deal(rt.store(), deal_id)?;
+
+                let provider = proposal.provider.id().unwrap();
+                provider_deals_to_remove
+                    .entry(provider)
+                    .or_default()
+                    .entry(deal_state.sector_number)
+                    .or_default()
+                    .push(deal_id);
+                let (deal_space, verified_deal_space) = cancelled_space
+                    .entry(provider)
+                    .or_default()
+                    .entry(deal_state.sector_number)
+                    .or_default();
+                if proposal.verified_deal {
+                    *verified_deal_space += proposal.piece_size.0;
+                    provider_claims.entry(provider).or_default().push(cancellation.claim_id);
+                } else {
+                    *deal_space += proposal.piece_size.0;
+                }
+                cancelled.push((deal_id, proposal.client.id().unwrap(), provider));
+                removed_proposals.push((deal_id, proposal));
+            }
+            st.remove_sector_deal_ids(rt.store(), &provider_deals_to_remove)?;
+            st.unindex_party_deals(rt.store(), &removed_proposals)?;
+            Ok(())
+        })?;
+
+        for (deal_id, client, provider) in cancelled {
+            emit::deal_cancelled(rt, deal_id, client, provider)?;
+        }
+
+        for (provider, claim_ids) in provider_claims {
+            extract_send_result(rt.send_simple(
+                &VERIFIED_REGISTRY_ACTOR_ADDR,
+                ext::verifreg::CANCEL_CLAIMS_METHOD,
+                IpldBlock::serialize_cbor(&ext::verifreg::CancelClaimsParams {
+                    provider,
+                    claim_ids,
+                })?,
+                TokenAmount::zero(),
+            ))
+            .with_context(|| format!("failed to cancel claims of provider {}", provider))?;
+        }
+
+        for (provider, sectors) in cancelled_space {
+            let params = ext::miner::SectorDealsCancelledParams {
+                sectors: sectors
+                    .into_iter()
+                    .map(|(sector_number, (deal_space, verified_deal_space))| {
+                        ext::miner::SectorDealsCancellation {
+                            sector_number,
+                            deal_space,
+                            verified_deal_space,
+                        }
+                    })
+                    .collect(),
+            };
+            extract_send_result(rt.send_simple(
+                &Address::new_id(provider),
+                ext::miner::SECTOR_DEALS_CANCELLED,
+                IpldBlock::serialize_cbor(&params)?,
+                TokenAmount::zero(),
+            ))
+            .with_context(|| {
+                format!("failed to notify provider {} of cancelled deals", provider)
+            })?;
+        }
+
+        Ok(CancelDealsReturn { results: BatchReturn::of(&codes) })
+    }
 }
 
 fn get_proposals<BS: Blockstore>(
@@ -1481,6 +2157,260 @@ fn preactivate_deal<BS: Blockstore>(
     Ok(Ok(proposal))
 }
 
+// Validates an extension of an activated deal's term, given the market state before it is applied.
+fn validate_deal_extension(
+    rt: &impl Runtime,
+    st: &State,
+    extension: &ClientDealExtensionProposal,
+    caller: &Address,
+    curr_epoch: ChainEpoch,
+    controlling_providers: &mut BTreeMap<ActorID, bool>,
+) -> Result<(), ActorError> {
+    let ext = &extension.proposal;
+    let proposal = st.get_proposal(rt.store(), ext.deal_id)?;
+    let deal_state = st.find_deal_state(rt.store(), ext.deal_id)?.ok_or_else(|| {
+        ActorError::unchecked(
+            EX_DEAL_NOT_ACTIVATED,
+            format!("deal {} not yet activated", ext.deal_id),
+        )
+    })?;
+    if deal_state.slash_epoch != EPOCH_UNDEFINED {
+        return Err(ActorError::unchecked(
+            EX_DEAL_EXPIRED,
+            format!("deal {} is marked for termination", ext.deal_id),
+        ));
+    }
+    if curr_epoch > ext.expiration {
+        return Err(actor_error!(
+            illegal_argument,
+            "extension of deal {} expired at {}",
+            ext.deal_id,
+            ext.expiration
+        ));
+    }
+    let current = st.extended_proposal(rt.store(), ext.deal_id, proposal.clone())?;
+    if curr_epoch >= current.end_epoch {
+        return Err(ActorError::unchecked(
+            EX_DEAL_EXPIRED,
+            format!("deal {} ended at {}", ext.deal_id, current.end_epoch),
+        ));
+    }
+    if ext.end_epoch <= current.end_epoch {
+        return Err(actor_error!(
+            illegal_argument,
+            "end epoch {} does not extend current end epoch {}",
+            ext.end_epoch,
+            current.end_epoch
+        ));
+    }
+    let duration = ext.end_epoch - proposal.start_epoch;
+    let (_, max_duration) = deal_duration_bounds(proposal.piece_size);
+    if duration > max_duration {
+        return Err(actor_error!(
+            illegal_argument,
+            "extended duration {} exceeds maximum {}",
+            duration,
+            max_duration
+        ));
+    }
+    let (min_price, max_price) = deal_price_per_epoch_bounds(proposal.piece_size, duration);
+    if ext.storage_price_per_epoch < min_price || &ext.storage_price_per_epoch > max_price {
+        return Err(actor_error!(illegal_argument, "Storage price out of bounds."));
+    }
+
+    let provider_id = proposal.provider.id().unwrap();
+    let controlling = match controlling_providers.get(&provider_id) {
+        Some(controlling) => *controlling,
+        None => {
+            let controlling = is_controlling_address(rt, provider_id, *caller)?;
+            controlling_providers.insert(provider_id, controlling);
+            controlling
+        }
+    };
+    if !controlling {
+        return Err(actor_error!(
+            forbidden,
+            "caller {} is not worker or control address of provider {}",
+            caller,
+            provider_id
+        ));
+    }
+
+    authenticate_client_message(
+        rt,
+        &proposal.client,
+        &extension.client_signature,
+        extension.eip712_signature,
+        || serialize_vec(ext, "deal extension proposal"),
+        || deal_extension_eip712_message(rt, rt.chain_id().into(), ext),
+    )
+    .context("extension authentication failed")?;
+
+    let sector_expiration = request_sector_expiration(rt, provider_id, deal_state.sector_number)?;
+    if ext.end_epoch > sector_expiration {
+        return Err(actor_error!(
+            illegal_argument,
+            "end epoch {} exceeds sector {} expiration {}",
+            ext.end_epoch,
+            deal_state.sector_number,
+            sector_expiration
+        ));
+    }
+    Ok(())
+}
+
+// Validates an early cancellation of an activated deal, given the market state before it is applied.
+fn validate_deal_cancellation(
+    rt: &impl Runtime,
+    st: &State,
+    cancellation: &ClientDealCancellationProposal,
+    caller: &Address,
+    curr_epoch: ChainEpoch,
+    controlling_providers: &mut BTreeMap<ActorID, bool>,
+    active_sectors: &mut BTreeMap<(ActorID, SectorNumber), bool>,
+) -> Result<(), ActorError> {
+    let cancel = &cancellation.proposal;
+    let proposal = st.get_proposal(rt.store(), cancel.deal_id)?;
+    let deal_state = st.find_deal_state(rt.store(), cancel.deal_id)?.ok_or_else(|| {
+        ActorError::unchecked(
+            EX_DEAL_NOT_ACTIVATED,
+            format!("deal {} not yet activated", cancel.deal_id),
+        )
+    })?;
+    if deal_state.slash_epoch != EPOCH_UNDEFINED {
+        return Err(ActorError::unchecked(
+            EX_DEAL_EXPIRED,
+            format!("deal {} is marked for termination", cancel.deal_id),
+        ));
+    }
+    let current = st.extended_proposal(rt.store(), cancel.deal_id, proposal.clone())?;
+    if curr_epoch >= current.end_epoch {
+        return Err(ActorError::unchecked(
+            EX_DEAL_EXPIRED,
+            format!("deal {} ended at {}", cancel.deal_id, current.end_epoch),
+        ));
+    }
+    if curr_epoch > cancel.expiration {
+        return Err(actor_error!(
+            illegal_argument,
+            "cancellation of deal {} expired at {}",
+            cancel.deal_id,
+            cancel.expiration
+        ));
+    }
+    if cancel.cancel_epoch > curr_epoch {
+        return Err(actor_error!(
+            illegal_argument,
+            "cancel epoch {} is in the future at {}",
+            cancel.cancel_epoch,
+            curr_epoch
+        ));
+    }
+    let settled_epoch = max(deal_state.last_updated_epoch, deal_state.sector_start_epoch);
+    if cancel.cancel_epoch < settled_epoch {
+        return Err(actor_error!(
+            illegal_argument,
+            "cancel epoch {} precedes deal settled or activated at {}",
+            cancel.cancel_epoch,
+            settled_epoch
+        ));
+    }
+
+    let provider_id = proposal.provider.id().unwrap();
+    let controlling = match controlling_providers.get(&provider_id) {
+        Some(controlling) => *controlling,
+        None => {
+            let controlling = is_controlling_address(rt, provider_id, *caller)?;
+            controlling_providers.insert(provider_id, controlling);
+            controlling
+        }
+    };
+    if !controlling {
+        return Err(actor_error!(
+            forbidden,
+            "caller {} is not worker or control address of provider {}",
+            caller,
+            provider_id
+        ));
+    }
+
+    if proposal.verified_deal {
+        validate_cancelled_claim(rt, cancel, &proposal, deal_state.sector_number)?;
+        let sector = (provider_id, deal_state.sector_number);
+        let active = match active_sectors.get(&sector) {
+            Some(active) => *active,
+            None => {
+                let active = is_sector_active(rt, provider_id, deal_state.sector_number)?;
+                active_sectors.insert(sector, active);
+                active
+            }
+        };
+        if !active {
+            return Err(actor_error!(
+                forbidden,
+                "sector {} holding verified deal {} is faulty or not yet proven",
+                deal_state.sector_number,
+                cancel.deal_id
+            ));
+        }
+    } else if cancel.claim_id != NO_ALLOCATION_ID {
+        return Err(actor_error!(
+            illegal_argument,
+            "unverified deal {} has no claim {}",
+            cancel.deal_id,
+            cancel.claim_id
+        ));
+    }
+
+    authenticate_client_message(
+        rt,
+        &proposal.client,
+        &cancellation.client_signature,
+        cancellation.eip712_signature,
+        || serialize_vec(cancel, "deal cancellation proposal"),
+        || Ok(deal_cancellation_eip712_message(rt, rt.chain_id().into(), cancel)),
+    )
+    .context("cancellation authentication failed")
+}
+
+// Checks that the claim named by a cancellation is the verified registry's claim for the
+// cancelled deal's data, in the sector holding the deal.
+fn validate_cancelled_claim(
+    rt: &impl Runtime,
+    cancel: &DealCancellationProposal,
+    proposal: &DealProposal,
+    sector_number: SectorNumber,
+) -> Result<(), ActorError> {
+    let provider = proposal.provider.id().unwrap();
+    let ret: ext::verifreg::GetClaimsReturn =
+        deserialize_block(extract_send_result(rt.send_simple(
+            &VERIFIED_REGISTRY_ACTOR_ADDR,
+            ext::verifreg::GET_CLAIMS_METHOD,
+            IpldBlock::serialize_cbor(&ext::verifreg::GetClaimsParams {
+                provider,
+                claim_ids: vec![cancel.claim_id],
+            })?,
+            TokenAmount::zero(),
+        ))?)?;
+    let claim = ret.claims.first().ok_or_else(|| {
+        actor_error!(not_found, "no claim {} for provider {}", cancel.claim_id, provider)
+    })?;
+    if claim.client != proposal.client.id().unwrap()
+        || claim.data != proposal.piece_cid
+        || claim.size != proposal.piece_size
+        || claim.sector != sector_number
+    {
+        return Err(actor_error!(
+            illegal_argument,
+            "claim {} is not for deal {} in sector {}",
+            cancel.claim_id,
+            cancel.deal_id,
+            sector_number
+        ));
+    }
+    Ok(())
+}
+
 fn alloc_request_for_deal(
     // Deal proposal must have ID addresses
     deal: &DealProposal,
@@ -1612,13 +2542,44 @@ fn validate_deal_can_activate(
     Ok(())
 }
 
+// Validates an FRC-46 receiver hook payload, returning the tokens received.
+fn validate_tokens_received(
+    params: &UniversalReceiverParams,
+    my_id: u64,
+) -> Result<FRC46TokenReceived, ActorError> {
+    if params.type_ != FRC46_TOKEN_TYPE {
+        return Err(actor_error!(
+            illegal_argument,
+            "invalid token type {}, expected {} (FRC-46)",
+            params.type_,
+            FRC46_TOKEN_TYPE
+        ));
+    }
+    let payload: FRC46TokenReceived = deserialize(&params.payload, "receiver hook payload")?;
+    // Payload to address must match receiving actor.
+    if payload.to != my_id {
+        return Err(actor_error!(
+            illegal_argument,
+            "token receiver expected to {}, was {}",
+            my_id,
+            payload.to
+        ));
+    }
+    Ok(payload)
+}
+
 fn validate_deal(
     rt: &impl Runtime,
     deal: &ClientDealProposal,
+    payment_token: Option<&Address>,
+    replicated: Option<&ReplicatedDealProposal>,
     network_raw_power: &StoragePower,
     baseline_power: &StoragePower,
 ) -> Result<(), ActorError> {
-    deal_proposal_is_internally_valid(rt, deal)?;
+    if let Some(replicated) = replicated {
+        validate_replica(rt, &deal.proposal.provider, replicated)?;
+    }
+    deal_proposal_is_internally_valid(rt, deal, payment_token, replicated)?;
 
     let proposal = &deal.proposal;
 
@@ -1686,20 +2647,118 @@ fn validate_deal(
     Ok(())
 }
 
+/// Checks that a provider is allowed to publish a replica of a replicated proposal.
+fn validate_replica(
+    rt: &impl Runtime,
+    provider: &Address,
+    replicated: &ReplicatedDealProposal,
+) -> Result<(), ActorError> {
+    if replicated.replicas == 0 || replicated.replicas > replicated.providers.len() as u64 {
+        return Err(actor_error!(
+            illegal_argument,
+            "replica count {} must be positive and at most the number of providers {}",
+            replicated.replicas,
+            replicated.providers.len()
+        ));
+    }
+    let provider_id = rt.resolve_address(provider);
+    if provider_id.is_none()
+        || !replicated.providers.iter().any(|allowed| rt.resolve_address(allowed) == provider_id)
+    {
+        return Err(actor_error!(
+            forbidden,
+            "provider {} is not allowed to publish a replica",
+            provider
+        ));
+    }
+    Ok(())
+}
+
 fn deal_proposal_is_internally_valid(
     rt: &impl Runtime,
     proposal: &ClientDealProposal,
+    payment_token: Option<&Address>,
+    replicated: Option<&ReplicatedDealProposal>,
 ) -> Result<(), ActorError> {
-    let signature_bytes = proposal.client_signature.bytes.clone();
-    // Generate unsigned bytes
-    let proposal_bytes = serialize(&proposal.proposal, "deal proposal")?;
+    let client = &proposal.proposal.client;
+    let signature = &proposal.client_signature;
+    let eip712_signature = proposal.eip712_signature;
+    // A client signs a replicated proposal once for all replicas.
+    if let Some(replicated) = replicated {
+        return authenticate_client_message(
+            rt,
+            client,
+            signature,
+            eip712_signature,
+            || serialize_vec(replicated, "replicated deal proposal"),
+            || replicated_deal_proposal_eip712_message(rt, rt.chain_id().into(), replicated),
+        );
+    }
+    // A client signs a token deal together with its payment token.
+    if let Some(payment_token) = payment_token {
+        let token_proposal = TokenDealProposal {
+            proposal: proposal.proposal.clone(),
+            payment_token: *payment_token,
+        };
+        return authenticate_client_message(
+            rt,
+            client,
+            signature,
+            eip712_signature,
+            || serialize_vec(&token_proposal, "token deal proposal"),
+            || token_deal_proposal_eip712_message(rt, rt.chain_id().into(), &token_proposal),
+        );
+    }
+
+    authenticate_client_message(
+        rt,
+        client,
+        signature,
+        eip712_signature,
+        || serialize_vec(&proposal.proposal, "deal proposal"),
+        || deal_proposal_eip712_message(rt, rt.chain_id().into(), &proposal.proposal),
+    )
+}
 
+// Authenticates a message signed by a client. The client signs the message's CBOR encoding,
+// unless its signature is marked as EIP-712 (the `eip712_signature` field of the signed
+// proposals, omitted from their encoding when false), in which case it signs the message's
+// EIP-712 typed data encoding. Only a client with an Ethereum address may sign EIP-712 messages.
+fn authenticate_client_message(
+    rt: &impl Runtime,
+    client: &Address,
+    signature: &Signature,
+    eip712_signature: bool,
+    message: impl FnOnce() -> Result<Vec<u8>, ActorError>,
+    eip712_message: impl FnOnce() -> Result<Vec<u8>, ActorError>,
+) -> Result<(), ActorError> {
+    let message = if eip712_signature {
+        if !client_has_eth_address(rt, client) {
+            return Err(actor_error!(
+                illegal_argument,
+                "client {} has no Ethereum address to sign EIP-712 messages",
+                client
+            ));
+        }
+        eip712_message()?
+    } else {
+        message()?
+    };
+    authenticate_client(rt, client, signature, message)
+}
+
+fn authenticate_client(
+    rt: &impl Runtime,
+    client: &Address,
+    signature: &Signature,
+    message: Vec<u8>,
+) -> Result<(), ActorError> {
     if !extract_send_result(rt.send(
-        &proposal.proposal.client,
+        client,
         ext::account::AUTHENTICATE_MESSAGE_METHOD,
         IpldBlock::serialize_cbor(&ext::account::AuthenticateMessageParams {
-            signature: signature_bytes,
-            message: proposal_bytes.to_vec(),
+            signature: signature.bytes.clone(),
+            message,
         })?,
         TokenAmount::zero(),
         None,
@@ -1714,12 +2773,28 @@ fn deal_proposal_is_internally_valid(
     }
 }
 
+fn client_has_eth_address(rt: &impl Runtime, client: &Address) -> bool {
+    rt.resolve_address(client).and_then(|id| rt.lookup_delegated_address(id)).map_or(
+        false,
+        |addr| matches!(addr.payload(), Payload::Delegated(d) if d.namespace() == EAM_ACTOR_ID),
+    )
+}
+
 /// Compute a deal CID using the runtime.
 pub fn deal_cid(rt: &impl Runtime, proposal: &DealProposal) -> Result<Cid, ActorError> {
     let data = serialize(proposal, "deal proposal")?;
     serialized_deal_cid(rt, data.bytes())
 }
 
+/// Compute the CID of a replicated deal proposal using the runtime.
+pub fn replicated_deal_cid(
+    rt: &impl Runtime,
+    proposal: &ReplicatedDealProposal,
+) -> Result<Cid, ActorError> {
+    let data = serialize(proposal, "replicated deal proposal")?;
+    serialized_deal_cid(rt, data.bytes())
+}
+
 /// Compute a deal CID from serialized proposal using the runtime
 pub(crate) fn serialized_deal_cid(rt: &impl Runtime, data: &[u8]) -> Result<Cid, ActorError> {
     const DIGEST_SIZE: u32 = 32;
@@ -1744,6 +2819,99 @@ fn request_miner_control_addrs(
     Ok((addrs.owner, addrs.worker, addrs.control_addresses))
 }
 
+fn is_controlling_address(
+    rt: &impl Runtime,
+    miner_id: ActorID,
+    address: Address,
+) -> Result<bool, ActorError> {
+    let ret: ext::miner::IsControllingAddressReturn =
+        deserialize_block(extract_send_result(rt.send_simple(
+            &Address::new_id(miner_id),
+            ext::miner::IS_CONTROLLING_ADDRESS_EXPORTED,
+            IpldBlock::serialize_cbor(&ext::miner::IsControllingAddressParam { address })?,
+            TokenAmount::zero(),
+        ))?)?;
+    Ok(ret.is_controlling)
+}
+
+fn is_sector_active(
+    rt: &impl Runtime,
+    miner_id: ActorID,
+    sector_number: SectorNumber,
+) -> Result<bool, ActorError> {
+    let ret: ext::miner::GetSectorStatusReturn =
+        deserialize_block(extract_send_result(rt.send_simple(
+            &Address::new_id(miner_id),
+            ext::miner::GET_SECTOR_STATUS_EXPORTED,
+            IpldBlock::serialize_cbor(&ext::miner::GetSectorStatusParams { sector_number })?,
+            TokenAmount::zero(),
+        ))?)?;
+    Ok(ret.status == ext::miner::SECTOR_STATUS_ACTIVE)
+}
+
+// Resolves the party whose deals are listed, and checks the page size.
+fn resolve_list_deals_params(
+    rt: &impl Runtime,
+    params: &ListDealsParams,
+) -> Result<(ActorID, usize), ActorError> {
+    if params.limit == 0 || params.limit > MAX_LIST_DEALS_LIMIT {
+        return Err(actor_error!(
+            illegal_argument,
+            "limit {} must be between 1 and {}",
+            params.limit,
+            MAX_LIST_DEALS_LIMIT
+        ));
+    }
+    let party = rt.resolve_address(&params.party).ok_or_else(|| {
+        actor_error!(illegal_argument, "failed to resolve address {}", params.party)
+    })?;
+    Ok((party, params.limit as usize))
+}
+
+fn summarize_deals(
+    rt: &impl Runtime,
+    st: &State,
+    deal_ids: &[DealID],
+) -> Result<Vec<PartyDealSummary>, ActorError> {
+    deal_ids
+        .iter()
+        .map(|deal_id| {
+            let proposal = st.get_proposal(rt.store(), *deal_id)?;
+            let current = st.extended_proposal(rt.store(), *deal_id, proposal)?;
+            let activated = st
+                .find_deal_state(rt.store(), *deal_id)?
+                .map_or(EPOCH_UNDEFINED, |state| state.sector_start_epoch);
+            Ok(PartyDealSummary {
+                id: *deal_id,
+                client: current.client.id().unwrap(),
+                provider: current.provider.id().unwrap(),
+                piece_cid: current.piece_cid,
+                piece_size: current.piece_size,
+                verified_deal: current.verified_deal,
+                start_epoch: current.start_epoch,
+                end_epoch: current.end_epoch,
+                storage_price_per_epoch: current.storage_price_per_epoch,
+                activated,
+            })
+        })
+        .collect()
+}
+
+fn request_sector_expiration(
+    rt: &impl Runtime,
+    miner_id: ActorID,
+    sector_number: SectorNumber,
+) -> Result<ChainEpoch, ActorError> {
+    let ret: ext::miner::GetSectorExpirationReturn =
+        deserialize_block(extract_send_result(rt.send_simple(
+            &Address::new_id(miner_id),
+            ext::miner::GET_SECTOR_EXPIRATION_EXPORTED,
+            IpldBlock::serialize_cbor(&ext::miner::GetSectorExpirationParams { sector_number })?,
+            TokenAmount::zero(),
+        ))?)?;
+    Ok(ret.expiration)
+}
+
 /// Resolves a provider or client address to the canonical form against which a balance should be held, and
 /// the designated recipient address of withdrawals (which is the same, for simple account parties).
 fn escrow_address(
@@ -1835,6 +3003,19 @@ impl ActorCode for Actor {
         GetDealActivationExported => get_deal_activation,
         GetDealSectorExported => get_deal_sector,
         SettleDealPaymentsExported => settle_deal_payments,
+        ExtendDealTermsExported => extend_deal_terms,
+        PublishTokenDealsExported => publish_token_deals,
+        WithdrawTokenBalanceExported => withdraw_token_balance,
+        GetTokenBalanceExported => get_token_balance,
+        GetDealPaymentTokenExported => get_deal_payment_token,
+        CancelDealsExported => cancel_deals,
+        GetClientDealsExported => get_client_deals,
+        GetProviderDealsExported => get_provider_deals,
+        GetClientDealSummariesExported => get_client_deal_summaries,
+        GetProviderDealSummariesExported => get_provider_deal_summaries,
+        PublishReplicaDealsExported => publish_replica_deals,
+        GetReplicaDealsExported => get_replica_deals,
+        UniversalReceiverHook => universal_receiver_hook,
         SectorContentChangedExported => sector_content_changed,
     }
 }
@@ -26,7 +26,7 @@ use crate::ext::verifreg::AllocationID;
 
 use super::policy::*;
 use super::types::*;
-use super::{DealProposal, DealState, EX_DEAL_EXPIRED};
+use super::{DealExtension, DealProposal, DealState, ReplicaDeal, ReplicaDeals, EX_DEAL_EXPIRED};
 
 pub enum Reason {
     ClientCollateral,
@@ -35,6 +35,10 @@ pub enum Reason {
 }
 
 /// Market actor state
+///
+/// The fields from `deal_extensions` on were added in v15 and are required, so state written
+/// by v14 doesn't decode. The v15 upgrade's state migration must decode the v14 fields, and
+/// complete the state with `init_v15_fields`.
 #[derive(Clone, Default, Serialize_tuple, Deserialize_tuple, Debug)]
 pub struct State {
     /// Proposals are deals that have been proposed and not yet cleaned up after expiry or termination.
@@ -86,6 +90,62 @@ pub struct State {
     /// of multiple sectors all belonging to the same provider.
     /// HAMT[ActorID]HAMT[SectorNumber][]DealID
     pub provider_sectors: Cid,
+
+    /// Revised terms of activated deals whose term has been extended.
+    /// An entry is removed along with the deal's proposal.
+    /// HAMT[DealID]DealExtension
+    // * Added in v15
+    pub deal_extensions: Cid,
+
+    /// Escrow and locked balances of FRC-46 tokens in which deal storage fees are paid,
+    /// indexed by token actor.
+    /// HAMT[ActorID]TokenBalances
+    // * Added in v15
+    pub token_balances: Cid,
+
+    /// The token in which the storage fee of a deal is paid, for deals not paid in FIL.
+    /// An entry is removed along with the deal's proposal.
+    /// HAMT[DealID]ActorID
+    // * Added in v15
+    pub deal_payment_tokens: Cid,
+
+    /// Deal IDs indexed by client, supporting listing the deals of a client in deal ID order.
+    /// A deal is indexed with its proposal, and removed along with it.
+    /// Deals published before v15 are indexed by the upgrade with `init_v15_fields`.
+    /// HAMT[ActorID]AMT[DealID]
+    // * Added in v15
+    pub client_deals: Cid,
+
+    /// Deal IDs indexed by provider, supporting listing the deals of a provider in deal ID order.
+    /// A deal is indexed with its proposal, and removed along with it.
+    /// Deals published before v15 are indexed by the upgrade with `init_v15_fields`.
+    /// HAMT[ActorID]AMT[DealID]
+    // * Added in v15
+    pub provider_deals: Cid,
+
+    /// Replica deals published from each replicated deal proposal, by proposal CID.
+    /// Entries are retained until the proposal's start epoch so that a replica cannot be
+    /// published twice.
+    /// HAMT[Cid]ReplicaDeals
+    // * Added in v15
+    pub replica_deals: Cid,
+
+    /// Replicated deal proposal CIDs by the start epoch at which cron removes their replica deals.
+    /// HAMT[ChainEpoch]HAMT[Cid]
+    // * Added in v15
+    pub replica_deals_by_epoch: Cid,
+}
+
+/// Escrow and locked balances of an FRC-46 token.
+/// Only deal storage fees are paid in tokens, so all locked amounts are client storage fees.
+#[derive(Clone, Serialize_tuple, Deserialize_tuple, Debug, PartialEq, Eq)]
+pub struct TokenBalances {
+    /// Total amount held in escrow, indexed by actor address (including both locked and unlocked amounts).
+    pub escrow_table: Cid,
+    /// Amount locked, indexed by actor address.
+    pub locked_table: Cid,
+    /// Total storage fee that is locked in escrow -> unlocked when payments are made
+    pub total_client_storage_fee: TokenAmount,
 }
 
 pub type PendingProposalsSet<BS> = Set<BS, Cid>;
@@ -106,6 +166,34 @@ pub const PROVIDER_SECTORS_CONFIG: Config =
 pub type SectorDealsMap<BS> = Map2<BS, SectorNumber, Vec<DealID>>;
 pub const SECTOR_DEALS_CONFIG: Config = Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG };
 
+pub type DealExtensionsMap<BS> = Map2<BS, DealID, DealExtension>;
+pub const DEAL_EXTENSIONS_CONFIG: Config =
+    Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG };
+
+pub type TokenBalancesMap<BS> = Map2<BS, ActorID, TokenBalances>;
+pub const TOKEN_BALANCES_CONFIG: Config =
+    Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG };
+
+pub type DealPaymentTokensMap<BS> = Map2<BS, DealID, ActorID>;
+pub const DEAL_PAYMENT_TOKENS_CONFIG: Config =
+    Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG };
+
+pub type PartyDealsMap<BS> = Map2<BS, ActorID, Cid>;
+pub const PARTY_DEALS_CONFIG: Config = Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG };
+
+// A party's deal IDs, as the indexes of an AMT with empty values.
+pub type PartyDealIDs<'bs, BS> = Array<'bs, (), BS>;
+
+pub type ReplicaDealsMap<BS> = Map2<BS, Cid, ReplicaDeals>;
+pub const REPLICA_DEALS_CONFIG: Config =
+    Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG };
+
+pub type ReplicaDealsByEpoch<BS> = SetMultimap<BS, ChainEpoch, Cid>;
+pub const REPLICA_DEALS_BY_EPOCH_CONFIG: SetMultimapConfig = SetMultimapConfig {
+    outer: Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG },
+    inner: Config { bit_width: HAMT_BIT_WIDTH, ..DEFAULT_HAMT_CONFIG },
+};
+
 impl State {
     pub fn new<BS: Blockstore>(store: &BS) -> Result<Self, ActorError> {
         let empty_proposals_array =
@@ -137,7 +225,7 @@ impl State {
         let empty_sector_deals_hamt =
             ProviderSectorsMap::empty(store, PROVIDER_SECTORS_CONFIG, "sector deals").flush()?;
 
-        Ok(Self {
+        let mut st = Self {
             proposals: empty_proposals_array,
             states: empty_states_array,
             pending_proposals: empty_pending_proposals,
@@ -152,7 +240,45 @@ impl State {
             total_client_storage_fee: TokenAmount::default(),
             pending_deal_allocation_ids: empty_pending_deal_allocation_map,
             provider_sectors: empty_sector_deals_hamt,
-        })
+            ..Default::default()
+        };
+        st.init_v15_fields(store)?;
+        Ok(st)
+    }
+
+    /// Initializes the fields added in v15, overwriting their values, and indexes every deal
+    /// proposal in state by client and by provider.
+    /// This is the whole of the v15 upgrade's migration of market state, applied to state with
+    /// the v14 fields. Indexing is idempotent, so deals already indexed are unaffected.
+    pub fn init_v15_fields<BS: Blockstore>(&mut self, store: &BS) -> Result<(), ActorError> {
+        self.deal_extensions =
+            DealExtensionsMap::empty(store, DEAL_EXTENSIONS_CONFIG, "deal extensions").flush()?;
+        self.token_balances =
+            TokenBalancesMap::empty(store, TOKEN_BALANCES_CONFIG, "token balances").flush()?;
+        self.deal_payment_tokens =
+            DealPaymentTokensMap::empty(store, DEAL_PAYMENT_TOKENS_CONFIG, "deal payment tokens")
+                .flush()?;
+        let empty_party_deals =
+            PartyDealsMap::empty(store, PARTY_DEALS_CONFIG, "party deals").flush()?;
+        self.client_deals = empty_party_deals;
+        self.provider_deals = empty_party_deals;
+        self.replica_deals =
+            ReplicaDealsMap::empty(store, REPLICA_DEALS_CONFIG, "replica deals").flush()?;
+        self.replica_deals_by_epoch = ReplicaDealsByEpoch::empty(
+            store,
+            REPLICA_DEALS_BY_EPOCH_CONFIG,
+            "replica deals by epoch",
+        )
+        .flush()?;
+
+        let mut proposals = Vec::new();
+        self.load_proposals(store)?
+            .for_each(|deal_id, proposal| {
+                proposals.push((deal_id, proposal.clone()));
+                Ok(())
+            })
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to iterate deal proposals")?;
+        self.index_party_deals(store, &proposals)
     }
 
     pub fn get_total_locked(&self) -> TokenAmount {
@@ -255,6 +381,9 @@ impl State {
         find_proposal(&self.load_proposals(store)?, deal_id)
     }
 
+    /// Removes a deal proposal and its payment token.
+    /// The caller must also remove the deal from the client and provider indexes with
+    /// `unindex_party_deals`, batched across the deals removed in a transaction.
     pub fn remove_proposal<BS>(
         &mut self,
         store: &BS,
@@ -276,6 +405,7 @@ impl State {
             .flush()
             .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to flush deal proposals")?;
 
+        self.remove_deal_payment_token(store, deal_id)?;
         Ok(proposal)
     }
 
@@ -301,7 +431,7 @@ impl State {
             .flush()
             .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to flush deal proposals")?;
 
-        Ok(())
+        self.index_party_deals(store, new_deal_proposals)
     }
 
     pub fn load_pending_deal_allocation_ids<BS>(
@@ -716,6 +846,7 @@ impl State {
     }
 
     /// Delete proposal and state simultaneously.
+    /// The caller must also remove the deal from the client and provider indexes.
     pub fn remove_completed_deal<BS>(
         &mut self,
         store: &BS,
@@ -735,11 +866,526 @@ impl State {
                 "failed to delete deal proposal: does not exist"
             ));
         }
+        self.remove_deal_extension(store, deal_id)?;
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    // Deal extensions
+    ////////////////////////////////////////////////////////////////////////////////
+
+    pub fn load_deal_extensions<BS>(&self, store: BS) -> Result<DealExtensionsMap<BS>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        DealExtensionsMap::load(
+            store,
+            &self.deal_extensions,
+            DEAL_EXTENSIONS_CONFIG,
+            "deal extensions",
+        )
+    }
+
+    pub fn find_deal_extension<BS>(
+        &self,
+        store: &BS,
+        deal_id: DealID,
+    ) -> Result<Option<DealExtension>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        Ok(self.load_deal_extensions(store)?.get(&deal_id)?.cloned())
+    }
+
+    /// Returns a deal's proposal with the end epoch and price revised by any extension
+    /// of its term.
+    pub fn extended_proposal<BS>(
+        &self,
+        store: &BS,
+        deal_id: DealID,
+        proposal: DealProposal,
+    ) -> Result<DealProposal, ActorError>
+    where
+        BS: Blockstore,
+    {
+        Ok(match self.find_deal_extension(store, deal_id)? {
+            Some(extension) => extension.apply(&proposal),
+            None => proposal,
+        })
+    }
+
+    fn remove_deal_extension<BS>(&mut self, store: &BS, deal_id: DealID) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let mut extensions = self.load_deal_extensions(store)?;
+        if extensions.delete(&deal_id)?.is_some() {
+            self.deal_extensions = extensions.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Extends the term of an activated deal to a new end epoch and price per epoch,
+    /// which applies from the current epoch.
+    /// The deal must have been settled up to the current epoch, which must precede its end.
+    /// The client's locked storage fee is adjusted to cover the remainder of the revised term.
+    #[allow(clippy::too_many_arguments)]
+    pub fn extend_deal_term<BS>(
+        &mut self,
+        store: &BS,
+        deal_id: DealID,
+        proposal: &DealProposal,
+        end_epoch: ChainEpoch,
+        storage_price_per_epoch: &TokenAmount,
+        curr_epoch: ChainEpoch,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let (current, total_storage_fee) = match self.find_deal_extension(store, deal_id)? {
+            Some(extension) => (extension.apply(proposal), extension.total_storage_fee()),
+            None => (proposal.clone(), proposal.total_storage_fee()),
+        };
+        if end_epoch <= current.end_epoch {
+            return Err(actor_error!(
+                illegal_argument,
+                "deal {} end epoch {} does not extend current end epoch {}",
+                deal_id,
+                end_epoch,
+                current.end_epoch
+            ));
+        }
+        let repriced_epoch = max(curr_epoch, proposal.start_epoch);
+        let fee_remaining = deal_get_payment_remaining(&current, repriced_epoch)?;
+        let extension = DealExtension {
+            end_epoch,
+            storage_price_per_epoch: storage_price_per_epoch.clone(),
+            repriced_epoch,
+            prior_storage_fee: total_storage_fee - &fee_remaining,
+        };
+        let revised_fee_remaining = storage_price_per_epoch * (end_epoch - repriced_epoch);
+
+        let payment_token = self.find_payment_token(store, deal_id)?;
+        if revised_fee_remaining > fee_remaining {
+            let amount = &revised_fee_remaining - &fee_remaining;
+            self.lock_storage_fee(store, payment_token, &proposal.client, &amount)
+                .context("locking client storage fee")?;
+        } else {
+            self.unlock_storage_fee(
+                store,
+                payment_token,
+                &proposal.client,
+                &(&fee_remaining - &revised_fee_remaining),
+            )
+            .context("unlocking client storage fee")?;
+        }
+
+        let mut extensions = self.load_deal_extensions(store)?;
+        extensions.set(&deal_id, extension)?;
+        self.deal_extensions = extensions.flush()?;
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    // Payment tokens
+    ////////////////////////////////////////////////////////////////////////////////
+
+    pub fn load_token_balances<BS>(&self, store: BS) -> Result<TokenBalancesMap<BS>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        TokenBalancesMap::load(store, &self.token_balances, TOKEN_BALANCES_CONFIG, "token balances")
+    }
+
+    /// Returns the balances of a token, which are empty if the market has never held it.
+    pub fn get_token_balances<BS>(
+        &self,
+        store: &BS,
+        token: ActorID,
+    ) -> Result<TokenBalances, ActorError>
+    where
+        BS: Blockstore,
+    {
+        match self.load_token_balances(store)?.get(&token)? {
+            Some(balances) => Ok(balances.clone()),
+            None => {
+                let empty_balance_table = BalanceTable::new(store, "balance table").root()?;
+                Ok(TokenBalances {
+                    escrow_table: empty_balance_table,
+                    locked_table: empty_balance_table,
+                    total_client_storage_fee: TokenAmount::zero(),
+                })
+            }
+        }
+    }
+
+    fn put_token_balances<BS>(
+        &mut self,
+        store: &BS,
+        token: ActorID,
+        balances: TokenBalances,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let mut token_balances = self.load_token_balances(store)?;
+        token_balances.set(&token, balances)?;
+        self.token_balances = token_balances.flush()?;
+        Ok(())
+    }
+
+    pub fn add_balance_to_token_escrow_table<BS>(
+        &mut self,
+        store: &BS,
+        token: ActorID,
+        addr: &Address,
+        amount: &TokenAmount,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let mut balances = self.get_token_balances(store, token)?;
+        let mut escrow_table =
+            BalanceTable::from_root(store, &balances.escrow_table, "token escrow table")?;
+        escrow_table.add(addr, amount)?;
+        balances.escrow_table = escrow_table.root()?;
+        self.put_token_balances(store, token, balances)
+    }
+
+    pub fn withdraw_balance_from_token_escrow_table<BS>(
+        &mut self,
+        store: &BS,
+        token: ActorID,
+        addr: &Address,
+        amount: &TokenAmount,
+    ) -> Result<TokenAmount, ActorError>
+    where
+        BS: Blockstore,
+    {
+        let mut balances = self.get_token_balances(store, token)?;
+        let mut escrow_table =
+            BalanceTable::from_root(store, &balances.escrow_table, "token escrow table")?;
+        let locked_table =
+            BalanceTable::from_root(store, &balances.locked_table, "token locked table")?;
+
+        let min_balance = locked_table.get(addr)?;
+        let ex = escrow_table.subtract_with_minimum(addr, amount, &min_balance)?;
+        if ex.is_zero() {
+            return Ok(ex);
+        }
+
+        balances.escrow_table = escrow_table.root()?;
+        self.put_token_balances(store, token, balances)?;
+        Ok(ex)
+    }
+
+    // Return true when the token funds in escrow for the input address can cover an additional
+    // lockup of amount_to_lock
+    pub fn token_balance_covered<BS>(
+        &self,
+        store: &BS,
+        token: ActorID,
+        addr: Address,
+        amount_to_lock: &TokenAmount,
+    ) -> Result<bool, ActorError>
+    where
+        BS: Blockstore,
+    {
+        let balances = self.get_token_balances(store, token)?;
+        let escrow_table =
+            BalanceTable::from_root(store, &balances.escrow_table, "token escrow table")?;
+        let locked_table =
+            BalanceTable::from_root(store, &balances.locked_table, "token locked table")?;
+
+        let escrow_balance = escrow_table.get(&addr)?;
+        let prev_locked = locked_table.get(&addr)?;
+        Ok((prev_locked + amount_to_lock) <= escrow_balance)
+    }
+
+    pub fn load_deal_payment_tokens<BS>(
+        &self,
+        store: BS,
+    ) -> Result<DealPaymentTokensMap<BS>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        DealPaymentTokensMap::load(
+            store,
+            &self.deal_payment_tokens,
+            DEAL_PAYMENT_TOKENS_CONFIG,
+            "deal payment tokens",
+        )
+    }
+
+    /// Returns the token in which a deal's storage fee is paid, or None if it is paid in FIL.
+    pub fn find_payment_token<BS>(
+        &self,
+        store: &BS,
+        deal_id: DealID,
+    ) -> Result<Option<ActorID>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        Ok(self.load_deal_payment_tokens(store)?.get(&deal_id)?.cloned())
+    }
+
+    pub fn put_deal_payment_tokens<BS>(
+        &mut self,
+        store: &BS,
+        payment_tokens: &[(DealID, ActorID)],
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        if payment_tokens.is_empty() {
+            return Ok(());
+        }
+        let mut deal_payment_tokens = self.load_deal_payment_tokens(store)?;
+        for (deal_id, token) in payment_tokens {
+            deal_payment_tokens.set(deal_id, *token)?;
+        }
+        self.deal_payment_tokens = deal_payment_tokens.flush()?;
+        Ok(())
+    }
+
+    fn remove_deal_payment_token<BS>(
+        &mut self,
+        store: &BS,
+        deal_id: DealID,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let mut deal_payment_tokens = self.load_deal_payment_tokens(store)?;
+        if deal_payment_tokens.delete(&deal_id)?.is_some() {
+            self.deal_payment_tokens = deal_payment_tokens.flush()?;
+        }
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    // Replicated deal proposals
+    ////////////////////////////////////////////////////////////////////////////////
+
+    pub fn load_replica_deals<BS>(&self, store: BS) -> Result<ReplicaDealsMap<BS>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        ReplicaDealsMap::load(store, &self.replica_deals, REPLICA_DEALS_CONFIG, "replica deals")
+    }
+
+    /// Returns the replica deals published from a replicated deal proposal, if any.
+    pub fn get_replica_deals<BS>(
+        &self,
+        store: &BS,
+        proposal_cid: &Cid,
+    ) -> Result<Option<ReplicaDeals>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        Ok(self.load_replica_deals(store)?.get(proposal_cid)?.cloned())
+    }
+
+    pub fn load_replica_deals_by_epoch<BS>(
+        &self,
+        store: BS,
+    ) -> Result<ReplicaDealsByEpoch<BS>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        ReplicaDealsByEpoch::load(
+            store,
+            &self.replica_deals_by_epoch,
+            REPLICA_DEALS_BY_EPOCH_CONFIG,
+            "replica deals by epoch",
+        )
+    }
+
+    /// Records replica deals published from replicated deal proposals,
+    /// each given with the proposal's CID, replica count and start epoch.
+    /// The first replica of a proposal schedules its record for removal at the start epoch.
+    pub fn put_replica_deals<BS>(
+        &mut self,
+        store: &BS,
+        new_replica_deals: &[(Cid, u64, ChainEpoch, ReplicaDeal)],
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        if new_replica_deals.is_empty() {
+            return Ok(());
+        }
+        let mut replica_deals = self.load_replica_deals(store)?;
+        let mut replica_deals_by_epoch = self.load_replica_deals_by_epoch(store)?;
+        for (proposal_cid, replicas, start_epoch, deal) in new_replica_deals {
+            let mut entry = match replica_deals.get(proposal_cid)? {
+                Some(entry) => entry.clone(),
+                None => {
+                    replica_deals_by_epoch.put(start_epoch, *proposal_cid)?;
+                    ReplicaDeals { replicas: *replicas, start_epoch: *start_epoch, deals: vec![] }
+                }
+            };
+            if entry.deals.len() as u64 >= entry.replicas {
+                return Err(actor_error!(
+                    illegal_state,
+                    "all {} replicas of proposal {} already published",
+                    entry.replicas,
+                    proposal_cid
+                ));
+            }
+            entry.deals.push(deal.clone());
+            replica_deals.set(proposal_cid, entry)?;
+        }
+        self.replica_deals = replica_deals.flush()?;
+        self.replica_deals_by_epoch = replica_deals_by_epoch.flush()?;
+        Ok(())
+    }
+
+    /// Removes the replica deals of proposals starting at the given epochs,
+    /// whose replicas can no longer be published.
+    pub fn remove_replica_deals_by_epoch<BS>(
+        &mut self,
+        store: &BS,
+        epochs_to_remove: &[ChainEpoch],
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let mut replica_deals_by_epoch = self.load_replica_deals_by_epoch(store)?;
+        let mut proposal_cids = Vec::new();
+        for epoch in epochs_to_remove {
+            if let Some(set) = replica_deals_by_epoch.get(epoch)? {
+                set.for_each(|proposal_cid| {
+                    proposal_cids.push(proposal_cid);
+                    Ok(())
+                })?;
+                replica_deals_by_epoch.remove_all(epoch)?;
+            }
+        }
+        if proposal_cids.is_empty() {
+            return Ok(());
+        }
+
+        let mut replica_deals = self.load_replica_deals(store)?;
+        for proposal_cid in &proposal_cids {
+            replica_deals.delete(proposal_cid)?;
+        }
+        self.replica_deals = replica_deals.flush()?;
+        self.replica_deals_by_epoch = replica_deals_by_epoch.flush()?;
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    // Client and provider deal indexes
+    ////////////////////////////////////////////////////////////////////////////////
+
+    pub fn load_client_deals<BS>(&self, store: BS) -> Result<PartyDealsMap<BS>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        PartyDealsMap::load(store, &self.client_deals, PARTY_DEALS_CONFIG, "client deals")
+    }
+
+    pub fn load_provider_deals<BS>(&self, store: BS) -> Result<PartyDealsMap<BS>, ActorError>
+    where
+        BS: Blockstore,
+    {
+        PartyDealsMap::load(store, &self.provider_deals, PARTY_DEALS_CONFIG, "provider deals")
+    }
+
+    // Indexes newly published deals by client and by provider.
+    fn index_party_deals<BS>(
+        &mut self,
+        store: &BS,
+        proposals: &[(DealID, DealProposal)],
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        self.update_party_deals(store, proposals, true)
+    }
+
+    /// Removes deals from the client and provider indexes, after their proposals are removed.
+    pub fn unindex_party_deals<BS>(
+        &mut self,
+        store: &BS,
+        proposals: &[(DealID, DealProposal)],
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        self.update_party_deals(store, proposals, false)
+    }
+
+    // Adds deals to, or removes them from, the client and provider indexes.
+    fn update_party_deals<BS>(
+        &mut self,
+        store: &BS,
+        proposals: &[(DealID, DealProposal)],
+        add: bool,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        if proposals.is_empty() {
+            return Ok(());
+        }
+        let mut by_client: BTreeMap<ActorID, Vec<DealID>> = BTreeMap::new();
+        let mut by_provider: BTreeMap<ActorID, Vec<DealID>> = BTreeMap::new();
+        for (deal_id, proposal) in proposals {
+            by_client.entry(party_id(&proposal.client)?).or_default().push(*deal_id);
+            by_provider.entry(party_id(&proposal.provider)?).or_default().push(*deal_id);
+        }
+
+        let mut client_deals = self.load_client_deals(store)?;
+        for (client, deal_ids) in by_client {
+            update_party_deal_ids(store, &mut client_deals, client, &deal_ids, add)?;
+        }
+        self.client_deals = client_deals.flush()?;
+
+        let mut provider_deals = self.load_provider_deals(store)?;
+        for (provider, deal_ids) in by_provider {
+            update_party_deal_ids(store, &mut provider_deals, provider, &deal_ids, add)?;
+        }
+        self.provider_deals = provider_deals.flush()?;
         Ok(())
     }
 
+    /// Lists up to `limit` of a client's deals in ID order, starting at the first deal with an ID
+    /// no less than `cursor` if provided.
+    /// Returns the deal IDs and the ID at which to resume listing, if any deals remain.
+    pub fn list_client_deals<BS>(
+        &self,
+        store: &BS,
+        client: ActorID,
+        cursor: Option<DealID>,
+        limit: usize,
+    ) -> Result<(Vec<DealID>, Option<DealID>), ActorError>
+    where
+        BS: Blockstore,
+    {
+        list_party_deals(store, &self.load_client_deals(store)?, client, cursor, limit)
+    }
+
+    /// Lists up to `limit` of a provider's deals in ID order, starting at the first deal with an
+    /// ID no less than `cursor` if provided.
+    /// Returns the deal IDs and the ID at which to resume listing, if any deals remain.
+    pub fn list_provider_deals<BS>(
+        &self,
+        store: &BS,
+        provider: ActorID,
+        cursor: Option<DealID>,
+        limit: usize,
+    ) -> Result<(Vec<DealID>, Option<DealID>), ActorError>
+    where
+        BS: Blockstore,
+    {
+        list_party_deals(store, &self.load_provider_deals(store)?, provider, cursor, limit)
+    }
+
     /// Given a DealProposal, checks that the corresponding deal has activated
-    /// If not, checks that the deal is past its activation epoch and performs cleanup
+    /// If not, checks that the deal is past its activation epoch and performs cleanup.
+    /// The caller must remove an expired proposal from the client and provider indexes.
     pub fn get_active_deal_or_process_timeout<BS>(
         &mut self,
         store: &BS,
@@ -762,7 +1408,7 @@ impl State {
                 }
 
                 // if not activated, the proposal has timed out
-                let slashed = self.process_deal_init_timed_out(store, deal_proposal)?;
+                let slashed = self.process_deal_init_timed_out(store, deal_id, deal_proposal)?;
 
                 // delete the proposal (but not state, which doesn't exist)
                 let deleted = self.remove_proposal(store, deal_id)?;
@@ -805,6 +1451,7 @@ impl State {
     pub fn process_deal_update<BS>(
         &mut self,
         store: &BS,
+        deal_id: DealID,
         state: &DealState,
         deal: &DealProposal,
         deal_cid: &Cid,
@@ -879,8 +1526,15 @@ impl State {
         let num_epochs_elapsed = payment_end_epoch - payment_start_epoch;
 
         let elapsed_payment = &deal.storage_price_per_epoch * num_epochs_elapsed;
+        let payment_token = self.find_payment_token(store, deal_id)?;
         if elapsed_payment.is_positive() {
-            self.transfer_balance(store, &deal.client, &deal.provider, &elapsed_payment)?;
+            self.pay_storage_fee(
+                store,
+                payment_token,
+                &deal.client,
+                &deal.provider,
+                &elapsed_payment,
+            )?;
         }
 
         // TODO: remove handling of terminated deals *after* transition to synchronous deal termination
@@ -891,7 +1545,7 @@ impl State {
             let payment_remaining = deal_get_payment_remaining(deal, state.slash_epoch)?;
 
             // Unlock remaining storage fee
-            self.unlock_balance(store, &deal.client, &payment_remaining, Reason::ClientStorageFee)
+            self.unlock_storage_fee(store, payment_token, &deal.client, &payment_remaining)
                 .context("unlocking client storage fee")?;
 
             // Unlock client collateral
@@ -922,6 +1576,7 @@ impl State {
     pub fn process_slashed_deal<BS>(
         &mut self,
         store: &BS,
+        deal_id: DealID,
         proposal: &DealProposal,
         state: &DealState,
     ) -> Result<TokenAmount, ActorError>
@@ -933,15 +1588,22 @@ impl State {
         let payment_end_epoch = min(proposal.end_epoch, state.slash_epoch);
         let num_epochs_elapsed = max(0, payment_end_epoch - payment_start_epoch);
         let total_payment = &proposal.storage_price_per_epoch * num_epochs_elapsed;
+        let payment_token = self.find_payment_token(store, deal_id)?;
         if total_payment.is_positive() {
-            self.transfer_balance(store, &proposal.client, &proposal.provider, &total_payment)?;
+            self.pay_storage_fee(
+                store,
+                payment_token,
+                &proposal.client,
+                &proposal.provider,
+                &total_payment,
+            )?;
         }
 
         // unlock client collateral and locked storage fee
         let payment_remaining = deal_get_payment_remaining(proposal, state.slash_epoch)?;
 
         // Unlock remaining storage fee
-        self.unlock_balance(store, &proposal.client, &payment_remaining, Reason::ClientStorageFee)
+        self.unlock_storage_fee(store, payment_token, &proposal.client, &payment_remaining)
             .context("unlocking client storage fee")?;
 
         // Unlock client collateral
@@ -961,24 +1623,63 @@ impl State {
         Ok(slashed)
     }
 
+    /// Deal cancelled by agreement of its client and provider.
+    /// Pays the provider for the epochs up to the cancel epoch, unlocks the client's remaining
+    /// storage fee, and unlocks the collateral of both parties without penalty.
+    /// The deal's terms must reflect any extension.
+    pub fn process_deal_cancelled<BS>(
+        &mut self,
+        store: &BS,
+        deal_id: DealID,
+        deal: &DealProposal,
+        state: &DealState,
+        cancel_epoch: ChainEpoch,
+    ) -> Result<TokenAmount, ActorError>
+    where
+        BS: Blockstore,
+    {
+        if cancel_epoch < state.last_updated_epoch || cancel_epoch >= deal.end_epoch {
+            return Err(actor_error!(
+                illegal_state,
+                "deal {} cancel epoch {} outside unsettled term [{}, {})",
+                deal_id,
+                cancel_epoch,
+                state.last_updated_epoch,
+                deal.end_epoch
+            ));
+        }
+
+        let payment_start_epoch = max(deal.start_epoch, state.last_updated_epoch);
+        let num_epochs_elapsed = max(0, cancel_epoch - payment_start_epoch);
+        let payment = &deal.storage_price_per_epoch * num_epochs_elapsed;
+        let payment_token = self.find_payment_token(store, deal_id)?;
+        if payment.is_positive() {
+            self.pay_storage_fee(store, payment_token, &deal.client, &deal.provider, &payment)?;
+        }
+
+        let payment_remaining = deal_get_payment_remaining(deal, cancel_epoch)?;
+        self.unlock_storage_fee(store, payment_token, &deal.client, &payment_remaining)
+            .context("unlocking client storage fee")?;
+
+        self.process_deal_expired(store, deal, state)?;
+        Ok(payment)
+    }
+
     /// Deal start deadline elapsed without appearing in a proven sector.
     /// Slash a portion of provider's collateral, and unlock remaining collaterals
     /// for both provider and client.
     pub fn process_deal_init_timed_out<BS>(
         &mut self,
         store: &BS,
+        deal_id: DealID,
         deal: &DealProposal,
     ) -> Result<TokenAmount, ActorError>
     where
         BS: Blockstore,
     {
-        self.unlock_balance(
-            store,
-            &deal.client,
-            &deal.total_storage_fee(),
-            Reason::ClientStorageFee,
-        )
-        .context("unlocking client storage fee")?;
+        let payment_token = self.find_payment_token(store, deal_id)?;
+        self.unlock_storage_fee(store, payment_token, &deal.client, &deal.total_storage_fee())
+            .context("unlocking client storage fee")?;
 
         self.unlock_balance(store, &deal.client, &deal.client_collateral, Reason::ClientCollateral)
             .context("unlocking client collateral")?;
@@ -1063,36 +1764,35 @@ impl State {
 
         let escrow_table = BalanceTable::from_root(store, &self.escrow_table, "escrow table")?;
         let mut locked_table = BalanceTable::from_root(store, &self.locked_table, "locked table")?;
-
-        let prev_locked = locked_table.get(addr)?;
-        let escrow_balance = escrow_table.get(addr)?;
-        if &prev_locked + amount > escrow_balance {
-            return Err(actor_error!(insufficient_funds;
-                    "not enough balance to lock for addr{}: \
-                    escrow balance {} < prev locked {} + amount {}",
-                    addr, escrow_balance, prev_locked, amount));
-        }
-
-        locked_table.add(addr, amount)?;
+        lock_balance(&escrow_table, &mut locked_table, addr, amount)?;
         self.locked_table = locked_table.root()?;
         Ok(())
     }
 
+    /// Locks a deal's collateral and storage fee.
+    /// The storage fee is locked in the deal's payment token, if it is not paid in FIL.
     pub fn lock_client_and_provider_balances<BS>(
         &mut self,
         store: &BS,
         proposal: &DealProposal,
+        payment_token: Option<ActorID>,
     ) -> Result<(), ActorError>
     where
         BS: Blockstore,
     {
-        self.maybe_lock_balance(store, &proposal.client, &proposal.client_balance_requirement())
+        self.maybe_lock_balance(store, &proposal.client, &proposal.client_collateral)
             .context("locking client funds")?;
+        self.lock_storage_fee(
+            store,
+            payment_token,
+            &proposal.client,
+            &proposal.total_storage_fee(),
+        )
+        .context("locking client funds")?;
         self.maybe_lock_balance(store, &proposal.provider, &proposal.provider_collateral)
             .context("locking provider funds")?;
 
         self.total_client_locked_collateral += &proposal.client_collateral;
-        self.total_client_storage_fee += proposal.total_storage_fee();
         self.total_provider_locked_collateral += &proposal.provider_collateral;
         Ok(())
     }
@@ -1179,6 +1879,203 @@ impl State {
         self.escrow_table = escrow_table.root()?;
         self.unlock_balance(store, addr, amount, lock_reason)
     }
+
+    /// Locks a client's storage fee in FIL, or in the deal's payment token.
+    fn lock_storage_fee<BS>(
+        &mut self,
+        store: &BS,
+        payment_token: Option<ActorID>,
+        client: &Address,
+        amount: &TokenAmount,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let token = match payment_token {
+            Some(token) => token,
+            None => {
+                self.maybe_lock_balance(store, client, amount)?;
+                self.total_client_storage_fee += amount;
+                return Ok(());
+            }
+        };
+        if amount.is_negative() {
+            return Err(actor_error!(illegal_state, "cannot lock negative amount {}", amount));
+        }
+        let mut balances = self.get_token_balances(store, token)?;
+        let escrow_table =
+            BalanceTable::from_root(store, &balances.escrow_table, "token escrow table")?;
+        let mut locked_table =
+            BalanceTable::from_root(store, &balances.locked_table, "token locked table")?;
+        lock_balance(&escrow_table, &mut locked_table, client, amount)?;
+        balances.locked_table = locked_table.root()?;
+        balances.total_client_storage_fee += amount;
+        self.put_token_balances(store, token, balances)
+    }
+
+    /// Unlocks a client's storage fee in FIL, or in the deal's payment token.
+    fn unlock_storage_fee<BS>(
+        &mut self,
+        store: &BS,
+        payment_token: Option<ActorID>,
+        client: &Address,
+        amount: &TokenAmount,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let token = match payment_token {
+            Some(token) => token,
+            None => return self.unlock_balance(store, client, amount, Reason::ClientStorageFee),
+        };
+        if amount.is_negative() {
+            return Err(actor_error!(illegal_state, "unlock negative amount: {}", amount));
+        }
+        let mut balances = self.get_token_balances(store, token)?;
+        let mut locked_table =
+            BalanceTable::from_root(store, &balances.locked_table, "token locked table")?;
+        locked_table.must_subtract(client, amount).context("unlocking balance")?;
+        balances.locked_table = locked_table.root()?;
+        balances.total_client_storage_fee -= amount;
+        self.put_token_balances(store, token, balances)
+    }
+
+    /// Pays a client's locked storage fee to a provider in FIL, or in the deal's payment token.
+    fn pay_storage_fee<BS>(
+        &mut self,
+        store: &BS,
+        payment_token: Option<ActorID>,
+        client: &Address,
+        provider: &Address,
+        amount: &TokenAmount,
+    ) -> Result<(), ActorError>
+    where
+        BS: Blockstore,
+    {
+        let token = match payment_token {
+            Some(token) => token,
+            None => return self.transfer_balance(store, client, provider, amount),
+        };
+        if amount.is_negative() {
+            return Err(actor_error!(illegal_state, "transfer negative amount: {}", amount));
+        }
+        self.unlock_storage_fee(store, payment_token, client, amount)
+            .context("unlocking client balance")?;
+
+        let mut balances = self.get_token_balances(store, token)?;
+        let mut escrow_table =
+            BalanceTable::from_root(store, &balances.escrow_table, "token escrow table")?;
+        escrow_table.must_subtract(client, amount)?;
+        escrow_table.add(provider, amount)?;
+        balances.escrow_table = escrow_table.root()?;
+        self.put_token_balances(store, token, balances)
+    }
+}
+
+// Locks an amount of an address's escrow balance, which must cover it.
+fn lock_balance<BS>(
+    escrow_table: &BalanceTable<BS>,
+    locked_table: &mut BalanceTable<BS>,
+    addr: &Address,
+    amount: &TokenAmount,
+) -> Result<(), ActorError>
+where
+    BS: Blockstore,
+{
+    let prev_locked = locked_table.get(addr)?;
+    let escrow_balance = escrow_table.get(addr)?;
+    if &prev_locked + amount > escrow_balance {
+        return Err(actor_error!(insufficient_funds;
+                "not enough balance to lock for addr{}: \
+                escrow balance {} < prev locked {} + amount {}",
+                addr, escrow_balance, prev_locked, amount));
+    }
+    locked_table.add(addr, amount)?;
+    Ok(())
+}
+
+// Returns the actor ID of a deal party, whose address is stored in ID form.
+fn party_id(addr: &Address) -> Result<ActorID, ActorError> {
+    addr.id().map_err(|_| actor_error!(illegal_state, "deal party {} is not an ID address", addr))
+}
+
+pub fn load_party_deal_ids<'bs, BS>(
+    store: &'bs BS,
+    party_deals: &PartyDealsMap<&BS>,
+    party: ActorID,
+) -> Result<PartyDealIDs<'bs, BS>, ActorError>
+where
+    BS: Blockstore,
+{
+    match party_deals.get(&party)? {
+        Some(root) => PartyDealIDs::load(root, store)
+            .with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+                format!("failed to load deals of party {}", party)
+            }),
+        None => Ok(PartyDealIDs::new_with_bit_width(store, PARTY_DEALS_AMT_BITWIDTH)),
+    }
+}
+
+// Adds deal IDs to, or removes them from, the deals indexed for a party.
+// A party with no remaining deals is removed from the index.
+fn update_party_deal_ids<BS>(
+    store: &BS,
+    party_deals: &mut PartyDealsMap<&BS>,
+    party: ActorID,
+    deal_ids: &[DealID],
+    add: bool,
+) -> Result<(), ActorError>
+where
+    BS: Blockstore,
+{
+    let mut ids = load_party_deal_ids(store, party_deals, party)?;
+    for deal_id in deal_ids {
+        if add {
+            ids.set(*deal_id, ()).with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+                format!("failed to index deal {} for party {}", deal_id, party)
+            })?;
+        } else {
+            ids.delete(*deal_id).with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+                format!("failed to unindex deal {} for party {}", deal_id, party)
+            })?;
+        }
+    }
+    if ids.count() == 0 {
+        party_deals.delete(&party)?;
+    } else {
+        let root = ids.flush().with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+            format!("failed to save deals of party {}", party)
+        })?;
+        party_deals.set(&party, root)?;
+    }
+    Ok(())
+}
+
+// Lists up to `limit` of the deals indexed for a party in ID order, starting at the first deal
+// with an ID no less than `cursor` if provided.
+// A deal is removed from the index along with its proposal, so a cursor returned by an earlier
+// call may no longer be indexed. Listing then resumes at the next deal that is.
+fn list_party_deals<BS>(
+    store: &BS,
+    party_deals: &PartyDealsMap<&BS>,
+    party: ActorID,
+    cursor: Option<DealID>,
+    limit: usize,
+) -> Result<(Vec<DealID>, Option<DealID>), ActorError>
+where
+    BS: Blockstore,
+{
+    let ids = load_party_deal_ids(store, party_deals, party)?;
+    let mut deal_ids = Vec::new();
+    let (_, next) = ids
+        .for_each_ranged(cursor, Some(limit as u64), |deal_id, _| {
+            deal_ids.push(deal_id);
+            Ok(())
+        })
+        .with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+            format!("failed to iterate deals of party {}", party)
+        })?;
+    Ok((deal_ids, next))
 }
 
 pub enum LoadDealState {
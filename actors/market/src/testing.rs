@@ -28,9 +28,13 @@ use fil_actors_runtime::{
 
 use crate::ext::verifreg::AllocationID;
 use crate::{
-    balance_table::BalanceTable, DealArray, DealMetaArray, DealOpsByEpoch, DealProposal,
-    PendingProposalsSet, ProviderSectorsMap, SectorDealsMap, State, DEAL_OPS_BY_EPOCH_CONFIG,
-    PENDING_PROPOSALS_CONFIG, PROVIDER_SECTORS_CONFIG, SECTOR_DEALS_CONFIG,
+    balance_table::BalanceTable, DealArray, DealExtensionsMap, DealMetaArray, DealOpsByEpoch,
+    DealPaymentTokensMap, DealProposal, PartyDealIDs, PartyDealsMap, PendingProposalsSet,
+    ProviderSectorsMap, ReplicaDealsByEpoch, ReplicaDealsMap, SectorDealsMap, State,
+    TokenBalancesMap, DEAL_EXTENSIONS_CONFIG, DEAL_OPS_BY_EPOCH_CONFIG, DEAL_PAYMENT_TOKENS_CONFIG,
+    PARTY_DEALS_CONFIG, PENDING_PROPOSALS_CONFIG, PROVIDER_SECTORS_CONFIG,
+    REPLICA_DEALS_BY_EPOCH_CONFIG, REPLICA_DEALS_CONFIG, SECTOR_DEALS_CONFIG,
+    TOKEN_BALANCES_CONFIG,
 };
 
 #[derive(Clone)]
@@ -105,6 +109,8 @@ pub fn check_state_invariants<BS: Blockstore>(
     let mut max_deal_id = -1;
     let mut proposal_stats = BTreeMap::<DealID, DealSummary>::new();
     let mut expected_deal_ops = BTreeSet::<DealID>::new();
+    let mut expected_client_deals = BTreeMap::<ActorID, BTreeSet<DealID>>::new();
+    let mut expected_provider_deals = BTreeMap::<ActorID, BTreeSet<DealID>>::new();
     let mut total_proposal_collateral = TokenAmount::zero();
 
     match DealArray::load(&state.proposals, store) {
@@ -134,6 +140,11 @@ pub fn check_state_invariants<BS: Blockstore>(
                 total_proposal_collateral +=
                     &proposal.client_collateral + &proposal.provider_collateral;
 
+                if let (Ok(client), Ok(provider)) = (proposal.client.id(), proposal.provider.id()) {
+                    expected_client_deals.entry(client).or_default().insert(deal_id);
+                    expected_provider_deals.entry(provider).or_default().insert(deal_id);
+                }
+
                 acc.require(
                     proposal.client.protocol() == Protocol::ID,
                     "client address for deal {deal_id} is not an ID address",
@@ -225,6 +236,46 @@ pub fn check_state_invariants<BS: Blockstore>(
         Err(e) => acc.add(format!("error loading deal states: {e}")),
     };
 
+    // deal extensions
+    match DealExtensionsMap::load(
+        store,
+        &state.deal_extensions,
+        DEAL_EXTENSIONS_CONFIG,
+        "deal extensions",
+    ) {
+        Ok(deal_extensions) => {
+            let ret = deal_extensions.for_each(|deal_id, extension| {
+                if let Some(stats) = proposal_stats.get_mut(&deal_id) {
+                    acc.require(
+                        stats.sector_start_epoch >= 0,
+                        format!("extended deal {deal_id} is not activated"),
+                    );
+                    acc.require(
+                        extension.end_epoch > stats.end_epoch,
+                        format!(
+                            "deal {deal_id} extension end {} does not extend proposal end {}",
+                            extension.end_epoch, stats.end_epoch
+                        ),
+                    );
+                    acc.require(
+                        extension.repriced_epoch >= stats.start_epoch
+                            && extension.repriced_epoch < extension.end_epoch,
+                        format!(
+                            "deal {deal_id} extension repriced at {} outside term [{}, {})",
+                            extension.repriced_epoch, stats.start_epoch, extension.end_epoch
+                        ),
+                    );
+                    stats.end_epoch = extension.end_epoch;
+                } else {
+                    acc.add(format!("no deal proposal for deal extension {deal_id}"));
+                }
+                Ok(())
+            });
+            acc.require_no_error(ret, "error iterating deal extensions");
+        }
+        Err(e) => acc.add(format!("error loading deal extensions: {e}")),
+    };
+
     // Provider->sector->deal mapping
     // Each entry corresponds to non-terminated deal state.
     // A deal may have expired but remain in the mapping until settlement.
@@ -365,6 +416,155 @@ pub fn check_state_invariants<BS: Blockstore>(
         }
     };
 
+    // payment tokens
+    let mut payment_tokens = BTreeSet::<ActorID>::new();
+    match DealPaymentTokensMap::load(
+        store,
+        &state.deal_payment_tokens,
+        DEAL_PAYMENT_TOKENS_CONFIG,
+        "deal payment tokens",
+    ) {
+        Ok(deal_payment_tokens) => {
+            let ret = deal_payment_tokens.for_each(|deal_id, token| {
+                acc.require(
+                    proposal_stats.contains_key(&deal_id),
+                    format!("no deal proposal for deal {deal_id} paid in token {token}"),
+                );
+                payment_tokens.insert(*token);
+                Ok(())
+            });
+            acc.require_no_error(ret, "error iterating deal payment tokens");
+        }
+        Err(e) => acc.add(format!("error loading deal payment tokens: {e}")),
+    };
+
+    // token escrow tables and locked tables
+    match TokenBalancesMap::load(
+        store,
+        &state.token_balances,
+        TOKEN_BALANCES_CONFIG,
+        "token balances",
+    ) {
+        Ok(token_balances) => {
+            let ret = token_balances.for_each(|token, balances| {
+                payment_tokens.remove(&token);
+                let escrow_table =
+                    BalanceTable::from_root(store, &balances.escrow_table, "token escrow table")?;
+                let lock_table =
+                    BalanceTable::from_root(store, &balances.locked_table, "token locked table")?;
+                let mut locked_total = TokenAmount::zero();
+                lock_table.0.for_each(|address, locked_amount| {
+                    locked_total += locked_amount;
+                    let escrow_amount = &escrow_table.get(&address)?;
+                    acc.require(escrow_amount >= locked_amount, format!("locked token {token} for {address}, {locked_amount}, greater than escrow amount, {escrow_amount}"));
+                    Ok(())
+                })?;
+                // Only client storage fees are locked in tokens.
+                acc.require(locked_total == balances.total_client_storage_fee, format!("locked token {token} total, {locked_total}, does not equal client storage fee, {}", balances.total_client_storage_fee));
+                Ok(())
+            });
+            acc.require_no_error(ret, "error iterating token balances");
+        }
+        Err(e) => acc.add(format!("error loading token balances: {e}")),
+    };
+    acc.require(
+        payment_tokens.is_empty(),
+        format!("no balances for deal payment tokens: {payment_tokens:?}"),
+    );
+
+    // client and provider deal indexes
+    check_party_deals_index(&acc, store, &state.client_deals, "client", expected_client_deals);
+    check_party_deals_index(
+        &acc,
+        store,
+        &state.provider_deals,
+        "provider",
+        expected_provider_deals,
+    );
+
+    // replica deals
+    let mut replica_start_epochs = BTreeMap::<Cid, ChainEpoch>::new();
+    match ReplicaDealsMap::load(store, &state.replica_deals, REPLICA_DEALS_CONFIG, "replica deals")
+    {
+        Ok(replica_deals) => {
+            let ret = replica_deals.for_each(|proposal_cid, published| {
+                acc.require(
+                    published.start_epoch > state.last_cron,
+                    format!(
+                        "proposal {proposal_cid} replicas starting at {} not removed by cron at {}",
+                        published.start_epoch, state.last_cron
+                    ),
+                );
+                replica_start_epochs.insert(proposal_cid, published.start_epoch);
+                acc.require(
+                    published.deals.len() as u64 <= published.replicas,
+                    format!(
+                        "proposal {proposal_cid} has {} replicas published, more than {}",
+                        published.deals.len(),
+                        published.replicas
+                    ),
+                );
+                let mut providers = BTreeSet::<ActorID>::new();
+                for replica in &published.deals {
+                    acc.require(
+                        providers.insert(replica.provider),
+                        format!(
+                            "provider {} published more than one replica of proposal {proposal_cid}",
+                            replica.provider
+                        ),
+                    );
+                    acc.require(
+                        replica.deal_id < state.next_id,
+                        format!(
+                            "replica deal {} of proposal {proposal_cid} has not been published",
+                            replica.deal_id
+                        ),
+                    );
+                    if let Some(stats) = proposal_stats.get(&replica.deal_id) {
+                        acc.require(
+                            stats.provider == Address::new_id(replica.provider),
+                            format!(
+                                "replica deal {} of proposal {proposal_cid} provider {} does not match {}",
+                                replica.deal_id, stats.provider, replica.provider
+                            ),
+                        );
+                    }
+                }
+                Ok(())
+            });
+            acc.require_no_error(ret, "error iterating replica deals");
+        }
+        Err(e) => acc.add(format!("error loading replica deals: {e}")),
+    };
+
+    // replica deals by epoch
+    match ReplicaDealsByEpoch::load(
+        store,
+        &state.replica_deals_by_epoch,
+        REPLICA_DEALS_BY_EPOCH_CONFIG,
+        "replica deals by epoch",
+    ) {
+        Ok(replica_deals_by_epoch) => {
+            let ret = replica_deals_by_epoch.for_each(|epoch, _| {
+                replica_deals_by_epoch.for_each_in(&epoch, |proposal_cid: Cid| {
+                    acc.require(
+                        replica_start_epochs.remove(&proposal_cid) == Some(epoch),
+                        format!(
+                            "proposal {proposal_cid} scheduled at {epoch} has no replicas starting then"
+                        ),
+                    );
+                    Ok(())
+                })
+            });
+            acc.require_no_error(ret, "error iterating replica deals by epoch");
+        }
+        Err(e) => acc.add(format!("error loading replica deals by epoch: {e}")),
+    };
+    acc.require(
+        replica_start_epochs.is_empty(),
+        format!("replica deals not scheduled for removal: {replica_start_epochs:?}"),
+    );
+
     // deals ops by epoch
     let (mut deal_op_epoch_count, mut deal_op_count) = (0, 0);
     match DealOpsByEpoch::load(
@@ -407,6 +607,41 @@ pub fn check_state_invariants<BS: Blockstore>(
     )
 }
 
+// Checks that a client or provider deal index contains exactly the expected deals for each party.
+fn check_party_deals_index<BS: Blockstore>(
+    acc: &MessageAccumulator,
+    store: &BS,
+    root: &Cid,
+    role: &str,
+    mut expected: BTreeMap<ActorID, BTreeSet<DealID>>,
+) {
+    match PartyDealsMap::load(store, root, PARTY_DEALS_CONFIG, "party deals") {
+        Ok(index) => {
+            let ret = index.for_each(|party, deals_root| {
+                let mut indexed = BTreeSet::<DealID>::new();
+                PartyDealIDs::load(deals_root, store)
+                    .and_then(|deals| {
+                        deals.for_each(|deal_id, _| {
+                            indexed.insert(deal_id);
+                            Ok(())
+                        })
+                    })
+                    .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to iterate party deals")?;
+                acc.require(!indexed.is_empty(), format!("empty {role} deal index for {party}"));
+                let expected_deals = expected.remove(&party).unwrap_or_default();
+                acc.require(
+                    indexed == expected_deals,
+                    format!("{role} {party} deals indexed as {indexed:?} but proposals are {expected_deals:?}"),
+                );
+                Ok(())
+            });
+            acc.require_no_error(ret, format!("error iterating {role} deal index"));
+        }
+        Err(e) => acc.add(format!("error loading {role} deal index: {e}")),
+    };
+    acc.require(expected.is_empty(), format!("{role} deals missing from index: {expected:?}"));
+}
+
 /// Compute a deal CID directly (the actor code uses a runtime built-in).
 pub(crate) fn deal_cid(proposal: &DealProposal) -> Result<Cid, ActorError> {
     const DIGEST_SIZE: u32 = 32;
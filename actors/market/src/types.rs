@@ -1,8 +1,9 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use super::ext::verifreg::AllocationID;
+use super::ext::verifreg::{AllocationID, ClaimID};
 use cid::Cid;
+use fil_actors_runtime::impl_trailing_optional_tuple;
 use fil_actors_runtime::Array;
 use fil_actors_runtime::BatchReturn;
 use fvm_ipld_bitfield::BitField;
@@ -11,6 +12,7 @@ use fvm_ipld_encoding::tuple::*;
 use fvm_shared::address::Address;
 use fvm_shared::bigint::{bigint_ser, BigInt};
 use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::signature::Signature;
 use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::piece::PaddedPieceSize;
@@ -19,10 +21,14 @@ use fvm_shared::ActorID;
 use crate::Label;
 use fvm_shared::sector::{RegisteredSealProof, SectorNumber};
 
-use super::deal::{ClientDealProposal, DealProposal, DealState};
+use super::deal::{
+    ClientDealProposal, ClientReplicatedDealProposal, ClientTokenDealProposal, DealProposal,
+    DealState, ReplicaDeals,
+};
 
 pub const PROPOSALS_AMT_BITWIDTH: u32 = 5;
 pub const STATES_AMT_BITWIDTH: u32 = 6;
+pub const PARTY_DEALS_AMT_BITWIDTH: u32 = 5;
 
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
 #[serde(transparent)]
@@ -54,6 +60,19 @@ pub struct GetBalanceReturn {
     pub locked: TokenAmount,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct WithdrawTokenBalanceParams {
+    pub token: Address,
+    pub provider_or_client: Address,
+    pub amount: TokenAmount,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct GetTokenBalanceParams {
+    pub token: Address,
+    pub account: Address,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, PartialEq)] // Add Eq when BitField does
 pub struct OnMinerSectorsTerminateParams {
     pub epoch: ChainEpoch,
@@ -65,12 +84,97 @@ pub struct PublishStorageDealsParams {
     pub deals: Vec<ClientDealProposal>,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct PublishTokenDealsParams {
+    pub deals: Vec<ClientTokenDealProposal>,
+}
+
+/// Replicated deal proposals from which a provider publishes a replica deal each.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct PublishReplicaDealsParams {
+    pub provider: Address,
+    pub deals: Vec<ClientReplicatedDealProposal>,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, PartialEq)] // Add Eq when BitField does
 pub struct PublishStorageDealsReturn {
     pub ids: Vec<DealID>,
     pub valid_deals: BitField,
 }
 
+/// New terms for an activated deal, which extend its end epoch.
+/// The price applies to the remainder of the deal's term from the epoch the extension is made.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct DealExtensionProposal {
+    pub deal_id: DealID,
+    pub end_epoch: ChainEpoch,
+    pub storage_price_per_epoch: TokenAmount,
+    /// The last epoch at which the extension may be made.
+    pub expiration: ChainEpoch,
+}
+
+/// A DealExtensionProposal signed by the deal's client.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ClientDealExtensionProposal {
+    pub proposal: DealExtensionProposal,
+    pub client_signature: Signature,
+    /// Whether the client signed EIP-712 typed data (see `authenticate_client_message`).
+    pub eip712_signature: bool,
+}
+
+impl_trailing_optional_tuple!(ClientDealExtensionProposal { proposal, client_signature; eip712_signature });
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct ExtendDealTermsParams {
+    pub extensions: Vec<ClientDealExtensionProposal>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct ExtendDealTermsReturn {
+    /// Indicators of success or failure for each extension.
+    pub results: BatchReturn,
+}
+
+/// Early cancellation of an activated deal, agreed by its client and provider.
+/// The deal is settled up to the cancel epoch, which must not precede the epoch to which it
+/// was last settled, nor follow the epoch at which the cancellation is made.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct DealCancellationProposal {
+    pub deal_id: DealID,
+    pub cancel_epoch: ChainEpoch,
+    /// The verified registry claim for a verified deal's data, which is removed with the deal.
+    /// Must be NO_ALLOCATION_ID for an unverified deal.
+    pub claim_id: ClaimID,
+    /// The last epoch at which the cancellation may be made.
+    pub expiration: ChainEpoch,
+}
+
+/// A DealCancellationProposal signed by the deal's client.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ClientDealCancellationProposal {
+    pub proposal: DealCancellationProposal,
+    pub client_signature: Signature,
+    /// Whether the client signed EIP-712 typed data (see `authenticate_client_message`).
+    pub eip712_signature: bool,
+}
+
+impl_trailing_optional_tuple!(ClientDealCancellationProposal { proposal, client_signature; eip712_signature });
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct CancelDealsParams {
+    pub cancellations: Vec<ClientDealCancellationProposal>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct CancelDealsReturn {
+    /// Indicators of success or failure for each cancellation.
+    pub results: BatchReturn,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
 pub struct VerifyDealsForActivationParams {
     /// Deals to verify, grouped by sector.
@@ -198,12 +302,87 @@ pub struct GetDealTermReturn {
 
 pub type GetDealTotalPriceParams = DealQueryParams;
 
+/// The total price of a deal, denominated in its payment token (see `GetDealPaymentToken`).
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
 #[serde(transparent)]
 pub struct GetDealTotalPriceReturn {
     pub total_price: TokenAmount,
 }
 
+pub type GetDealPaymentTokenParams = DealQueryParams;
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct GetDealPaymentTokenReturn {
+    /// The FRC-46 token in which the deal's storage fee is paid, or None if it is paid in FIL.
+    pub token: Option<ActorID>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct GetReplicaDealsParams {
+    /// CID of the replicated deal proposal.
+    pub proposal_cid: Cid,
+}
+
+pub type GetReplicaDealsReturn = ReplicaDeals;
+
+/// Parameters for listing the deals of a client or provider.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct ListDealsParams {
+    /// The client or provider whose deals are listed.
+    pub party: Address,
+    /// Deal ID at which to resume listing, as returned by a previous call.
+    /// Listing starts at the party's first deal with an ID no less than the cursor, so the
+    /// cursor remains valid if that deal has since been removed.
+    pub cursor: Option<DealID>,
+    /// Maximum number of deals to return.
+    pub limit: u64,
+}
+
+pub type GetClientDealsParams = ListDealsParams;
+pub type GetProviderDealsParams = ListDealsParams;
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct ListDealsReturn {
+    pub deals: Vec<DealID>,
+    /// Cursor for the next page, or none if all the party's deals have been listed.
+    pub next_cursor: Option<DealID>,
+}
+
+pub type GetClientDealsReturn = ListDealsReturn;
+pub type GetProviderDealsReturn = ListDealsReturn;
+
+pub type GetClientDealSummariesParams = ListDealsParams;
+pub type GetProviderDealSummariesParams = ListDealsParams;
+
+/// A deal's parties, piece, current terms and activation, as listed for a client or provider.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct PartyDealSummary {
+    pub id: DealID,
+    pub client: ActorID,
+    pub provider: ActorID,
+    pub piece_cid: Cid,
+    pub piece_size: PaddedPieceSize,
+    pub verified_deal: bool,
+    pub start_epoch: ChainEpoch,
+    /// End epoch of the deal's term, reflecting any extension.
+    pub end_epoch: ChainEpoch,
+    /// Price per epoch of the deal's term, reflecting any extension.
+    pub storage_price_per_epoch: TokenAmount,
+    /// Epoch at which the deal was activated, or -1.
+    pub activated: ChainEpoch,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct ListDealSummariesReturn {
+    pub deals: Vec<PartyDealSummary>,
+    /// Cursor for the next page, or none if all the party's deals have been listed.
+    pub next_cursor: Option<DealID>,
+}
+
+pub type GetClientDealSummariesReturn = ListDealSummariesReturn;
+pub type GetProviderDealSummariesReturn = ListDealSummariesReturn;
+
 pub type GetDealClientCollateralParams = DealQueryParams;
 
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
@@ -0,0 +1,451 @@
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::error::ExitCode;
+
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::sys::SendFlags;
+use num_traits::Zero;
+
+use fil_actor_market::ext::account::{AuthenticateMessageParams, AUTHENTICATE_MESSAGE_METHOD};
+use fil_actor_market::ext::miner::SECTOR_STATUS_ACTIVE;
+use fil_actor_market::{deal_cancellation_eip712_message, EX_DEAL_EXPIRED, EX_DEAL_NOT_ACTIVATED};
+use fil_actors_runtime::network::EPOCHS_IN_DAY;
+use fil_actors_runtime::EAM_ACTOR_ID;
+
+use harness::*;
+
+mod harness;
+
+const START_EPOCH: ChainEpoch = 10;
+const END_EPOCH: ChainEpoch = START_EPOCH + 200 * EPOCHS_IN_DAY;
+const SECTOR_NUMBER: u64 = 7;
+const SECTOR_EXPIRY: ChainEpoch = END_EPOCH + 100;
+
+#[test]
+fn cancels_deal_without_penalty() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+    let client_before = get_balance(&rt, &CLIENT_ADDR);
+
+    // The cancellation is agreed some time after the cancel epoch.
+    let cancel_epoch = START_EPOCH + 150;
+    rt.set_epoch(cancel_epoch + 50);
+    let cancellation = deal_cancellation(deal_id, cancel_epoch);
+    expect_cancellation_checks(&rt, &proposal, addrs.worker, &cancellation);
+    expect_emitted(
+        &rt,
+        "deal-cancelled",
+        deal_id,
+        CLIENT_ADDR.id().unwrap(),
+        PROVIDER_ADDR.id().unwrap(),
+    );
+    expect_sector_deals_cancelled(
+        &rt,
+        addrs.provider,
+        &[(SECTOR_NUMBER, proposal.piece_size.0, 0)],
+    );
+    let ret = cancel_deals(&rt, addrs.worker, vec![cancellation]);
+    assert!(ret.results.all_ok());
+
+    // The provider is paid up to the cancel epoch, and nobody's collateral is slashed.
+    let payment = &proposal.storage_price_per_epoch * (cancel_epoch - START_EPOCH);
+    let client_after = get_balance(&rt, &CLIENT_ADDR);
+    assert_eq!(&client_before.balance - &payment, client_after.balance);
+    assert!(client_after.locked.is_zero());
+    let provider_after = get_balance(&rt, &PROVIDER_ADDR);
+    assert_eq!(&proposal.provider_collateral + &payment, provider_after.balance);
+    assert!(provider_after.locked.is_zero());
+    assert_deal_deleted(&rt, deal_id, &proposal, SECTOR_NUMBER, true);
+    check_state(&rt);
+}
+
+#[test]
+fn cancels_settled_deal_and_verified_deal() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+    let claim_id = 1;
+    let verified_id = generate_and_publish_verified_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        START_EPOCH,
+        END_EPOCH + 1,
+        claim_id,
+    );
+    let verified = get_deal_proposal(&rt, verified_id);
+    activate_deals(&rt, SECTOR_EXPIRY, addrs.provider, 0, SECTOR_NUMBER + 1, &[verified_id]);
+
+    // Settle the unverified deal part way, then cancel both deals at a later epoch.
+    rt.set_epoch(START_EPOCH + 100);
+    settle_deal_payments(&rt, addrs.provider, &[deal_id], &[], &[]);
+    let client_before = get_balance(&rt, &CLIENT_ADDR);
+    let provider_before = get_balance(&rt, &PROVIDER_ADDR);
+
+    let cancel_epoch = START_EPOCH + 300;
+    rt.set_epoch(cancel_epoch);
+    let cancellations = vec![
+        deal_cancellation(deal_id, cancel_epoch),
+        verified_deal_cancellation(verified_id, cancel_epoch, claim_id),
+    ];
+    expect_cancellation_checks(&rt, &proposal, addrs.worker, &cancellations[0]);
+    // The verified deal's claim is checked against the deal and its sector, which must be active.
+    expect_cancelled_claim_checked(&rt, &verified, claim_id, SECTOR_NUMBER + 1);
+    expect_sector_status(&rt, addrs.provider, SECTOR_NUMBER + 1, SECTOR_STATUS_ACTIVE);
+    expect_cancellation_authenticated(&rt, verified.client, &cancellations[1]);
+    for id in [deal_id, verified_id] {
+        expect_emitted(
+            &rt,
+            "deal-cancelled",
+            id,
+            CLIENT_ADDR.id().unwrap(),
+            PROVIDER_ADDR.id().unwrap(),
+        );
+    }
+    // The claim is removed, and the provider reduces the verified sector's power.
+    expect_claims_cancelled(&rt, PROVIDER_ADDR.id().unwrap(), vec![claim_id]);
+    expect_sector_deals_cancelled(
+        &rt,
+        addrs.provider,
+        &[(SECTOR_NUMBER, proposal.piece_size.0, 0), (SECTOR_NUMBER + 1, 0, verified.piece_size.0)],
+    );
+    let ret = cancel_deals(&rt, addrs.worker, cancellations);
+    assert!(ret.results.all_ok());
+
+    // Each deal is paid up to the cancel epoch from when it was last settled.
+    let payment = &proposal.storage_price_per_epoch * 200 + &verified.storage_price_per_epoch * 300;
+    let client_after = get_balance(&rt, &CLIENT_ADDR);
+    assert_eq!(&client_before.balance - &payment, client_after.balance);
+    assert!(client_after.locked.is_zero());
+    let provider_after = get_balance(&rt, &PROVIDER_ADDR);
+    assert_eq!(&provider_before.balance + &payment, provider_after.balance);
+    assert!(provider_after.locked.is_zero());
+    assert_deal_deleted(&rt, deal_id, &proposal, SECTOR_NUMBER, true);
+    assert_deal_deleted(&rt, verified_id, &verified, SECTOR_NUMBER + 1, true);
+    check_state(&rt);
+}
+
+#[test]
+fn rejects_invalid_claims() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+    let claim_id = 1;
+    let verified_id = generate_and_publish_verified_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        START_EPOCH,
+        END_EPOCH + 1,
+        claim_id,
+    );
+    let verified = get_deal_proposal(&rt, verified_id);
+    activate_deals(&rt, SECTOR_EXPIRY, addrs.provider, 0, SECTOR_NUMBER + 1, &[verified_id]);
+    let cancel_epoch = START_EPOCH + 100;
+    rt.set_epoch(cancel_epoch);
+
+    // An unverified deal has no claim, and a verified deal's claim must exist.
+    expect_provider_is_control_address(&rt, addrs.provider, addrs.worker, true);
+    expect_get_claims(&rt, PROVIDER_ADDR.id().unwrap(), claim_id + 1, None);
+    let ret = cancel_deals(
+        &rt,
+        addrs.worker,
+        vec![
+            verified_deal_cancellation(deal_id, cancel_epoch, claim_id),
+            verified_deal_cancellation(verified_id, cancel_epoch, claim_id + 1),
+        ],
+    );
+    assert_eq!(vec![ExitCode::USR_ILLEGAL_ARGUMENT, ExitCode::USR_NOT_FOUND], ret.results.codes());
+
+    // The claim must be for the deal's sector.
+    expect_provider_is_control_address(&rt, addrs.provider, addrs.worker, true);
+    expect_cancelled_claim_checked(&rt, &verified, claim_id, SECTOR_NUMBER);
+    let ret = cancel_deals(
+        &rt,
+        addrs.worker,
+        vec![verified_deal_cancellation(verified_id, cancel_epoch, claim_id)],
+    );
+    assert_eq!(vec![ExitCode::USR_ILLEGAL_ARGUMENT], ret.results.codes());
+
+    // A claim is cancelled at most once per batch.
+    let cancellation = verified_deal_cancellation(verified_id, cancel_epoch, claim_id);
+    expect_provider_is_control_address(&rt, addrs.provider, addrs.worker, true);
+    expect_cancelled_claim_checked(&rt, &verified, claim_id, SECTOR_NUMBER + 1);
+    expect_sector_status(&rt, addrs.provider, SECTOR_NUMBER + 1, SECTOR_STATUS_ACTIVE);
+    expect_cancellation_authenticated(&rt, verified.client, &cancellation);
+    expect_emitted(
+        &rt,
+        "deal-cancelled",
+        verified_id,
+        CLIENT_ADDR.id().unwrap(),
+        PROVIDER_ADDR.id().unwrap(),
+    );
+    expect_claims_cancelled(&rt, PROVIDER_ADDR.id().unwrap(), vec![claim_id]);
+    expect_sector_deals_cancelled(
+        &rt,
+        addrs.provider,
+        &[(SECTOR_NUMBER + 1, 0, verified.piece_size.0)],
+    );
+    let ret = cancel_deals(
+        &rt,
+        addrs.worker,
+        vec![cancellation, verified_deal_cancellation(deal_id, cancel_epoch, claim_id)],
+    );
+    assert_eq!(vec![ExitCode::OK, ExitCode::USR_ILLEGAL_ARGUMENT], ret.results.codes());
+    assert_eq!(proposal, get_deal_proposal(&rt, deal_id));
+    check_state(&rt);
+}
+
+#[test]
+fn rejects_verified_deal_in_inactive_sector() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (faulty_claim, active_claim) = (1, 2);
+    let faulty_id = generate_and_publish_verified_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        START_EPOCH,
+        END_EPOCH,
+        faulty_claim,
+    );
+    let active_id = generate_and_publish_verified_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        START_EPOCH,
+        END_EPOCH + 1,
+        active_claim,
+    );
+    let faulty = get_deal_proposal(&rt, faulty_id);
+    let active = get_deal_proposal(&rt, active_id);
+    activate_deals(&rt, SECTOR_EXPIRY, addrs.provider, 0, SECTOR_NUMBER, &[faulty_id]);
+    activate_deals(&rt, SECTOR_EXPIRY, addrs.provider, 0, SECTOR_NUMBER + 1, &[active_id]);
+    let cancel_epoch = START_EPOCH + 100;
+    rt.set_epoch(cancel_epoch);
+
+    // The deal in the faulty sector fails without failing the rest of the batch.
+    let cancellations = vec![
+        verified_deal_cancellation(faulty_id, cancel_epoch, faulty_claim),
+        verified_deal_cancellation(active_id, cancel_epoch, active_claim),
+    ];
+    expect_provider_is_control_address(&rt, addrs.provider, addrs.worker, true);
+    expect_cancelled_claim_checked(&rt, &faulty, faulty_claim, SECTOR_NUMBER);
+    expect_sector_status(&rt, addrs.provider, SECTOR_NUMBER, SECTOR_STATUS_ACTIVE + 1);
+    expect_cancelled_claim_checked(&rt, &active, active_claim, SECTOR_NUMBER + 1);
+    expect_sector_status(&rt, addrs.provider, SECTOR_NUMBER + 1, SECTOR_STATUS_ACTIVE);
+    expect_cancellation_authenticated(&rt, active.client, &cancellations[1]);
+    expect_emitted(
+        &rt,
+        "deal-cancelled",
+        active_id,
+        CLIENT_ADDR.id().unwrap(),
+        PROVIDER_ADDR.id().unwrap(),
+    );
+    expect_claims_cancelled(&rt, PROVIDER_ADDR.id().unwrap(), vec![active_claim]);
+    expect_sector_deals_cancelled(
+        &rt,
+        addrs.provider,
+        &[(SECTOR_NUMBER + 1, 0, active.piece_size.0)],
+    );
+    let ret = cancel_deals(&rt, addrs.worker, cancellations);
+    assert_eq!(vec![ExitCode::USR_FORBIDDEN, ExitCode::OK], ret.results.codes());
+    assert_eq!(faulty, get_deal_proposal(&rt, faulty_id));
+    assert_deal_deleted(&rt, active_id, &active, SECTOR_NUMBER + 1, true);
+    check_state(&rt);
+}
+
+#[test]
+fn accepts_cancellation_signed_as_eip712_typed_data() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+    rt.set_delegated_address(CLIENT_ID, Address::new_delegated(EAM_ACTOR_ID, &[0xaa; 20]).unwrap());
+    let cancel_epoch = START_EPOCH + 100;
+    rt.set_epoch(cancel_epoch);
+    let mut cancellation = deal_cancellation(deal_id, cancel_epoch);
+    cancellation.eip712_signature = true;
+
+    // The signature is not over the CBOR encoding, but over the EIP-712 typed data.
+    expect_provider_is_control_address(&rt, addrs.provider, addrs.worker, true);
+    rt.expect_send(
+        CLIENT_ADDR,
+        AUTHENTICATE_MESSAGE_METHOD,
+        IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+            signature: cancellation.client_signature.bytes.clone(),
+            message: deal_cancellation_eip712_message(
+                &rt,
+                rt.chain_id.into(),
+                &cancellation.proposal,
+            ),
+        })
+        .unwrap(),
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+        IpldBlock::serialize_cbor(&true).unwrap(),
+        ExitCode::OK,
+        None,
+    );
+    expect_emitted(
+        &rt,
+        "deal-cancelled",
+        deal_id,
+        CLIENT_ADDR.id().unwrap(),
+        PROVIDER_ADDR.id().unwrap(),
+    );
+    expect_sector_deals_cancelled(
+        &rt,
+        addrs.provider,
+        &[(SECTOR_NUMBER, proposal.piece_size.0, 0)],
+    );
+    let ret = cancel_deals(&rt, addrs.worker, vec![cancellation]);
+    assert!(ret.results.all_ok());
+    check_state(&rt);
+}
+
+#[test]
+fn rejects_invalid_cancellations() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+    let unactivated_deal =
+        generate_and_publish_deal(&rt, CLIENT_ADDR, &addrs, START_EPOCH, END_EPOCH + 1).0;
+    rt.set_epoch(START_EPOCH + 100);
+    settle_deal_payments(&rt, addrs.provider, &[deal_id], &[], &[]);
+    let client_before = get_balance(&rt, &CLIENT_ADDR);
+
+    // Checks that need no calls out.
+    let mut expired_cancellation = deal_cancellation(deal_id, START_EPOCH + 100);
+    expired_cancellation.proposal.expiration = START_EPOCH + 99;
+    let ret = cancel_deals(
+        &rt,
+        addrs.worker,
+        vec![
+            deal_cancellation(deal_id, START_EPOCH + 101),
+            deal_cancellation(deal_id, START_EPOCH + 99),
+            deal_cancellation(unactivated_deal, START_EPOCH + 100),
+            deal_cancellation(1234, START_EPOCH + 100),
+            expired_cancellation,
+        ],
+    );
+    assert_eq!(
+        vec![
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            EX_DEAL_NOT_ACTIVATED,
+            ExitCode::USR_NOT_FOUND,
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+        ],
+        ret.results.codes()
+    );
+
+    // The caller must control the provider.
+    expect_provider_is_control_address(&rt, addrs.provider, CLIENT_ADDR, false);
+    let ret = cancel_deals(&rt, CLIENT_ADDR, vec![deal_cancellation(deal_id, START_EPOCH + 100)]);
+    assert_eq!(vec![ExitCode::USR_FORBIDDEN], ret.results.codes());
+
+    // Only a client with an Ethereum address may sign as EIP-712 typed data.
+    let mut eip712_cancellation = deal_cancellation(deal_id, START_EPOCH + 100);
+    eip712_cancellation.eip712_signature = true;
+    expect_provider_is_control_address(&rt, addrs.provider, addrs.worker, true);
+    let ret = cancel_deals(&rt, addrs.worker, vec![eip712_cancellation]);
+    assert_eq!(vec![ExitCode::USR_ILLEGAL_ARGUMENT], ret.results.codes());
+
+    // A deal is cancelled at most once per batch.
+    let cancellation = deal_cancellation(deal_id, START_EPOCH + 100);
+    expect_cancellation_checks(&rt, &proposal, addrs.worker, &cancellation);
+    expect_emitted(
+        &rt,
+        "deal-cancelled",
+        deal_id,
+        CLIENT_ADDR.id().unwrap(),
+        PROVIDER_ADDR.id().unwrap(),
+    );
+    expect_sector_deals_cancelled(
+        &rt,
+        addrs.provider,
+        &[(SECTOR_NUMBER, proposal.piece_size.0, 0)],
+    );
+    let ret = cancel_deals(&rt, addrs.worker, vec![cancellation.clone(), cancellation]);
+    assert_eq!(vec![ExitCode::OK, ExitCode::USR_ILLEGAL_ARGUMENT], ret.results.codes());
+
+    // Cancelling at the last settled epoch pays nothing more.
+    assert_eq!(client_before.balance, get_balance(&rt, &CLIENT_ADDR).balance);
+
+    // A cancelled deal is gone.
+    let ret = cancel_deals(&rt, addrs.worker, vec![deal_cancellation(deal_id, START_EPOCH + 100)]);
+    assert_eq!(vec![EX_DEAL_EXPIRED], ret.results.codes());
+    check_state(&rt);
+}
+
+#[test]
+fn ended_deal_cannot_be_cancelled() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+
+    rt.set_epoch(END_EPOCH);
+    let ret = cancel_deals(&rt, addrs.worker, vec![deal_cancellation(deal_id, END_EPOCH - 1)]);
+    assert_eq!(vec![EX_DEAL_EXPIRED], ret.results.codes());
+    assert_eq!(proposal, get_deal_proposal(&rt, deal_id));
+    assert!(!get_balance(&rt, &CLIENT_ADDR).locked.is_zero());
+    assert_eq!(proposal.provider_collateral, get_balance(&rt, &PROVIDER_ADDR).locked);
+    check_state(&rt);
+}
@@ -81,8 +81,11 @@ fn publishing_timed_out_deal_again_should_work_after_cron_tick_as_it_should_no_l
     );
     let buf = RawBytes::serialize(deal_proposal2.clone()).expect("failed to marshal deal proposal");
     let sig = Signature::new_bls(buf.to_vec());
-    let client_deal_proposal =
-        ClientDealProposal { proposal: deal_proposal2.clone(), client_signature: sig };
+    let client_deal_proposal = ClientDealProposal {
+        proposal: deal_proposal2.clone(),
+        client_signature: sig,
+        eip712_signature: false,
+    };
     let params = PublishStorageDealsParams { deals: vec![client_deal_proposal] };
     rt.expect_validate_caller_any();
     expect_provider_is_control_address(&rt, PROVIDER_ADDR, WORKER_ADDR, true);
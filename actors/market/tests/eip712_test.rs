@@ -0,0 +1,309 @@
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::utils::hex;
+use fil_actor_market::{
+    deal_cancellation_eip712_message, deal_extension_eip712_message, deal_proposal_eip712_message,
+    replicated_deal_proposal_eip712_message, token_deal_proposal_eip712_message,
+    DealCancellationProposal, DealExtensionProposal, DealProposal, Label, ReplicatedDealProposal,
+    TokenDealProposal,
+};
+use fil_actors_runtime::runtime::Primitives;
+use fil_actors_runtime::test_utils::{make_piece_cid, MockRuntime};
+use fil_actors_runtime::EAM_ACTOR_ID;
+use fvm_shared::address::Address;
+use fvm_shared::crypto::hash::SupportedHashes;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::piece::PaddedPieceSize;
+use serde_json::{json, Value};
+
+fn proposal(label: Label) -> DealProposal {
+    DealProposal {
+        piece_cid: make_piece_cid(b"eip712"),
+        piece_size: PaddedPieceSize(2048),
+        verified_deal: true,
+        client: Address::new_delegated(EAM_ACTOR_ID, &[0xaa; 20]).unwrap(),
+        provider: Address::new_id(1000),
+        label,
+        start_epoch: 100,
+        end_epoch: 200_000,
+        storage_price_per_epoch: TokenAmount::from_atto(1_000_000_000_000u64),
+        provider_collateral: TokenAmount::from_whole(2),
+        client_collateral: TokenAmount::from_atto(0),
+    }
+}
+
+fn domain_type() -> Value {
+    json!([
+        { "name": "name", "type": "string" },
+        { "name": "version", "type": "string" },
+        { "name": "chainId", "type": "uint256" },
+    ])
+}
+
+fn deal_proposal_type() -> Value {
+    json!([
+        { "name": "pieceCid", "type": "string" },
+        { "name": "pieceSize", "type": "uint64" },
+        { "name": "verifiedDeal", "type": "bool" },
+        { "name": "client", "type": "bytes" },
+        { "name": "provider", "type": "bytes" },
+        { "name": "label", "type": "string" },
+        { "name": "labelBytes", "type": "bytes" },
+        { "name": "startEpoch", "type": "int64" },
+        { "name": "endEpoch", "type": "int64" },
+        { "name": "storagePricePerEpoch", "type": "uint256" },
+        { "name": "providerCollateral", "type": "uint256" },
+        { "name": "clientCollateral", "type": "uint256" },
+    ])
+}
+
+fn hex_bytes(b: &[u8]) -> String {
+    format!("0x{}", hex::encode(b))
+}
+
+// ethers parses signed integers from hex strings only.
+fn hex_int(v: i64) -> String {
+    format!("{:#x}", v)
+}
+
+fn label_values(label: &Label) -> (String, String) {
+    match label {
+        Label::String(s) => (s.clone(), hex_bytes(&[])),
+        Label::Bytes(b) => (String::new(), hex_bytes(b)),
+    }
+}
+
+fn deal_proposal_value(proposal: &DealProposal) -> Value {
+    let (label, label_bytes) = label_values(&proposal.label);
+    json!({
+        "pieceCid": proposal.piece_cid.to_string(),
+        "pieceSize": proposal.piece_size.0.to_string(),
+        "verifiedDeal": proposal.verified_deal,
+        "client": hex_bytes(&proposal.client.to_bytes()),
+        "provider": hex_bytes(&proposal.provider.to_bytes()),
+        "label": label,
+        "labelBytes": label_bytes,
+        "startEpoch": hex_int(proposal.start_epoch),
+        "endEpoch": hex_int(proposal.end_epoch),
+        "storagePricePerEpoch": proposal.storage_price_per_epoch.atto().to_string(),
+        "providerCollateral": proposal.provider_collateral.atto().to_string(),
+        "clientCollateral": proposal.client_collateral.atto().to_string(),
+    })
+}
+
+// Computes the signing hash of typed data with an Ethereum library.
+fn ethers_signing_hash(
+    chain_id: u64,
+    types: Value,
+    primary_type: &str,
+    message: Value,
+) -> [u8; 32] {
+    let mut types = types;
+    types["EIP712Domain"] = domain_type();
+    let typed_data: TypedData = serde_json::from_value(json!({
+        "types": types,
+        "primaryType": primary_type,
+        "domain": {
+            "name": "Filecoin Storage Market",
+            "version": "1",
+            "chainId": chain_id,
+        },
+        "message": message,
+    }))
+    .unwrap();
+    typed_data.encode_eip712().unwrap()
+}
+
+fn assert_matches_ethers(chain_id: u64, proposal: &DealProposal) {
+    let rt = MockRuntime::default();
+    let message = deal_proposal_eip712_message(&rt, chain_id, proposal).unwrap();
+    assert_eq!(66, message.len());
+    let expected = ethers_signing_hash(
+        chain_id,
+        json!({ "DealProposal": deal_proposal_type() }),
+        "DealProposal",
+        deal_proposal_value(proposal),
+    );
+    assert_eq!(expected.to_vec(), rt.hash(SupportedHashes::Keccak256, &message));
+}
+
+#[test]
+fn signing_hash_matches_ethers() {
+    assert_matches_ethers(314, &proposal(Label::String("deal label".to_string())));
+    assert_matches_ethers(314, &proposal(Label::String(String::new())));
+    assert_matches_ethers(314159, &proposal(Label::Bytes(vec![0xde, 0xad, 0xbe, 0xef])));
+}
+
+#[test]
+fn signing_hash_depends_on_chain_id() {
+    let rt = MockRuntime::default();
+    let proposal = proposal(Label::String("deal label".to_string()));
+    assert_ne!(
+        deal_proposal_eip712_message(&rt, 314, &proposal).unwrap(),
+        deal_proposal_eip712_message(&rt, 314159, &proposal).unwrap()
+    );
+}
+
+#[test]
+fn signing_hash_distinguishes_label_types() {
+    let rt = MockRuntime::default();
+    assert_ne!(
+        deal_proposal_eip712_message(&rt, 314, &proposal(Label::String("abc".to_string())))
+            .unwrap(),
+        deal_proposal_eip712_message(&rt, 314, &proposal(Label::Bytes(b"abc".to_vec()))).unwrap()
+    );
+}
+
+#[test]
+fn rejects_negative_token_amounts() {
+    let rt = MockRuntime::default();
+    let mut proposal = proposal(Label::String(String::new()));
+    proposal.storage_price_per_epoch = TokenAmount::from_atto(-1);
+    assert!(deal_proposal_eip712_message(&rt, 314, &proposal).is_err());
+}
+
+#[test]
+fn token_deal_signing_hash_matches_ethers() {
+    let rt = MockRuntime::default();
+    let proposal = TokenDealProposal {
+        proposal: proposal(Label::String("deal label".to_string())),
+        payment_token: Address::new_id(1234),
+    };
+    let expected = ethers_signing_hash(
+        314,
+        json!({
+            "TokenDealProposal": [
+                { "name": "proposal", "type": "DealProposal" },
+                { "name": "paymentToken", "type": "bytes" },
+            ],
+            "DealProposal": deal_proposal_type(),
+        }),
+        "TokenDealProposal",
+        json!({
+            "proposal": deal_proposal_value(&proposal.proposal),
+            "paymentToken": hex_bytes(&proposal.payment_token.to_bytes()),
+        }),
+    );
+    let message = token_deal_proposal_eip712_message(&rt, 314, &proposal).unwrap();
+    assert_eq!(expected.to_vec(), rt.hash(SupportedHashes::Keccak256, &message));
+}
+
+#[test]
+fn replicated_deal_signing_hash_matches_ethers() {
+    let rt = MockRuntime::default();
+    let single = proposal(Label::Bytes(vec![0xde, 0xad]));
+    let proposal = ReplicatedDealProposal {
+        piece_cid: single.piece_cid,
+        piece_size: single.piece_size,
+        verified_deal: single.verified_deal,
+        client: single.client,
+        providers: vec![Address::new_id(1000), Address::new_id(1001), Address::new_id(1002)],
+        replicas: 2,
+        label: single.label.clone(),
+        start_epoch: single.start_epoch,
+        end_epoch: single.end_epoch,
+        storage_price_per_epoch: single.storage_price_per_epoch.clone(),
+        provider_collateral: single.provider_collateral.clone(),
+        client_collateral: single.client_collateral,
+    };
+    let (label, label_bytes) = label_values(&proposal.label);
+    let expected = ethers_signing_hash(
+        314,
+        json!({
+            "ReplicatedDealProposal": [
+                { "name": "pieceCid", "type": "string" },
+                { "name": "pieceSize", "type": "uint64" },
+                { "name": "verifiedDeal", "type": "bool" },
+                { "name": "client", "type": "bytes" },
+                { "name": "providers", "type": "bytes[]" },
+                { "name": "replicas", "type": "uint64" },
+                { "name": "label", "type": "string" },
+                { "name": "labelBytes", "type": "bytes" },
+                { "name": "startEpoch", "type": "int64" },
+                { "name": "endEpoch", "type": "int64" },
+                { "name": "storagePricePerEpoch", "type": "uint256" },
+                { "name": "providerCollateral", "type": "uint256" },
+                { "name": "clientCollateral", "type": "uint256" },
+            ],
+        }),
+        "ReplicatedDealProposal",
+        json!({
+            "pieceCid": proposal.piece_cid.to_string(),
+            "pieceSize": proposal.piece_size.0.to_string(),
+            "verifiedDeal": proposal.verified_deal,
+            "client": hex_bytes(&proposal.client.to_bytes()),
+            "providers": proposal.providers.iter().map(|p| hex_bytes(&p.to_bytes())).collect::<Vec<_>>(),
+            "replicas": proposal.replicas.to_string(),
+            "label": label,
+            "labelBytes": label_bytes,
+            "startEpoch": hex_int(proposal.start_epoch),
+            "endEpoch": hex_int(proposal.end_epoch),
+            "storagePricePerEpoch": proposal.storage_price_per_epoch.atto().to_string(),
+            "providerCollateral": proposal.provider_collateral.atto().to_string(),
+            "clientCollateral": proposal.client_collateral.atto().to_string(),
+        }),
+    );
+    let message = replicated_deal_proposal_eip712_message(&rt, 314, &proposal).unwrap();
+    assert_eq!(expected.to_vec(), rt.hash(SupportedHashes::Keccak256, &message));
+}
+
+#[test]
+fn deal_extension_signing_hash_matches_ethers() {
+    let rt = MockRuntime::default();
+    let extension = DealExtensionProposal {
+        deal_id: 42,
+        end_epoch: 300_000,
+        storage_price_per_epoch: TokenAmount::from_atto(2_000_000_000_000u64),
+        expiration: 150_000,
+    };
+    let expected = ethers_signing_hash(
+        314,
+        json!({
+            "DealExtension": [
+                { "name": "dealId", "type": "uint64" },
+                { "name": "endEpoch", "type": "int64" },
+                { "name": "storagePricePerEpoch", "type": "uint256" },
+                { "name": "expiration", "type": "int64" },
+            ],
+        }),
+        "DealExtension",
+        json!({
+            "dealId": extension.deal_id.to_string(),
+            "endEpoch": hex_int(extension.end_epoch),
+            "storagePricePerEpoch": extension.storage_price_per_epoch.atto().to_string(),
+            "expiration": hex_int(extension.expiration),
+        }),
+    );
+    let message = deal_extension_eip712_message(&rt, 314, &extension).unwrap();
+    assert_eq!(expected.to_vec(), rt.hash(SupportedHashes::Keccak256, &message));
+}
+
+#[test]
+fn deal_cancellation_signing_hash_matches_ethers() {
+    let rt = MockRuntime::default();
+    let cancellation = DealCancellationProposal {
+        deal_id: 42,
+        cancel_epoch: 120_000,
+        claim_id: 7,
+        expiration: 150_000,
+    };
+    let expected = ethers_signing_hash(
+        314,
+        json!({
+            "DealCancellation": [
+                { "name": "dealId", "type": "uint64" },
+                { "name": "cancelEpoch", "type": "int64" },
+                { "name": "claimId", "type": "uint64" },
+                { "name": "expiration", "type": "int64" },
+            ],
+        }),
+        "DealCancellation",
+        json!({
+            "dealId": cancellation.deal_id.to_string(),
+            "cancelEpoch": hex_int(cancellation.cancel_epoch),
+            "claimId": cancellation.claim_id.to_string(),
+            "expiration": hex_int(cancellation.expiration),
+        }),
+    );
+    let message = deal_cancellation_eip712_message(&rt, 314, &cancellation);
+    assert_eq!(expected.to_vec(), rt.hash(SupportedHashes::Keccak256, &message));
+}
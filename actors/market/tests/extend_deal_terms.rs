@@ -0,0 +1,357 @@
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
+
+use fil_actor_market::ext::account::{AuthenticateMessageParams, AUTHENTICATE_MESSAGE_METHOD};
+use fil_actor_market::{
+    deal_extension_eip712_message, DealSettlementSummary, GetDealTermParams, GetDealTermReturn,
+    GetDealTotalPriceParams, GetDealTotalPriceReturn, Method, EX_DEAL_EXPIRED,
+    EX_DEAL_NOT_ACTIVATED,
+};
+use fil_actors_runtime::network::EPOCHS_IN_DAY;
+use fil_actors_runtime::{BURNT_FUNDS_ACTOR_ADDR, EAM_ACTOR_ID};
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::address::Address;
+use fvm_shared::sys::SendFlags;
+use num_traits::Zero;
+
+use harness::*;
+
+mod harness;
+
+const START_EPOCH: ChainEpoch = 10;
+const END_EPOCH: ChainEpoch = START_EPOCH + 200 * EPOCHS_IN_DAY;
+const EXTENDED_END_EPOCH: ChainEpoch = END_EPOCH + 100 * EPOCHS_IN_DAY;
+const SECTOR_NUMBER: u64 = 7;
+const SECTOR_EXPIRY: ChainEpoch = EXTENDED_END_EPOCH + 100;
+
+#[test]
+fn extends_deal_and_settles_at_revised_price() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+    let new_price = &proposal.storage_price_per_epoch * 2;
+
+    let extension_epoch = START_EPOCH + 100;
+    rt.set_epoch(extension_epoch);
+    let old_payment = &proposal.storage_price_per_epoch * (extension_epoch - START_EPOCH);
+    let new_fee = &new_price * (EXTENDED_END_EPOCH - extension_epoch);
+    let old_fee_remaining = &proposal.storage_price_per_epoch * (END_EPOCH - extension_epoch);
+    add_participant_funds(&rt, CLIENT_ADDR, &new_fee - &old_fee_remaining);
+    let client_before = get_balance(&rt, &CLIENT_ADDR);
+    let provider_before = get_balance(&rt, &PROVIDER_ADDR);
+
+    let extension = deal_extension(deal_id, EXTENDED_END_EPOCH, new_price.clone());
+    expect_extension_checks(&rt, &proposal, addrs.worker, &extension, SECTOR_NUMBER, SECTOR_EXPIRY);
+    let ret = extend_deal_terms(&rt, addrs.worker, vec![extension]);
+    assert!(ret.results.all_ok());
+
+    // The deal is settled up to the extension at its original price,
+    // and the client's locked fee covers the remainder at the revised price.
+    let client_after = get_balance(&rt, &CLIENT_ADDR);
+    let provider_after = get_balance(&rt, &PROVIDER_ADDR);
+    assert_eq!(&client_before.balance - &old_payment, client_after.balance);
+    assert_eq!(&proposal.client_collateral + &new_fee, client_after.locked);
+    assert_eq!(&provider_before.balance + &old_payment, provider_after.balance);
+    assert_eq!(extension_epoch, get_deal_state(&rt, deal_id).last_updated_epoch);
+
+    rt.expect_validate_caller_any();
+    let term: GetDealTermReturn = rt
+        .call::<fil_actor_market::Actor>(
+            Method::GetDealTermExported as u64,
+            IpldBlock::serialize_cbor(&GetDealTermParams { id: deal_id }).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    assert_eq!(EXTENDED_END_EPOCH - START_EPOCH, term.duration);
+    rt.expect_validate_caller_any();
+    let price: GetDealTotalPriceReturn = rt
+        .call::<fil_actor_market::Actor>(
+            Method::GetDealTotalPriceExported as u64,
+            IpldBlock::serialize_cbor(&GetDealTotalPriceParams { id: deal_id }).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    assert_eq!(&old_payment + &new_fee, price.total_price);
+    check_state(&rt);
+
+    // The deal continues past its original end and completes at the extended end.
+    rt.set_epoch(END_EPOCH + 1);
+    let ret = settle_deal_payments(&rt, addrs.provider, &[deal_id], &[], &[]);
+    assert_eq!(
+        DealSettlementSummary {
+            completed: false,
+            payment: &new_price * (END_EPOCH + 1 - extension_epoch)
+        },
+        ret.settlements[0]
+    );
+    rt.set_epoch(EXTENDED_END_EPOCH);
+    let ret = settle_deal_payments(&rt, addrs.provider, &[deal_id], &[deal_id], &[]);
+    assert!(ret.settlements[0].completed);
+    assert_deal_deleted(&rt, deal_id, &proposal, SECTOR_NUMBER, true);
+
+    let client_after = get_balance(&rt, &CLIENT_ADDR);
+    assert_eq!(&client_before.balance - &old_payment - &new_fee, client_after.balance);
+    assert!(client_after.locked.is_zero());
+    check_state(&rt);
+}
+
+#[test]
+fn reduced_price_unlocks_client_fee() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+
+    // Extend before the deal starts, with no payment yet due.
+    rt.set_epoch(1);
+    let extension = deal_extension(deal_id, END_EPOCH + 1, TokenAmount::zero());
+    expect_extension_checks(&rt, &proposal, addrs.worker, &extension, SECTOR_NUMBER, SECTOR_EXPIRY);
+    let ret = extend_deal_terms(&rt, addrs.worker, vec![extension]);
+    assert!(ret.results.all_ok());
+
+    let client = get_balance(&rt, &CLIENT_ADDR);
+    assert_eq!(proposal.client_collateral, client.locked);
+    check_state(&rt);
+}
+
+#[test]
+fn insufficient_client_funds_rejects_extension() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+    rt.set_epoch(START_EPOCH + 100);
+    let client_before = get_balance(&rt, &CLIENT_ADDR);
+
+    let extension =
+        deal_extension(deal_id, EXTENDED_END_EPOCH, proposal.storage_price_per_epoch.clone());
+    expect_extension_checks(&rt, &proposal, addrs.worker, &extension, SECTOR_NUMBER, SECTOR_EXPIRY);
+    let ret = extend_deal_terms(&rt, addrs.worker, vec![extension]);
+    assert_eq!(vec![ExitCode::USR_INSUFFICIENT_FUNDS], ret.results.codes());
+
+    // Nothing is settled or locked for a rejected extension.
+    assert_eq!(client_before, get_balance(&rt, &CLIENT_ADDR));
+    assert_eq!(proposal, get_deal_proposal(&rt, deal_id));
+    check_state(&rt);
+}
+
+#[test]
+fn rejects_invalid_extensions() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+    let unactivated_deal =
+        generate_and_publish_deal(&rt, CLIENT_ADDR, &addrs, START_EPOCH, END_EPOCH + 1).0;
+    let price = proposal.storage_price_per_epoch.clone();
+    rt.set_epoch(START_EPOCH + 100);
+
+    // Checks that need no calls out.
+    let ret = extend_deal_terms(
+        &rt,
+        addrs.worker,
+        vec![
+            deal_extension(deal_id, END_EPOCH, price.clone()),
+            deal_extension(unactivated_deal, EXTENDED_END_EPOCH, price.clone()),
+            deal_extension(1234, EXTENDED_END_EPOCH, price.clone()),
+        ],
+    );
+    assert_eq!(
+        vec![ExitCode::USR_ILLEGAL_ARGUMENT, EX_DEAL_NOT_ACTIVATED, ExitCode::USR_NOT_FOUND],
+        ret.results.codes()
+    );
+    let ret = extend_deal_terms(
+        &rt,
+        addrs.worker,
+        vec![deal_extension(deal_id, START_EPOCH + 1300 * EPOCHS_IN_DAY, price.clone())],
+    );
+    assert_eq!(vec![ExitCode::USR_ILLEGAL_ARGUMENT], ret.results.codes());
+
+    // The caller must control the provider.
+    expect_provider_is_control_address(&rt, addrs.provider, CLIENT_ADDR, false);
+    let ret = extend_deal_terms(
+        &rt,
+        CLIENT_ADDR,
+        vec![deal_extension(deal_id, EXTENDED_END_EPOCH, price.clone())],
+    );
+    assert_eq!(vec![ExitCode::USR_FORBIDDEN], ret.results.codes());
+
+    // The deal cannot outlive its sector, and is extended at most once per batch.
+    let extension = deal_extension(deal_id, EXTENDED_END_EPOCH, price.clone());
+    expect_extension_checks(
+        &rt,
+        &proposal,
+        addrs.worker,
+        &extension,
+        SECTOR_NUMBER,
+        EXTENDED_END_EPOCH - 1,
+    );
+    let ret = extend_deal_terms(&rt, addrs.worker, vec![extension.clone(), extension]);
+    assert_eq!(
+        vec![ExitCode::USR_ILLEGAL_ARGUMENT, ExitCode::USR_ILLEGAL_ARGUMENT],
+        ret.results.codes()
+    );
+
+    // An ended deal cannot be extended.
+    rt.set_epoch(END_EPOCH);
+    let ret = extend_deal_terms(
+        &rt,
+        addrs.worker,
+        vec![deal_extension(deal_id, EXTENDED_END_EPOCH, price)],
+    );
+    assert_eq!(vec![EX_DEAL_EXPIRED], ret.results.codes());
+    assert_eq!(proposal, get_deal_proposal(&rt, deal_id));
+    check_state(&rt);
+}
+
+#[test]
+fn rejects_expired_extension() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+    let extension_epoch = START_EPOCH + 100;
+    rt.set_epoch(extension_epoch);
+    let mut extension = deal_extension(deal_id, EXTENDED_END_EPOCH, TokenAmount::zero());
+    extension.proposal.expiration = extension_epoch - 1;
+    let ret = extend_deal_terms(&rt, addrs.worker, vec![extension.clone()]);
+    assert_eq!(vec![ExitCode::USR_ILLEGAL_ARGUMENT], ret.results.codes());
+
+    // The extension may be made up to and including its expiration.
+    extension.proposal.expiration = extension_epoch;
+    expect_extension_checks(&rt, &proposal, addrs.worker, &extension, SECTOR_NUMBER, SECTOR_EXPIRY);
+    let ret = extend_deal_terms(&rt, addrs.worker, vec![extension]);
+    assert!(ret.results.all_ok());
+    check_state(&rt);
+}
+
+#[test]
+fn eth_client_signs_extension_as_typed_data() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, _) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+    rt.set_delegated_address(CLIENT_ID, Address::new_delegated(EAM_ACTOR_ID, &[0xaa; 20]).unwrap());
+    rt.set_epoch(START_EPOCH + 100);
+    let mut extension = deal_extension(deal_id, EXTENDED_END_EPOCH, TokenAmount::zero());
+    extension.eip712_signature = true;
+
+    // The signature is not over the CBOR encoding, but over the EIP-712 typed data.
+    expect_provider_is_control_address(&rt, addrs.provider, addrs.worker, true);
+    rt.expect_send(
+        CLIENT_ADDR,
+        AUTHENTICATE_MESSAGE_METHOD,
+        IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+            signature: extension.client_signature.bytes.clone(),
+            message: deal_extension_eip712_message(&rt, rt.chain_id.into(), &extension.proposal)
+                .unwrap(),
+        })
+        .unwrap(),
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+        IpldBlock::serialize_cbor(&true).unwrap(),
+        ExitCode::OK,
+        None,
+    );
+    expect_get_sector_expiration(&rt, addrs.provider, SECTOR_NUMBER, SECTOR_EXPIRY);
+    let ret = extend_deal_terms(&rt, addrs.worker, vec![extension]);
+    assert!(ret.results.all_ok());
+    check_state(&rt);
+}
+
+#[test]
+fn termination_after_original_end_slashes_extended_deal() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, proposal) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+    rt.set_epoch(START_EPOCH + 100);
+    let extension = deal_extension(deal_id, EXTENDED_END_EPOCH, TokenAmount::zero());
+    expect_extension_checks(&rt, &proposal, addrs.worker, &extension, SECTOR_NUMBER, SECTOR_EXPIRY);
+    assert!(extend_deal_terms(&rt, addrs.worker, vec![extension]).results.all_ok());
+
+    rt.set_epoch(END_EPOCH + 100);
+    rt.expect_send_simple(
+        BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        None,
+        proposal.provider_collateral.clone(),
+        None,
+        ExitCode::OK,
+    );
+    terminate_deals_raw(&rt, addrs.provider, &[SECTOR_NUMBER], &[deal_id]).unwrap();
+    rt.verify();
+    assert_deal_deleted(&rt, deal_id, &proposal, SECTOR_NUMBER, true);
+
+    let client = get_balance(&rt, &CLIENT_ADDR);
+    assert!(client.locked.is_zero());
+    // The provider keeps the payment settled at extension, but its collateral is burnt.
+    let provider = get_balance(&rt, &PROVIDER_ADDR);
+    assert_eq!(&proposal.storage_price_per_epoch * 100, provider.balance);
+    assert!(provider.locked.is_zero());
+    check_state(&rt);
+}
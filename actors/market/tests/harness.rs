@@ -6,7 +6,9 @@ use std::{cell::RefCell, collections::HashMap, collections::HashSet};
 
 use cid::Cid;
 use fil_actors_runtime::reward::{FilterEstimate, ThisEpochRewardReturn};
-use frc46_token::token::types::{TransferFromParams, TransferFromReturn};
+use frc46_token::receiver::{FRC46TokenReceived, FRC46_TOKEN_TYPE};
+use frc46_token::token::types::{TransferFromParams, TransferFromReturn, TransferParams};
+use fvm_actor_utils::receiver::UniversalReceiverParams;
 use fvm_ipld_bitfield::BitField;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::{to_vec, RawBytes};
@@ -18,7 +20,8 @@ use fvm_shared::piece::PaddedPieceSize;
 use fvm_shared::sector::{RegisteredSealProof, SectorNumber, StoragePower};
 use fvm_shared::sys::SendFlags;
 use fvm_shared::{
-    address::Address, econ::TokenAmount, error::ExitCode, ActorID, METHOD_CONSTRUCTOR, METHOD_SEND,
+    address::Address, econ::TokenAmount, error::ExitCode, ActorID, MethodNum, METHOD_CONSTRUCTOR,
+    METHOD_SEND,
 };
 use num_traits::{FromPrimitive, Zero};
 use regex::Regex;
@@ -27,12 +30,23 @@ use fil_actor_market::ext::account::{AuthenticateMessageParams, AUTHENTICATE_MES
 use fil_actor_market::ext::miner::{
     PieceChange, SectorChanges, SectorContentChangedParams, SectorContentChangedReturn,
 };
-use fil_actor_market::ext::verifreg::{AllocationID, AllocationRequest, AllocationsResponse};
+use fil_actor_market::ext::verifreg::{
+    AllocationID, AllocationRequest, AllocationsResponse, CancelClaimsParams, Claim, ClaimID,
+    GetClaimsParams, GetClaimsReturn,
+};
 use fil_actor_market::{
-    deal_cid, deal_get_payment_remaining, BatchActivateDealsParams, BatchActivateDealsResult,
-    DealOpsByEpoch, PendingDealAllocationsMap, PendingProposalsSet, ProviderSectorsMap,
-    SectorDealsMap, SettleDealPaymentsParams, SettleDealPaymentsReturn, PENDING_ALLOCATIONS_CONFIG,
-    PENDING_PROPOSALS_CONFIG, PROVIDER_SECTORS_CONFIG, SECTOR_DEALS_CONFIG,
+    deal_cid, deal_get_payment_remaining, replicated_deal_cid, BatchActivateDealsParams,
+    BatchActivateDealsResult, CancelDealsParams, CancelDealsReturn, ClientDealCancellationProposal,
+    ClientDealExtensionProposal, ClientReplicatedDealProposal, ClientTokenDealProposal,
+    DealCancellationProposal, DealExtensionProposal, DealOpsByEpoch, ExtendDealTermsParams,
+    ExtendDealTermsReturn, GetDealPaymentTokenParams, GetDealPaymentTokenReturn,
+    GetDealTotalPriceParams, GetDealTotalPriceReturn, GetReplicaDealsParams, GetTokenBalanceParams,
+    ListDealSummariesReturn, ListDealsParams, ListDealsReturn, PendingDealAllocationsMap,
+    PendingProposalsSet, ProviderSectorsMap, PublishReplicaDealsParams, PublishTokenDealsParams,
+    ReplicaDeals, ReplicatedDealProposal, SectorDealsMap, SettleDealPaymentsParams,
+    SettleDealPaymentsReturn, TokenDealProposal, WithdrawTokenBalanceParams,
+    PENDING_ALLOCATIONS_CONFIG, PENDING_PROPOSALS_CONFIG, PROVIDER_SECTORS_CONFIG,
+    SECTOR_DEALS_CONFIG,
 };
 use fil_actor_market::{
     ext, ext::miner::GetControlAddressesReturnParams, next_update_epoch,
@@ -741,8 +755,11 @@ pub fn publish_deals(
         // create a client proposal with a valid signature
         let buf = RawBytes::serialize(deal.clone()).expect("failed to marshal deal proposal");
         let sig = Signature::new_bls("does not matter".as_bytes().to_vec());
-        let client_proposal =
-            ClientDealProposal { proposal: deal.clone(), client_signature: sig.clone() };
+        let client_proposal = ClientDealProposal {
+            proposal: deal.clone(),
+            client_signature: sig.clone(),
+            eip712_signature: false,
+        };
         params.deals.push(client_proposal);
 
         // Expect an invocation of authenticate_message to verify the signature.
@@ -931,7 +948,7 @@ pub fn publish_deals_expect_abort(
 
     rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, WORKER_ADDR);
     let deal_params = PublishStorageDealsParams {
-        deals: vec![ClientDealProposal { proposal, client_signature }],
+        deals: vec![ClientDealProposal { proposal, client_signature, eip712_signature: false }],
     };
     expect_abort(
         expected_exit_code,
@@ -1299,7 +1316,11 @@ where
     );
 
     let params: PublishStorageDealsParams = PublishStorageDealsParams {
-        deals: vec![ClientDealProposal { proposal: deal_proposal, client_signature: sig }],
+        deals: vec![ClientDealProposal {
+            proposal: deal_proposal,
+            client_signature: sig,
+            eip712_signature: false,
+        }],
     };
 
     assert_eq!(
@@ -1787,3 +1808,659 @@ pub fn expect_emitted(rt: &MockRuntime, typ: &str, id: DealID, client: ActorID,
             .unwrap(),
     );
 }
+
+pub fn deal_extension(
+    deal_id: DealID,
+    end_epoch: ChainEpoch,
+    storage_price_per_epoch: TokenAmount,
+) -> ClientDealExtensionProposal {
+    ClientDealExtensionProposal {
+        // A deal can't be extended after it ends, so this doesn't limit the extension.
+        proposal: DealExtensionProposal {
+            deal_id,
+            end_epoch,
+            storage_price_per_epoch,
+            expiration: end_epoch,
+        },
+        client_signature: Signature::new_bls("does not matter".as_bytes().to_vec()),
+        eip712_signature: false,
+    }
+}
+
+pub fn expect_extension_authenticated(
+    rt: &MockRuntime,
+    client: Address,
+    extension: &ClientDealExtensionProposal,
+) {
+    rt.expect_send(
+        client,
+        ext::account::AUTHENTICATE_MESSAGE_METHOD,
+        IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+            signature: extension.client_signature.bytes.clone(),
+            message: serialize(&extension.proposal, "deal extension proposal").unwrap().to_vec(),
+        })
+        .unwrap(),
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+        AUTHENTICATE_MESSAGE_RESPONSE.clone(),
+        ExitCode::OK,
+        None,
+    );
+}
+
+pub fn expect_get_sector_expiration(
+    rt: &MockRuntime,
+    provider: Address,
+    sector_number: SectorNumber,
+    expiration: ChainEpoch,
+) {
+    rt.expect_send_simple(
+        provider,
+        ext::miner::GET_SECTOR_EXPIRATION_EXPORTED,
+        IpldBlock::serialize_cbor(&ext::miner::GetSectorExpirationParams { sector_number })
+            .unwrap(),
+        TokenAmount::zero(),
+        IpldBlock::serialize_cbor(&ext::miner::GetSectorExpirationReturn { expiration }).unwrap(),
+        ExitCode::OK,
+    );
+}
+
+/// Expects the checks made for a single valid extension of a deal in the given sector,
+/// submitted by a control address of the deal's provider.
+pub fn expect_extension_checks(
+    rt: &MockRuntime,
+    deal: &DealProposal,
+    caller: Address,
+    extension: &ClientDealExtensionProposal,
+    sector_number: SectorNumber,
+    sector_expiration: ChainEpoch,
+) {
+    expect_provider_is_control_address(rt, deal.provider, caller, true);
+    expect_extension_authenticated(rt, deal.client, extension);
+    expect_get_sector_expiration(rt, deal.provider, sector_number, sector_expiration);
+}
+
+pub fn extend_deal_terms(
+    rt: &MockRuntime,
+    caller: Address,
+    extensions: Vec<ClientDealExtensionProposal>,
+) -> ExtendDealTermsReturn {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
+    rt.expect_validate_caller_any();
+    let params = IpldBlock::serialize_cbor(&ExtendDealTermsParams { extensions }).unwrap();
+    let ret = rt
+        .call::<MarketActor>(Method::ExtendDealTermsExported as u64, params)
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret
+}
+
+pub fn deal_cancellation(
+    deal_id: DealID,
+    cancel_epoch: ChainEpoch,
+) -> ClientDealCancellationProposal {
+    verified_deal_cancellation(deal_id, cancel_epoch, NO_ALLOCATION_ID)
+}
+
+pub fn verified_deal_cancellation(
+    deal_id: DealID,
+    cancel_epoch: ChainEpoch,
+    claim_id: ClaimID,
+) -> ClientDealCancellationProposal {
+    ClientDealCancellationProposal {
+        // The cancellation may be made for up to a day after the cancel epoch.
+        proposal: DealCancellationProposal {
+            deal_id,
+            cancel_epoch,
+            claim_id,
+            expiration: cancel_epoch + EPOCHS_IN_DAY,
+        },
+        client_signature: Signature::new_bls("does not matter".as_bytes().to_vec()),
+        eip712_signature: false,
+    }
+}
+
+pub fn expect_cancellation_authenticated(
+    rt: &MockRuntime,
+    client: Address,
+    cancellation: &ClientDealCancellationProposal,
+) {
+    rt.expect_send(
+        client,
+        ext::account::AUTHENTICATE_MESSAGE_METHOD,
+        IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+            signature: cancellation.client_signature.bytes.clone(),
+            message: serialize(&cancellation.proposal, "deal cancellation proposal")
+                .unwrap()
+                .to_vec(),
+        })
+        .unwrap(),
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+        AUTHENTICATE_MESSAGE_RESPONSE.clone(),
+        ExitCode::OK,
+        None,
+    );
+}
+
+pub fn expect_cancellation_checks(
+    rt: &MockRuntime,
+    deal: &DealProposal,
+    caller: Address,
+    cancellation: &ClientDealCancellationProposal,
+) {
+    expect_provider_is_control_address(rt, deal.provider, caller, true);
+    expect_cancellation_authenticated(rt, deal.client, cancellation);
+}
+
+/// Expects the verified registry to be asked for the claim of a verified deal being cancelled,
+/// returning a claim matching the deal in the given sector.
+pub fn expect_cancelled_claim_checked(
+    rt: &MockRuntime,
+    deal: &DealProposal,
+    claim_id: ClaimID,
+    sector_number: SectorNumber,
+) {
+    let claim = Claim {
+        provider: deal.provider.id().unwrap(),
+        client: deal.client.id().unwrap(),
+        data: deal.piece_cid,
+        size: deal.piece_size,
+        term_min: deal.end_epoch - deal.start_epoch,
+        term_max: deal.end_epoch - deal.start_epoch,
+        term_start: deal.start_epoch,
+        sector: sector_number,
+    };
+    expect_get_claims(rt, deal.provider.id().unwrap(), claim_id, Some(claim));
+}
+
+/// Expects the provider to be asked for the status of a sector holding a verified deal being
+/// cancelled.
+pub fn expect_sector_status(
+    rt: &MockRuntime,
+    provider: Address,
+    sector_number: SectorNumber,
+    status: u8,
+) {
+    rt.expect_send_simple(
+        provider,
+        ext::miner::GET_SECTOR_STATUS_EXPORTED,
+        IpldBlock::serialize_cbor(&ext::miner::GetSectorStatusParams { sector_number }).unwrap(),
+        TokenAmount::zero(),
+        IpldBlock::serialize_cbor(&ext::miner::GetSectorStatusReturn { status }).unwrap(),
+        ExitCode::OK,
+    );
+}
+
+/// Expects the verified registry to be asked for a single claim, returning it if present.
+pub fn expect_get_claims(
+    rt: &MockRuntime,
+    provider: ActorID,
+    claim_id: ClaimID,
+    claim: Option<Claim>,
+) {
+    let batch_info = if claim.is_some() {
+        BatchReturn::ok(1)
+    } else {
+        BatchReturn::of(&[ExitCode::USR_NOT_FOUND])
+    };
+    rt.expect_send_simple(
+        VERIFIED_REGISTRY_ACTOR_ADDR,
+        ext::verifreg::GET_CLAIMS_METHOD,
+        IpldBlock::serialize_cbor(&GetClaimsParams { provider, claim_ids: vec![claim_id] })
+            .unwrap(),
+        TokenAmount::zero(),
+        IpldBlock::serialize_cbor(&GetClaimsReturn {
+            batch_info,
+            claims: claim.into_iter().collect(),
+        })
+        .unwrap(),
+        ExitCode::OK,
+    );
+}
+
+/// Expects the verified registry to be asked to remove the claims of cancelled verified deals.
+pub fn expect_claims_cancelled(rt: &MockRuntime, provider: ActorID, claim_ids: Vec<ClaimID>) {
+    rt.expect_send_simple(
+        VERIFIED_REGISTRY_ACTOR_ADDR,
+        ext::verifreg::CANCEL_CLAIMS_METHOD,
+        IpldBlock::serialize_cbor(&CancelClaimsParams { provider, claim_ids }).unwrap(),
+        TokenAmount::zero(),
+        None,
+        ExitCode::OK,
+    );
+}
+
+/// Expects the provider to be notified of the unverified and verified deal space cancelled in
+/// each sector.
+pub fn expect_sector_deals_cancelled(
+    rt: &MockRuntime,
+    provider: Address,
+    sectors: &[(SectorNumber, u64, u64)],
+) {
+    let params = ext::miner::SectorDealsCancelledParams {
+        sectors: sectors
+            .iter()
+            .map(|(sector_number, deal_space, verified_deal_space)| {
+                ext::miner::SectorDealsCancellation {
+                    sector_number: *sector_number,
+                    deal_space: *deal_space,
+                    verified_deal_space: *verified_deal_space,
+                }
+            })
+            .collect(),
+    };
+    rt.expect_send_simple(
+        provider,
+        ext::miner::SECTOR_DEALS_CANCELLED,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+        TokenAmount::zero(),
+        None,
+        ExitCode::OK,
+    );
+}
+
+pub fn cancel_deals(
+    rt: &MockRuntime,
+    caller: Address,
+    cancellations: Vec<ClientDealCancellationProposal>,
+) -> CancelDealsReturn {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, caller);
+    rt.expect_validate_caller_any();
+    let params = IpldBlock::serialize_cbor(&CancelDealsParams { cancellations }).unwrap();
+    let ret = rt
+        .call::<MarketActor>(Method::CancelDealsExported as u64, params)
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret
+}
+
+/// Lists a page of a party's deal IDs, or summaries, with the given exported method.
+pub fn list_deals_raw(
+    rt: &MockRuntime,
+    method: MethodNum,
+    party: Address,
+    cursor: Option<DealID>,
+    limit: u64,
+) -> Result<Option<IpldBlock>, ActorError> {
+    rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(1234));
+    rt.expect_validate_caller_any();
+    let params = IpldBlock::serialize_cbor(&ListDealsParams { party, cursor, limit }).unwrap();
+    let ret = rt.call::<MarketActor>(method, params);
+    rt.verify();
+    ret
+}
+
+/// Lists all of a party's deal IDs by paging through them with the given exported method.
+pub fn list_all_deals(
+    rt: &MockRuntime,
+    method: MethodNum,
+    party: Address,
+    limit: u64,
+) -> Vec<DealID> {
+    let mut deals = vec![];
+    let mut cursor = None;
+    loop {
+        let page: ListDealsReturn = list_deals_raw(rt, method, party, cursor, limit)
+            .unwrap()
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        assert!(page.deals.len() as u64 <= limit);
+        deals.extend(page.deals);
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    deals.sort();
+    deals
+}
+
+/// Lists a page of a party's deal IDs with the given exported method.
+pub fn list_deals(
+    rt: &MockRuntime,
+    method: MethodNum,
+    party: Address,
+    cursor: Option<DealID>,
+    limit: u64,
+) -> ListDealsReturn {
+    list_deals_raw(rt, method, party, cursor, limit).unwrap().unwrap().deserialize().unwrap()
+}
+
+pub fn list_deal_summaries(
+    rt: &MockRuntime,
+    method: MethodNum,
+    party: Address,
+    cursor: Option<DealID>,
+    limit: u64,
+) -> ListDealSummariesReturn {
+    list_deals_raw(rt, method, party, cursor, limit).unwrap().unwrap().deserialize().unwrap()
+}
+
+/// Transfers tokens to the market's escrow for a beneficiary, as the token actor's receiver hook.
+pub fn add_token_funds(
+    rt: &MockRuntime,
+    token: Address,
+    from: Address,
+    beneficiary: Option<Address>,
+    amount: TokenAmount,
+) -> Result<Option<IpldBlock>, ActorError> {
+    rt.set_caller(*EVM_ACTOR_CODE_ID, token);
+    rt.expect_validate_caller_any();
+    let operator_data = match beneficiary {
+        Some(beneficiary) => serialize(&beneficiary, "add balance params").unwrap(),
+        None => RawBytes::default(),
+    };
+    let payload = FRC46TokenReceived {
+        from: from.id().unwrap(),
+        to: STORAGE_MARKET_ACTOR_ADDR.id().unwrap(),
+        operator: from.id().unwrap(),
+        amount,
+        operator_data,
+        token_data: RawBytes::default(),
+    };
+    let params = UniversalReceiverParams {
+        type_: FRC46_TOKEN_TYPE,
+        payload: serialize(&payload, "payload").unwrap(),
+    };
+    let ret = rt.call::<MarketActor>(
+        Method::UniversalReceiverHook as u64,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+    );
+    rt.verify();
+    ret
+}
+
+pub fn get_token_balance(rt: &MockRuntime, token: Address, addr: Address) -> GetBalanceReturn {
+    rt.expect_validate_caller_any();
+    let ret = rt
+        .call::<MarketActor>(
+            Method::GetTokenBalanceExported as u64,
+            IpldBlock::serialize_cbor(&GetTokenBalanceParams { token, account: addr }).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret
+}
+
+pub fn withdraw_client_tokens(
+    rt: &MockRuntime,
+    token: Address,
+    withdraw_amount: TokenAmount,
+    expected_send: TokenAmount,
+    client: Address,
+) {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, client);
+    rt.expect_validate_caller_addr(vec![client]);
+    if expected_send.is_positive() {
+        rt.expect_send_simple(
+            token,
+            ext::token::TRANSFER_METHOD,
+            IpldBlock::serialize_cbor(&TransferParams {
+                to: client,
+                amount: expected_send.clone(),
+                operator_data: RawBytes::default(),
+            })
+            .unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+    }
+
+    let params =
+        WithdrawTokenBalanceParams { token, provider_or_client: client, amount: withdraw_amount };
+    let ret: WithdrawBalanceReturn = rt
+        .call::<MarketActor>(
+            Method::WithdrawTokenBalanceExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    assert_eq!(expected_send, ret.amount_withdrawn);
+}
+
+pub fn get_deal_payment_token(rt: &MockRuntime, deal_id: DealID) -> Option<ActorID> {
+    rt.expect_validate_caller_any();
+    let ret: GetDealPaymentTokenReturn = rt
+        .call::<MarketActor>(
+            Method::GetDealPaymentTokenExported as u64,
+            IpldBlock::serialize_cbor(&GetDealPaymentTokenParams { id: deal_id }).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret.token
+}
+
+pub fn get_deal_total_price(rt: &MockRuntime, deal_id: DealID) -> GetDealTotalPriceReturn {
+    rt.expect_validate_caller_any();
+    let ret = rt
+        .call::<MarketActor>(
+            Method::GetDealTotalPriceExported as u64,
+            IpldBlock::serialize_cbor(&GetDealTotalPriceParams { id: deal_id }).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret
+}
+
+/// Publishes deals paid in a token, of which only those marked valid are expected to be published.
+pub fn publish_token_deals(
+    rt: &MockRuntime,
+    addrs: &MinerAddresses,
+    token: Address,
+    deals: &[DealProposal],
+    valid: &[bool],
+) -> PublishStorageDealsReturn {
+    let st: State = rt.get_state();
+    let mut deal_id = st.next_id;
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, addrs.worker);
+    rt.expect_validate_caller_any();
+    expect_provider_is_control_address(rt, addrs.provider, addrs.worker, true);
+    expect_query_network_info(rt);
+
+    let mut params = PublishTokenDealsParams { deals: vec![] };
+    for deal in deals {
+        let proposal = TokenDealProposal { proposal: deal.clone(), payment_token: token };
+        let client_signature = Signature::new_bls("does not matter".as_bytes().to_vec());
+        rt.expect_send(
+            deal.client,
+            ext::account::AUTHENTICATE_MESSAGE_METHOD,
+            IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+                signature: client_signature.bytes.clone(),
+                message: serialize(&proposal, "token deal proposal").unwrap().to_vec(),
+            })
+            .unwrap(),
+            TokenAmount::zero(),
+            None,
+            SendFlags::READ_ONLY,
+            AUTHENTICATE_MESSAGE_RESPONSE.clone(),
+            ExitCode::OK,
+            None,
+        );
+        params.deals.push(ClientTokenDealProposal {
+            proposal,
+            client_signature,
+            eip712_signature: false,
+        });
+    }
+
+    for (deal, _) in deals.iter().zip(valid).filter(|(_, valid)| **valid) {
+        rt.expect_send_simple(
+            deal.client,
+            MARKET_NOTIFY_DEAL_METHOD,
+            IpldBlock::serialize_cbor(&MarketNotifyDealParams {
+                proposal: serialize(deal, "deal proposal").unwrap().to_vec(),
+                deal_id,
+            })
+            .unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+        expect_emitted(
+            rt,
+            "deal-published",
+            deal_id,
+            deal.client.id().unwrap(),
+            deal.provider.id().unwrap(),
+        );
+        deal_id += 1;
+    }
+
+    let ret = rt
+        .call::<MarketActor>(
+            Method::PublishTokenDealsExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret
+}
+
+/// Generates a replicated proposal allowing each of the providers to publish a replica,
+/// and adds funds for the client to pay for every replica and for each provider's collateral.
+pub fn generate_replicated_deal_and_add_funds(
+    rt: &MockRuntime,
+    client: Address,
+    providers: &[MinerAddresses],
+    replicas: u64,
+    start_epoch: ChainEpoch,
+    end_epoch: ChainEpoch,
+) -> ReplicatedDealProposal {
+    let deal = generate_deal_proposal(client, providers[0].provider, start_epoch, end_epoch);
+    for addrs in providers {
+        add_provider_funds(rt, deal.provider_collateral.clone(), addrs);
+    }
+    add_participant_funds(rt, client, deal.client_balance_requirement() * replicas);
+    ReplicatedDealProposal {
+        piece_cid: deal.piece_cid,
+        piece_size: deal.piece_size,
+        verified_deal: deal.verified_deal,
+        client: deal.client,
+        providers: providers.iter().map(|addrs| addrs.provider).collect(),
+        replicas,
+        label: deal.label,
+        start_epoch: deal.start_epoch,
+        end_epoch: deal.end_epoch,
+        storage_price_per_epoch: deal.storage_price_per_epoch,
+        provider_collateral: deal.provider_collateral,
+        client_collateral: deal.client_collateral,
+    }
+}
+
+/// Publishes a replica of each replicated proposal for a provider, of which only those marked
+/// valid are expected to be published.
+/// The client is expected to authenticate each proposal that allows the provider a replica.
+pub fn publish_replica_deals(
+    rt: &MockRuntime,
+    addrs: &MinerAddresses,
+    deals: &[ReplicatedDealProposal],
+    valid: &[bool],
+) -> Result<PublishStorageDealsReturn, ActorError> {
+    let st: State = rt.get_state();
+    let mut deal_id = st.next_id;
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, addrs.worker);
+    rt.expect_validate_caller_any();
+    expect_provider_is_control_address(rt, addrs.provider, addrs.worker, true);
+    expect_query_network_info(rt);
+
+    let mut params = PublishReplicaDealsParams { provider: addrs.provider, deals: vec![] };
+    for deal in deals {
+        let client_signature = Signature::new_bls("does not matter".as_bytes().to_vec());
+        let allowed = deal.replicas > 0
+            && deal.replicas <= deal.providers.len() as u64
+            && deal.providers.contains(&addrs.provider);
+        if allowed {
+            rt.expect_send(
+                deal.client,
+                ext::account::AUTHENTICATE_MESSAGE_METHOD,
+                IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+                    signature: client_signature.bytes.clone(),
+                    message: serialize(deal, "replicated deal proposal").unwrap().to_vec(),
+                })
+                .unwrap(),
+                TokenAmount::zero(),
+                None,
+                SendFlags::READ_ONLY,
+                AUTHENTICATE_MESSAGE_RESPONSE.clone(),
+                ExitCode::OK,
+                None,
+            );
+        }
+        params.deals.push(ClientReplicatedDealProposal {
+            proposal: deal.clone(),
+            client_signature,
+            eip712_signature: false,
+        });
+    }
+
+    for (deal, _) in deals.iter().zip(valid).filter(|(_, valid)| **valid) {
+        let replica = deal.replica(addrs.provider);
+        rt.expect_send_simple(
+            deal.client,
+            MARKET_NOTIFY_DEAL_METHOD,
+            IpldBlock::serialize_cbor(&MarketNotifyDealParams {
+                proposal: serialize(&replica, "deal proposal").unwrap().to_vec(),
+                deal_id,
+            })
+            .unwrap(),
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+        expect_emitted(
+            rt,
+            "deal-published",
+            deal_id,
+            deal.client.id().unwrap(),
+            addrs.provider.id().unwrap(),
+        );
+        deal_id += 1;
+    }
+
+    let ret = rt.call::<MarketActor>(
+        Method::PublishReplicaDealsExported as u64,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+    );
+    rt.verify();
+    Ok(ret?.unwrap().deserialize().unwrap())
+}
+
+pub fn get_replica_deals(
+    rt: &MockRuntime,
+    proposal: &ReplicatedDealProposal,
+) -> Result<ReplicaDeals, ActorError> {
+    rt.expect_validate_caller_any();
+    let proposal_cid = replicated_deal_cid(rt, proposal).unwrap();
+    let ret = rt.call::<MarketActor>(
+        Method::GetReplicaDealsExported as u64,
+        IpldBlock::serialize_cbor(&GetReplicaDealsParams { proposal_cid }).unwrap(),
+    );
+    rt.verify();
+    Ok(ret?.unwrap().deserialize().unwrap())
+}
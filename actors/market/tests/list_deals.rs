@@ -0,0 +1,247 @@
+use fvm_shared::address::Address;
+use fvm_shared::clock::{ChainEpoch, EPOCH_UNDEFINED};
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use num_traits::Zero;
+use regex::Regex;
+
+use fil_actor_market::{Method, PartyDealSummary, State, MAX_LIST_DEALS_LIMIT, NO_ALLOCATION_ID};
+use fil_actors_runtime::network::EPOCHS_IN_DAY;
+use fil_actors_runtime::test_utils::*;
+
+use harness::*;
+
+mod harness;
+
+const START_EPOCH: ChainEpoch = 10;
+const END_EPOCH: ChainEpoch = START_EPOCH + 200 * EPOCHS_IN_DAY;
+const SECTOR_NUMBER: u64 = 7;
+const SECTOR_EXPIRY: ChainEpoch = END_EPOCH + 100;
+
+#[test]
+fn lists_deals_by_client_and_provider() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let other_client = Address::new_id(900);
+
+    let client_deals: Vec<_> = (0..5)
+        .map(|i| generate_deal_and_add_funds(&rt, CLIENT_ADDR, &addrs, START_EPOCH, END_EPOCH + i))
+        .collect();
+    let other_deal = generate_deal_and_add_funds(&rt, other_client, &addrs, START_EPOCH, END_EPOCH);
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, addrs.worker);
+    let mut deal_ids =
+        publish_deals(&rt, &addrs, &client_deals, TokenAmount::zero(), NO_ALLOCATION_ID);
+    let other_id =
+        publish_deals(&rt, &addrs, &[other_deal], TokenAmount::zero(), NO_ALLOCATION_ID)[0];
+
+    // Pages of any size together list each of a party's deals once.
+    for limit in [1, 2, 5, MAX_LIST_DEALS_LIMIT] {
+        assert_eq!(
+            deal_ids,
+            list_all_deals(&rt, Method::GetClientDealsExported as u64, CLIENT_ADDR, limit)
+        );
+    }
+    assert_eq!(
+        vec![other_id],
+        list_all_deals(&rt, Method::GetClientDealsExported as u64, other_client, 2)
+    );
+    deal_ids.push(other_id);
+    assert_eq!(
+        deal_ids,
+        list_all_deals(&rt, Method::GetProviderDealsExported as u64, PROVIDER_ADDR, 4)
+    );
+
+    // A party with no deals has none listed.
+    assert!(list_all_deals(&rt, Method::GetProviderDealsExported as u64, CLIENT_ADDR, 4).is_empty());
+    check_state(&rt);
+}
+
+#[test]
+fn lists_deal_summaries() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (activated_id, activated) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        1,
+        SECTOR_EXPIRY,
+    );
+    let (pending_id, pending) =
+        generate_and_publish_deal(&rt, CLIENT_ADDR, &addrs, START_EPOCH, END_EPOCH + 1);
+
+    let ret = list_deal_summaries(
+        &rt,
+        Method::GetProviderDealSummariesExported as u64,
+        PROVIDER_ADDR,
+        None,
+        MAX_LIST_DEALS_LIMIT,
+    );
+    assert_eq!(None, ret.next_cursor);
+    // Deals are listed in ID order.
+    assert_eq!(
+        vec![
+            PartyDealSummary {
+                id: activated_id,
+                client: CLIENT_ADDR.id().unwrap(),
+                provider: PROVIDER_ADDR.id().unwrap(),
+                piece_cid: activated.piece_cid,
+                piece_size: activated.piece_size,
+                verified_deal: false,
+                start_epoch: START_EPOCH,
+                end_epoch: END_EPOCH,
+                storage_price_per_epoch: activated.storage_price_per_epoch.clone(),
+                activated: 1,
+            },
+            PartyDealSummary {
+                id: pending_id,
+                client: CLIENT_ADDR.id().unwrap(),
+                provider: PROVIDER_ADDR.id().unwrap(),
+                piece_cid: pending.piece_cid,
+                piece_size: pending.piece_size,
+                verified_deal: false,
+                start_epoch: START_EPOCH,
+                end_epoch: END_EPOCH + 1,
+                storage_price_per_epoch: pending.storage_price_per_epoch.clone(),
+                activated: EPOCH_UNDEFINED,
+            },
+        ],
+        ret.deals
+    );
+
+    // Pages of summaries follow the same cursor as pages of IDs.
+    let first = list_deal_summaries(
+        &rt,
+        Method::GetClientDealSummariesExported as u64,
+        CLIENT_ADDR,
+        None,
+        1,
+    );
+    assert_eq!(1, first.deals.len());
+    let second = list_deal_summaries(
+        &rt,
+        Method::GetClientDealSummariesExported as u64,
+        CLIENT_ADDR,
+        first.next_cursor,
+        1,
+    );
+    assert_eq!(Some(second.deals[0].id), first.next_cursor);
+    assert_eq!(None, second.next_cursor);
+    check_state(&rt);
+}
+
+#[test]
+fn removed_deals_are_unlisted() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (first_id, _) =
+        generate_and_publish_deal(&rt, CLIENT_ADDR, &addrs, START_EPOCH, END_EPOCH + 1);
+    let (deal_id, _) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+    let (remaining_id, _) =
+        generate_and_publish_deal(&rt, CLIENT_ADDR, &addrs, START_EPOCH, END_EPOCH + 2);
+
+    // Start listing, with a cursor at the deal to be removed.
+    let page = list_deals(&rt, Method::GetClientDealsExported as u64, CLIENT_ADDR, None, 1);
+    assert_eq!(vec![first_id], page.deals);
+    assert_eq!(Some(deal_id), page.next_cursor);
+
+    rt.set_epoch(START_EPOCH + 100);
+    terminate_deals(&rt, addrs.provider, &[SECTOR_NUMBER], &[deal_id]);
+    assert_eq!(
+        vec![first_id, remaining_id],
+        list_all_deals(&rt, Method::GetClientDealsExported as u64, CLIENT_ADDR, 2)
+    );
+    assert_eq!(
+        vec![first_id, remaining_id],
+        list_all_deals(&rt, Method::GetProviderDealsExported as u64, PROVIDER_ADDR, 2)
+    );
+    // Listing from a cursor at a deal that has since been removed resumes at the next deal.
+    let page =
+        list_deals(&rt, Method::GetClientDealsExported as u64, CLIENT_ADDR, page.next_cursor, 1);
+    assert_eq!(vec![remaining_id], page.deals);
+    assert_eq!(None, page.next_cursor);
+    check_state(&rt);
+}
+
+#[test]
+fn backfills_index_of_deals_published_before_upgrade() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, _) = publish_and_activate_deal(
+        &rt,
+        CLIENT_ADDR,
+        &addrs,
+        SECTOR_NUMBER,
+        START_EPOCH,
+        END_EPOCH,
+        0,
+        SECTOR_EXPIRY,
+    );
+    let (pending_id, _) =
+        generate_and_publish_deal(&rt, CLIENT_ADDR, &addrs, START_EPOCH, END_EPOCH + 1);
+
+    // Clear the indexes, as for deals published before they were introduced.
+    let mut st: State = rt.get_state();
+    let empty = State::new(&rt.store).unwrap();
+    st.client_deals = empty.client_deals;
+    st.provider_deals = empty.provider_deals;
+    rt.replace_state(&st);
+    assert!(list_all_deals(&rt, Method::GetClientDealsExported as u64, CLIENT_ADDR, 2).is_empty());
+    check_state_with_expected(
+        &rt,
+        &[
+            Regex::new("^client deals missing from index").unwrap(),
+            Regex::new("^provider deals missing from index").unwrap(),
+        ],
+    );
+
+    st.init_v15_fields(&rt.store).unwrap();
+    rt.replace_state(&st);
+    assert_eq!(
+        vec![deal_id, pending_id],
+        list_all_deals(&rt, Method::GetClientDealsExported as u64, CLIENT_ADDR, 2)
+    );
+    assert_eq!(
+        vec![deal_id, pending_id],
+        list_all_deals(&rt, Method::GetProviderDealsExported as u64, PROVIDER_ADDR, 2)
+    );
+    check_state(&rt);
+}
+
+#[test]
+fn rejects_invalid_listing_params() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    generate_and_publish_deal(&rt, CLIENT_ADDR, &addrs, START_EPOCH, END_EPOCH);
+
+    for method in
+        [Method::GetClientDealsExported as u64, Method::GetProviderDealSummariesExported as u64]
+    {
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            list_deals_raw(&rt, method, CLIENT_ADDR, None, 0),
+        );
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            list_deals_raw(&rt, method, CLIENT_ADDR, None, MAX_LIST_DEALS_LIMIT + 1),
+        );
+    }
+    let unresolvable = Address::new_secp256k1(&[3; 65]).unwrap();
+    expect_abort(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        list_deals_raw(&rt, Method::GetClientDealsExported as u64, unresolvable, None, 1),
+    );
+    check_state(&rt);
+}
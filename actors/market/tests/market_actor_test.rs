@@ -851,7 +851,11 @@ fn provider_and_client_addresses_are_resolved_before_persisting_state_and_sent_t
     let mut params = PublishStorageDealsParams { deals: vec![] };
     let buf = RawBytes::serialize(&deal).expect("failed to marshal deal proposal");
     let sig = Signature::new_bls(buf.to_vec());
-    let client_proposal = ClientDealProposal { client_signature: sig, proposal: deal.clone() };
+    let client_proposal = ClientDealProposal {
+        client_signature: sig,
+        proposal: deal.clone(),
+        eip712_signature: false,
+    };
     params.deals.push(client_proposal);
     // expect a call to verify the above signature
 
@@ -1471,7 +1475,11 @@ fn cannot_publish_the_same_deal_twice_before_a_cron_tick() {
     let buf = RawBytes::serialize(d2.clone()).expect("failed to marshal deal proposal");
     let sig = Signature::new_bls(buf.to_vec());
     let params = PublishStorageDealsParams {
-        deals: vec![ClientDealProposal { proposal: d2.clone(), client_signature: sig }],
+        deals: vec![ClientDealProposal {
+            proposal: d2.clone(),
+            client_signature: sig,
+            eip712_signature: false,
+        }],
     };
     rt.expect_validate_caller_any();
     expect_provider_is_control_address(&rt, PROVIDER_ADDR, WORKER_ADDR, true);
@@ -1899,8 +1907,16 @@ fn insufficient_client_balance_in_a_batch() {
     let sig2 = Signature::new_bls(buf2.to_vec());
     let params = PublishStorageDealsParams {
         deals: vec![
-            ClientDealProposal { proposal: deal1.clone(), client_signature: sig1 },
-            ClientDealProposal { proposal: deal2.clone(), client_signature: sig2 },
+            ClientDealProposal {
+                proposal: deal1.clone(),
+                client_signature: sig1,
+                eip712_signature: false,
+            },
+            ClientDealProposal {
+                proposal: deal2.clone(),
+                client_signature: sig2,
+                eip712_signature: false,
+            },
         ],
     };
 
@@ -2047,8 +2063,16 @@ fn insufficient_provider_balance_in_a_batch() {
 
     let params = PublishStorageDealsParams {
         deals: vec![
-            ClientDealProposal { proposal: deal1.clone(), client_signature: sig1 },
-            ClientDealProposal { proposal: deal2.clone(), client_signature: sig2 },
+            ClientDealProposal {
+                proposal: deal1.clone(),
+                client_signature: sig1,
+                eip712_signature: false,
+            },
+            ClientDealProposal {
+                proposal: deal2.clone(),
+                client_signature: sig2,
+                eip712_signature: false,
+            },
         ],
     };
 
@@ -2202,7 +2226,11 @@ fn psd_restricted_correctly() {
     let sig = Signature::new_bls(buf.to_vec());
 
     let params = PublishStorageDealsParams {
-        deals: vec![ClientDealProposal { proposal: deal.clone(), client_signature: sig }],
+        deals: vec![ClientDealProposal {
+            proposal: deal.clone(),
+            client_signature: sig,
+            eip712_signature: false,
+        }],
     };
 
     // set caller to not-builtin
@@ -251,7 +251,11 @@ fn fail_when_provider_has_some_funds_but_not_enough_for_a_deal() {
     let buf = RawBytes::serialize(deal1.clone()).expect("failed to marshal deal proposal");
     let sig = Signature::new_bls(buf.to_vec());
     let params = PublishStorageDealsParams {
-        deals: vec![ClientDealProposal { proposal: deal1.clone(), client_signature: sig }],
+        deals: vec![ClientDealProposal {
+            proposal: deal1.clone(),
+            client_signature: sig,
+            eip712_signature: false,
+        }],
     };
 
     rt.expect_validate_caller_any();
@@ -315,8 +319,16 @@ fn fail_when_deals_have_different_providers() {
     let sig2 = Signature::new_bls(buf2.to_vec());
     let params = PublishStorageDealsParams {
         deals: vec![
-            ClientDealProposal { proposal: deal1.clone(), client_signature: sig1 },
-            ClientDealProposal { proposal: deal2.clone(), client_signature: sig2 },
+            ClientDealProposal {
+                proposal: deal1.clone(),
+                client_signature: sig1,
+                eip712_signature: false,
+            },
+            ClientDealProposal {
+                proposal: deal2.clone(),
+                client_signature: sig2,
+                eip712_signature: false,
+            },
         ],
     };
 
@@ -426,7 +438,11 @@ fn fail_to_resolve_provider_address() {
 
     let sig = Signature::new_bls("does not matter".as_bytes().to_vec());
     let params = PublishStorageDealsParams {
-        deals: vec![ClientDealProposal { proposal: deal, client_signature: sig }],
+        deals: vec![ClientDealProposal {
+            proposal: deal,
+            client_signature: sig,
+            eip712_signature: false,
+        }],
     };
     rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, WORKER_ADDR);
     rt.expect_validate_caller_any();
@@ -449,7 +465,11 @@ fn caller_is_not_the_same_as_the_worker_address_for_miner() {
     let deal = generate_deal_proposal(CLIENT_ADDR, PROVIDER_ADDR, start_epoch, end_epoch);
     let sig = Signature::new_bls("does not matter".as_bytes().to_vec());
     let params = PublishStorageDealsParams {
-        deals: vec![ClientDealProposal { proposal: deal, client_signature: sig }],
+        deals: vec![ClientDealProposal {
+            proposal: deal,
+            client_signature: sig,
+            eip712_signature: false,
+        }],
     };
 
     rt.expect_validate_caller_any();
@@ -478,7 +498,11 @@ fn fails_if_provider_is_not_a_storage_miner_actor() {
 
     let sig = Signature::new_bls("does not matter".as_bytes().to_vec());
     let params = PublishStorageDealsParams {
-        deals: vec![ClientDealProposal { proposal: deal, client_signature: sig }],
+        deals: vec![ClientDealProposal {
+            proposal: deal,
+            client_signature: sig,
+            eip712_signature: false,
+        }],
     };
 
     rt.expect_validate_caller_any();
@@ -510,7 +534,11 @@ fn fails_if_notify_deal_fails() {
     let buf = RawBytes::serialize(deal.clone()).expect("failed to marshal deal proposal");
     let sig = Signature::new_bls("does not matter".as_bytes().to_vec());
     let params = PublishStorageDealsParams {
-        deals: vec![ClientDealProposal { proposal: deal, client_signature: sig.clone() }],
+        deals: vec![ClientDealProposal {
+            proposal: deal,
+            client_signature: sig.clone(),
+            eip712_signature: false,
+        }],
     };
 
     rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, WORKER_ADDR);
@@ -0,0 +1,200 @@
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::error::ExitCode;
+
+use fil_actor_market::{ReplicaDeal, ReplicaDeals, State};
+use fil_actors_runtime::network::EPOCHS_IN_DAY;
+use fil_actors_runtime::test_utils::*;
+
+use harness::*;
+
+mod harness;
+
+const START_EPOCH: ChainEpoch = 10;
+const END_EPOCH: ChainEpoch = START_EPOCH + 200 * EPOCHS_IN_DAY;
+
+fn providers() -> Vec<MinerAddresses> {
+    vec![
+        MinerAddresses::default(),
+        MinerAddresses { provider: Address::new_id(201), ..MinerAddresses::default() },
+        MinerAddresses { provider: Address::new_id(202), ..MinerAddresses::default() },
+    ]
+}
+
+#[test]
+fn providers_publish_replicas_up_to_limit() {
+    let rt = setup();
+    let providers = providers();
+    let proposal = generate_replicated_deal_and_add_funds(
+        &rt,
+        CLIENT_ADDR,
+        &providers,
+        2,
+        START_EPOCH,
+        END_EPOCH,
+    );
+    expect_abort(ExitCode::USR_NOT_FOUND, get_replica_deals(&rt, &proposal));
+
+    let first = publish_replica_deals(&rt, &providers[1], &[proposal.clone()], &[true]).unwrap();
+    let second = publish_replica_deals(&rt, &providers[0], &[proposal.clone()], &[true]).unwrap();
+    let (first_id, second_id) = (first.ids[0], second.ids[0]);
+    // The replica limit is reached, so the third provider's replica is dropped.
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "All deal proposals invalid",
+        publish_replica_deals(&rt, &providers[2], &[proposal.clone()], &[false]),
+    );
+
+    assert_eq!(
+        ReplicaDeals {
+            replicas: 2,
+            start_epoch: START_EPOCH,
+            deals: vec![
+                ReplicaDeal { provider: 201, deal_id: first_id },
+                ReplicaDeal { provider: PROVIDER_ID, deal_id: second_id },
+            ],
+        },
+        get_replica_deals(&rt, &proposal).unwrap()
+    );
+    assert_eq!(proposal.replica(providers[1].provider), find_deal_proposal(&rt, first_id).unwrap());
+    assert_eq!(
+        proposal.replica(providers[0].provider),
+        find_deal_proposal(&rt, second_id).unwrap()
+    );
+
+    // Each replica is activated and tracked as a separate deal.
+    activate_deals(&rt, END_EPOCH + 100, PROVIDER_ADDR, START_EPOCH - 1, 1, &[second_id]);
+    let st: State = rt.get_state();
+    assert_eq!(
+        START_EPOCH - 1,
+        st.find_deal_state(&rt.store, second_id).unwrap().unwrap().sector_start_epoch
+    );
+    assert!(st.find_deal_state(&rt.store, first_id).unwrap().is_none());
+    check_state(&rt);
+}
+
+#[test]
+fn provider_publishes_one_replica() {
+    let rt = setup();
+    let providers = providers();
+    let proposal = generate_replicated_deal_and_add_funds(
+        &rt,
+        CLIENT_ADDR,
+        &providers,
+        3,
+        START_EPOCH,
+        END_EPOCH,
+    );
+
+    // A second replica in the same batch is a duplicate deal.
+    let ret = publish_replica_deals(
+        &rt,
+        &providers[0],
+        &[proposal.clone(), proposal.clone()],
+        &[true, false],
+    )
+    .unwrap();
+    assert_eq!(1, ret.ids.len());
+
+    // A later batch cannot publish another replica, even once the first is activated
+    // and no longer pending.
+    activate_deals(&rt, END_EPOCH + 100, PROVIDER_ADDR, START_EPOCH - 1, 1, &ret.ids);
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "All deal proposals invalid",
+        publish_replica_deals(&rt, &providers[0], &[proposal.clone()], &[false]),
+    );
+    assert_eq!(1, get_replica_deals(&rt, &proposal).unwrap().deals.len());
+    check_state(&rt);
+}
+
+#[test]
+fn replica_deals_removed_at_start_epoch() {
+    let rt = setup();
+    let providers = providers();
+    let proposal = generate_replicated_deal_and_add_funds(
+        &rt,
+        CLIENT_ADDR,
+        &providers,
+        2,
+        START_EPOCH,
+        END_EPOCH,
+    );
+    let ret = publish_replica_deals(&rt, &providers[0], &[proposal.clone()], &[true]).unwrap();
+    activate_deals(&rt, END_EPOCH + 100, PROVIDER_ADDR, START_EPOCH - 1, 1, &ret.ids);
+
+    // The replicas are retained until the proposal's start epoch.
+    rt.set_epoch(START_EPOCH - 1);
+    cron_tick(&rt);
+    assert_eq!(1, get_replica_deals(&rt, &proposal).unwrap().deals.len());
+    check_state(&rt);
+
+    // From then no replica can be published, so cron removes them.
+    rt.set_epoch(START_EPOCH);
+    cron_tick(&rt);
+    expect_abort(ExitCode::USR_NOT_FOUND, get_replica_deals(&rt, &proposal));
+    let st: State = rt.get_state();
+    assert!(st
+        .load_replica_deals_by_epoch(&rt.store)
+        .unwrap()
+        .get(&START_EPOCH)
+        .unwrap()
+        .is_none());
+    check_state(&rt);
+}
+
+#[test]
+fn rejects_replica_not_allowed_to_provider() {
+    let rt = setup();
+    let providers = providers();
+    let proposal = generate_replicated_deal_and_add_funds(
+        &rt,
+        CLIENT_ADDR,
+        &providers[..2],
+        1,
+        START_EPOCH,
+        END_EPOCH,
+    );
+    add_provider_funds(&rt, proposal.provider_collateral.clone(), &providers[2]);
+
+    // The proposal does not name the provider, so is rejected before the client is asked
+    // to authenticate it.
+    expect_abort(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        publish_replica_deals(&rt, &providers[2], &[proposal.clone()], &[false]),
+    );
+    // A proposal must allow at least one replica, and no more than one per provider.
+    for replicas in [0, 3] {
+        let invalid = fil_actor_market::ReplicatedDealProposal { replicas, ..proposal.clone() };
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            publish_replica_deals(&rt, &providers[0], &[invalid], &[false]),
+        );
+    }
+    check_state(&rt);
+}
+
+#[test]
+fn client_escrow_covers_each_replica() {
+    let rt = setup();
+    let providers = providers();
+    let proposal = generate_replicated_deal_and_add_funds(
+        &rt,
+        CLIENT_ADDR,
+        &providers,
+        2,
+        START_EPOCH,
+        END_EPOCH,
+    );
+    // Withdrawing a replica's share of the client's escrow leaves enough for only one replica.
+    let replica_cost = proposal.replica(PROVIDER_ADDR).client_balance_requirement();
+    withdraw_client_balance(&rt, replica_cost.clone(), replica_cost, CLIENT_ADDR);
+
+    publish_replica_deals(&rt, &providers[0], &[proposal.clone()], &[true]).unwrap();
+    expect_abort(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        publish_replica_deals(&rt, &providers[1], &[proposal.clone()], &[false]),
+    );
+    assert_eq!(1, get_replica_deals(&rt, &proposal).unwrap().deals.len());
+    check_state(&rt);
+}
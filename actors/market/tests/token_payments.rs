@@ -0,0 +1,219 @@
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::deal::DealID;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::METHOD_SEND;
+
+use fil_actor_market::{Actor as MarketActor, DealProposal, Label, Method, State};
+use fil_actors_runtime::network::EPOCHS_IN_DAY;
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{BURNT_FUNDS_ACTOR_ADDR, DATACAP_TOKEN_ACTOR_ADDR};
+use fvm_actor_utils::receiver::UniversalReceiverParams;
+use num_traits::Zero;
+
+use harness::*;
+
+mod harness;
+
+const TOKEN_ID: u64 = 300;
+const TOKEN_ADDR: Address = Address::new_id(TOKEN_ID);
+const START_EPOCH: ChainEpoch = 10;
+const END_EPOCH: ChainEpoch = START_EPOCH + 200 * EPOCHS_IN_DAY;
+const SECTOR_NUMBER: u64 = 7;
+const SECTOR_EXPIRY: ChainEpoch = END_EPOCH + 100;
+
+#[test]
+fn deposits_and_withdraws_tokens() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let amount = TokenAmount::from_whole(10);
+
+    // Tokens are credited to the sender, or to the beneficiary named in the operator data.
+    add_token_funds(&rt, TOKEN_ADDR, CLIENT_ADDR, None, amount.clone()).unwrap();
+    expect_provider_control_address(&rt, addrs.provider, addrs.owner, addrs.worker);
+    add_token_funds(&rt, TOKEN_ADDR, CLIENT_ADDR, Some(addrs.provider), amount.clone()).unwrap();
+    let client = get_token_balance(&rt, TOKEN_ADDR, CLIENT_ADDR);
+    assert_eq!(amount, client.balance);
+    assert!(client.locked.is_zero());
+    assert_eq!(amount, get_token_balance(&rt, TOKEN_ADDR, addrs.provider).balance);
+    assert_account_zero(&rt, CLIENT_ADDR);
+
+    // Withdrawal yields at most the available balance.
+    let withdrawn = TokenAmount::from_whole(4);
+    withdraw_client_tokens(&rt, TOKEN_ADDR, withdrawn.clone(), withdrawn.clone(), CLIENT_ADDR);
+    withdraw_client_tokens(&rt, TOKEN_ADDR, amount.clone(), &amount - &withdrawn, CLIENT_ADDR);
+    assert!(get_token_balance(&rt, TOKEN_ADDR, CLIENT_ADDR).balance.is_zero());
+
+    // Nothing is transferred of a token never deposited.
+    let other_token = Address::new_id(TOKEN_ID + 1);
+    withdraw_client_tokens(&rt, other_token, amount, TokenAmount::zero(), CLIENT_ADDR);
+    check_state(&rt);
+}
+
+#[test]
+fn rejects_invalid_token_transfers() {
+    let rt = setup();
+    let amount = TokenAmount::from_whole(10);
+
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        add_token_funds(&rt, DATACAP_TOKEN_ACTOR_ADDR, CLIENT_ADDR, None, amount),
+    );
+    expect_abort(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        add_token_funds(&rt, TOKEN_ADDR, CLIENT_ADDR, None, TokenAmount::zero()),
+    );
+
+    rt.set_caller(*EVM_ACTOR_CODE_ID, TOKEN_ADDR);
+    rt.expect_validate_caller_any();
+    let params = UniversalReceiverParams { type_: 0, payload: RawBytes::default() };
+    expect_abort(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        rt.call::<MarketActor>(
+            Method::UniversalReceiverHook as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        ),
+    );
+    rt.verify();
+    check_state(&rt);
+}
+
+#[test]
+fn publishes_deals_with_storage_fee_locked_in_tokens() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let deal = generate_deal_proposal(CLIENT_ADDR, addrs.provider, START_EPOCH, END_EPOCH);
+    let mut unfunded_deal = deal.clone();
+    unfunded_deal.label = Label::String("unfunded".to_string());
+
+    // FIL covers the collateral of both deals, but tokens cover only one storage fee.
+    add_provider_funds(&rt, &deal.provider_collateral * 2, &addrs);
+    add_participant_funds(&rt, CLIENT_ADDR, &deal.client_collateral * 2);
+    add_token_funds(&rt, TOKEN_ADDR, CLIENT_ADDR, None, deal.total_storage_fee()).unwrap();
+
+    let ret = publish_token_deals(
+        &rt,
+        &addrs,
+        TOKEN_ADDR,
+        &[deal.clone(), unfunded_deal],
+        &[true, false],
+    );
+    assert_eq!(1, ret.ids.len());
+    let deal_id = ret.ids[0];
+    assert_eq!(deal, get_deal_proposal(&rt, deal_id));
+    assert_eq!(Some(TOKEN_ID), get_deal_payment_token(&rt, deal_id));
+    let price = get_deal_total_price(&rt, deal_id);
+    assert_eq!(deal.total_storage_fee(), price.total_price);
+
+    // Collateral is locked in FIL, and the storage fee in tokens.
+    assert_eq!(deal.client_collateral, get_balance(&rt, &CLIENT_ADDR).locked);
+    let client_tokens = get_token_balance(&rt, TOKEN_ADDR, CLIENT_ADDR);
+    assert_eq!(deal.total_storage_fee(), client_tokens.locked);
+    withdraw_client_tokens(
+        &rt,
+        TOKEN_ADDR,
+        deal.total_storage_fee(),
+        TokenAmount::zero(),
+        CLIENT_ADDR,
+    );
+    check_state(&rt);
+}
+
+#[test]
+fn token_deal_pays_provider_in_tokens() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, deal) = publish_and_activate_token_deal(&rt, &addrs);
+    let fee = deal.total_storage_fee();
+
+    rt.set_epoch(START_EPOCH + 100);
+    let ret = settle_deal_payments(&rt, addrs.provider, &[deal_id], &[], &[]);
+    let payment = &deal.storage_price_per_epoch * 100;
+    assert_eq!(payment, ret.settlements[0].payment);
+    assert_eq!(payment, get_token_balance(&rt, TOKEN_ADDR, addrs.provider).balance);
+    let client_tokens = get_token_balance(&rt, TOKEN_ADDR, CLIENT_ADDR);
+    assert_eq!(&fee - &payment, client_tokens.balance);
+    assert_eq!(&fee - &payment, client_tokens.locked);
+    // No FIL changes hands.
+    assert_eq!(deal.provider_collateral, get_balance(&rt, &addrs.provider).balance);
+    check_state(&rt);
+
+    rt.set_epoch(END_EPOCH);
+    let ret = settle_deal_payments(&rt, addrs.provider, &[deal_id], &[deal_id], &[]);
+    assert!(ret.settlements[0].completed);
+    assert_eq!(fee, get_token_balance(&rt, TOKEN_ADDR, addrs.provider).balance);
+    let client_tokens = get_token_balance(&rt, TOKEN_ADDR, CLIENT_ADDR);
+    assert!(client_tokens.balance.is_zero());
+    assert!(client_tokens.locked.is_zero());
+    assert!(get_balance(&rt, &CLIENT_ADDR).locked.is_zero());
+    let st: State = rt.get_state();
+    assert_eq!(None, st.find_payment_token(&rt.store, deal_id).unwrap());
+    check_state(&rt);
+}
+
+#[test]
+fn terminated_token_deal_unlocks_client_tokens() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let (deal_id, deal) = publish_and_activate_token_deal(&rt, &addrs);
+    let fee = deal.total_storage_fee();
+
+    rt.set_epoch(START_EPOCH + 100);
+    terminate_deals(&rt, addrs.provider, &[SECTOR_NUMBER], &[deal_id]);
+
+    // The provider is paid in tokens up to termination, and its collateral is burnt.
+    let payment = &deal.storage_price_per_epoch * 100;
+    assert_eq!(payment, get_token_balance(&rt, TOKEN_ADDR, addrs.provider).balance);
+    let client_tokens = get_token_balance(&rt, TOKEN_ADDR, CLIENT_ADDR);
+    assert_eq!(&fee - &payment, client_tokens.balance);
+    assert!(client_tokens.locked.is_zero());
+    assert_account_zero(&rt, addrs.provider);
+    assert!(get_balance(&rt, &CLIENT_ADDR).locked.is_zero());
+    check_state(&rt);
+}
+
+#[test]
+fn timed_out_token_deal_unlocks_client_tokens() {
+    let rt = setup();
+    let addrs = MinerAddresses::default();
+    let deal = fund_token_deal(&rt, &addrs);
+    let deal_id = publish_token_deals(&rt, &addrs, TOKEN_ADDR, &[deal.clone()], &[true]).ids[0];
+
+    rt.set_epoch(process_epoch(START_EPOCH, deal_id));
+    rt.expect_send_simple(
+        BURNT_FUNDS_ACTOR_ADDR,
+        METHOD_SEND,
+        None,
+        deal.provider_collateral.clone(),
+        None,
+        ExitCode::OK,
+    );
+    cron_tick(&rt);
+
+    let client_tokens = get_token_balance(&rt, TOKEN_ADDR, CLIENT_ADDR);
+    assert_eq!(deal.total_storage_fee(), client_tokens.balance);
+    assert!(client_tokens.locked.is_zero());
+    assert_deal_deleted(&rt, deal_id, &deal, 0, true);
+    check_state(&rt);
+}
+
+fn fund_token_deal(rt: &MockRuntime, addrs: &MinerAddresses) -> DealProposal {
+    let deal = generate_deal_proposal(CLIENT_ADDR, addrs.provider, START_EPOCH, END_EPOCH);
+    add_provider_funds(rt, deal.provider_collateral.clone(), addrs);
+    add_participant_funds(rt, CLIENT_ADDR, deal.client_collateral.clone());
+    add_token_funds(rt, TOKEN_ADDR, CLIENT_ADDR, None, deal.total_storage_fee()).unwrap();
+    deal
+}
+
+fn publish_and_activate_token_deal(
+    rt: &MockRuntime,
+    addrs: &MinerAddresses,
+) -> (DealID, DealProposal) {
+    let deal = fund_token_deal(rt, addrs);
+    let deal_ids = publish_token_deals(rt, addrs, TOKEN_ADDR, &[deal.clone()], &[true]).ids;
+    activate_deals(rt, SECTOR_EXPIRY, addrs.provider, 0, SECTOR_NUMBER, &deal_ids);
+    (deal_ids[0], deal)
+}
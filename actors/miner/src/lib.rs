@@ -139,6 +139,7 @@ pub enum Method {
     ProveCommitSectors3 = 34,
     ProveReplicaUpdates3 = 35,
     ProveCommitSectorsNI = 36,
+    SectorDealsCancelled = 37,
     // Method numbers derived from FRC-0042 standards
     ChangeWorkerAddressExported = frc42_dispatch::method_hash!("ChangeWorkerAddress"),
     ChangePeerIDExported = frc42_dispatch::method_hash!("ChangePeerID"),
@@ -156,6 +157,8 @@ pub enum Method {
     GetVestingFundsExported = frc42_dispatch::method_hash!("GetVestingFunds"),
     GetPeerIDExported = frc42_dispatch::method_hash!("GetPeerID"),
     GetMultiaddrsExported = frc42_dispatch::method_hash!("GetMultiaddrs"),
+    GetSectorStatusExported = frc42_dispatch::method_hash!("GetSectorStatus"),
+    GetSectorExpirationExported = frc42_dispatch::method_hash!("GetSectorExpiration"),
 }
 
 pub const SECTOR_CONTENT_CHANGED: MethodNum = frc42_dispatch::method_hash!("SectorContentChanged");
@@ -474,6 +477,174 @@ impl Actor {
         Ok(GetMultiaddrsReturn { multi_addrs })
     }
 
+    /// Returns the status of a sector: whether it is active, faulty, not yet proven, or dead.
+    /// Sectors that don't exist (including those never committed) are reported as dead.
+    fn get_sector_status(
+        rt: &impl Runtime,
+        params: GetSectorStatusParams,
+    ) -> Result<GetSectorStatusReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        if params.sector_number > MAX_SECTOR_NUMBER {
+            return Err(actor_error!(illegal_argument, "sector number out of range"));
+        }
+        let state: State = rt.state()?;
+        let status = state.get_sector_status(rt.store(), params.sector_number)?;
+        Ok(GetSectorStatusReturn { status })
+    }
+
+    /// Returns the epoch at which a sector is scheduled to expire.
+    /// Fails with USR_NOT_FOUND if the sector doesn't exist or has been terminated.
+    fn get_sector_expiration(
+        rt: &impl Runtime,
+        params: GetSectorExpirationParams,
+    ) -> Result<GetSectorExpirationReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        if params.sector_number > MAX_SECTOR_NUMBER {
+            return Err(actor_error!(illegal_argument, "sector number out of range"));
+        }
+        let state: State = rt.state()?;
+        let sector = match state.get_sector_status(rt.store(), params.sector_number)? {
+            SectorStatus::Dead => None,
+            _ => state.get_sector(rt.store(), params.sector_number)?,
+        }
+        .ok_or_else(|| actor_error!(not_found, "sector {} not found", params.sector_number))?;
+        Ok(GetSectorExpirationReturn { expiration: sector.expiration })
+    }
+
+    /// Removes the remaining spacetime of deals cancelled by their client and provider
+    /// from the deal weights of the sectors holding them.
+    /// Removing cancelled verified deal space reduces a sector's quality-adjusted power.
+    /// The sector must be active (proven and not faulty) for that. Its pledge is unchanged.
+    fn sector_deals_cancelled(
+        rt: &impl Runtime,
+        params: SectorDealsCancelledParams,
+    ) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&STORAGE_MARKET_ACTOR_ADDR))?;
+        let curr_epoch = rt.curr_epoch();
+
+        let (power_delta, pledge_delta) = rt.transaction(|state: &mut State, rt| {
+            let info = get_miner_info(rt.store(), state)?;
+            let mut sectors = Sectors::load(rt.store(), &state.sectors).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load sectors")
+            })?;
+            let mut updated = Vec::with_capacity(params.sectors.len());
+            let mut reweighted = Vec::new();
+            for cancelled in &params.sectors {
+                let old_sector = sectors.must_get(cancelled.sector_number)?;
+                let mut sector = old_sector.clone();
+                let removed_weight =
+                    BigInt::from(cancelled.deal_space) * max(0, sector.expiration - curr_epoch);
+                sector.deal_weight = max(BigInt::zero(), &sector.deal_weight - removed_weight);
+                if cancelled.verified_deal_space > 0 {
+                    // Verified deal weight is the verified space over the whole power term,
+                    // so the space's power is removed from now on.
+                    let removed_weight = BigInt::from(cancelled.verified_deal_space)
+                        * (sector.expiration - sector.power_base_epoch);
+                    sector.verified_deal_weight =
+                        max(BigInt::zero(), &sector.verified_deal_weight - removed_weight);
+                    reweighted.push((old_sector, sector.clone()));
+                }
+                updated.push(sector);
+            }
+            sectors.store(updated).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to update sector infos")
+            })?;
+            state.sectors = sectors.amt.flush().map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save sectors")
+            })?;
+
+            let mut power_delta = PowerPair::zero();
+            let mut pledge_delta = TokenAmount::zero();
+            if reweighted.is_empty() {
+                return Ok((power_delta, pledge_delta));
+            }
+            let mut deadlines =
+                state.load_deadlines(rt.store()).map_err(|e| e.wrap("failed to load deadlines"))?;
+            for (old_sector, new_sector) in reweighted {
+                let sector_number = old_sector.sector_number;
+                let (dl_idx, p_idx) =
+                    state.find_sector(rt.store(), sector_number).map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::USR_ILLEGAL_STATE,
+                            format!("failed to find sector {}", sector_number),
+                        )
+                    })?;
+                let quant = state.quant_spec_for_deadline(rt.policy(), dl_idx);
+                let mut deadline = deadlines.load_deadline(rt.store(), dl_idx)?;
+                let mut partitions = deadline.partitions_amt(rt.store()).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to load partitions for deadline {}", dl_idx),
+                    )
+                })?;
+                let mut partition = partitions
+                    .get(p_idx)
+                    .map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::USR_ILLEGAL_STATE,
+                            format!("failed to load partition {}:{}", dl_idx, p_idx),
+                        )
+                    })?
+                    .cloned()
+                    .ok_or_else(|| {
+                        actor_error!(illegal_state, "no partition {}:{}", dl_idx, p_idx)
+                    })?;
+                if !partition.active_sectors().get(sector_number) {
+                    return Err(actor_error!(
+                        forbidden,
+                        "cannot remove verified deal space from inactive sector {}",
+                        sector_number
+                    ));
+                }
+
+                let (partition_power_delta, partition_pledge_delta) = partition
+                    .replace_sectors(
+                        rt.store(),
+                        &[old_sector],
+                        &[new_sector],
+                        info.sector_size,
+                        quant,
+                    )
+                    .map_err(|e| {
+                        e.downcast_default(
+                            ExitCode::USR_ILLEGAL_STATE,
+                            format!("failed to replace sector {}", sector_number),
+                        )
+                    })?;
+                power_delta += &partition_power_delta;
+                pledge_delta += partition_pledge_delta; // expected to be zero
+
+                partitions.set(p_idx, partition).map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to save partition {}:{}", dl_idx, p_idx),
+                    )
+                })?;
+                deadline.partitions = partitions.flush().map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to save partitions for deadline {}", dl_idx),
+                    )
+                })?;
+                deadlines.update_deadline(rt.policy(), rt.store(), dl_idx, &deadline).map_err(
+                    |e| {
+                        e.downcast_default(
+                            ExitCode::USR_ILLEGAL_STATE,
+                            format!("failed to save deadline {}", dl_idx),
+                        )
+                    },
+                )?;
+            }
+            state.save_deadlines(rt.store(), deadlines).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save deadlines")
+            })?;
+            Ok((power_delta, pledge_delta))
+        })?;
+
+        request_update_power(rt, power_delta)?;
+        notify_pledge_changed(rt, &pledge_delta)
+    }
+
     fn change_multiaddresses(
         rt: &impl Runtime,
         params: ChangeMultiaddrsParams,
@@ -6029,6 +6200,9 @@ impl ActorCode for Actor {
         GetVestingFundsExported => get_vesting_funds,
         GetPeerIDExported => get_peer_id,
         GetMultiaddrsExported => get_multiaddresses,
+        GetSectorStatusExported => get_sector_status,
+        GetSectorExpirationExported => get_sector_expiration,
+        SectorDealsCancelled => sector_deals_cancelled,
         ProveCommitSectors3 => prove_commit_sectors3,
         ProveReplicaUpdates3 => prove_replica_updates3,
         ProveCommitSectorsNI => prove_commit_sectors_ni,
@@ -712,6 +712,36 @@ impl State {
         Ok(true)
     }
 
+    /// Returns the status of a sector, which may or may not exist.
+    pub fn get_sector_status<BS: Blockstore>(
+        &self,
+        store: &BS,
+        sector_number: SectorNumber,
+    ) -> Result<SectorStatus, ActorError> {
+        if self.get_sector(store, sector_number)?.is_none() {
+            return Ok(SectorStatus::Dead);
+        }
+
+        let (deadline_idx, partition_idx) = self
+            .find_sector(store, sector_number)
+            .with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+                format!("failed to find sector {}", sector_number)
+            })?;
+        let dls = self.load_deadlines(store)?;
+        let dl = dls.load_deadline(store, deadline_idx)?;
+        let partition = dl.load_partition(store, partition_idx)?;
+
+        Ok(if partition.terminated.get(sector_number) {
+            SectorStatus::Dead
+        } else if partition.faults.get(sector_number) {
+            SectorStatus::Faulty
+        } else if partition.unproven.get(sector_number) {
+            SectorStatus::Unproven
+        } else {
+            SectorStatus::Active
+        })
+    }
+
     /// Returns an error if the target sector cannot be found and/or is faulty/terminated.
     pub fn check_sector_health<BS: Blockstore>(
         &self,
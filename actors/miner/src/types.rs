@@ -3,6 +3,7 @@
 
 use cid::Cid;
 use fvm_ipld_bitfield::BitField;
+use fvm_ipld_encoding::repr::*;
 use fvm_ipld_encoding::{strict_bytes, BytesDe};
 use fvm_ipld_encoding::{tuple::*, RawBytes};
 use fvm_shared::address::Address;
@@ -620,6 +621,60 @@ pub struct GetMultiaddrsReturn {
     pub multi_addrs: Vec<BytesDe>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetSectorStatusParams {
+    pub sector_number: SectorNumber,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetSectorStatusReturn {
+    pub status: SectorStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetSectorExpirationParams {
+    pub sector_number: SectorNumber,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetSectorExpirationReturn {
+    pub expiration: ChainEpoch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct SectorDealsCancelledParams {
+    pub sectors: Vec<SectorDealsCancellation>,
+}
+
+/// Deals cancelled in a sector.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SectorDealsCancellation {
+    pub sector_number: SectorNumber,
+    /// Total size of the cancelled unverified deals' pieces.
+    pub deal_space: u64,
+    /// Total size of the cancelled verified deals' pieces, whose claims have been removed.
+    pub verified_deal_space: u64,
+}
+
+/// The status of a sector, as reported by GetSectorStatus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
+#[repr(u8)]
+pub enum SectorStatus {
+    /// The sector doesn't exist, or has been terminated.
+    Dead = 0,
+    /// The sector is proven and not faulty.
+    Active = 1,
+    /// The sector is faulty (declared or detected), and not currently contributing power.
+    Faulty = 2,
+    /// The sector has been activated, but not yet proven in a window PoSt.
+    Unproven = 3,
+}
+
 // Notification of change committed to one or more sectors.
 // The relevant state must be already committed so the receiver can observe any impacts
 // at the sending miner actor.
@@ -1,12 +1,15 @@
 use fil_actor_miner::{
-    Actor, GetAvailableBalanceReturn, GetOwnerReturn, GetSectorSizeReturn,
-    IsControllingAddressParam, IsControllingAddressReturn, Method,
+    Actor, GetAvailableBalanceReturn, GetOwnerReturn, GetSectorExpirationParams,
+    GetSectorExpirationReturn, GetSectorSizeReturn, GetSectorStatusParams, GetSectorStatusReturn,
+    IsControllingAddressParam, IsControllingAddressReturn, Method, SectorStatus,
 };
 use fil_actors_runtime::runtime::policy_constants::MAX_SECTOR_NUMBER;
-use fil_actors_runtime::test_utils::EVM_ACTOR_CODE_ID;
-use fil_actors_runtime::INIT_ACTOR_ADDR;
+use fil_actors_runtime::test_utils::{expect_abort, MockRuntime, EVM_ACTOR_CODE_ID};
+use fil_actors_runtime::{ActorError, INIT_ACTOR_ADDR};
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_shared::address::Address;
+use fvm_shared::error::ExitCode;
+use fvm_shared::sector::SectorNumber;
 use fvm_shared::{clock::ChainEpoch, econ::TokenAmount};
 use std::ops::Sub;
 
@@ -144,3 +147,85 @@ fn collateral_getters() {
 
     h.check_state(&rt);
 }
+
+fn get_sector_status(rt: &MockRuntime, sector_number: SectorNumber) -> SectorStatus {
+    rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(1234));
+    rt.expect_validate_caller_any();
+    let ret: GetSectorStatusReturn = rt
+        .call::<Actor>(
+            Method::GetSectorStatusExported as u64,
+            IpldBlock::serialize_cbor(&GetSectorStatusParams { sector_number }).unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+    rt.verify();
+    ret.status
+}
+
+#[test]
+fn sector_status_getter() {
+    let mut h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+
+    // Unknown sectors are dead.
+    assert_eq!(SectorStatus::Dead, get_sector_status(&rt, 1234));
+
+    let sectors =
+        h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION as u64, vec![], true);
+    let sector_number = sectors[0].sector_number;
+    assert_eq!(SectorStatus::Unproven, get_sector_status(&rt, sector_number));
+
+    h.advance_and_submit_posts(&rt, &sectors);
+    assert_eq!(SectorStatus::Active, get_sector_status(&rt, sector_number));
+
+    h.declare_faults(&rt, &sectors);
+    assert_eq!(SectorStatus::Faulty, get_sector_status(&rt, sector_number));
+
+    // Out of range sector numbers are rejected.
+    rt.expect_validate_caller_any();
+    let result = rt.call::<Actor>(
+        Method::GetSectorStatusExported as u64,
+        IpldBlock::serialize_cbor(&GetSectorStatusParams { sector_number: MAX_SECTOR_NUMBER + 1 })
+            .unwrap(),
+    );
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, result);
+    rt.verify();
+
+    h.check_state(&rt);
+}
+
+fn get_sector_expiration(
+    rt: &MockRuntime,
+    sector_number: SectorNumber,
+) -> Result<ChainEpoch, ActorError> {
+    rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(1234));
+    rt.expect_validate_caller_any();
+    let ret = rt.call::<Actor>(
+        Method::GetSectorExpirationExported as u64,
+        IpldBlock::serialize_cbor(&GetSectorExpirationParams { sector_number }).unwrap(),
+    );
+    rt.verify();
+    Ok(ret?.unwrap().deserialize::<GetSectorExpirationReturn>().unwrap().expiration)
+}
+
+#[test]
+fn sector_expiration_getter() {
+    let mut h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+
+    expect_abort(ExitCode::USR_NOT_FOUND, get_sector_expiration(&rt, 1234));
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, get_sector_expiration(&rt, MAX_SECTOR_NUMBER + 1));
+
+    let sectors =
+        h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION as u64, vec![], true);
+    let sector = &sectors[0];
+    assert_eq!(sector.expiration, get_sector_expiration(&rt, sector.sector_number).unwrap());
+
+    h.check_state(&rt);
+}
@@ -0,0 +1,211 @@
+use fil_actor_market::NO_ALLOCATION_ID;
+use fil_actor_miner::ext::power::UPDATE_CLAIMED_POWER_METHOD;
+use fil_actor_miner::{
+    power_for_sector, Actor, Method, PowerPair, SectorDealsCancellation, SectorDealsCancelledParams,
+};
+use fil_actor_power::UpdateClaimedPowerParams;
+use fil_actors_runtime::test_utils::{
+    expect_abort, MockRuntime, MARKET_ACTOR_CODE_ID, MINER_ACTOR_CODE_ID,
+};
+use fil_actors_runtime::{ActorError, STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR};
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::bigint::BigInt;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use num_traits::{Signed, Zero};
+
+mod util;
+
+use util::*;
+
+const PERIOD_OFFSET: ChainEpoch = 100;
+const DEFAULT_SECTOR_EXPIRATION: ChainEpoch = 220;
+
+#[test]
+fn removes_remaining_deal_weight_of_cancelled_deals() {
+    let mut h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+
+    let deal_space = h.sector_size as u64 / 2;
+    let mut pcc = ProveCommitConfig::empty();
+    pcc.add_activated_deals(
+        h.next_sector_no,
+        vec![
+            test_activated_deal(deal_space / 2, NO_ALLOCATION_ID),
+            test_activated_deal(deal_space / 2, NO_ALLOCATION_ID),
+        ],
+    );
+    let sector = h.commit_and_prove_sectors_with_cfgs(
+        &rt,
+        1,
+        DEFAULT_SECTOR_EXPIRATION as u64,
+        vec![vec![1, 2]],
+        true,
+        pcc,
+    )[0]
+    .clone();
+    assert!(sector.deal_weight > BigInt::from(0));
+
+    // Cancel one of the two deals part way through the sector's life.
+    let cancel_epoch = *rt.epoch.borrow() + 100;
+    rt.set_epoch(cancel_epoch);
+    let remaining = sector.expiration - cancel_epoch;
+    sector_deals_cancelled(&rt, sector.sector_number, deal_space / 2, 0).unwrap();
+
+    let updated = h.get_sector(&rt, sector.sector_number);
+    let expected = &sector.deal_weight - BigInt::from(deal_space / 2) * remaining;
+    assert_eq!(expected, updated.deal_weight);
+    // Verified weight, and so power, is untouched.
+    assert_eq!(sector.verified_deal_weight, updated.verified_deal_weight);
+    assert_eq!(sector.expiration, updated.expiration);
+
+    // Weight never drops below zero.
+    sector_deals_cancelled(&rt, sector.sector_number, h.sector_size as u64, 0).unwrap();
+    assert_eq!(BigInt::from(0), h.get_sector(&rt, sector.sector_number).deal_weight);
+    h.check_state(&rt);
+}
+
+#[test]
+fn removes_power_of_cancelled_verified_deals() {
+    let mut h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+
+    let deal_space = h.sector_size as u64 / 2;
+    let mut pcc = ProveCommitConfig::empty();
+    pcc.add_activated_deals(
+        h.next_sector_no,
+        vec![test_activated_deal(deal_space, 1), test_activated_deal(deal_space, 2)],
+    );
+    let sector = h.commit_and_prove_sectors_with_cfgs(
+        &rt,
+        1,
+        DEFAULT_SECTOR_EXPIRATION as u64,
+        vec![vec![1, 2]],
+        true,
+        pcc,
+    )[0]
+    .clone();
+    h.advance_and_submit_posts(&rt, &[sector.clone()]);
+    let duration = sector.expiration - sector.power_base_epoch;
+    assert_eq!(BigInt::from(2 * deal_space) * duration, sector.verified_deal_weight);
+
+    // Cancelling one of the verified deals removes its space from the sector's power.
+    let mut expected = sector.clone();
+    expected.verified_deal_weight = BigInt::from(deal_space) * duration;
+    let delta =
+        power_for_sector(h.sector_size, &expected) - power_for_sector(h.sector_size, &sector);
+    assert!(delta.qa.is_negative());
+    assert!(delta.raw.is_zero());
+    expect_update_claimed_power(&rt, delta);
+    sector_deals_cancelled(&rt, sector.sector_number, 0, deal_space).unwrap();
+
+    let updated = h.get_sector(&rt, sector.sector_number);
+    assert_eq!(expected.verified_deal_weight, updated.verified_deal_weight);
+    assert_eq!(sector.deal_weight, updated.deal_weight);
+    assert_eq!(sector.initial_pledge, updated.initial_pledge);
+    h.check_state(&rt);
+}
+
+#[test]
+fn rejects_verified_deal_cancellation_in_inactive_sector() {
+    let mut h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+
+    let deal_space = h.sector_size as u64 / 2;
+    let mut pcc = ProveCommitConfig::empty();
+    pcc.add_activated_deals(h.next_sector_no, vec![test_activated_deal(deal_space, 1)]);
+    let sector = h.commit_and_prove_sectors_with_cfgs(
+        &rt,
+        1,
+        DEFAULT_SECTOR_EXPIRATION as u64,
+        vec![vec![1]],
+        true,
+        pcc,
+    )[0]
+    .clone();
+
+    // The sector is not yet proven by a window PoSt.
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        sector_deals_cancelled(&rt, sector.sector_number, 0, deal_space),
+    );
+    assert_eq!(sector, h.get_sector(&rt, sector.sector_number));
+    h.check_state(&rt);
+}
+
+#[test]
+fn rejects_unknown_caller_and_sector() {
+    let mut h = ActorHarness::new(PERIOD_OFFSET);
+    let rt = h.new_runtime();
+    rt.set_balance(BIG_BALANCE.clone());
+    h.construct_and_verify(&rt);
+    let sector = h.commit_and_prove_sectors(&rt, 1, DEFAULT_SECTOR_EXPIRATION as u64, vec![], true)
+        [0]
+    .clone();
+
+    // Only the market may report cancelled deals.
+    rt.set_caller(*MINER_ACTOR_CODE_ID, h.worker);
+    rt.expect_validate_caller_addr(vec![STORAGE_MARKET_ACTOR_ADDR]);
+    let params = SectorDealsCancelledParams {
+        sectors: vec![SectorDealsCancellation {
+            sector_number: sector.sector_number,
+            deal_space: 1,
+            verified_deal_space: 0,
+        }],
+    };
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        rt.call::<Actor>(
+            Method::SectorDealsCancelled as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        ),
+    );
+    rt.verify();
+
+    expect_abort(
+        ExitCode::USR_NOT_FOUND,
+        sector_deals_cancelled(&rt, sector.sector_number + 1, 1, 0),
+    );
+    h.check_state(&rt);
+}
+
+fn sector_deals_cancelled(
+    rt: &MockRuntime,
+    sector_number: u64,
+    deal_space: u64,
+    verified_deal_space: u64,
+) -> Result<(), ActorError> {
+    rt.set_caller(*MARKET_ACTOR_CODE_ID, STORAGE_MARKET_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![STORAGE_MARKET_ACTOR_ADDR]);
+    let params = SectorDealsCancelledParams {
+        sectors: vec![SectorDealsCancellation { sector_number, deal_space, verified_deal_space }],
+    };
+    let ret = rt.call::<Actor>(
+        Method::SectorDealsCancelled as u64,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+    );
+    rt.verify();
+    ret.map(|_| ())
+}
+
+fn expect_update_claimed_power(rt: &MockRuntime, delta: PowerPair) {
+    rt.expect_send_simple(
+        STORAGE_POWER_ACTOR_ADDR,
+        UPDATE_CLAIMED_POWER_METHOD,
+        IpldBlock::serialize_cbor(&UpdateClaimedPowerParams {
+            raw_byte_delta: delta.raw,
+            quality_adjusted_delta: delta.qa,
+        })
+        .unwrap(),
+        TokenAmount::zero(),
+        None,
+        ExitCode::OK,
+    );
+}
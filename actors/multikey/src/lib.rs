@@ -0,0 +1,253 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::collections::BTreeSet;
+
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::{Address, Protocol};
+use fvm_shared::chainid::ChainID;
+use fvm_shared::crypto::signature::Signature;
+use fvm_shared::crypto::signature::SignatureType::{Secp256k1, BLS};
+use fvm_shared::error::ExitCode;
+use fvm_shared::{MethodNum, METHOD_CONSTRUCTOR};
+use num_derive::FromPrimitive;
+
+use fil_actors_runtime::builtin::singletons::INIT_ACTOR_ADDR;
+use fil_actors_runtime::cbor::serialize_vec;
+use fil_actors_runtime::runtime::{ActorCode, Primitives, Runtime};
+use fil_actors_runtime::{
+    actor_dispatch, extract_send_result, ActorDowncast, AsActorError, FIRST_EXPORTED_METHOD_NUMBER,
+};
+use fil_actors_runtime::{actor_error, ActorError};
+
+use crate::types::{
+    AuthenticateMessageParams, AuthenticateMessageReturn, ConstructorParams, ExecuteParams,
+    ExecuteReturn, ExecuteSignatureData, KeySignature, MultiKeySignature,
+};
+
+pub use self::state::State;
+
+mod state;
+pub mod testing;
+pub mod types;
+
+#[cfg(feature = "fil-actor")]
+fil_actors_runtime::wasm_trampoline!(Actor);
+
+/// Multi-key account actor methods available
+#[derive(FromPrimitive)]
+#[repr(u64)]
+pub enum Method {
+    Constructor = METHOD_CONSTRUCTOR,
+    AuthenticateMessageExported = frc42_dispatch::method_hash!("AuthenticateMessage"),
+    ExecuteExported = frc42_dispatch::method_hash!("Execute"),
+}
+
+/// Maximum number of keys of a multi-key account.
+pub const SIGNERS_MAX: usize = 256;
+
+/// Multi-key account actor, jointly controlled by a threshold of BLS or SECP256K1 keys.
+/// No single key can sign chain messages for the account, so it acts by authenticating
+/// messages for other actors, and by sending messages authorized through `Execute`.
+pub struct Actor;
+
+impl Actor {
+    /// Constructor for a multi-key account, which is created through the init actor.
+    pub fn constructor(rt: &impl Runtime, params: ConstructorParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&INIT_ACTOR_ADDR))?;
+        if params.signers.is_empty() {
+            return Err(actor_error!(illegal_argument; "must have at least one signer"));
+        }
+        if params.signers.len() > SIGNERS_MAX {
+            return Err(actor_error!(illegal_argument;
+                "cannot add more than {} signers", SIGNERS_MAX));
+        }
+        let mut seen = BTreeSet::new();
+        for signer in &params.signers {
+            if !matches!(signer.protocol(), Protocol::Secp256k1 | Protocol::BLS) {
+                return Err(actor_error!(illegal_argument;
+                    "signer {} must use BLS or SECP protocol", signer));
+            }
+            if !seen.insert(signer.to_bytes()) {
+                return Err(actor_error!(illegal_argument;
+                    "duplicate signer not allowed: {}", signer));
+            }
+        }
+        if params.threshold == 0 || params.threshold > params.signers.len() as u64 {
+            return Err(actor_error!(illegal_argument;
+                "threshold {} must be between 1 and the number of signers {}",
+                params.threshold, params.signers.len()));
+        }
+        rt.create(&State { signers: params.signers, threshold: params.threshold, next_nonce: 0 })?;
+        Ok(())
+    }
+
+    /// Authenticates whether the provided signature is valid for the provided message.
+    /// The signature is a serialized `MultiKeySignature`, which must hold valid signatures from
+    /// at least the threshold number of distinct keys.
+    /// Errors with USR_ILLEGAL_ARGUMENT if the authentication is invalid.
+    pub fn authenticate_message(
+        rt: &impl Runtime,
+        params: AuthenticateMessageParams,
+    ) -> Result<AuthenticateMessageReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let sigs: MultiKeySignature = fvm_ipld_encoding::from_slice(&params.signature)
+            .context_code(ExitCode::USR_ILLEGAL_ARGUMENT, "failed to decode signatures")?;
+        verify_signatures(rt, &st, &sigs.signatures, &params.message)?;
+        Ok(AuthenticateMessageReturn { authenticated: true })
+    }
+
+    /// Sends a message on behalf of the account, authorized by signatures from at least the
+    /// threshold number of its keys over the digest of the `ExecuteSignatureData` for the call.
+    /// The call's nonce must be the account's next nonce, which is consumed even if the call fails.
+    /// May be called by anyone.
+    pub fn execute(rt: &impl Runtime, params: ExecuteParams) -> Result<ExecuteReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        if params.value.is_negative() {
+            return Err(actor_error!(
+                illegal_argument,
+                "value must be non-negative, was {}",
+                params.value
+            ));
+        }
+
+        let st: State = rt.state()?;
+        if params.nonce != st.next_nonce {
+            return Err(actor_error!(
+                illegal_argument,
+                "nonce {} does not match next nonce {}",
+                params.nonce,
+                st.next_nonce
+            ));
+        }
+        let digest = compute_execute_digest(rt.chain_id(), &rt.message().receiver(), &params, rt)?;
+        verify_signatures(rt, &st, &params.signatures, &digest)?;
+
+        if params.value > rt.current_balance() {
+            return Err(actor_error!(
+                insufficient_funds,
+                "value {} exceeds balance {}",
+                params.value,
+                rt.current_balance()
+            ));
+        }
+
+        // Consume the nonce before sending, so the signatures can't be replayed by the callee.
+        rt.transaction(|st: &mut State, _| {
+            st.next_nonce += 1;
+            Ok(())
+        })?;
+
+        let mut ret = RawBytes::default();
+        let mut code = ExitCode::OK;
+        match extract_send_result(rt.send_simple(
+            &params.to,
+            params.method,
+            params.params.into(),
+            params.value,
+        )) {
+            Ok(Some(r)) => {
+                ret = RawBytes::new(r.data);
+            }
+            Err(mut e) => {
+                if let Some(r) = e.take_data() {
+                    ret = RawBytes::new(r.data);
+                }
+                code = e.exit_code();
+            }
+            _ => {}
+        }
+        Ok(ExecuteReturn { code, ret })
+    }
+
+    /// Fallback method for unimplemented method numbers.
+    pub fn fallback(
+        rt: &impl Runtime,
+        method: MethodNum,
+        _: Option<IpldBlock>,
+    ) -> Result<Option<IpldBlock>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        if method >= FIRST_EXPORTED_METHOD_NUMBER {
+            Ok(None)
+        } else {
+            Err(actor_error!(unhandled_message; "invalid method: {}", method))
+        }
+    }
+}
+
+impl ActorCode for Actor {
+    type Methods = Method;
+
+    fn name() -> &'static str {
+        "MultiKeyAccount"
+    }
+
+    actor_dispatch! {
+        Constructor => constructor,
+        AuthenticateMessageExported => authenticate_message,
+        ExecuteExported => execute,
+        _ => fallback,
+    }
+}
+
+/// Verifies that signatures over a message come from at least the threshold number of
+/// distinct keys of the account.
+fn verify_signatures(
+    rt: &impl Runtime,
+    st: &State,
+    signatures: &[KeySignature],
+    message: &[u8],
+) -> Result<(), ActorError> {
+    let mut signed = Vec::<&Address>::new();
+    for sig in signatures {
+        if !st.is_signer(&sig.signer) {
+            return Err(actor_error!(illegal_argument, "{} is not a signer", sig.signer));
+        }
+        if signed.contains(&&sig.signer) {
+            return Err(actor_error!(illegal_argument, "duplicate signature from {}", sig.signer));
+        }
+        let sig_type = match sig.signer.protocol() {
+            Protocol::Secp256k1 => Secp256k1,
+            _ => BLS,
+        };
+        let signature = Signature { sig_type, bytes: sig.signature.clone() };
+        rt.verify_signature(&signature, &sig.signer, message).map_err(|e| {
+            e.downcast_default(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                format!("failed to verify signature from {}", sig.signer),
+            )
+        })?;
+        signed.push(&sig.signer);
+    }
+    if (signed.len() as u64) < st.threshold {
+        return Err(actor_error!(
+            illegal_argument,
+            "{} signatures do not meet threshold {}",
+            signed.len(),
+            st.threshold
+        ));
+    }
+    Ok(())
+}
+
+/// Computes the digest signed by the keys of a multi-key account to authorize a call with `Execute`.
+pub fn compute_execute_digest(
+    chain_id: ChainID,
+    account: &Address,
+    params: &ExecuteParams,
+    sys: &dyn Primitives,
+) -> Result<[u8; 32], ActorError> {
+    let data = ExecuteSignatureData {
+        chain_id: chain_id.into(),
+        account,
+        nonce: params.nonce,
+        to: &params.to,
+        value: &params.value,
+        method: &params.method,
+        params: &params.params,
+    };
+    let data = serialize_vec(&data, "execute signature data")?;
+    Ok(sys.hash_blake2b(&data))
+}
@@ -0,0 +1,22 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::address::Address;
+
+/// State of a multi-key account: the keys that jointly control it.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, PartialEq, Eq)]
+pub struct State {
+    /// BLS or SECP256K1 key addresses.
+    pub signers: Vec<Address>,
+    /// Number of signers whose signatures are required to act for the account.
+    pub threshold: u64,
+    /// Nonce expected by the next `Execute`, preventing signed calls from being replayed.
+    pub next_nonce: u64,
+}
+
+impl State {
+    pub fn is_signer(&self, address: &Address) -> bool {
+        self.signers.contains(address)
+    }
+}
@@ -0,0 +1,28 @@
+use std::collections::HashSet;
+
+use fil_actors_runtime::MessageAccumulator;
+use fvm_shared::address::Protocol;
+
+use crate::State;
+
+/// Checks internal invariants of multi-key account state.
+pub fn check_state_invariants(state: &State) -> MessageAccumulator {
+    let acc = MessageAccumulator::default();
+    let mut seen = HashSet::new();
+    for signer in &state.signers {
+        acc.require(
+            matches!(signer.protocol(), Protocol::BLS | Protocol::Secp256k1),
+            format!("signer {signer} must be BLS or SECP256K1 protocol"),
+        );
+        acc.require(seen.insert(signer), format!("duplicate signer {signer}"));
+    }
+    acc.require(
+        state.threshold > 0 && state.threshold <= state.signers.len() as u64,
+        format!(
+            "threshold {} must be between 1 and the number of signers {}",
+            state.threshold,
+            state.signers.len()
+        ),
+    );
+    acc
+}
@@ -0,0 +1,85 @@
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{strict_bytes, RawBytes};
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::MethodNum;
+
+/// Multi-key account constructor parameters.
+#[derive(Debug, Clone, Serialize_tuple, Deserialize_tuple)]
+pub struct ConstructorParams {
+    /// BLS or SECP256K1 key addresses.
+    pub signers: Vec<Address>,
+    /// Number of signers whose signatures are required to act for the account.
+    pub threshold: u64,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct AuthenticateMessageParams {
+    /// The CBOR encoding of a `MultiKeySignature`.
+    #[serde(with = "strict_bytes")]
+    pub signature: Vec<u8>,
+    #[serde(with = "strict_bytes")]
+    pub message: Vec<u8>,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct AuthenticateMessageReturn {
+    pub authenticated: bool,
+}
+
+/// A signature by one key of a multi-key account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct KeySignature {
+    /// The signer's key address.
+    pub signer: Address,
+    /// The raw bytes of the signature, without a signature type.
+    #[serde(with = "strict_bytes")]
+    pub signature: Vec<u8>,
+}
+
+/// The signatures authenticating a message for a multi-key account.
+/// `AuthenticateMessageParams::signature` holds the CBOR encoding of this type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct MultiKeySignature {
+    pub signatures: Vec<KeySignature>,
+}
+
+/// Execute method call parameters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct ExecuteParams {
+    pub to: Address,
+    pub value: TokenAmount,
+    pub method: MethodNum,
+    pub params: RawBytes,
+    /// Must equal the account's next nonce.
+    pub nonce: u64,
+    /// Signatures over the digest of the `ExecuteSignatureData` for the call on this chain.
+    pub signatures: Vec<KeySignature>,
+}
+
+/// Data signed by the keys of a multi-key account to authorize a call with `Execute`.
+/// ChainID - The chain ID of the network, so the signatures can't be replayed on another
+/// network where the account has the same address.
+/// Account - The ID address of the multi-key account.
+#[derive(Serialize_tuple, Debug)]
+pub struct ExecuteSignatureData<'a> {
+    pub chain_id: u64,
+    pub account: &'a Address,
+    pub nonce: u64,
+    pub to: &'a Address,
+    pub value: &'a TokenAmount,
+    pub method: &'a MethodNum,
+    pub params: &'a RawBytes,
+}
+
+/// Execute method call return.
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ExecuteReturn {
+    /// Code is the exit code of the call.
+    pub code: ExitCode,
+    /// Ret is the return value of the call.
+    pub ret: RawBytes,
+}
@@ -0,0 +1,319 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use anyhow::anyhow;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
+use fvm_shared::chainid::ChainID;
+use fvm_shared::crypto::signature::Signature;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::{MethodNum, METHOD_SEND};
+
+use fil_actor_multikey::testing::check_state_invariants;
+use fil_actor_multikey::types::{
+    AuthenticateMessageParams, ConstructorParams, ExecuteParams, ExecuteReturn, KeySignature,
+    MultiKeySignature,
+};
+use fil_actor_multikey::{compute_execute_digest, Actor as MultiKeyActor, Method, State};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{ActorError, INIT_ACTOR_ADDR, SYSTEM_ACTOR_ADDR};
+
+const ACCOUNT: u64 = 1000;
+
+fn keys() -> Vec<Address> {
+    vec![
+        Address::new_bls(&[1; fvm_shared::address::BLS_PUB_LEN]).unwrap(),
+        Address::new_secp256k1(&[2; fvm_shared::address::SECP_PUB_LEN]).unwrap(),
+        Address::new_bls(&[3; fvm_shared::address::BLS_PUB_LEN]).unwrap(),
+    ]
+}
+
+fn construct(
+    rt: &MockRuntime,
+    signers: Vec<Address>,
+    threshold: u64,
+) -> Result<Option<IpldBlock>, ActorError> {
+    rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
+    let params = ConstructorParams { signers, threshold };
+    let ret = rt.call::<MultiKeyActor>(
+        Method::Constructor as MethodNum,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+    );
+    rt.verify();
+    ret
+}
+
+fn setup() -> MockRuntime {
+    let rt = MockRuntime { receiver: Address::new_id(ACCOUNT), ..Default::default() };
+    construct(&rt, keys(), 2).unwrap();
+    rt
+}
+
+fn check_state(rt: &MockRuntime) {
+    check_state_invariants(&rt.get_state()).assert_empty();
+}
+
+fn signature_type(signer: &Address) -> fvm_shared::crypto::signature::SignatureType {
+    match signer.protocol() {
+        fvm_shared::address::Protocol::Secp256k1 => {
+            fvm_shared::crypto::signature::SignatureType::Secp256k1
+        }
+        _ => fvm_shared::crypto::signature::SignatureType::BLS,
+    }
+}
+
+// Signs with the given keys, expecting each signature to be verified.
+fn sign(rt: &MockRuntime, signers: &[Address], plaintext: &[u8]) -> Vec<KeySignature> {
+    signers
+        .iter()
+        .map(|signer| {
+            let bytes = signer.to_bytes();
+            rt.expect_verify_signature(ExpectedVerifySig {
+                sig: Signature { sig_type: signature_type(signer), bytes: bytes.clone() },
+                signer: *signer,
+                plaintext: plaintext.to_vec(),
+                result: Ok(()),
+            });
+            KeySignature { signer: *signer, signature: bytes }
+        })
+        .collect()
+}
+
+fn authenticate(
+    rt: &MockRuntime,
+    signatures: Vec<KeySignature>,
+    message: &[u8],
+) -> Result<Option<IpldBlock>, ActorError> {
+    rt.set_caller(*MARKET_ACTOR_CODE_ID, Address::new_id(5));
+    rt.expect_validate_caller_any();
+    let signature = fvm_ipld_encoding::to_vec(&MultiKeySignature { signatures }).unwrap();
+    let ret = rt.call::<MultiKeyActor>(
+        Method::AuthenticateMessageExported as MethodNum,
+        IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+            signature,
+            message: message.to_vec(),
+        })
+        .unwrap(),
+    );
+    rt.verify();
+    ret
+}
+
+fn execute_params(nonce: u64) -> ExecuteParams {
+    ExecuteParams {
+        to: Address::new_id(2000),
+        value: TokenAmount::from_atto(100),
+        method: METHOD_SEND,
+        params: RawBytes::default(),
+        nonce,
+        signatures: vec![],
+    }
+}
+
+fn execute(rt: &MockRuntime, params: &ExecuteParams) -> Result<ExecuteReturn, ActorError> {
+    rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(3000));
+    rt.expect_validate_caller_any();
+    let ret = rt.call::<MultiKeyActor>(
+        Method::ExecuteExported as MethodNum,
+        IpldBlock::serialize_cbor(params).unwrap(),
+    );
+    rt.verify();
+    ret.map(|r| r.unwrap().deserialize().unwrap())
+}
+
+#[test]
+fn construction() {
+    let rt = setup();
+    let state: State = rt.get_state();
+    assert_eq!(State { signers: keys(), threshold: 2, next_nonce: 0 }, state);
+    check_state(&rt);
+
+    // A multi-key account has no pubkey address.
+    rt.expect_validate_caller_any();
+    expect_abort(ExitCode::USR_UNHANDLED_MESSAGE, rt.call::<MultiKeyActor>(2, None));
+}
+
+#[test]
+fn construction_fails_for_invalid_keys() {
+    let rt = MockRuntime { receiver: Address::new_id(ACCOUNT), ..Default::default() };
+    let k = keys();
+    for (signers, threshold) in [
+        (vec![], 1),
+        (k.clone(), 0),
+        (k.clone(), 4),
+        (vec![k[0], k[1], k[0]], 2),
+        (vec![k[0], Address::new_id(100)], 1),
+        (vec![k[0], Address::new_actor(b"actor")], 1),
+    ] {
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, construct(&rt, signers, threshold));
+    }
+
+    // Only the init actor may construct a multi-key account.
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        rt.call::<MultiKeyActor>(
+            Method::Constructor as MethodNum,
+            IpldBlock::serialize_cbor(&ConstructorParams { signers: k, threshold: 2 }).unwrap(),
+        ),
+    );
+    rt.verify();
+}
+
+#[test]
+fn authenticate_message_with_threshold_signatures() {
+    let rt = setup();
+    let k = keys();
+    let message = b"deal proposal".to_vec();
+
+    let sigs = sign(&rt, &[k[2], k[1]], &message);
+    let ret = authenticate(&rt, sigs, &message).unwrap().unwrap();
+    assert!(ret.deserialize::<bool>().unwrap());
+
+    // One signature doesn't meet the threshold.
+    let sigs = sign(&rt, &[k[0]], &message);
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "do not meet threshold",
+        authenticate(&rt, sigs, &message),
+    );
+
+    // Repeating a signature doesn't count twice.
+    let mut sigs = sign(&rt, &[k[0]], &message);
+    sigs.push(sigs[0].clone());
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "duplicate signature",
+        authenticate(&rt, sigs, &message),
+    );
+
+    // Signatures from other keys are rejected.
+    let outsider = Address::new_bls(&[9; fvm_shared::address::BLS_PUB_LEN]).unwrap();
+    let sigs = vec![
+        KeySignature { signer: outsider, signature: vec![] },
+        KeySignature { signer: k[0], signature: vec![] },
+    ];
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "is not a signer",
+        authenticate(&rt, sigs, &message),
+    );
+
+    // An invalid signature fails authentication.
+    let mut sigs = sign(&rt, &[k[0]], &message);
+    rt.expect_verify_signature(ExpectedVerifySig {
+        sig: Signature::new_secp256k1(vec![]),
+        signer: k[1],
+        plaintext: message.clone(),
+        result: Err(anyhow!("bad signature")),
+    });
+    sigs.push(KeySignature { signer: k[1], signature: vec![] });
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "bad signature",
+        authenticate(&rt, sigs, &message),
+    );
+
+    // The signature must decode as signatures from the account's keys.
+    rt.expect_validate_caller_any();
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "failed to decode signatures",
+        rt.call::<MultiKeyActor>(
+            Method::AuthenticateMessageExported as MethodNum,
+            IpldBlock::serialize_cbor(&AuthenticateMessageParams {
+                signature: vec![1, 2, 3],
+                message,
+            })
+            .unwrap(),
+        ),
+    );
+    check_state(&rt);
+}
+
+#[test]
+fn execute_sends_with_threshold_signatures() {
+    let rt = setup();
+    rt.set_balance(TokenAmount::from_atto(1000));
+    let k = keys();
+
+    let mut params = execute_params(0);
+    let digest = compute_execute_digest(rt.chain_id, &rt.receiver, &params, &rt).unwrap();
+    params.signatures = sign(&rt, &[k[0], k[1]], &digest);
+    rt.expect_send_simple(params.to, METHOD_SEND, None, params.value.clone(), None, ExitCode::OK);
+    let ret = execute(&rt, &params).unwrap();
+    assert_eq!(ExitCode::OK, ret.code);
+    let state: State = rt.get_state();
+    assert_eq!(1, state.next_nonce);
+
+    // The same signatures can't be replayed.
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "does not match next nonce",
+        execute(&rt, &params),
+    );
+
+    // Signatures over a different call don't authorize this one.
+    let mut params = execute_params(1);
+    let digest = compute_execute_digest(rt.chain_id, &rt.receiver, &params, &rt).unwrap();
+    params.signatures = sign(&rt, &[k[0]], &digest);
+    params.signatures.push(KeySignature { signer: k[2], signature: vec![] });
+    rt.expect_verify_signature(ExpectedVerifySig {
+        sig: Signature::new_bls(vec![]),
+        signer: k[2],
+        plaintext: digest.to_vec(),
+        result: Err(anyhow!("bad signature")),
+    });
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, execute(&rt, &params));
+
+    // A failed send still consumes the nonce, and its exit code is returned.
+    let mut params = execute_params(1);
+    let digest = compute_execute_digest(rt.chain_id, &rt.receiver, &params, &rt).unwrap();
+    params.signatures = sign(&rt, &[k[1], k[2]], &digest);
+    rt.expect_send_simple(
+        params.to,
+        METHOD_SEND,
+        None,
+        params.value.clone(),
+        None,
+        ExitCode::USR_FORBIDDEN,
+    );
+    let ret = execute(&rt, &params).unwrap();
+    assert_eq!(ExitCode::USR_FORBIDDEN, ret.code);
+    let state: State = rt.get_state();
+    assert_eq!(2, state.next_nonce);
+    check_state(&rt);
+}
+
+#[test]
+fn execute_rejects_signatures_for_another_chain() {
+    let mut rt = setup();
+    rt.set_balance(TokenAmount::from_atto(1000));
+    let k = keys();
+
+    let mut params = execute_params(0);
+    let signed_digest = compute_execute_digest(rt.chain_id, &rt.receiver, &params, &rt).unwrap();
+    params.signatures = vec![
+        KeySignature { signer: k[0], signature: k[0].to_bytes() },
+        KeySignature { signer: k[1], signature: k[1].to_bytes() },
+    ];
+
+    // The same account address on another network can't accept the signatures.
+    rt.chain_id = ChainID::from(314);
+    let digest = compute_execute_digest(rt.chain_id, &rt.receiver, &params, &rt).unwrap();
+    assert_ne!(signed_digest, digest);
+    rt.expect_verify_signature(ExpectedVerifySig {
+        sig: Signature { sig_type: signature_type(&k[0]), bytes: k[0].to_bytes() },
+        signer: k[0],
+        plaintext: digest.to_vec(),
+        result: Err(anyhow!("bad signature")),
+    });
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, execute(&rt, &params));
+    let state: State = rt.get_state();
+    assert_eq!(0, state.next_nonce);
+    check_state(&rt);
+}
@@ -8,6 +8,7 @@ use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::RawBytes;
 use fvm_shared::address::Address;
+use fvm_shared::chainid::ChainID;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::MethodNum;
@@ -20,7 +21,7 @@ use fil_actors_runtime::runtime::{ActorCode, Primitives, Runtime};
 use fil_actors_runtime::FIRST_EXPORTED_METHOD_NUMBER;
 use fil_actors_runtime::{
     actor_dispatch, actor_error, extract_send_result, resolve_to_actor_id, ActorContext,
-    ActorError, AsActorError, INIT_ACTOR_ADDR,
+    ActorDowncast, ActorError, AsActorError, INIT_ACTOR_ADDR,
 };
 
 pub use self::state::*;
@@ -48,6 +49,14 @@ pub enum Method {
     LockBalance = 9,
     // Method numbers derived from FRC-0042 standards
     UniversalReceiverHook = frc42_dispatch::method_hash!("Receive"),
+    PruneExpiredExported = frc42_dispatch::method_hash!("PruneExpired"),
+    ProposeWithSignaturesExported = frc42_dispatch::method_hash!("ProposeWithSignatures"),
+    SetSignerWeightExported = frc42_dispatch::method_hash!("SetSignerWeight"),
+    SetMethodPolicyExported = frc42_dispatch::method_hash!("SetMethodPolicy"),
+    GetSignersExported = frc42_dispatch::method_hash!("GetSigners"),
+    GetPendingTransactionsExported = frc42_dispatch::method_hash!("GetPendingTransactions"),
+    GetAvailableBalanceExported = frc42_dispatch::method_hash!("GetAvailableBalance"),
+    GetVestingScheduleExported = frc42_dispatch::method_hash!("GetVestingSchedule"),
 }
 
 /// Multisig Actor
@@ -100,6 +109,8 @@ impl Actor {
         let empty_root = PendingTxnMap::empty(rt.store(), PENDING_TXN_CONFIG, "empty").flush()?;
 
         let mut st: State = State {
+            signer_weights: vec![1; resolved_signers.len()],
+            method_policies: Vec::new(),
             signers: resolved_signers,
             num_approvals_threshold: params.num_approvals_threshold,
             pending_txs: empty_root,
@@ -134,6 +145,17 @@ impl Actor {
             ));
         }
 
+        if let Some(expiration) = params.expiration {
+            if expiration <= rt.curr_epoch() {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "expiration {} must be after the current epoch {}",
+                    expiration,
+                    rt.curr_epoch()
+                ));
+            }
+        }
+
         let (txn_id, txn) = rt.transaction(|st: &mut State, rt| {
             if !st.is_signer(&proposer) {
                 return Err(actor_error!(forbidden, "{} is not a signer", proposer));
@@ -154,6 +176,7 @@ impl Actor {
                 method: params.method,
                 params: params.params,
                 approved: Vec::new(),
+                expiration: params.expiration,
             };
 
             ptx.set(&t_id, txn.clone())?;
@@ -165,6 +188,106 @@ impl Actor {
         Ok(ProposeReturn { txn_id, applied, code, ret })
     }
 
+    /// Proposes a transaction approved by signatures from signers, gathered off-chain.
+    /// Each signature is over the digest of the `ProposalSignatureData` for the proposal, the
+    /// chain and the reserved transaction ID. The transaction is executed immediately if the signatures
+    /// meet the approval threshold, otherwise it remains pending with those approvals.
+    /// May be called by anyone.
+    pub fn propose_with_signatures(
+        rt: &impl Runtime,
+        params: ProposeWithSignaturesParams,
+    ) -> Result<ProposeReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let proposal = params.proposal;
+
+        if proposal.value.is_negative() {
+            return Err(actor_error!(
+                illegal_argument,
+                "proposed value must be non-negative, was {}",
+                proposal.value
+            ));
+        }
+
+        if let Some(expiration) = proposal.expiration {
+            if expiration <= rt.curr_epoch() {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "expiration {} must be after the current epoch {}",
+                    expiration,
+                    rt.curr_epoch()
+                ));
+            }
+        }
+
+        if params.signatures.is_empty() {
+            return Err(actor_error!(illegal_argument, "must provide at least one signature"));
+        }
+
+        let mut txn = Transaction {
+            to: proposal.to,
+            value: proposal.value,
+            method: proposal.method,
+            params: proposal.params,
+            approved: Vec::new(),
+            expiration: proposal.expiration,
+        };
+        let digest = compute_signing_digest(
+            rt.chain_id(),
+            &rt.message().receiver(),
+            params.txn_id,
+            &txn,
+            rt,
+        )
+        .with_context_code(ExitCode::USR_ILLEGAL_STATE, || {
+            format!("failed to compute signing digest for (tx: {:?})", params.txn_id)
+        })?;
+
+        let st: State = rt.state()?;
+        for sig in &params.signatures {
+            // An address that doesn't resolve can't belong to a signer.
+            let signer = rt
+                .resolve_address(&sig.signer)
+                .map(Address::new_id)
+                .filter(|signer| st.is_signer(signer))
+                .ok_or_else(|| actor_error!(forbidden, "{} is not a signer", sig.signer))?;
+            if txn.approved.contains(&signer) {
+                return Err(actor_error!(illegal_argument, "duplicate signature from {}", signer));
+            }
+            rt.verify_signature(&sig.signature, &sig.signer, &digest).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_ARGUMENT, "failed to verify signature")
+            })?;
+            txn.approved.push(signer);
+        }
+
+        let (txn_id, st) = rt.transaction(|st: &mut State, rt| {
+            // Consuming the reserved transaction ID prevents the signatures being replayed.
+            if params.txn_id != st.next_tx_id {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "transaction ID {} does not match next transaction ID {}",
+                    params.txn_id,
+                    st.next_tx_id
+                ));
+            }
+
+            let mut ptx = PendingTxnMap::load(
+                rt.store(),
+                &st.pending_txs,
+                PENDING_TXN_CONFIG,
+                "pending txns",
+            )?;
+            let t_id = st.next_tx_id;
+            st.next_tx_id.0 += 1;
+
+            ptx.set(&t_id, txn.clone())?;
+            st.pending_txs = ptx.flush()?;
+            Ok((t_id, st.clone()))
+        })?;
+
+        let (applied, ret, code) = execute_transaction_if_approved(rt, &st, txn_id, &txn)?;
+        Ok(ProposeReturn { txn_id, applied, code, ret })
+    }
+
     /// Multisig actor approve function
     pub fn approve(rt: &impl Runtime, params: TxnIDParams) -> Result<ApproveReturn, ActorError> {
         rt.validate_immediate_caller_accept_any()?;
@@ -183,6 +306,14 @@ impl Actor {
             )?;
 
             let txn = get_transaction(rt, &ptx, params.id, params.proposal_hash)?;
+            if txn.is_expired(rt.curr_epoch()) {
+                return Err(actor_error!(
+                    forbidden,
+                    "transaction {} expired at epoch {}",
+                    params.id,
+                    txn.expiration.unwrap_or_default()
+                ));
+            }
 
             // Go implementation holds reference to state after transaction so state must be cloned
             // to match to handle possible exit code inconsistency
@@ -259,7 +390,7 @@ impl Actor {
             }
 
             // Add signer and increase threshold if set
-            st.signers.push(Address::new_id(resolved_new_signer));
+            st.add_signer(Address::new_id(resolved_new_signer), 1);
             if params.increase {
                 st.num_approvals_threshold += 1;
             }
@@ -275,39 +406,42 @@ impl Actor {
         let resolved_old_signer = resolve_to_actor_id(rt, &params.signer, false)?;
 
         rt.transaction(|st: &mut State, rt| {
-            if !st.is_signer(&Address::new_id(resolved_old_signer)) {
-                return Err(actor_error!(forbidden, "{} is not a signer", resolved_old_signer));
-            }
+            let weight =
+                st.signer_weight(&Address::new_id(resolved_old_signer)).ok_or_else(|| {
+                    actor_error!(forbidden, "{} is not a signer", resolved_old_signer)
+                })?;
 
             if st.signers.len() == 1 {
                 return Err(actor_error!(forbidden; "Cannot remove only signer"));
             }
 
-            if !params.decrease && ((st.signers.len() - 1) as u64) < st.num_approvals_threshold {
+            if !params.decrease && st.total_weight() - weight < st.num_approvals_threshold {
                 return Err(actor_error!(
                     illegal_argument,
-                    "can't reduce signers to {} below threshold {} with decrease=false",
-                    st.signers.len(),
+                    "can't reduce signer weight to {} below threshold {} with decrease=false",
+                    st.total_weight() - weight,
                     st.num_approvals_threshold
                 ));
             }
 
             if params.decrease {
-                if st.num_approvals_threshold < 2 {
+                // The threshold is decreased by the weight of the removed signer.
+                if st.num_approvals_threshold <= weight {
                     return Err(actor_error!(
                         illegal_argument,
                         "can't decrease approvals from {} to {}",
                         st.num_approvals_threshold,
-                        st.num_approvals_threshold - 1
+                        st.num_approvals_threshold as i128 - weight as i128
                     ));
                 }
-                st.num_approvals_threshold -= 1;
+                st.num_approvals_threshold -= weight;
             }
 
             // Remove approvals from removed signer
             st.purge_approvals(rt.store(), &Address::new_id(resolved_old_signer))
                 .context("failed to purge approvals of removed signer")?;
-            st.signers.retain(|s| s != &Address::new_id(resolved_old_signer));
+            st.remove_signer(&Address::new_id(resolved_old_signer));
+            st.check_thresholds()?;
 
             Ok(())
         })?;
@@ -331,11 +465,13 @@ impl Actor {
                 return Err(actor_error!(illegal_argument; "{} is already a signer", to_resolved));
             }
 
-            // Remove signer from state (retain preserves order of elements)
-            st.signers.retain(|s| s != &Address::new_id(from_resolved));
+            // Remove signer from state (preserving the order of elements)
+            let weight = st.remove_signer(&Address::new_id(from_resolved)).ok_or_else(|| {
+                actor_error!(illegal_state, "signer {} has no weight", from_resolved)
+            })?;
 
-            // Add new signer
-            st.signers.push(Address::new_id(to_resolved));
+            // Add new signer, with the weight of the signer it replaces
+            st.add_signer(Address::new_id(to_resolved), weight);
 
             st.purge_approvals(rt.store(), &Address::new_id(from_resolved))?;
             Ok(())
@@ -354,7 +490,7 @@ impl Actor {
 
         rt.transaction(|st: &mut State, _| {
             // Check if valid threshold value
-            if params.new_threshold == 0 || params.new_threshold > st.signers.len() as u64 {
+            if params.new_threshold == 0 || params.new_threshold > st.total_weight() {
                 return Err(actor_error!(illegal_argument; "New threshold value not supported"));
             }
 
@@ -366,6 +502,62 @@ impl Actor {
         Ok(())
     }
 
+    /// Multisig actor function to change the weight of a signer
+    pub fn set_signer_weight(
+        rt: &impl Runtime,
+        params: SetSignerWeightParams,
+    ) -> Result<(), ActorError> {
+        let receiver = rt.message().receiver();
+        rt.validate_immediate_caller_is(std::iter::once(&receiver))?;
+
+        if params.weight == 0 || params.weight > SIGNER_WEIGHT_MAX {
+            return Err(actor_error!(
+                illegal_argument,
+                "signer weight {} must be between 1 and {}",
+                params.weight,
+                SIGNER_WEIGHT_MAX
+            ));
+        }
+        let signer = match rt.resolve_address(&params.signer) {
+            Some(id) => Address::new_id(id),
+            None => return Err(actor_error!(forbidden, "{} is not a signer", params.signer)),
+        };
+
+        rt.transaction(|st: &mut State, _| {
+            st.set_signer_weight(&signer, params.weight)?;
+            st.check_thresholds()
+        })
+    }
+
+    /// Multisig actor function to set (or remove) the approvals threshold for a method
+    pub fn set_method_policy(
+        rt: &impl Runtime,
+        params: SetMethodPolicyParams,
+    ) -> Result<(), ActorError> {
+        let receiver = rt.message().receiver();
+        rt.validate_immediate_caller_is(std::iter::once(&receiver))?;
+        let to = Address::new_id(resolve_to_actor_id(rt, &params.to, true)?);
+
+        rt.transaction(|st: &mut State, _| {
+            st.method_policies.retain(|p| !(p.to == to && p.method == params.method));
+            if params.threshold != 0 {
+                if st.method_policies.len() >= METHOD_POLICIES_MAX {
+                    return Err(actor_error!(
+                        forbidden,
+                        "cannot add more than {} method policies",
+                        METHOD_POLICIES_MAX
+                    ));
+                }
+                st.method_policies.push(MethodPolicy {
+                    to,
+                    method: params.method,
+                    threshold: params.threshold,
+                });
+            }
+            st.check_thresholds()
+        })
+    }
+
     /// Multisig actor function to change number of approvals needed
     pub fn lock_balance(rt: &impl Runtime, params: LockBalanceParams) -> Result<(), ActorError> {
         let receiver = rt.message().receiver();
@@ -390,6 +582,69 @@ impl Actor {
         Ok(())
     }
 
+    /// Removes the pending transactions that have expired among `limit` transaction IDs
+    /// from `start`. May be called by anyone.
+    pub fn prune_expired(
+        rt: &impl Runtime,
+        params: PruneExpiredParams,
+    ) -> Result<PruneExpiredReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        check_list_limit(params.limit)?;
+        let curr_epoch = rt.curr_epoch();
+        let start = params.start.unwrap_or_default();
+
+        rt.transaction(|st: &mut State, rt| {
+            st.prune_expired(rt.store(), curr_epoch, start, params.limit)
+        })
+    }
+
+    /// Returns the signers, their weights and the approvals threshold.
+    pub fn get_signers(rt: &impl Runtime) -> Result<GetSignersReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(GetSignersReturn {
+            signers: st.signers,
+            signer_weights: st.signer_weights,
+            num_approvals_threshold: st.num_approvals_threshold,
+        })
+    }
+
+    /// Returns the pending transactions among `limit` transaction IDs from `start`.
+    pub fn get_pending_transactions(
+        rt: &impl Runtime,
+        params: GetPendingTransactionsParams,
+    ) -> Result<GetPendingTransactionsReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        check_list_limit(params.limit)?;
+        let st: State = rt.state()?;
+        st.list_pending_txns(rt.store(), params.start.unwrap_or_default(), params.limit)
+    }
+
+    /// Returns the balance that may be spent at the current epoch, i.e. the balance not
+    /// locked by the vesting schedule.
+    pub fn get_available_balance(
+        rt: &impl Runtime,
+    ) -> Result<GetAvailableBalanceReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let locked = st.amount_locked(rt.curr_epoch() - st.start_epoch);
+        let available_balance = std::cmp::max(rt.current_balance() - locked, TokenAmount::zero());
+        Ok(GetAvailableBalanceReturn { available_balance })
+    }
+
+    /// Returns the vesting schedule and the amount currently locked by it.
+    pub fn get_vesting_schedule(rt: &impl Runtime) -> Result<GetVestingScheduleReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let locked_balance = st.amount_locked(rt.curr_epoch() - st.start_epoch);
+        Ok(GetVestingScheduleReturn {
+            initial_balance: st.initial_balance,
+            start_epoch: st.start_epoch,
+            unlock_duration: st.unlock_duration,
+            locked_balance,
+        })
+    }
+
     fn approve_transaction(
         rt: &impl Runtime,
         tx_id: TxnID,
@@ -459,7 +714,10 @@ fn execute_transaction_if_approved(
     let mut out = RawBytes::default();
     let mut code = ExitCode::OK;
     let mut applied = false;
-    let threshold_met = txn.approved.len() as u64 >= st.num_approvals_threshold;
+    // Method policies are keyed by ID address.
+    let to = rt.resolve_address(&txn.to).map_or(txn.to, Address::new_id);
+    let threshold_met =
+        st.approval_weight(&txn.approved) >= st.approval_threshold(&to, txn.method, &txn.value);
     if threshold_met {
         st.check_available(rt.current_balance(), &txn.value, rt.curr_epoch())?;
 
@@ -530,6 +788,19 @@ where
     Ok(txn)
 }
 
+// Checks the number of transaction IDs to list or prune.
+fn check_list_limit(limit: u64) -> Result<(), ActorError> {
+    if limit == 0 || limit > LIST_PENDING_TRANSACTIONS_MAX {
+        return Err(actor_error!(
+            illegal_argument,
+            "limit {} must be between 1 and {}",
+            limit,
+            LIST_PENDING_TRANSACTIONS_MAX
+        ));
+    }
+    Ok(())
+}
+
 /// Computes a digest of a proposed transaction. This digest is used to confirm identity
 /// of the transaction associated with an ID, which might change under chain re-orgs.
 pub fn compute_proposal_hash(txn: &Transaction, sys: &dyn Primitives) -> anyhow::Result<[u8; 32]> {
@@ -544,6 +815,27 @@ pub fn compute_proposal_hash(txn: &Transaction, sys: &dyn Primitives) -> anyhow:
     Ok(sys.hash_blake2b(&data))
 }
 
+/// Computes the digest signed by signers approving a proposal with `ProposeWithSignatures`.
+/// The proposal hash is computed with no requester, as the transaction has no proposer.
+pub fn compute_signing_digest(
+    chain_id: ChainID,
+    multisig: &Address,
+    txn_id: TxnID,
+    txn: &Transaction,
+    sys: &dyn Primitives,
+) -> anyhow::Result<[u8; 32]> {
+    let proposal_hash = compute_proposal_hash(txn, sys)?;
+    let data = ProposalSignatureData {
+        chain_id: chain_id.into(),
+        multisig,
+        txn_id,
+        proposal_hash: &proposal_hash,
+        expiration: txn.expiration,
+    };
+    let data = serialize_vec(&data, "proposal signature data")?;
+    Ok(sys.hash_blake2b(&data))
+}
+
 impl ActorCode for Actor {
     type Methods = Method;
 
@@ -562,6 +854,14 @@ impl ActorCode for Actor {
       ChangeNumApprovalsThreshold => change_num_approvals_threshold,
       LockBalance => lock_balance,
       UniversalReceiverHook => universal_receiver_hook,
+      PruneExpiredExported => prune_expired,
+      ProposeWithSignaturesExported => propose_with_signatures,
+      SetSignerWeightExported => set_signer_weight,
+      SetMethodPolicyExported => set_method_policy,
+      GetSignersExported => get_signers,
+      GetPendingTransactionsExported => get_pending_transactions,
+      GetAvailableBalanceExported => get_available_balance,
+      GetVestingScheduleExported => get_vesting_schedule,
       _ => fallback,
     }
 }
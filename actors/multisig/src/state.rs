@@ -3,27 +3,31 @@
 
 use cid::Cid;
 use fvm_ipld_blockstore::Blockstore;
-use fvm_ipld_encoding::tuple::*;
 use fvm_shared::address::Address;
 use fvm_shared::bigint::BigInt;
 use fvm_shared::bigint::Integer;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
+use fvm_shared::MethodNum;
 use indexmap::IndexMap;
 use num_traits::Zero;
 
-use fil_actors_runtime::{actor_error, ActorError, Config, Map2, DEFAULT_HAMT_CONFIG};
+use fil_actors_runtime::{
+    actor_error, impl_trailing_optional_tuple, ActorError, Config, Map2, DEFAULT_HAMT_CONFIG,
+};
 
-use super::types::Transaction;
+use super::types::{GetPendingTransactionsReturn, MethodPolicy, PruneExpiredReturn, Transaction};
 use super::TxnID;
 
 pub type PendingTxnMap<BS> = Map2<BS, TxnID, Transaction>;
 pub const PENDING_TXN_CONFIG: Config = DEFAULT_HAMT_CONFIG;
 
 /// Multisig actor state
-#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct State {
     pub signers: Vec<Address>,
+    /// The total weight of approvals required to execute a transaction,
+    /// unless a method policy applies.
     pub num_approvals_threshold: u64,
     pub next_tx_id: TxnID,
 
@@ -33,14 +37,122 @@ pub struct State {
     pub unlock_duration: ChainEpoch,
 
     pub pending_txs: Cid,
+
+    /// Weight of each signer, in the same order as `signers`. The approvals threshold
+    /// is a threshold on the total weight of a transaction's approvers.
+    /// Omitted from the encoding when every weight is 1 (and there are no method policies),
+    /// and decoded as weight 1 for every signer when absent.
+    // * Added in v15
+    pub signer_weights: Vec<u64>,
+    /// Approval thresholds for specific methods of specific actors, overriding the
+    /// default approvals threshold.
+    /// Omitted from the encoding when empty, and decoded as empty when absent.
+    // * Added in v15
+    pub method_policies: Vec<MethodPolicy>,
 }
 
+// The weights and policies are trailing optional fields, so that state written before they were
+// added still decodes, and state that doesn't use them keeps its previous encoding.
+impl_trailing_optional_tuple!(State {
+    signers,
+    num_approvals_threshold,
+    next_tx_id,
+    initial_balance,
+    start_epoch,
+    unlock_duration,
+    pending_txs;
+    signer_weights = vec![1; signers.len()],
+    method_policies,
+});
+
 impl State {
     /// Checks if `address` is in the list of signers
     pub fn is_signer(&self, address: &Address) -> bool {
         self.signers.contains(address)
     }
 
+    /// Returns the weight of a signer, or None if `address` is not a signer.
+    pub fn signer_weight(&self, address: &Address) -> Option<u64> {
+        let i = self.signers.iter().position(|s| s == address)?;
+        self.signer_weights.get(i).copied()
+    }
+
+    /// Adds a signer with the given weight.
+    pub fn add_signer(&mut self, address: Address, weight: u64) {
+        self.signers.push(address);
+        self.signer_weights.push(weight);
+    }
+
+    /// Removes a signer (preserving the order of the others), returning its weight.
+    /// Returns None, and removes nothing, if `address` is not a signer or has no weight.
+    pub fn remove_signer(&mut self, address: &Address) -> Option<u64> {
+        let i = self.signers.iter().position(|s| s == address)?;
+        if i >= self.signer_weights.len() {
+            return None;
+        }
+        self.signers.remove(i);
+        Some(self.signer_weights.remove(i))
+    }
+
+    /// Sets the weight of an existing signer.
+    pub fn set_signer_weight(&mut self, address: &Address, weight: u64) -> Result<(), ActorError> {
+        let i = self
+            .signers
+            .iter()
+            .position(|s| s == address)
+            .ok_or_else(|| actor_error!(forbidden, "{} is not a signer", address))?;
+        self.signer_weights[i] = weight;
+        Ok(())
+    }
+
+    /// Returns the total weight of all signers.
+    pub fn total_weight(&self) -> u64 {
+        self.signer_weights.iter().sum()
+    }
+
+    /// Returns the total weight of the signers among `approvers`.
+    pub fn approval_weight(&self, approvers: &[Address]) -> u64 {
+        approvers.iter().filter_map(|a| self.signer_weight(a)).sum()
+    }
+
+    /// Returns the approvals threshold for a call to `method` on the actor `to` transferring
+    /// `value`. Method policies only match ID addresses, and only calls that transfer no value,
+    /// so that transfers of the multisig's funds always need the default threshold.
+    pub fn approval_threshold(&self, to: &Address, method: MethodNum, value: &TokenAmount) -> u64 {
+        if !value.is_zero() {
+            return self.num_approvals_threshold;
+        }
+        self.method_policies
+            .iter()
+            .find(|p| &p.to == to && p.method == method)
+            .map_or(self.num_approvals_threshold, |p| p.threshold)
+    }
+
+    /// Checks that the signers' total weight can meet the default approvals threshold
+    /// and the threshold of every method policy.
+    pub fn check_thresholds(&self) -> Result<(), ActorError> {
+        let total_weight = self.total_weight();
+        if self.num_approvals_threshold > total_weight {
+            return Err(actor_error!(
+                illegal_argument,
+                "approvals threshold {} exceeds total signer weight {}",
+                self.num_approvals_threshold,
+                total_weight
+            ));
+        }
+        if let Some(p) = self.method_policies.iter().find(|p| p.threshold > total_weight) {
+            return Err(actor_error!(
+                illegal_argument,
+                "threshold {} for method {} of {} exceeds total signer weight {}",
+                p.threshold,
+                p.method,
+                p.to,
+                total_weight
+            ));
+        }
+        Ok(())
+    }
+
     /// Set locked amount in multisig state.
     pub fn set_locked(
         &mut self,
@@ -107,6 +219,57 @@ impl State {
         Ok(())
     }
 
+    /// Lists the pending transactions with IDs from `start`, in ID order, examining at most
+    /// `limit` IDs, along with the ID from which to continue listing if there are later IDs.
+    pub fn list_pending_txns<BS: Blockstore>(
+        &self,
+        store: &BS,
+        start: TxnID,
+        limit: u64,
+    ) -> Result<GetPendingTransactionsReturn, ActorError> {
+        let txns =
+            PendingTxnMap::load(store, &self.pending_txs, PENDING_TXN_CONFIG, "pending txns")?;
+        let start = start.0.max(0);
+        let end = start.saturating_add_unsigned(limit).min(self.next_tx_id.0);
+        let mut transactions = Vec::new();
+        for id in start..end {
+            if let Some(txn) = txns.get(&TxnID(id))? {
+                transactions.push((TxnID(id), txn.clone()));
+            }
+        }
+        let next = (end < self.next_tx_id.0).then_some(TxnID(end));
+        Ok(GetPendingTransactionsReturn { transactions, next })
+    }
+
+    /// Removes the pending transactions that have expired as of `curr_epoch` among `limit`
+    /// transaction IDs from `start`, along with the ID from which to continue if there are
+    /// later IDs.
+    pub fn prune_expired<BS: Blockstore>(
+        &mut self,
+        store: &BS,
+        curr_epoch: ChainEpoch,
+        start: TxnID,
+        limit: u64,
+    ) -> Result<PruneExpiredReturn, ActorError> {
+        let listed = self.list_pending_txns(store, start, limit)?;
+        let pruned: Vec<TxnID> = listed
+            .transactions
+            .iter()
+            .filter(|(_, txn)| txn.is_expired(curr_epoch))
+            .map(|(tx_id, _)| *tx_id)
+            .collect();
+
+        if !pruned.is_empty() {
+            let mut txns =
+                PendingTxnMap::load(store, &self.pending_txs, PENDING_TXN_CONFIG, "pending txns")?;
+            for tx_id in &pruned {
+                txns.delete(tx_id)?;
+            }
+            self.pending_txs = txns.flush()?;
+        }
+        Ok(PruneExpiredReturn { pruned, next: listed.next })
+    }
+
     pub(crate) fn check_available(
         &self,
         balance: TokenAmount,
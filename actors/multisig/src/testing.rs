@@ -1,14 +1,19 @@
 use std::{collections::HashSet, iter::FromIterator};
 
 use fvm_ipld_blockstore::Blockstore;
-use fvm_shared::address::Address;
+use fvm_shared::address::{Address, Protocol};
+use fvm_shared::MethodNum;
 
 use fil_actors_runtime::MessageAccumulator;
 
-use crate::{PendingTxnMap, State, TxnID, PENDING_TXN_CONFIG, SIGNERS_MAX};
+use crate::{
+    MethodPolicy, PendingTxnMap, State, TxnID, METHOD_POLICIES_MAX, PENDING_TXN_CONFIG,
+    SIGNERS_MAX, SIGNER_WEIGHT_MAX,
+};
 
 pub struct StateSummary {
     pub pending_tx_count: u64,
+    pub expiring_tx_count: u64,
     pub num_approvals_threshold: u64,
     pub signer_count: usize,
 }
@@ -26,13 +31,49 @@ pub fn check_state_invariants<BS: Blockstore>(
         format!("multisig has too many signers: {}", state.signers.len()),
     );
     acc.require(
-        state.signers.len() as u64 >= state.num_approvals_threshold,
+        state.signers.len() == state.signer_weights.len(),
         format!(
-            "multisig has insufficient signers to meet threshold ({} < {})",
+            "multisig has {} signers but {} signer weights",
             state.signers.len(),
-            state.num_approvals_threshold
+            state.signer_weights.len()
         ),
     );
+    for (signer, weight) in state.signers.iter().zip(state.signer_weights.iter()) {
+        acc.require(
+            (1..=SIGNER_WEIGHT_MAX).contains(weight),
+            format!("signer {signer} has invalid weight {weight}"),
+        );
+    }
+    let total_weight = state.total_weight();
+    acc.require(
+        total_weight >= state.num_approvals_threshold,
+        format!(
+            "multisig has insufficient signer weight to meet threshold ({} < {})",
+            total_weight, state.num_approvals_threshold
+        ),
+    );
+
+    // assert invariants involving method policies
+    acc.require(
+        state.method_policies.len() <= METHOD_POLICIES_MAX,
+        format!("multisig has too many method policies: {}", state.method_policies.len()),
+    );
+    let mut seen_policies = HashSet::<(&Address, MethodNum)>::new();
+    for policy in &state.method_policies {
+        let MethodPolicy { to, method, threshold } = policy;
+        acc.require(
+            to.protocol() == Protocol::ID,
+            format!("method policy target {to} is not an ID address"),
+        );
+        acc.require(
+            *threshold >= 1 && *threshold <= total_weight,
+            format!("method policy for {to} method {method} has invalid threshold {threshold}"),
+        );
+        acc.require(
+            seen_policies.insert((to, *method)),
+            format!("duplicate method policy for {to} method {method}"),
+        );
+    }
 
     // See https://github.com/filecoin-project/specs-actors/issues/1185
     if state.unlock_duration == 0 {
@@ -52,6 +93,7 @@ pub fn check_state_invariants<BS: Blockstore>(
     // test pending transactions
     let mut max_tx_id = TxnID(-1);
     let mut pending_tx_count = 0u64;
+    let mut expiring_tx_count = 0u64;
 
     match PendingTxnMap::load(store, &state.pending_txs, PENDING_TXN_CONFIG, "pending txns") {
         Ok(transactions) => {
@@ -75,9 +117,32 @@ pub fn check_state_invariants<BS: Blockstore>(
                     );
                     seen_approvals.insert(approval);
                 });
-                acc.require((seen_approvals.len() as u64) < state.num_approvals_threshold,
-                    format!("number of approvals ({}) meets the approvals threshold ({}), transaction should not be pending",
-                    seen_approvals.len(), state.num_approvals_threshold));
+                // Policies are keyed by ID address, and a non-ID target may resolve to any of
+                // them, so only the highest applicable threshold is a sound bound.
+                let threshold = if transaction.to.protocol() == Protocol::ID
+                    || !transaction.value.is_zero()
+                {
+                    state.approval_threshold(&transaction.to, transaction.method, &transaction.value)
+                } else {
+                    state
+                        .method_policies
+                        .iter()
+                        .filter(|p| p.method == transaction.method)
+                        .map(|p| p.threshold)
+                        .fold(state.num_approvals_threshold, u64::max)
+                };
+                let approval_weight = state.approval_weight(&transaction.approved);
+                acc.require(approval_weight < threshold,
+                    format!("weight of approvals ({}) meets the approvals threshold ({}), transaction should not be pending",
+                    approval_weight, threshold));
+
+                if let Some(expiration) = transaction.expiration {
+                    acc.require(
+                        expiration > 0,
+                        format!("transaction {tx_id} has non-positive expiration {expiration}"),
+                    );
+                    expiring_tx_count += 1;
+                }
 
                 pending_tx_count += 1;
 
@@ -97,6 +162,7 @@ pub fn check_state_invariants<BS: Blockstore>(
     (
         StateSummary {
             pending_tx_count,
+            expiring_tx_count,
             num_approvals_threshold: state.num_approvals_threshold,
             signer_count: state.signers.len(),
         },
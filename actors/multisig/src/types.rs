@@ -7,17 +7,28 @@ use fvm_ipld_encoding::tuple::*;
 use fvm_ipld_encoding::{strict_bytes, RawBytes};
 use fvm_shared::address::Address;
 use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::signature::Signature;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
 use fvm_shared::MethodNum;
 use serde::{Deserialize, Serialize};
 
-use fil_actors_runtime::MapKey;
+use fil_actors_runtime::{impl_trailing_optional_tuple, MapKey};
 
 /// SignersMax is the maximum number of signers allowed in a multisig. If more
 /// are required, please use a combining tree of multisigs.
 pub const SIGNERS_MAX: usize = 256;
 
+/// The maximum weight of a single signer. Together with `SIGNERS_MAX` this bounds the total
+/// weight of a multisig's signers.
+pub const SIGNER_WEIGHT_MAX: u64 = 1 << 32;
+
+/// The maximum number of method policies a multisig may have.
+pub const METHOD_POLICIES_MAX: usize = 64;
+
+/// The maximum number of transaction IDs a single call may list or prune.
+pub const LIST_PENDING_TRANSACTIONS_MAX: u64 = 1000;
+
 /// Transaction ID type
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Hash, Eq, PartialEq, PartialOrd)]
 #[serde(transparent)]
@@ -39,8 +50,11 @@ impl Display for TxnID {
     }
 }
 
-/// Transaction type used in multisig actor
-#[derive(Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
+/// Transaction type used in multisig actor.
+///
+/// For compatibility with existing state, a transaction without an expiration is encoded
+/// without the trailing expiration field.
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Transaction {
     pub to: Address,
     pub value: TokenAmount,
@@ -48,6 +62,29 @@ pub struct Transaction {
     pub params: RawBytes,
 
     pub approved: Vec<Address>,
+    /// Epoch at which the transaction expires, after which it can no longer be approved
+    /// and may be pruned by anyone. Transactions without an expiration remain pending
+    /// until approved or cancelled.
+    // * Added in v15
+    pub expiration: Option<ChainEpoch>,
+}
+
+impl Transaction {
+    /// Returns whether the transaction has expired as of `epoch`.
+    pub fn is_expired(&self, epoch: ChainEpoch) -> bool {
+        self.expiration.map_or(false, |expiration| epoch >= expiration)
+    }
+}
+
+/// An approvals threshold for calls to a specific method of a specific actor.
+/// The policy applies only to calls that transfer no value.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct MethodPolicy {
+    /// The ID address of the target actor.
+    pub to: Address,
+    pub method: MethodNum,
+    /// The total weight of approvals required for the call.
+    pub threshold: u64,
 }
 
 /// Data for a BLAKE2B-256 to be attached to methods referencing proposals via TXIDs.
@@ -55,7 +92,8 @@ pub struct Transaction {
 /// for offline signers and for protection when reorgs change a multisig TXID.
 ///
 /// Requester - The requesting multisig wallet member.
-/// All other fields - From the "Transaction" struct.
+/// All other fields - From the "Transaction" struct. The expiration is not included, so that
+/// hashes computed by existing tooling remain valid.
 #[derive(Serialize_tuple, Debug)]
 pub struct ProposalHashData<'a> {
     pub requester: Option<&'a Address>,
@@ -65,6 +103,30 @@ pub struct ProposalHashData<'a> {
     pub params: &'a RawBytes,
 }
 
+/// Data signed by each signer to approve a proposal off-chain (see `ProposeWithSignatures`).
+/// Binding the chain, the multisig and a reserved transaction ID prevents a set of signatures
+/// from being replayed, either on this multisig, on another with the same signers, or on
+/// another network where the multisig has the same address.
+///
+/// Since the reserved ID is the next transaction ID, any proposal made before the signatures
+/// are submitted (including one made by a signer in order to block them) consumes it, and the
+/// signatures must then be collected again for the new next transaction ID.
+///
+/// ChainID - The chain ID of the network.
+/// Multisig - The ID address of the multisig actor.
+/// TxnID - The transaction ID the proposal will be assigned, i.e. the next transaction ID.
+/// ProposalHash - The proposal hash of the transaction, with no requester.
+/// Expiration - The expiration of the transaction, which the proposal hash does not cover.
+#[derive(Serialize_tuple, Debug)]
+pub struct ProposalSignatureData<'a> {
+    pub chain_id: u64,
+    pub multisig: &'a Address,
+    pub txn_id: TxnID,
+    #[serde(with = "strict_bytes")]
+    pub proposal_hash: &'a [u8; 32],
+    pub expiration: Option<ChainEpoch>,
+}
+
 /// Constructor parameters for multisig actor.
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ConstructorParams {
@@ -76,16 +138,43 @@ pub struct ConstructorParams {
 }
 
 /// Propose method call parameters.
-#[derive(Serialize_tuple, Deserialize_tuple)]
+///
+/// As with [`Transaction`], the expiration is omitted from the encoding when absent, and
+/// may be omitted by callers.
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct ProposeParams {
     pub to: Address,
     pub value: TokenAmount,
     pub method: MethodNum,
     pub params: RawBytes,
+    /// Optional epoch at which the proposed transaction expires. Must be in the future.
+    // * Added in v15
+    pub expiration: Option<ChainEpoch>,
+}
+
+impl_trailing_optional_tuple!(Transaction { to, value, method, params, approved; expiration });
+impl_trailing_optional_tuple!(ProposeParams { to, value, method, params; expiration });
+
+/// A signature by a signer over a proposal.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ProposalSignature {
+    /// The signer's key address (BLS or SECP256K1).
+    pub signer: Address,
+    pub signature: Signature,
+}
+
+/// ProposeWithSignatures method call parameters.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct ProposeWithSignaturesParams {
+    pub proposal: ProposeParams,
+    /// The transaction ID reserved for the proposal, which must be the next transaction ID.
+    /// Any proposal made in the meantime consumes it, invalidating the signatures.
+    pub txn_id: TxnID,
+    pub signatures: Vec<ProposalSignature>,
 }
 
 /// Propose method call return.
-#[derive(Serialize_tuple, Deserialize_tuple)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
 pub struct ProposeReturn {
     /// TxnID is the ID of the proposed transaction.
     pub txn_id: TxnID,
@@ -122,6 +211,25 @@ pub struct ApproveReturn {
     pub ret: RawBytes,
 }
 
+/// PruneExpired method call parameters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct PruneExpiredParams {
+    /// The transaction ID at which to continue pruning, as returned by a previous call,
+    /// or none to start from the first transaction.
+    pub start: Option<TxnID>,
+    /// The number of transaction IDs to examine, from 1 to `LIST_PENDING_TRANSACTIONS_MAX`.
+    pub limit: u64,
+}
+
+/// PruneExpired method call return.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct PruneExpiredReturn {
+    /// IDs of the expired transactions that were removed.
+    pub pruned: Vec<TxnID>,
+    /// The transaction ID from which to continue pruning, if there are later transaction IDs.
+    pub next: Option<TxnID>,
+}
+
 /// Add signer params.
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct AddSignerParams {
@@ -136,6 +244,21 @@ pub struct RemoveSignerParams {
     pub decrease: bool,
 }
 
+/// Set signer weight params.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct SetSignerWeightParams {
+    pub signer: Address,
+    pub weight: u64,
+}
+
+/// Set method policy params. A threshold of zero removes the policy.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct SetMethodPolicyParams {
+    pub to: Address,
+    pub method: MethodNum,
+    pub threshold: u64,
+}
+
 /// Swap signer multisig method params
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct SwapSignerParams {
@@ -156,3 +279,44 @@ pub struct LockBalanceParams {
     pub unlock_duration: ChainEpoch,
     pub amount: TokenAmount,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetSignersReturn {
+    pub signers: Vec<Address>,
+    /// Weight of each signer, in the same order as `signers`.
+    pub signer_weights: Vec<u64>,
+    pub num_approvals_threshold: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetPendingTransactionsParams {
+    /// The transaction ID at which to continue listing, as returned by a previous call,
+    /// or none to start from the first transaction. It needn't be pending.
+    pub start: Option<TxnID>,
+    /// The number of transaction IDs to examine, from 1 to `LIST_PENDING_TRANSACTIONS_MAX`.
+    /// The transactions with these IDs that are still pending are returned.
+    pub limit: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetPendingTransactionsReturn {
+    /// Pending transactions, in ID order.
+    pub transactions: Vec<(TxnID, Transaction)>,
+    /// The transaction ID from which to continue listing, if there are later transaction IDs.
+    pub next: Option<TxnID>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct GetAvailableBalanceReturn {
+    pub available_balance: TokenAmount,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct GetVestingScheduleReturn {
+    pub initial_balance: TokenAmount,
+    pub start_epoch: ChainEpoch,
+    pub unlock_duration: ChainEpoch,
+    /// The amount still locked at the current epoch.
+    pub locked_balance: TokenAmount,
+}
@@ -1,7 +1,8 @@
 use fil_actor_multisig::testing::check_state_invariants;
 use fil_actor_multisig::{
-    compute_proposal_hash, Actor as MultisigActor, ConstructorParams, Method, ProposeReturn, State,
-    Transaction, TxnID, TxnIDParams, SIGNERS_MAX,
+    compute_proposal_hash, Actor as MultisigActor, ConstructorParams, Method, PendingTxnMap,
+    ProposeReturn, State, Transaction, TxnID, TxnIDParams, PENDING_TXN_CONFIG, SIGNERS_MAX,
+    SIGNER_WEIGHT_MAX,
 };
 use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::runtime::Runtime;
@@ -574,6 +575,7 @@ fn test_simple_propose() {
         method: METHOD_SEND,
         params: RawBytes::default(),
         approved: vec![anne],
+        expiration: None,
     };
     let expect_txns = vec![(TxnID(0), txn0)];
     h.assert_transactions(&rt, expect_txns);
@@ -1144,6 +1146,7 @@ fn test_swap_signer_removes_approvals() {
                     method: METHOD_SEND,
                     params: RawBytes::default(),
                     approved: vec![bob],
+                    expiration: None,
                 },
             ),
             (
@@ -1154,6 +1157,7 @@ fn test_swap_signer_removes_approvals() {
                     method: METHOD_SEND,
                     params: RawBytes::default(),
                     approved: vec![bob],
+                    expiration: None,
                 },
             ),
         ],
@@ -1185,6 +1189,28 @@ fn test_swap_signer_deletes_solo_proposals() {
     check_state(&rt);
 }
 
+#[test]
+fn test_swap_signer_without_weight_fails() {
+    let msig = Address::new_id(100);
+    let anne = Address::new_id(101);
+    let bob = Address::new_id(102);
+    let darlene = Address::new_id(104);
+
+    let rt = construct_runtime(msig);
+    let h = util::ActorHarness::new();
+    h.construct_and_verify(&rt, 1, 0, 0, vec![anne, bob]);
+
+    // bob has no weight entry
+    let mut st: State = rt.get_state();
+    st.signer_weights.pop();
+    rt.replace_state(&st);
+
+    rt.set_caller(*MULTISIG_ACTOR_CODE_ID, msig);
+    expect_abort(ExitCode::USR_ILLEGAL_STATE, h.swap_signers(&rt, bob, darlene));
+    let st: State = rt.get_state();
+    assert_eq!(vec![anne, bob], st.signers);
+}
+
 #[test]
 fn test_remove_signer_removes_approvals() {
     let msig = Address::new_id(100);
@@ -1230,6 +1256,7 @@ fn test_remove_signer_removes_approvals() {
                     method: METHOD_SEND,
                     params: RawBytes::default(),
                     approved: vec![bob],
+                    expiration: None,
                 },
             ),
             (
@@ -1240,6 +1267,7 @@ fn test_remove_signer_removes_approvals() {
                     method: METHOD_SEND,
                     params: RawBytes::default(),
                     approved: vec![bob],
+                    expiration: None,
                 },
             ),
         ],
@@ -1305,6 +1333,7 @@ mod approval_tests {
             method: fake_method,
             params: fake_params.clone(),
             approved: vec![anne],
+            expiration: None,
         };
         h.assert_transactions(&rt, vec![(TxnID(0), expect_txn)]);
 
@@ -1391,6 +1420,7 @@ mod approval_tests {
                     method: fake_method,
                     params: fake_params.clone(),
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -1441,6 +1471,7 @@ mod approval_tests {
                     method: fake_method,
                     params: fake_params,
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -1478,6 +1509,7 @@ mod approval_tests {
                     method: fake_method,
                     params: fake_params,
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -1512,6 +1544,7 @@ mod approval_tests {
                 method: fake_method,
                 params: fake_params,
                 approved: vec![bob], //mismatch
+                expiration: None,
             },
             &rt,
         )
@@ -1550,6 +1583,7 @@ mod approval_tests {
             method: fake_method,
             params: fake_params.clone(),
             approved: vec![anne],
+            expiration: None,
         };
         h.assert_transactions(&rt, vec![(TxnID(0), expect_txn)]);
 
@@ -1647,6 +1681,7 @@ mod approval_tests {
                     method: fake_method,
                     params: fake_params,
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -1713,6 +1748,7 @@ mod approval_tests {
                     method: fake_method,
                     params: fake_params,
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -1852,6 +1888,685 @@ mod approval_tests {
     }
 }
 
+// Expiration
+mod expiration_tests {
+    use super::*;
+    use fil_actor_multisig::PruneExpiredReturn;
+
+    #[test]
+    fn expired_transaction_cannot_be_approved() {
+        let msig = Address::new_id(100);
+        let anne = Address::new_id(101);
+        let bob = Address::new_id(102);
+        let chuck = Address::new_id(103);
+        let rt = construct_runtime(msig);
+        let h = util::ActorHarness::new();
+        h.construct_and_verify(&rt, 2, 0, 0, vec![anne, bob]);
+
+        let send_value = TokenAmount::from_atto(10u8);
+        rt.set_balance(send_value.clone());
+        rt.set_epoch(10);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, anne);
+        h.propose_with_expiration(
+            &rt,
+            chuck,
+            send_value.clone(),
+            METHOD_SEND,
+            RawBytes::default(),
+            Some(20),
+        )
+        .unwrap();
+        let txn = Transaction {
+            to: chuck,
+            value: send_value.clone(),
+            method: METHOD_SEND,
+            params: RawBytes::default(),
+            approved: vec![anne],
+            expiration: Some(20),
+        };
+        h.assert_transactions(&rt, vec![(TxnID(0), txn.clone())]);
+        let proposal_hash = compute_proposal_hash(&txn, &rt).unwrap();
+
+        // The transaction expires at its expiration epoch.
+        rt.set_epoch(20);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, bob);
+        expect_abort_contains_message(
+            ExitCode::USR_FORBIDDEN,
+            "expired",
+            h.approve(&rt, TxnID(0), proposal_hash),
+        );
+        check_state(&rt);
+
+        // It could be approved the epoch before.
+        rt.set_epoch(19);
+        rt.expect_send_simple(chuck, METHOD_SEND, None, send_value, None, ExitCode::OK);
+        h.approve_ok(&rt, TxnID(0), proposal_hash);
+        h.assert_transactions(&rt, vec![]);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn fail_propose_with_past_expiration() {
+        let msig = Address::new_id(100);
+        let anne = Address::new_id(101);
+        let chuck = Address::new_id(103);
+        let rt = construct_runtime(msig);
+        let h = util::ActorHarness::new();
+        h.construct_and_verify(&rt, 1, 0, 0, vec![anne]);
+
+        rt.set_epoch(10);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, anne);
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.propose_with_expiration(
+                &rt,
+                chuck,
+                TokenAmount::zero(),
+                METHOD_SEND,
+                RawBytes::default(),
+                Some(10),
+            ),
+        );
+        rt.reset();
+        h.assert_transactions(&rt, vec![]);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn prune_removes_only_expired_transactions() {
+        let msig = Address::new_id(100);
+        let anne = Address::new_id(101);
+        let bob = Address::new_id(102);
+        let chuck = Address::new_id(103);
+        let rt = construct_runtime(msig);
+        let h = util::ActorHarness::new();
+        h.construct_and_verify(&rt, 2, 0, 0, vec![anne, bob]);
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, anne);
+        for expiration in [Some(5), None, Some(50)] {
+            h.propose_with_expiration(
+                &rt,
+                chuck,
+                TokenAmount::zero(),
+                METHOD_SEND,
+                RawBytes::default(),
+                expiration,
+            )
+            .unwrap();
+        }
+        let (summary, acc) = check_state_invariants(&rt.get_state(), rt.store());
+        acc.assert_empty();
+        assert_eq!(3, summary.pending_tx_count);
+        assert_eq!(2, summary.expiring_tx_count);
+
+        // Anyone may prune.
+        rt.set_epoch(5);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, chuck);
+        let ret = h.prune_expired(&rt, None, 10).unwrap();
+        assert_eq!(PruneExpiredReturn { pruned: vec![TxnID(0)], next: None }, ret);
+        let st: State = rt.get_state();
+        let ptx =
+            PendingTxnMap::load(&rt.store, &st.pending_txs, PENDING_TXN_CONFIG, "pending").unwrap();
+        assert!(ptx.get(&TxnID(0)).unwrap().is_none());
+        assert!(ptx.get(&TxnID(1)).unwrap().is_some());
+        assert!(ptx.get(&TxnID(2)).unwrap().is_some());
+
+        // Pruning again is a no-op.
+        assert!(h.prune_expired(&rt, None, 10).unwrap().pruned.is_empty());
+
+        // Pruning examines a bounded number of transaction IDs at a time.
+        rt.set_epoch(50);
+        let ret = h.prune_expired(&rt, None, 2).unwrap();
+        assert_eq!(PruneExpiredReturn { pruned: vec![], next: Some(TxnID(2)) }, ret);
+        let ret = h.prune_expired(&rt, ret.next, 2).unwrap();
+        assert_eq!(PruneExpiredReturn { pruned: vec![TxnID(2)], next: None }, ret);
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.prune_expired(&rt, None, 0));
+        rt.reset();
+        check_state(&rt);
+    }
+
+    #[test]
+    fn decode_transaction_without_expiration() {
+        #[derive(Serialize_tuple)]
+        struct LegacyTransaction {
+            to: Address,
+            value: TokenAmount,
+            method: MethodNum,
+            params: RawBytes,
+            approved: Vec<Address>,
+        }
+
+        let anne = Address::new_id(101);
+        let legacy = LegacyTransaction {
+            to: anne,
+            value: TokenAmount::from_atto(1),
+            method: METHOD_SEND,
+            params: RawBytes::default(),
+            approved: vec![anne],
+        };
+        let encoded = fvm_ipld_encoding::to_vec(&legacy).unwrap();
+        let txn: Transaction = fvm_ipld_encoding::from_slice(&encoded).unwrap();
+        assert_eq!(None, txn.expiration);
+        assert_eq!(vec![anne], txn.approved);
+        // A transaction without an expiration keeps the legacy encoding.
+        assert_eq!(encoded, fvm_ipld_encoding::to_vec(&txn).unwrap());
+    }
+}
+
+// Propose with signatures
+mod signature_tests {
+    use super::*;
+    use fil_actor_multisig::{compute_signing_digest, ProposalSignature, ProposeParams};
+    use fvm_shared::address::SECP_PUB_LEN;
+    use fvm_shared::chainid::ChainID;
+    use fvm_shared::crypto::signature::Signature;
+
+    struct Setup {
+        rt: MockRuntime,
+        h: util::ActorHarness,
+        msig: Address,
+        anne_key: Address,
+        bob_key: Address,
+        chuck: Address,
+    }
+
+    // Anne has a BLS key and Bob a SECP256K1 key. Chuck can only approve on-chain.
+    fn setup() -> Setup {
+        let msig = Address::new_id(TEST_MSIG_ADDR);
+        let anne = Address::new_id(TEST_ANNE_ADDR);
+        let bob = Address::new_id(TEST_BOB_ADDR);
+        let chuck = Address::new_id(TEST_CHUCK_ADDR);
+        let anne_key = new_bls_addr(1);
+        let bob_key = Address::new_secp256k1(&[2; SECP_PUB_LEN]).unwrap();
+        let rt = construct_runtime(msig);
+        rt.add_id_address(anne_key, anne);
+        rt.add_id_address(bob_key, bob);
+        let h = util::ActorHarness::new();
+        h.construct_and_verify(&rt, 2, 0, 0, vec![anne, bob, chuck]);
+        Setup { rt, h, msig, anne_key, bob_key, chuck }
+    }
+
+    fn proposal(to: Address, value: TokenAmount) -> ProposeParams {
+        ProposeParams {
+            to,
+            value,
+            method: METHOD_SEND,
+            params: RawBytes::default(),
+            expiration: None,
+        }
+    }
+
+    fn digest(rt: &MockRuntime, msig: Address, txn_id: TxnID, proposal: &ProposeParams) -> Vec<u8> {
+        let txn = Transaction {
+            to: proposal.to,
+            value: proposal.value.clone(),
+            method: proposal.method,
+            params: proposal.params.clone(),
+            approved: vec![],
+            expiration: proposal.expiration,
+        };
+        compute_signing_digest(rt.chain_id, &msig, txn_id, &txn, rt).unwrap().to_vec()
+    }
+
+    fn sign(
+        rt: &MockRuntime,
+        signer: Address,
+        signature: Signature,
+        plaintext: &[u8],
+    ) -> ProposalSignature {
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: signature.clone(),
+            signer,
+            plaintext: plaintext.to_vec(),
+            result: Ok(()),
+        });
+        ProposalSignature { signer, signature }
+    }
+
+    #[test]
+    fn execute_with_mixed_signatures() {
+        let Setup { rt, h, msig, anne_key, bob_key, chuck } = setup();
+        let send_value = TokenAmount::from_atto(10u8);
+        rt.set_balance(send_value.clone());
+
+        let proposal = proposal(chuck, send_value.clone());
+        let plaintext = digest(&rt, msig, TxnID(0), &proposal);
+        let signatures = vec![
+            sign(&rt, anne_key, Signature::new_bls(vec![1; 96]), &plaintext),
+            sign(&rt, bob_key, Signature::new_secp256k1(vec![2; 65]), &plaintext),
+        ];
+
+        // Anyone may submit the signatures.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(1000));
+        rt.expect_send_simple(chuck, METHOD_SEND, None, send_value, None, ExitCode::OK);
+        let ret =
+            h.propose_with_signatures(&rt, proposal.clone(), TxnID(0), signatures.clone()).unwrap();
+        assert_eq!(TxnID(0), ret.txn_id);
+        assert!(ret.applied);
+        assert_eq!(ExitCode::OK, ret.code);
+        h.assert_transactions(&rt, vec![]);
+        assert_eq!(TxnID(1), rt.get_state::<State>().next_tx_id);
+
+        // The signatures can't be replayed, as the transaction ID has been consumed.
+        for sig in &signatures {
+            sign(&rt, sig.signer, sig.signature.clone(), &plaintext);
+        }
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "does not match next transaction ID",
+            h.propose_with_signatures(&rt, proposal, TxnID(0), signatures),
+        );
+        check_state(&rt);
+    }
+
+    #[test]
+    fn insufficient_signatures_leave_transaction_pending() {
+        let Setup { rt, h, msig, anne_key, chuck, .. } = setup();
+        let send_value = TokenAmount::from_atto(10u8);
+        rt.set_balance(send_value.clone());
+
+        let proposal = proposal(chuck, send_value.clone());
+        let plaintext = digest(&rt, msig, TxnID(0), &proposal);
+        let signatures = vec![sign(&rt, anne_key, Signature::new_bls(vec![1; 96]), &plaintext)];
+        let ret = h.propose_with_signatures(&rt, proposal, TxnID(0), signatures).unwrap();
+        assert!(!ret.applied);
+
+        let txn = Transaction {
+            to: chuck,
+            value: send_value.clone(),
+            method: METHOD_SEND,
+            params: RawBytes::default(),
+            approved: vec![Address::new_id(TEST_ANNE_ADDR)],
+            expiration: None,
+        };
+        h.assert_transactions(&rt, vec![(TxnID(0), txn.clone())]);
+        check_state(&rt);
+
+        // Another signer can complete the approval on-chain.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, chuck);
+        rt.expect_send_simple(chuck, METHOD_SEND, None, send_value, None, ExitCode::OK);
+        h.approve_ok(&rt, TxnID(0), compute_proposal_hash(&txn, &rt).unwrap());
+        h.assert_transactions(&rt, vec![]);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn fail_with_changed_expiration() {
+        let Setup { rt, h, msig, anne_key, chuck, .. } = setup();
+        rt.set_epoch(10);
+        let signed =
+            ProposeParams { expiration: Some(100), ..proposal(chuck, TokenAmount::zero()) };
+        let signed_plaintext = digest(&rt, msig, TxnID(0), &signed);
+
+        // A relayer extends the expiration, so the signature no longer verifies.
+        for expiration in [Some(200), None] {
+            let relayed = ProposeParams { expiration, ..signed.clone() };
+            let plaintext = digest(&rt, msig, TxnID(0), &relayed);
+            assert_ne!(signed_plaintext, plaintext);
+            let signature = Signature::new_bls(vec![1; 96]);
+            rt.expect_verify_signature(ExpectedVerifySig {
+                sig: signature.clone(),
+                signer: anne_key,
+                plaintext,
+                result: Err(anyhow::anyhow!("bad signature")),
+            });
+            expect_abort_contains_message(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                "failed to verify signature",
+                h.propose_with_signatures(
+                    &rt,
+                    relayed,
+                    TxnID(0),
+                    vec![ProposalSignature { signer: anne_key, signature }],
+                ),
+            );
+            rt.reset();
+        }
+
+        h.assert_transactions(&rt, vec![]);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn fail_on_another_chain() {
+        let Setup { mut rt, h, msig, anne_key, chuck, .. } = setup();
+        let proposal = proposal(chuck, TokenAmount::zero());
+        let signed_plaintext = digest(&rt, msig, TxnID(0), &proposal);
+
+        // The same multisig address on another network can't accept the signatures.
+        rt.chain_id = ChainID::from(314);
+        let plaintext = digest(&rt, msig, TxnID(0), &proposal);
+        assert_ne!(signed_plaintext, plaintext);
+        let signature = Signature::new_bls(vec![1; 96]);
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: signature.clone(),
+            signer: anne_key,
+            plaintext,
+            result: Err(anyhow::anyhow!("bad signature")),
+        });
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "failed to verify signature",
+            h.propose_with_signatures(
+                &rt,
+                proposal,
+                TxnID(0),
+                vec![ProposalSignature { signer: anne_key, signature }],
+            ),
+        );
+        rt.reset();
+        h.assert_transactions(&rt, vec![]);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn fail_with_invalid_signatures() {
+        let Setup { rt, h, msig, anne_key, bob_key, chuck } = setup();
+        let proposal = proposal(chuck, TokenAmount::zero());
+        let plaintext = digest(&rt, msig, TxnID(0), &proposal);
+        let anne_sig =
+            ProposalSignature { signer: anne_key, signature: Signature::new_bls(vec![1; 96]) };
+
+        // Bad signature.
+        sign(&rt, anne_key, anne_sig.signature.clone(), &plaintext);
+        rt.expect_verify_signature(ExpectedVerifySig {
+            sig: Signature::new_secp256k1(vec![2; 65]),
+            signer: bob_key,
+            plaintext: plaintext.clone(),
+            result: Err(anyhow::anyhow!("bad signature")),
+        });
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "failed to verify signature",
+            h.propose_with_signatures(
+                &rt,
+                proposal.clone(),
+                TxnID(0),
+                vec![
+                    anne_sig.clone(),
+                    ProposalSignature {
+                        signer: bob_key,
+                        signature: Signature::new_secp256k1(vec![2; 65]),
+                    },
+                ],
+            ),
+        );
+        rt.reset();
+
+        // Duplicate signer.
+        sign(&rt, anne_key, anne_sig.signature.clone(), &plaintext);
+        expect_abort_contains_message(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            "duplicate signature",
+            h.propose_with_signatures(
+                &rt,
+                proposal.clone(),
+                TxnID(0),
+                vec![anne_sig.clone(), anne_sig.clone()],
+            ),
+        );
+        rt.reset();
+
+        // Not a signer.
+        let stranger = ProposalSignature {
+            signer: Address::new_secp256k1(&[3; SECP_PUB_LEN]).unwrap(),
+            signature: Signature::new_secp256k1(vec![3; 65]),
+        };
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            h.propose_with_signatures(&rt, proposal.clone(), TxnID(0), vec![stranger]),
+        );
+        rt.reset();
+
+        // No signatures.
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.propose_with_signatures(&rt, proposal, TxnID(0), vec![]),
+        );
+        rt.reset();
+
+        h.assert_transactions(&rt, vec![]);
+        assert_eq!(TxnID(0), rt.get_state::<State>().next_tx_id);
+        check_state(&rt);
+    }
+}
+
+// Weighted signers and method policies
+mod weight_tests {
+    use super::*;
+    use fil_actor_multisig::{ApproveReturn, MethodPolicy};
+
+    const WITHDRAW_BALANCE_METHOD: MethodNum = 16;
+
+    #[test]
+    fn weighted_approvals() {
+        let msig = Address::new_id(TEST_MSIG_ADDR);
+        let anne = Address::new_id(TEST_ANNE_ADDR);
+        let bob = Address::new_id(TEST_BOB_ADDR);
+        let chuck = Address::new_id(TEST_CHUCK_ADDR);
+        let darlene = Address::new_id(TEST_DARLENE_ADDR);
+        let rt = construct_runtime(msig);
+        let h = util::ActorHarness::new();
+        h.construct_and_verify(&rt, 3, 0, 0, vec![anne, bob, chuck]);
+
+        rt.set_caller(*MULTISIG_ACTOR_CODE_ID, msig);
+        h.set_signer_weight(&rt, anne, 3).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(vec![3, 1, 1], st.signer_weights);
+        check_state(&rt);
+
+        let send_value = TokenAmount::from_atto(10u8);
+        rt.set_balance(TokenAmount::from_atto(20u8));
+
+        // Anne's weight alone meets the threshold.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, anne);
+        rt.expect_send_simple(darlene, METHOD_SEND, None, send_value.clone(), None, ExitCode::OK);
+        let ret = h
+            .propose(&rt, darlene, send_value.clone(), METHOD_SEND, RawBytes::default())
+            .unwrap()
+            .unwrap()
+            .deserialize::<ProposeReturn>()
+            .unwrap();
+        assert!(ret.applied);
+
+        // Bob and Chuck together don't.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, bob);
+        let proposal_hash =
+            h.propose_ok(&rt, darlene, send_value.clone(), METHOD_SEND, RawBytes::default());
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, chuck);
+        let ret = h
+            .approve(&rt, TxnID(1), proposal_hash)
+            .unwrap()
+            .unwrap()
+            .deserialize::<ApproveReturn>()
+            .unwrap();
+        assert!(!ret.applied);
+        check_state(&rt);
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, anne);
+        rt.expect_send_simple(darlene, METHOD_SEND, None, send_value, None, ExitCode::OK);
+        h.approve_ok(&rt, TxnID(1), proposal_hash);
+        h.assert_transactions(&rt, vec![]);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn signer_weight_changes_preserve_thresholds() {
+        let msig = Address::new_id(TEST_MSIG_ADDR);
+        let anne = Address::new_id(TEST_ANNE_ADDR);
+        let bob = Address::new_id(TEST_BOB_ADDR);
+        let chuck = Address::new_id(TEST_CHUCK_ADDR);
+        let rt = construct_runtime(msig);
+        let h = util::ActorHarness::new();
+        h.construct_and_verify(&rt, 2, 0, 0, vec![anne, bob]);
+        rt.set_caller(*MULTISIG_ACTOR_CODE_ID, msig);
+
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.set_signer_weight(&rt, anne, 0));
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.set_signer_weight(&rt, anne, SIGNER_WEIGHT_MAX + 1),
+        );
+        expect_abort(ExitCode::USR_FORBIDDEN, h.set_signer_weight(&rt, chuck, 2));
+
+        // Swapping a signer keeps its weight.
+        h.set_signer_weight(&rt, bob, 5).unwrap();
+        h.swap_signers(&rt, bob, chuck).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(vec![anne, chuck], st.signers);
+        assert_eq!(vec![1, 5], st.signer_weights);
+
+        // Removing a signer decreases the threshold by its weight.
+        h.change_num_approvals_threshold(&rt, 6).unwrap();
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.change_num_approvals_threshold(&rt, 7));
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.remove_signer(&rt, chuck, false));
+        h.remove_signer(&rt, chuck, true).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(vec![anne], st.signers);
+        assert_eq!(vec![1], st.signer_weights);
+        assert_eq!(1, st.num_approvals_threshold);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn method_policy_overrides_threshold() {
+        let msig = Address::new_id(TEST_MSIG_ADDR);
+        let anne = Address::new_id(TEST_ANNE_ADDR);
+        let bob = Address::new_id(TEST_BOB_ADDR);
+        let chuck = Address::new_id(TEST_CHUCK_ADDR);
+        let miner = Address::new_id(1000);
+        let rt = construct_runtime(msig);
+        rt.actor_code_cids.borrow_mut().insert(miner, *MINER_ACTOR_CODE_ID);
+        let h = util::ActorHarness::new();
+        h.construct_and_verify(&rt, 3, 0, 0, vec![anne, bob, chuck]);
+
+        rt.set_caller(*MULTISIG_ACTOR_CODE_ID, msig);
+        expect_abort(
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+            h.set_method_policy(&rt, miner, WITHDRAW_BALANCE_METHOD, 4),
+        );
+        h.set_method_policy(&rt, miner, WITHDRAW_BALANCE_METHOD, 1).unwrap();
+        let st: State = rt.get_state();
+        assert_eq!(
+            vec![MethodPolicy { to: miner, method: WITHDRAW_BALANCE_METHOD, threshold: 1 }],
+            st.method_policies
+        );
+        check_state(&rt);
+
+        // A single approval suffices for the policy's method.
+        let fake_params = RawBytes::from(vec![1, 2, 3, 4]);
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, anne);
+        rt.expect_send_simple(
+            miner,
+            WITHDRAW_BALANCE_METHOD,
+            to_ipld_block(fake_params.clone()),
+            TokenAmount::zero(),
+            None,
+            ExitCode::OK,
+        );
+        let ret = h
+            .propose(&rt, miner, TokenAmount::zero(), WITHDRAW_BALANCE_METHOD, fake_params.clone())
+            .unwrap()
+            .unwrap()
+            .deserialize::<ProposeReturn>()
+            .unwrap();
+        assert!(ret.applied);
+
+        // But not for other methods, nor for calls to the method that transfer value.
+        h.propose_ok(&rt, miner, TokenAmount::zero(), 23, fake_params.clone());
+        let value = TokenAmount::from_atto(10);
+        h.propose_ok(&rt, miner, value.clone(), WITHDRAW_BALANCE_METHOD, fake_params.clone());
+        h.assert_transactions(
+            &rt,
+            vec![
+                (
+                    TxnID(1),
+                    Transaction {
+                        to: miner,
+                        value: TokenAmount::zero(),
+                        method: 23,
+                        params: fake_params.clone(),
+                        approved: vec![anne],
+                        expiration: None,
+                    },
+                ),
+                (
+                    TxnID(2),
+                    Transaction {
+                        to: miner,
+                        value,
+                        method: WITHDRAW_BALANCE_METHOD,
+                        params: fake_params.clone(),
+                        approved: vec![anne],
+                        expiration: None,
+                    },
+                ),
+            ],
+        );
+        check_state(&rt);
+
+        // Removing a signer can't leave a policy unsatisfiable.
+        rt.set_caller(*MULTISIG_ACTOR_CODE_ID, msig);
+        h.set_method_policy(&rt, miner, WITHDRAW_BALANCE_METHOD, 3).unwrap();
+        expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, h.remove_signer(&rt, chuck, true));
+
+        // A zero threshold removes the policy.
+        h.set_method_policy(&rt, miner, WITHDRAW_BALANCE_METHOD, 0).unwrap();
+        let st: State = rt.get_state();
+        assert!(st.method_policies.is_empty());
+        check_state(&rt);
+    }
+
+    #[test]
+    fn state_without_weights_is_compatible() {
+        use cid::Cid;
+        use fil_actor_multisig::State;
+
+        #[derive(Serialize_tuple)]
+        struct LegacyState {
+            signers: Vec<Address>,
+            num_approvals_threshold: u64,
+            next_tx_id: TxnID,
+            initial_balance: TokenAmount,
+            start_epoch: ChainEpoch,
+            unlock_duration: ChainEpoch,
+            pending_txs: Cid,
+        }
+        let legacy = LegacyState {
+            signers: vec![Address::new_id(TEST_ANNE_ADDR), Address::new_id(TEST_BOB_ADDR)],
+            num_approvals_threshold: 2,
+            next_tx_id: TxnID(3),
+            initial_balance: TokenAmount::zero(),
+            start_epoch: 0,
+            unlock_duration: 0,
+            pending_txs: Cid::default(),
+        };
+        let legacy = serialize(&legacy, "state").unwrap();
+
+        // Legacy state decodes with unit weights and no policies.
+        let mut st: State = fvm_ipld_encoding::from_slice(&legacy).unwrap();
+        assert_eq!(vec![1, 1], st.signer_weights);
+        assert!(st.method_policies.is_empty());
+        // And keeps its encoding until weights or policies are used.
+        assert_eq!(legacy, serialize(&st, "state").unwrap());
+
+        st.remove_signer(&Address::new_id(TEST_BOB_ADDR)).unwrap();
+        st.set_signer_weight(&Address::new_id(TEST_ANNE_ADDR), 2).unwrap();
+        let weighted: State =
+            fvm_ipld_encoding::from_slice(&serialize(&st, "state").unwrap()).unwrap();
+        assert_eq!(vec![2], weighted.signer_weights);
+
+        st.method_policies.push(MethodPolicy {
+            to: Address::new_id(1000),
+            method: WITHDRAW_BALANCE_METHOD,
+            threshold: 1,
+        });
+        let with_policy: State =
+            fvm_ipld_encoding::from_slice(&serialize(&st, "state").unwrap()).unwrap();
+        assert_eq!(vec![2], with_policy.signer_weights);
+        assert_eq!(st.method_policies, with_policy.method_policies);
+    }
+}
+
 // Cancel
 mod cancel_tests {
     use super::*;
@@ -1948,6 +2663,7 @@ mod cancel_tests {
                     method: fake_method,
                     params: RawBytes::default(),
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -1989,6 +2705,7 @@ mod cancel_tests {
                     method: fake_method,
                     params: RawBytes::default(),
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -2031,6 +2748,7 @@ mod cancel_tests {
                     method: fake_method,
                     params: RawBytes::default(),
                     approved: vec![anne],
+                    expiration: None,
                 },
             )],
         );
@@ -2083,6 +2801,7 @@ mod cancel_tests {
             method: fake_method,
             params: RawBytes::default(),
             approved: vec![bob], // anne's approval is gone
+            expiration: None,
         };
         let new_proposal_hash = compute_proposal_hash(&new_tx, &rt).unwrap();
         h.assert_transactions(&rt, vec![(TxnID(0), new_tx)]);
@@ -2485,3 +3204,141 @@ fn test_fallback() {
 
     rt.verify();
 }
+
+// Exported getters
+mod getter_tests {
+    use super::*;
+    use fil_actor_multisig::{
+        GetAvailableBalanceReturn, GetPendingTransactionsParams, GetPendingTransactionsReturn,
+        GetSignersReturn, GetVestingScheduleReturn, LIST_PENDING_TRANSACTIONS_MAX,
+    };
+
+    fn call_getter<T: serde::de::DeserializeOwned>(
+        rt: &MockRuntime,
+        method: Method,
+        params: Option<IpldBlock>,
+    ) -> T {
+        rt.expect_validate_caller_any();
+        let ret = rt.call::<MultisigActor>(method as u64, params).unwrap().unwrap();
+        rt.verify();
+        ret.deserialize().unwrap()
+    }
+
+    #[test]
+    fn get_signers_and_vesting() {
+        let msig = Address::new_id(TEST_MSIG_ADDR);
+        let anne = Address::new_id(TEST_ANNE_ADDR);
+        let bob = Address::new_id(TEST_BOB_ADDR);
+        let rt = construct_runtime(msig);
+        let h = util::ActorHarness::new();
+        let locked = TokenAmount::from_atto(100u8);
+        rt.set_received(locked.clone());
+        rt.set_balance(TokenAmount::from_atto(120u8));
+        h.construct_and_verify(&rt, 1, 10, 0, vec![anne, bob]);
+
+        let ret: GetSignersReturn = call_getter(&rt, Method::GetSignersExported, None);
+        assert_eq!(
+            GetSignersReturn {
+                signers: vec![anne, bob],
+                signer_weights: vec![1, 1],
+                num_approvals_threshold: 1
+            },
+            ret
+        );
+
+        rt.set_epoch(5);
+        let ret: GetVestingScheduleReturn =
+            call_getter(&rt, Method::GetVestingScheduleExported, None);
+        assert_eq!(
+            GetVestingScheduleReturn {
+                initial_balance: locked,
+                start_epoch: 0,
+                unlock_duration: 10,
+                locked_balance: TokenAmount::from_atto(50u8),
+            },
+            ret
+        );
+        let ret: GetAvailableBalanceReturn =
+            call_getter(&rt, Method::GetAvailableBalanceExported, None);
+        assert_eq!(TokenAmount::from_atto(70u8), ret.available_balance);
+
+        // The available balance is never negative.
+        rt.set_balance(TokenAmount::from_atto(10u8));
+        let ret: GetAvailableBalanceReturn =
+            call_getter(&rt, Method::GetAvailableBalanceExported, None);
+        assert_eq!(TokenAmount::zero(), ret.available_balance);
+    }
+
+    #[test]
+    fn get_pending_transactions_paginated() {
+        let msig = Address::new_id(TEST_MSIG_ADDR);
+        let anne = Address::new_id(TEST_ANNE_ADDR);
+        let bob = Address::new_id(TEST_BOB_ADDR);
+        let chuck = Address::new_id(TEST_CHUCK_ADDR);
+        let rt = construct_runtime(msig);
+        let h = util::ActorHarness::new();
+        h.construct_and_verify(&rt, 2, 0, 0, vec![anne, bob]);
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, anne);
+        let txn = |method: MethodNum| Transaction {
+            to: chuck,
+            value: TokenAmount::zero(),
+            method,
+            params: RawBytes::default(),
+            approved: vec![anne],
+            expiration: None,
+        };
+        for method in 0..5 {
+            h.propose_ok(&rt, chuck, TokenAmount::zero(), method, RawBytes::default());
+        }
+        // Leave a gap in the IDs.
+        let proposal_hash = compute_proposal_hash(&txn(1), &rt).unwrap();
+        h.cancel(&rt, TxnID(1), proposal_hash).unwrap();
+
+        let list = |start: Option<TxnID>,
+                    limit: u64|
+         -> Result<_, fil_actors_runtime::ActorError> {
+            rt.expect_validate_caller_any();
+            let ret = rt.call::<MultisigActor>(
+                Method::GetPendingTransactionsExported as MethodNum,
+                IpldBlock::serialize_cbor(&GetPendingTransactionsParams { start, limit }).unwrap(),
+            );
+            rt.verify();
+            Ok(ret?.unwrap().deserialize::<GetPendingTransactionsReturn>().unwrap())
+        };
+
+        // Page through all the pending transactions.
+        let mut listed = Vec::new();
+        let mut start = None;
+        loop {
+            let ret = list(start, 2).unwrap();
+            assert!(ret.transactions.len() <= 2);
+            listed.extend(ret.transactions);
+            start = ret.next;
+            if start.is_none() {
+                break;
+            }
+        }
+        let expected: Vec<_> =
+            [0, 2, 3, 4].into_iter().map(|i| (TxnID(i), txn(i as u64))).collect();
+        assert_eq!(expected, listed);
+
+        // Listing from a transaction that is no longer pending resumes from the next one.
+        let ret = list(Some(TxnID(1)), 2).unwrap();
+        assert_eq!(vec![(TxnID(2), txn(2))], ret.transactions);
+        assert_eq!(Some(TxnID(3)), ret.next);
+
+        let ret = list(Some(TxnID(3)), 5).unwrap();
+        assert_eq!(vec![(TxnID(3), txn(3)), (TxnID(4), txn(4))], ret.transactions);
+        assert_eq!(None, ret.next);
+
+        for limit in [0, LIST_PENDING_TRANSACTIONS_MAX + 1] {
+            expect_abort_contains_message(
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                "limit",
+                list(None, limit),
+            );
+            rt.reset();
+        }
+    }
+}
@@ -1,9 +1,13 @@
 use fil_actor_multisig::{
     compute_proposal_hash, Actor, AddSignerParams, ApproveReturn, ConstructorParams, Method,
-    PendingTxnMap, ProposeParams, ProposeReturn, RemoveSignerParams, State, SwapSignerParams,
+    PendingTxnMap, ProposalSignature, ProposeParams, ProposeReturn, ProposeWithSignaturesParams,
+    PruneExpiredParams, PruneExpiredReturn, RemoveSignerParams, State, SwapSignerParams,
     Transaction, TxnID, TxnIDParams, PENDING_TXN_CONFIG,
 };
-use fil_actor_multisig::{ChangeNumApprovalsThresholdParams, LockBalanceParams};
+use fil_actor_multisig::{
+    ChangeNumApprovalsThresholdParams, LockBalanceParams, SetMethodPolicyParams,
+    SetSignerWeightParams,
+};
 use fil_actors_runtime::test_utils::*;
 use fil_actors_runtime::ActorError;
 use fil_actors_runtime::INIT_ACTOR_ADDR;
@@ -102,7 +106,14 @@ impl ActorHarness {
         let ret = self.propose(rt, to, value.clone(), method, params.clone());
         ret.unwrap().unwrap().deserialize::<ProposeReturn>().unwrap();
         // compute proposal hash
-        let txn = Transaction { to, value, method, params, approved: vec![*rt.caller.borrow()] };
+        let txn = Transaction {
+            to,
+            value,
+            method,
+            params,
+            approved: vec![*rt.caller.borrow()],
+            expiration: None,
+        };
         compute_proposal_hash(&txn, rt).unwrap()
     }
 
@@ -122,9 +133,21 @@ impl ActorHarness {
         value: TokenAmount,
         method: MethodNum,
         params: RawBytes,
+    ) -> Result<Option<IpldBlock>, ActorError> {
+        self.propose_with_expiration(rt, to, value, method, params, None)
+    }
+
+    pub fn propose_with_expiration(
+        &self,
+        rt: &MockRuntime,
+        to: Address,
+        value: TokenAmount,
+        method: MethodNum,
+        params: RawBytes,
+        expiration: Option<ChainEpoch>,
     ) -> Result<Option<IpldBlock>, ActorError> {
         rt.expect_validate_caller_any();
-        let propose_params = ProposeParams { to, value, method, params };
+        let propose_params = ProposeParams { to, value, method, params, expiration };
         let ret = rt.call::<Actor>(
             Method::Propose as u64,
             IpldBlock::serialize_cbor(&propose_params).unwrap(),
@@ -167,6 +190,72 @@ impl ActorHarness {
         ret
     }
 
+    pub fn propose_with_signatures(
+        &self,
+        rt: &MockRuntime,
+        proposal: ProposeParams,
+        txn_id: TxnID,
+        signatures: Vec<ProposalSignature>,
+    ) -> Result<ProposeReturn, ActorError> {
+        rt.expect_validate_caller_any();
+        let params = ProposeWithSignaturesParams { proposal, txn_id, signatures };
+        let ret = rt.call::<Actor>(
+            Method::ProposeWithSignaturesExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        Ok(ret?.unwrap().deserialize::<ProposeReturn>().unwrap())
+    }
+
+    pub fn prune_expired(
+        &self,
+        rt: &MockRuntime,
+        start: Option<TxnID>,
+        limit: u64,
+    ) -> Result<PruneExpiredReturn, ActorError> {
+        rt.expect_validate_caller_any();
+        let params = PruneExpiredParams { start, limit };
+        let ret = rt.call::<Actor>(
+            Method::PruneExpiredExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        Ok(ret?.unwrap().deserialize::<PruneExpiredReturn>().unwrap())
+    }
+
+    pub fn set_signer_weight(
+        &self,
+        rt: &MockRuntime,
+        signer: Address,
+        weight: u64,
+    ) -> Result<Option<IpldBlock>, ActorError> {
+        rt.expect_validate_caller_addr(vec![rt.receiver]);
+        let params = SetSignerWeightParams { signer, weight };
+        let ret = rt.call::<Actor>(
+            Method::SetSignerWeightExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        ret
+    }
+
+    pub fn set_method_policy(
+        &self,
+        rt: &MockRuntime,
+        to: Address,
+        method: MethodNum,
+        threshold: u64,
+    ) -> Result<Option<IpldBlock>, ActorError> {
+        rt.expect_validate_caller_addr(vec![rt.receiver]);
+        let params = SetMethodPolicyParams { to, method, threshold };
+        let ret = rt.call::<Actor>(
+            Method::SetMethodPolicyExported as u64,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        ret
+    }
+
     pub fn lock_balance(
         &self,
         rt: &MockRuntime,
@@ -0,0 +1,108 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::error::ExitCode;
+use fvm_shared::{MethodNum, METHOD_CONSTRUCTOR};
+use num_derive::FromPrimitive;
+
+use fil_actors_runtime::builtin::singletons::INIT_ACTOR_ADDR;
+use fil_actors_runtime::runtime::{ActorCode, Runtime};
+use fil_actors_runtime::{actor_dispatch, AsActorError, FIRST_EXPORTED_METHOD_NUMBER};
+use fil_actors_runtime::{actor_error, ActorError};
+
+use crate::types::{
+    AuthenticateMessageParams, AuthenticateMessageReturn, ConstructorParams, WebAuthnAssertion,
+};
+
+pub use self::state::State;
+
+pub mod p256;
+mod state;
+pub mod testing;
+pub mod types;
+mod webauthn;
+
+#[cfg(feature = "fil-actor")]
+fil_actors_runtime::wasm_trampoline!(Actor);
+
+/// Passkey account actor methods available
+#[derive(FromPrimitive)]
+#[repr(u64)]
+pub enum Method {
+    Constructor = METHOD_CONSTRUCTOR,
+    AuthenticateMessageExported = frc42_dispatch::method_hash!("AuthenticateMessage"),
+}
+
+/// Passkey account actor, controlled by a WebAuthn passkey.
+/// A passkey can't sign chain messages, so the account acts by authenticating messages for
+/// other actors, e.g. as a market client.
+pub struct Actor;
+
+impl Actor {
+    /// Constructor for a passkey account, which is created through the init actor.
+    pub fn constructor(rt: &impl Runtime, params: ConstructorParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&INIT_ACTOR_ADDR))?;
+        if p256::PublicKey::from_sec1_bytes(&params.public_key).is_none() {
+            return Err(actor_error!(illegal_argument;
+                "public key must be an uncompressed P-256 point"));
+        }
+        if params.rp_id.is_empty() {
+            return Err(actor_error!(illegal_argument; "relying party ID must not be empty"));
+        }
+        if params.origin.is_empty() {
+            return Err(actor_error!(illegal_argument; "origin must not be empty"));
+        }
+        rt.create(&State {
+            public_key: params.public_key,
+            rp_id: params.rp_id,
+            origin: params.origin,
+        })?;
+        Ok(())
+    }
+
+    /// Authenticates whether the provided signature is valid for the provided message.
+    /// The signature is a serialized `WebAuthnAssertion` by the passkey whose challenge is the
+    /// SHA-256 digest of the message.
+    /// Errors with USR_ILLEGAL_ARGUMENT if the authentication is invalid.
+    pub fn authenticate_message(
+        rt: &impl Runtime,
+        params: AuthenticateMessageParams,
+    ) -> Result<AuthenticateMessageReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let assertion: WebAuthnAssertion = fvm_ipld_encoding::from_slice(&params.signature)
+            .context_code(ExitCode::USR_ILLEGAL_ARGUMENT, "failed to decode assertion")?;
+        webauthn::verify_assertion(rt, &st, &assertion, &params.message)
+            .map_err(|e| actor_error!(illegal_argument, "failed to authenticate message: {}", e))?;
+        Ok(AuthenticateMessageReturn { authenticated: true })
+    }
+
+    /// Fallback method for unimplemented method numbers.
+    pub fn fallback(
+        rt: &impl Runtime,
+        method: MethodNum,
+        _: Option<IpldBlock>,
+    ) -> Result<Option<IpldBlock>, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        if method >= FIRST_EXPORTED_METHOD_NUMBER {
+            Ok(None)
+        } else {
+            Err(actor_error!(unhandled_message; "invalid method: {}", method))
+        }
+    }
+}
+
+impl ActorCode for Actor {
+    type Methods = Method;
+
+    fn name() -> &'static str {
+        "PasskeyAccount"
+    }
+
+    actor_dispatch! {
+        Constructor => constructor,
+        AuthenticateMessageExported => authenticate_message,
+        _ => fallback,
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Verification of ECDSA signatures over the NIST P-256 (secp256r1) curve, as produced by
+//! WebAuthn passkeys, using the RustCrypto `p256` implementation.
+
+use p256::ecdsa::signature::hazmat::PrehashVerifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+
+/// Length of an uncompressed SEC1 encoded public key: 0x04 || x || y.
+pub const PUBLIC_KEY_LEN: usize = 65;
+
+/// A P-256 public key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicKey(VerifyingKey);
+
+impl PublicKey {
+    /// Parses an uncompressed SEC1 encoded public key, checking that it is a point on the curve.
+    /// The point at infinity has no uncompressed encoding, so is never accepted.
+    pub fn from_sec1_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != PUBLIC_KEY_LEN || bytes[0] != 0x04 {
+            return None;
+        }
+        VerifyingKey::from_sec1_bytes(bytes).ok().map(PublicKey)
+    }
+
+    /// Verifies an ECDSA signature over a 32-byte digest of the signed message.
+    /// Only signatures with a low `s` (at most half the group order) are accepted, so that a
+    /// valid signature can't be altered into another. Authenticators may produce either, so
+    /// clients must normalize `s` before submitting a signature.
+    pub fn verify_prehash(&self, digest: &[u8; 32], signature: &Signature) -> bool {
+        if signature.normalize_s().is_some() {
+            return false;
+        }
+        self.0.verify_prehash(digest, signature).is_ok()
+    }
+}
+
+/// Parses a DER encoded ECDSA signature, checking that `r` and `s` are in `[1, n)`.
+pub fn parse_der_signature(der: &[u8]) -> Option<Signature> {
+    Signature::from_der(der).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    // RFC 6979, A.2.5: P-256 with SHA-256, message "sample".
+    const RFC6979_PUBLIC_KEY: [u8; 65] = hex!(
+        "04"
+        "60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6"
+        "7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"
+    );
+    // SHA-256("sample")
+    const RFC6979_DIGEST: [u8; 32] =
+        hex!("af2bdbe1aa9b6ec1e2ade1d694f41fc71a831d0268e9891562113d8a62add1bf");
+    const RFC6979_R: [u8; 32] =
+        hex!("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716");
+    // The vector's s is high. This is n - s.
+    const RFC6979_LOW_S: [u8; 32] =
+        hex!("0834e36ad29a83bf2bc9385e491d6099c8fdf9d1ed67aa7ea5f51f93782857a9");
+    const RFC6979_HIGH_S: [u8; 32] =
+        hex!("f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8");
+    // The group order.
+    const N: [u8; 32] = hex!("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551");
+
+    fn der(r: &[u8; 32], s: &[u8; 32]) -> Vec<u8> {
+        let int = |v: &[u8; 32]| {
+            let v = &v[v.iter().position(|&b| b != 0).unwrap_or(31)..];
+            let mut out = vec![0x02, v.len() as u8];
+            if v[0] & 0x80 != 0 {
+                out[1] += 1;
+                out.push(0);
+            }
+            out.extend_from_slice(v);
+            out
+        };
+        let (r, s) = (int(r), int(s));
+        [&[0x30, (r.len() + s.len()) as u8][..], &r, &s].concat()
+    }
+
+    fn verify(key: &PublicKey, digest: &[u8; 32], r: &[u8; 32], s: &[u8; 32]) -> bool {
+        parse_der_signature(&der(r, s)).map_or(false, |sig| key.verify_prehash(digest, &sig))
+    }
+
+    #[test]
+    fn verifies_rfc6979_vector() {
+        let key = PublicKey::from_sec1_bytes(&RFC6979_PUBLIC_KEY).unwrap();
+        assert!(verify(&key, &RFC6979_DIGEST, &RFC6979_R, &RFC6979_LOW_S));
+
+        let mut digest = RFC6979_DIGEST;
+        digest[0] ^= 1;
+        assert!(!verify(&key, &digest, &RFC6979_R, &RFC6979_LOW_S));
+        let mut s = RFC6979_LOW_S;
+        s[31] ^= 1;
+        assert!(!verify(&key, &RFC6979_DIGEST, &RFC6979_R, &s));
+    }
+
+    #[test]
+    fn rejects_high_s() {
+        let key = PublicKey::from_sec1_bytes(&RFC6979_PUBLIC_KEY).unwrap();
+        let sig = parse_der_signature(&der(&RFC6979_R, &RFC6979_HIGH_S)).unwrap();
+        assert!(!key.verify_prehash(&RFC6979_DIGEST, &sig));
+    }
+
+    #[test]
+    fn rejects_out_of_range_scalars() {
+        let mut n_plus_1 = N;
+        n_plus_1[31] += 1;
+        for v in [[0; 32], N, n_plus_1, [0xff; 32]] {
+            assert!(parse_der_signature(&der(&v, &RFC6979_LOW_S)).is_none());
+            assert!(parse_der_signature(&der(&RFC6979_R, &v)).is_none());
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_public_keys() {
+        let mut key = RFC6979_PUBLIC_KEY;
+        key[64] ^= 1;
+        assert!(PublicKey::from_sec1_bytes(&key).is_none());
+        let mut key = RFC6979_PUBLIC_KEY;
+        key[0] = 0x02;
+        assert!(PublicKey::from_sec1_bytes(&key).is_none());
+        assert!(PublicKey::from_sec1_bytes(&RFC6979_PUBLIC_KEY[..33]).is_none());
+
+        // The point at infinity, in its SEC1 encoding and as zero coordinates.
+        assert!(PublicKey::from_sec1_bytes(&[0x00]).is_none());
+        let mut infinity = [0u8; 65];
+        infinity[0] = 0x04;
+        assert!(PublicKey::from_sec1_bytes(&infinity).is_none());
+    }
+
+    #[test]
+    fn parses_der_signatures() {
+        let der = der(&RFC6979_R, &RFC6979_LOW_S);
+        let sig = parse_der_signature(&der).unwrap();
+        assert_eq!(RFC6979_R, <[u8; 32]>::from(sig.r().to_bytes()));
+        assert_eq!(RFC6979_LOW_S, <[u8; 32]>::from(sig.s().to_bytes()));
+
+        // Trailing bytes, bad lengths, negative or non-minimal integers are rejected.
+        assert!(parse_der_signature(&[&der[..], &[0]].concat()).is_none());
+        assert!(parse_der_signature(&der[..der.len() - 1]).is_none());
+        assert!(parse_der_signature(&hex!("3006020181020101")).is_none());
+        assert!(parse_der_signature(&hex!("300702020001020101")).is_none());
+    }
+}
@@ -0,0 +1,17 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fvm_ipld_encoding::strict_bytes;
+use fvm_ipld_encoding::tuple::*;
+
+/// State of a passkey account: the WebAuthn passkey that controls it.
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, PartialEq, Eq)]
+pub struct State {
+    /// Uncompressed SEC1 encoding of the P-256 public key.
+    #[serde(with = "strict_bytes")]
+    pub public_key: Vec<u8>,
+    /// The relying party ID the passkey is scoped to, e.g. "example.com".
+    pub rp_id: String,
+    /// The origin assertions must be made from, e.g. "https://example.com".
+    pub origin: String,
+}
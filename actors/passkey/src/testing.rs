@@ -0,0 +1,16 @@
+use fil_actors_runtime::MessageAccumulator;
+
+use crate::p256::PublicKey;
+use crate::State;
+
+/// Checks internal invariants of passkey account state.
+pub fn check_state_invariants(state: &State) -> MessageAccumulator {
+    let acc = MessageAccumulator::default();
+    acc.require(
+        PublicKey::from_sec1_bytes(&state.public_key).is_some(),
+        "public key must be an uncompressed P-256 point",
+    );
+    acc.require(!state.rp_id.is_empty(), "relying party ID must not be empty");
+    acc.require(!state.origin.is_empty(), "origin must not be empty");
+    acc
+}
@@ -0,0 +1,45 @@
+use fvm_ipld_encoding::strict_bytes;
+use fvm_ipld_encoding::tuple::*;
+
+/// Passkey account constructor parameters.
+#[derive(Debug, Clone, Serialize_tuple, Deserialize_tuple)]
+pub struct ConstructorParams {
+    /// Uncompressed SEC1 encoding of the P-256 public key.
+    #[serde(with = "strict_bytes")]
+    pub public_key: Vec<u8>,
+    /// The relying party ID the passkey is scoped to.
+    pub rp_id: String,
+    /// The origin assertions must be made from.
+    pub origin: String,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct AuthenticateMessageParams {
+    /// The CBOR encoding of a `WebAuthnAssertion`.
+    #[serde(with = "strict_bytes")]
+    pub signature: Vec<u8>,
+    #[serde(with = "strict_bytes")]
+    pub message: Vec<u8>,
+}
+
+#[derive(Debug, Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct AuthenticateMessageReturn {
+    pub authenticated: bool,
+}
+
+/// A WebAuthn assertion authenticating a message, whose challenge is the SHA-256 digest of
+/// the message.
+/// `AuthenticateMessageParams::signature` holds the CBOR encoding of this type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct WebAuthnAssertion {
+    #[serde(with = "strict_bytes")]
+    pub authenticator_data: Vec<u8>,
+    /// The UTF-8 JSON serialization of the client data, as passed to the authenticator.
+    #[serde(with = "strict_bytes")]
+    pub client_data_json: Vec<u8>,
+    /// DER encoded ECDSA signature over the authenticator data and the client data hash,
+    /// with a low `s`.
+    #[serde(with = "strict_bytes")]
+    pub signature: Vec<u8>,
+}
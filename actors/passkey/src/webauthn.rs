@@ -0,0 +1,87 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Verification of WebAuthn assertions made with a passkey.
+//! See https://www.w3.org/TR/webauthn-2/#sctn-verifying-assertion.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use fvm_shared::crypto::hash::SupportedHashes;
+use serde::Deserialize;
+
+use fil_actors_runtime::runtime::Primitives;
+
+use crate::p256::{parse_der_signature, PublicKey};
+use crate::types::WebAuthnAssertion;
+use crate::State;
+
+/// Length of the fixed prefix of authenticator data: RP ID hash, flags and signature counter.
+const AUTHENTICATOR_DATA_MIN_LEN: usize = 37;
+/// User Present flag of the authenticator data.
+const FLAG_USER_PRESENT: u8 = 0x01;
+/// Client data type of an assertion.
+const ASSERTION_TYPE: &str = "webauthn.get";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClientData {
+    #[serde(rename = "type")]
+    typ: String,
+    challenge: String,
+    origin: String,
+    #[serde(default)]
+    cross_origin: bool,
+}
+
+/// Verifies that an assertion was made by the passkey for the relying party, over a challenge
+/// that is the SHA-256 digest of the message.
+/// The signature counter is not checked, as the same message may be authenticated repeatedly.
+pub fn verify_assertion(
+    sys: &dyn Primitives,
+    passkey: &State,
+    assertion: &WebAuthnAssertion,
+    message: &[u8],
+) -> Result<(), String> {
+    let auth_data = &assertion.authenticator_data;
+    if auth_data.len() < AUTHENTICATOR_DATA_MIN_LEN {
+        return Err(format!("authenticator data too short: {} bytes", auth_data.len()));
+    }
+    if auth_data[..32] != sha256(sys, passkey.rp_id.as_bytes())[..] {
+        return Err(format!("authenticator data is not for relying party {}", passkey.rp_id));
+    }
+    if auth_data[32] & FLAG_USER_PRESENT == 0 {
+        return Err("user not present".to_string());
+    }
+
+    let client_data: ClientData = serde_json::from_slice(&assertion.client_data_json)
+        .map_err(|e| format!("invalid client data: {}", e))?;
+    if client_data.typ != ASSERTION_TYPE {
+        return Err(format!("client data type {} is not {}", client_data.typ, ASSERTION_TYPE));
+    }
+    if client_data.challenge != URL_SAFE_NO_PAD.encode(sha256(sys, message)) {
+        return Err("challenge does not match message".to_string());
+    }
+    if client_data.origin != passkey.origin {
+        return Err(format!("origin {} is not {}", client_data.origin, passkey.origin));
+    }
+    if client_data.cross_origin {
+        return Err("cross-origin assertions are not accepted".to_string());
+    }
+
+    let public_key = PublicKey::from_sec1_bytes(&passkey.public_key)
+        .ok_or_else(|| "invalid passkey public key".to_string())?;
+    let signature = parse_der_signature(&assertion.signature)
+        .ok_or_else(|| "invalid signature encoding".to_string())?;
+    let mut signed = auth_data.clone();
+    signed.extend_from_slice(&sha256(sys, &assertion.client_data_json));
+    if !public_key.verify_prehash(&sha256(sys, &signed), &signature) {
+        return Err("signature invalid".to_string());
+    }
+    Ok(())
+}
+
+fn sha256(sys: &dyn Primitives, data: &[u8]) -> [u8; 32] {
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&sys.hash(SupportedHashes::Sha2_256, data));
+    digest
+}
@@ -0,0 +1,270 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::address::Address;
+use fvm_shared::error::ExitCode;
+use fvm_shared::MethodNum;
+use hex_literal::hex;
+
+use fil_actor_passkey::testing::check_state_invariants;
+use fil_actor_passkey::types::{AuthenticateMessageParams, ConstructorParams, WebAuthnAssertion};
+use fil_actor_passkey::{Actor as PasskeyActor, Method, State};
+use fil_actors_runtime::test_utils::*;
+use fil_actors_runtime::{ActorError, INIT_ACTOR_ADDR, SYSTEM_ACTOR_ADDR};
+
+const ACCOUNT: u64 = 1000;
+const RP_ID: &str = "example.com";
+const ORIGIN: &str = "https://example.com";
+const MESSAGE: &[u8] = b"deal proposal";
+const PUBLIC_KEY: [u8; 65] = hex!(
+    "04"
+    "e3df00b2dfe4eacfc6784653e1c5f8735e6ed9883645660e7dd92c56efbc00a2"
+    "51258d36f9334c0ef5025065e1c9aa5536f475433d4e3ce199811d59e514ce67"
+);
+// SHA-256 of the RP ID, followed by the flags and signature counter.
+const AUTH_DATA: [u8; 37] =
+    hex!("a379a6f6eeafb9a55e378c118034e2751e682fab9f2d30ab13d2125586ce19470500000007");
+// As AUTH_DATA, without the User Present flag.
+const AUTH_DATA_NO_UP: [u8; 37] =
+    hex!("a379a6f6eeafb9a55e378c118034e2751e682fab9f2d30ab13d2125586ce19470400000007");
+const CHALLENGE: &str = "IhBd2fKZ0k5F_7nmJq7YebugY3NNHR8ddz74XstGjAg";
+
+fn client_data(typ: &str, cross_origin: bool) -> Vec<u8> {
+    format!(
+        r#"{{"type":"{typ}","challenge":"{CHALLENGE}","origin":"{ORIGIN}","crossOrigin":{cross_origin}}}"#
+    )
+    .into_bytes()
+}
+
+fn assertion() -> WebAuthnAssertion {
+    WebAuthnAssertion {
+        authenticator_data: AUTH_DATA.to_vec(),
+        client_data_json: client_data("webauthn.get", false),
+        signature: hex!(
+            "30440220281097d096146b8bb63db6a216d14f8e73f2c1f8b745ae6005893b16b62c5d16"
+            "02203ec623a57c9a38ac9cd7fb0c5abe13be1955383eda2972b6601ee21e3e1d2ec6"
+        )
+        .to_vec(),
+    }
+}
+
+fn construct(
+    rt: &MockRuntime,
+    public_key: &[u8],
+    rp_id: &str,
+    origin: &str,
+) -> Result<Option<IpldBlock>, ActorError> {
+    rt.set_caller(*INIT_ACTOR_CODE_ID, INIT_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
+    let params = ConstructorParams {
+        public_key: public_key.to_vec(),
+        rp_id: rp_id.to_string(),
+        origin: origin.to_string(),
+    };
+    let ret = rt.call::<PasskeyActor>(
+        Method::Constructor as MethodNum,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+    );
+    rt.verify();
+    ret
+}
+
+fn setup(rp_id: &str, origin: &str) -> MockRuntime {
+    let rt = MockRuntime { receiver: Address::new_id(ACCOUNT), ..Default::default() };
+    construct(&rt, &PUBLIC_KEY, rp_id, origin).unwrap();
+    rt
+}
+
+fn authenticate(
+    rt: &MockRuntime,
+    assertion: &WebAuthnAssertion,
+    message: &[u8],
+) -> Result<Option<IpldBlock>, ActorError> {
+    rt.expect_validate_caller_any();
+    let params = AuthenticateMessageParams {
+        signature: fvm_ipld_encoding::to_vec(assertion).unwrap(),
+        message: message.to_vec(),
+    };
+    let ret = rt.call::<PasskeyActor>(
+        Method::AuthenticateMessageExported as MethodNum,
+        IpldBlock::serialize_cbor(&params).unwrap(),
+    );
+    rt.verify();
+    ret
+}
+
+#[test]
+fn construction() {
+    let rt = setup(RP_ID, ORIGIN);
+    let state: State = rt.get_state();
+    assert_eq!(
+        State {
+            public_key: PUBLIC_KEY.to_vec(),
+            rp_id: RP_ID.to_string(),
+            origin: ORIGIN.to_string(),
+        },
+        state
+    );
+    check_state_invariants(&state).assert_empty();
+
+    // A passkey account has no pubkey address.
+    rt.expect_validate_caller_any();
+    expect_abort(ExitCode::USR_UNHANDLED_MESSAGE, rt.call::<PasskeyActor>(2, None));
+}
+
+#[test]
+fn construction_fails_for_invalid_params() {
+    let rt = MockRuntime { receiver: Address::new_id(ACCOUNT), ..Default::default() };
+    let mut off_curve = PUBLIC_KEY;
+    off_curve[64] ^= 1;
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, construct(&rt, &off_curve, RP_ID, ORIGIN));
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, construct(&rt, &PUBLIC_KEY[..33], RP_ID, ORIGIN));
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, construct(&rt, &PUBLIC_KEY, "", ORIGIN));
+    expect_abort(ExitCode::USR_ILLEGAL_ARGUMENT, construct(&rt, &PUBLIC_KEY, RP_ID, ""));
+
+    // Only the init actor may create a passkey account.
+    rt.set_caller(*SYSTEM_ACTOR_CODE_ID, SYSTEM_ACTOR_ADDR);
+    rt.expect_validate_caller_addr(vec![INIT_ACTOR_ADDR]);
+    let params = ConstructorParams {
+        public_key: PUBLIC_KEY.to_vec(),
+        rp_id: RP_ID.to_string(),
+        origin: ORIGIN.to_string(),
+    };
+    expect_abort(
+        ExitCode::USR_FORBIDDEN,
+        rt.call::<PasskeyActor>(
+            Method::Constructor as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        ),
+    );
+}
+
+#[test]
+fn authenticate_message_with_assertion() {
+    let rt = setup(RP_ID, ORIGIN);
+    let ret = authenticate(&rt, &assertion(), MESSAGE).unwrap();
+    assert!(ret.unwrap().deserialize::<bool>().unwrap());
+
+    // The challenge must be the digest of the message.
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "challenge does not match message",
+        authenticate(&rt, &assertion(), b"another proposal"),
+    );
+
+    // The signature must be valid.
+    let mut tampered = assertion();
+    let last = tampered.signature.len() - 1;
+    tampered.signature[last] ^= 1;
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "signature invalid",
+        authenticate(&rt, &tampered, MESSAGE),
+    );
+
+    // The same signature with a high s is not accepted.
+    let high_s = WebAuthnAssertion {
+        signature: hex!(
+            "30450220281097d096146b8bb63db6a216d14f8e73f2c1f8b745ae6005893b16b62c5d16"
+            "022100c139dc598365c754632804f3a541ec41a391c26eccee2bce939ae8a4be45f68b"
+        )
+        .to_vec(),
+        ..assertion()
+    };
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "signature invalid",
+        authenticate(&rt, &high_s, MESSAGE),
+    );
+
+    // The assertion must decode.
+    rt.expect_validate_caller_any();
+    let params = AuthenticateMessageParams { signature: vec![1, 2, 3], message: MESSAGE.to_vec() };
+    expect_abort(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        rt.call::<PasskeyActor>(
+            Method::AuthenticateMessageExported as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        ),
+    );
+    rt.verify();
+}
+
+#[test]
+fn authenticate_message_rejects_invalid_assertions() {
+    let rt = setup(RP_ID, ORIGIN);
+
+    // The user must be present.
+    let no_user_present = WebAuthnAssertion {
+        authenticator_data: AUTH_DATA_NO_UP.to_vec(),
+        client_data_json: client_data("webauthn.get", false),
+        signature: hex!(
+            "30460221009c4569a31fe98eb113ba8b5032378c2d972cea36a9532646d9db804540d55692"
+            "02210082a1a13be110c8ddac6f9d87ba582f90a6b4120f2c8c77ce53b230b447620e99"
+        )
+        .to_vec(),
+    };
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "user not present",
+        authenticate(&rt, &no_user_present, MESSAGE),
+    );
+
+    // Registration responses are not assertions.
+    let create = WebAuthnAssertion {
+        authenticator_data: AUTH_DATA.to_vec(),
+        client_data_json: client_data("webauthn.create", false),
+        signature: hex!(
+            "30440220712c25e0e82a84d8c8cacf6aaecf28477f7dbe7ed5ff14d48b8f0c83a829b1fc"
+            "02203e96473e0ad88e62b774e5f0135ceef3e494b342fcce2dd7bf53fc4c7c8cc6d6"
+        )
+        .to_vec(),
+    };
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "client data type webauthn.create",
+        authenticate(&rt, &create, MESSAGE),
+    );
+
+    // Assertions made in a cross-origin iframe are not accepted.
+    let cross_origin = WebAuthnAssertion {
+        authenticator_data: AUTH_DATA.to_vec(),
+        client_data_json: client_data("webauthn.get", true),
+        signature: hex!(
+            "304502203f1fb5ef5cde2040304712ca4726cdcfb190098fc149406876603e73cb6bf1b2"
+            "022100ee5be878c799b1e9d2d69b91f438644648c00020fd28cb59b8688dd3c7508376"
+        )
+        .to_vec(),
+    };
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "cross-origin",
+        authenticate(&rt, &cross_origin, MESSAGE),
+    );
+
+    let mut short = assertion();
+    short.authenticator_data.truncate(36);
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "authenticator data too short",
+        authenticate(&rt, &short, MESSAGE),
+    );
+}
+
+#[test]
+fn authenticate_message_checks_relying_party_and_origin() {
+    let rt = setup("example.org", ORIGIN);
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "not for relying party example.org",
+        authenticate(&rt, &assertion(), MESSAGE),
+    );
+
+    let rt = setup(RP_ID, "https://login.example.com");
+    expect_abort_contains_message(
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+        "origin https://example.com is not https://login.example.com",
+        authenticate(&rt, &assertion(), MESSAGE),
+    );
+}
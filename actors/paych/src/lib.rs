@@ -1,15 +1,17 @@
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use cid::Cid;
 use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::runtime::{ActorCode, Runtime};
 use fil_actors_runtime::{
     actor_dispatch, actor_error, deserialize_block, extract_send_result, resolve_to_actor_id,
-    ActorContext, ActorDowncast, ActorError, Array,
+    ActorContext, ActorDowncast, ActorError, Array, BatchReturn,
 };
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::CBOR;
 use fvm_shared::address::Address;
+use std::collections::BTreeMap;
 
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_shared::econ::TokenAmount;
@@ -19,7 +21,7 @@ use fvm_shared::{METHOD_CONSTRUCTOR, METHOD_SEND};
 use num_derive::FromPrimitive;
 use num_traits::Zero;
 
-pub use self::state::{LaneState, Merge, State};
+pub use self::state::{LaneState, Merge, PendingHtlc, State};
 pub use self::types::*;
 
 #[cfg(feature = "fil-actor")]
@@ -40,6 +42,14 @@ pub enum Method {
     UpdateChannelState = 2,
     Settle = 3,
     Collect = 4,
+    // Method numbers derived from FRC-0042 standards
+    UpdateChannelStateBatchExported = frc42_dispatch::method_hash!("UpdateChannelStateBatch"),
+    LockHtlcExported = frc42_dispatch::method_hash!("LockHtlc"),
+    RedeemHtlcExported = frc42_dispatch::method_hash!("RedeemHtlc"),
+    RefundHtlcExported = frc42_dispatch::method_hash!("RefundHtlc"),
+    GetPartiesExported = frc42_dispatch::method_hash!("GetParties"),
+    GetSettlementStateExported = frc42_dispatch::method_hash!("GetSettlementState"),
+    GetLaneStateExported = frc42_dispatch::method_hash!("GetLaneState"),
 }
 
 pub const ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED: ExitCode = ExitCode::new(32);
@@ -73,193 +83,244 @@ impl Actor {
         rt: &impl Runtime,
         params: UpdateChannelStateParams,
     ) -> Result<(), ActorError> {
-        let st: State = rt.state()?;
+        process_voucher(rt, params.sv, VoucherMode::Redeem(params.secret))
+    }
 
-        rt.validate_immediate_caller_is([st.from, st.to].iter())?;
-        let signer = if rt.message().caller() == st.from { st.to } else { st.from };
-        let sv = params.sv;
-
-        // Pull signature from signed voucher
-        let sig = &sv
-            .signature
-            .as_ref()
-            .ok_or_else(|| actor_error!(illegal_argument, "voucher has no signature"))?
-            .bytes;
-
-        if st.settling_at != 0 && rt.curr_epoch() >= st.settling_at {
-            return Err(ActorError::unchecked(
-                ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED,
-                "no vouchers can be processed after settling at epoch".to_string(),
+    /// Redeems a batch of vouchers in a single message, applying them in order with the
+    /// same rules as `UpdateChannelState`. If `all_or_nothing` is set, the whole batch
+    /// fails if any voucher fails. Otherwise, the valid vouchers are redeemed and the
+    /// result for each voucher is returned.
+    ///
+    /// Since a voucher sets its lane's redeemed amount (rather than adding to it), a voucher
+    /// without merges is superseded by a voucher with a higher nonce on the same lane in the
+    /// batch. The vouchers on a lane are tried from the highest nonce down until one can be
+    /// applied, and only the vouchers tried are authenticated, so a superseded voucher is
+    /// authenticated only if every voucher with a higher nonce on its lane failed. A voucher
+    /// that is outdated by the channel's lanes fails without being authenticated. A superseded
+    /// voucher that isn't tried succeeds, as the voucher applied on its lane covers it.
+    /// The lanes are saved once, after all vouchers have been authenticated.
+    pub fn update_channel_state_batch(
+        rt: &impl Runtime,
+        params: UpdateChannelStateBatchParams,
+    ) -> Result<BatchReturn, ActorError> {
+        if params.updates.len() > MAX_VOUCHER_BATCH_SIZE {
+            return Err(actor_error!(
+                illegal_argument,
+                "batch of {} too large, max {}",
+                params.updates.len(),
+                MAX_VOUCHER_BATCH_SIZE
             ));
         }
 
-        if params.secret.len() > MAX_SECRET_SIZE {
-            return Err(actor_error!(illegal_argument, "secret must be at most 256 bytes long"));
-        }
-
-        // Generate unsigned bytes
-        let sv_bz = sv.signing_bytes().map_err(|e| {
-            ActorError::serialization(format!("failed to serialized SignedVoucher: {}", e))
-        })?;
-
-        // Validate signature
-
-        if !extract_send_result(rt.send(
-            &signer,
-            ext::account::AUTHENTICATE_MESSAGE_METHOD,
-            IpldBlock::serialize_cbor(&ext::account::AuthenticateMessageParams {
-                signature: sig.to_vec(),
-                message: sv_bz,
-            })?,
-            TokenAmount::zero(),
-            None,
-            SendFlags::READ_ONLY,
-        ))
-        .and_then(deserialize_block)
-        .context("proposal authentication failed")?
-        {
-            return Err(actor_error!(illegal_argument, "voucher sig authentication failed"));
-        }
+        let st: State = rt.state()?;
 
-        let pch_addr = rt.message().receiver();
-        let svpch_id = rt.resolve_address(&sv.channel_addr).ok_or_else(|| {
-            actor_error!(
-                illegal_argument,
-                "voucher payment channel address {} does not resolve to an ID address",
-                sv.channel_addr
-            )
-        })?;
-        if pch_addr != Address::new_id(svpch_id) {
-            return Err(actor_error!(illegal_argument;
-                    "voucher payment channel address {} does not match receiver {}",
-                    svpch_id, pch_addr));
-        }
+        rt.validate_immediate_caller_is([st.from, st.to].iter())?;
+        let signer = if rt.message().caller() == st.from { st.to } else { st.from };
 
-        if rt.curr_epoch() < sv.time_lock_min {
-            return Err(actor_error!(illegal_argument; "cannot use this voucher yet"));
+        check_not_settled(rt, &st)?;
+
+        // The highest-nonce voucher on each lane, which supersedes the lane's other vouchers.
+        let mut latest: BTreeMap<u64, (u64, usize)> = BTreeMap::new();
+        for (i, update) in params.updates.iter().enumerate() {
+            let (lane, nonce) = (update.sv.lane, update.sv.nonce);
+            latest
+                .entry(lane)
+                .and_modify(|l| {
+                    if nonce > l.0 {
+                        *l = (nonce, i)
+                    }
+                })
+                .or_insert((nonce, i));
         }
-
-        if sv.time_lock_max != 0 && rt.curr_epoch() > sv.time_lock_max {
-            return Err(actor_error!(illegal_argument; "this voucher has expired"));
+        // The vouchers to try on each lane, keyed by the index of the lane's latest voucher
+        // and ordered from highest to lowest nonce. Vouchers with merges are only tried when
+        // they are the latest on their lane, and are otherwise applied on their own.
+        let mut fallbacks: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        let mut superseded = vec![false; params.updates.len()];
+        for (i, update) in params.updates.iter().enumerate() {
+            let (nonce, j) = latest[&update.sv.lane];
+            if i == j || (update.sv.merges.is_empty() && nonce > update.sv.nonce) {
+                fallbacks.entry(j).or_default().push(i);
+                superseded[i] = i != j;
+            }
         }
-
-        if sv.amount.is_negative() {
-            return Err(actor_error!(illegal_argument;
-                    "voucher amount must be non-negative, was {}", sv.amount));
+        for lane in fallbacks.values_mut() {
+            lane.sort_by_key(|&i| std::cmp::Reverse(params.updates[i].sv.nonce));
         }
 
-        if !sv.secret_pre_image.is_empty() {
-            let hashed_secret: &[u8] = &rt.hash_blake2b(&params.secret);
-            if hashed_secret != sv.secret_pre_image.as_slice() {
-                return Err(actor_error!(illegal_argument; "incorrect secret"));
+        let all_or_nothing = params.all_or_nothing;
+        let mut codes = vec![ExitCode::OK; params.updates.len()];
+        let mut updates: Vec<_> = params.updates.into_iter().map(Some).collect();
+        // The vouchers to apply, in order. They are applied to a copy of the state as they are
+        // chosen, so that a voucher which can't be applied falls back to the next on its lane.
+        let mut chosen = Vec::new();
+        let mut sim = st.clone();
+        let mut sim_lanes = Lanes::load(&sim.lane_states, rt.store())?;
+        let mut sim_htlcs = sim.load_htlcs(rt.store())?;
+        let balance = rt.current_balance();
+        for i in (0..updates.len()).filter(|&i| !superseded[i]) {
+            let lane = fallbacks.get(&i).map_or(std::slice::from_ref(&i), Vec::as_slice);
+            // Try the lane's vouchers until one is applied. The vouchers it supersedes are not
+            // authenticated, and keep their own result only if they were tried.
+            for &j in lane {
+                let update = updates[j].take().unwrap();
+                let mode = VoucherMode::Redeem(update.secret);
+                let res = match sim_lanes.get(update.sv.lane) {
+                    Ok(Some(state)) if state.nonce >= update.sv.nonce => Err(actor_error!(
+                        illegal_argument,
+                        "voucher has an outdated nonce, existing: {}, voucher: {}, cannot redeem",
+                        state.nonce,
+                        update.sv.nonce
+                    )),
+                    Ok(_) => validate_voucher(rt, &signer, &update.sv, &mode).and_then(|_| {
+                        apply_voucher(
+                            &mut sim,
+                            &mut sim_lanes,
+                            &mut sim_htlcs,
+                            update.sv.clone(),
+                            &mode,
+                            &balance,
+                        )
+                    }),
+                    Err(e) => Err(e),
+                };
+                match res {
+                    Ok(()) => {
+                        chosen.push((j, update.sv, mode));
+                        break;
+                    }
+                    Err(e) if all_or_nothing || e.exit_code() == ExitCode::USR_ILLEGAL_STATE => {
+                        return Err(e.wrap(format!("failed to redeem voucher {}", j)));
+                    }
+                    Err(e) => codes[j] = e.exit_code(),
+                }
             }
         }
 
-        if let Some(extra) = &sv.extra {
-            extract_send_result(rt.send_simple(
-                &extra.actor,
-                extra.method,
-                Some(IpldBlock { codec: CBOR, data: extra.data.to_vec() }),
-                TokenAmount::zero(),
-            ))
-            .map_err(|e| e.wrap("spend voucher verification failed"))?;
-        }
-
+        // Apply the chosen vouchers to the state as it is after authentication, which may have
+        // been changed by a re-entrant call.
         rt.transaction(|st: &mut State, rt| {
-            let mut l_states = Array::load(&st.lane_states, rt.store()).map_err(|e| {
-                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load lane states")
-            })?;
-
-            // Find the voucher lane, create and insert it in sorted order if necessary.
-            let lane_id = sv.lane;
-            let lane_state = find_lane(&l_states, lane_id)?;
-
-            let mut lane_state = if let Some(state) = lane_state {
-                if state.nonce >= sv.nonce {
-                    return Err(actor_error!(illegal_argument;
-                        "voucher has an outdated nonce, existing: {}, voucher: {}, cannot redeem",
-                        state.nonce, sv.nonce));
-                }
-                state.clone()
-            } else {
-                LaneState::default()
-            };
-
-            // The next section actually calculates the payment amounts to update
-            // the payment channel state
-            // 1. (optional) sum already redeemed value of all merging lanes
-            let mut redeemed_from_others = TokenAmount::zero();
-            for merge in sv.merges {
-                if merge.lane == sv.lane {
-                    return Err(actor_error!(illegal_argument;
-                        "voucher cannot merge lanes into it's own lane"));
-                }
-                let mut other_ls = find_lane(&l_states, merge.lane)?
-                    .ok_or_else(|| {
-                        actor_error!(illegal_argument;
-                        "voucher specifies invalid merge lane {}", merge.lane)
-                    })?
-                    .clone();
-
-                if other_ls.nonce >= merge.nonce {
-                    return Err(actor_error!(illegal_argument;
-                            "merged lane in voucher has outdated nonce, cannot redeem"));
+            let mut lanes = Lanes::load(&st.lane_states, rt.store())?;
+            let mut htlcs = st.load_htlcs(rt.store())?;
+            let balance = rt.current_balance();
+            for (j, sv, mode) in chosen {
+                match apply_voucher(st, &mut lanes, &mut htlcs, sv, &mode, &balance) {
+                    Ok(()) => {}
+                    Err(e) if all_or_nothing || e.exit_code() == ExitCode::USR_ILLEGAL_STATE => {
+                        return Err(e.wrap(format!("failed to redeem voucher {}", j)));
+                    }
+                    Err(e) => codes[j] = e.exit_code(),
                 }
-
-                redeemed_from_others += &other_ls.redeemed;
-                other_ls.nonce = merge.nonce;
-                l_states.set(merge.lane, other_ls).map_err(|e| {
-                    e.downcast_default(
-                        ExitCode::USR_ILLEGAL_STATE,
-                        format!("failed to store lane {}", merge.lane),
-                    )
-                })?;
             }
+            st.lane_states = lanes.flush()?;
+            Ok(())
+        })?;
 
-            // 2. To prevent double counting, remove already redeemed amounts (from
-            // voucher or other lanes) from the voucher amount
-            lane_state.nonce = sv.nonce;
-            let balance_delta = &sv.amount - (redeemed_from_others + &lane_state.redeemed);
+        Ok(BatchReturn::of(&codes))
+    }
 
-            // 3. set new redeemed value for merged-into lane
-            lane_state.redeemed = sv.amount;
+    /// Locks the amount of a hash-timelocked voucher on its lane, without revealing the
+    /// secret. The amount is paid out if the secret is revealed with `RedeemHtlc` by the
+    /// voucher's expiry, and may be refunded to the lane with `RefundHtlc` afterwards.
+    /// The channel can't be collected until the voucher has expired.
+    ///
+    /// Locking an HTLC voucher secures the payment on-chain before the secret is revealed,
+    /// so a party forwarding a payment along a route of channels can safely reveal the
+    /// secret on the next channel.
+    pub fn lock_htlc(rt: &impl Runtime, params: LockHtlcParams) -> Result<(), ActorError> {
+        process_voucher(rt, params.sv, VoucherMode::LockHtlc)
+    }
 
-            // 4. check operation validity
-            let new_send_balance = balance_delta + &st.to_send;
+    /// Pays out the amount locked on a lane by an HTLC voucher, given the secret.
+    pub fn redeem_htlc(rt: &impl Runtime, params: RedeemHtlcParams) -> Result<(), ActorError> {
+        let st: State = rt.state()?;
+        rt.validate_immediate_caller_is([st.from, st.to].iter())?;
+        check_not_settled(rt, &st)?;
 
-            if new_send_balance < TokenAmount::zero() {
-                return Err(actor_error!(illegal_argument;
-                    "voucher would leave channel balance negative"));
-            }
+        if params.secret.len() > MAX_SECRET_SIZE {
+            return Err(actor_error!(illegal_argument, "secret must be at most 256 bytes long"));
+        }
+        let hashed_secret = rt.hash_blake2b(&params.secret);
 
-            if new_send_balance > rt.current_balance() {
-                return Err(actor_error!(illegal_argument;
-                    "not enough funds in channel to cover voucher"));
+        rt.transaction(|st: &mut State, rt| {
+            let mut htlcs = st.load_htlcs(rt.store())?;
+            let htlc = find_htlc(&htlcs, params.lane)?
+                .ok_or_else(|| actor_error!(not_found, "no pending HTLC on lane {}", params.lane))?
+                .clone();
+
+            if rt.curr_epoch() > htlc.expiry {
+                return Err(actor_error!(
+                    illegal_argument,
+                    "HTLC on lane {} expired at epoch {}",
+                    params.lane,
+                    htlc.expiry
+                ));
+            }
+            if hashed_secret.as_slice() != htlc.hash.as_slice() {
+                return Err(actor_error!(illegal_argument; "incorrect secret"));
             }
 
-            // 5. add new redemption ToSend
-            st.to_send = new_send_balance;
+            htlcs.delete(params.lane).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to delete HTLC on lane {}", params.lane),
+                )
+            })?;
+            st.save_htlcs(&mut htlcs)?;
+            st.htlc_locked -= &htlc.amount;
+            st.to_send += htlc.amount;
+            Ok(())
+        })
+    }
 
-            // update channel settlingAt and MinSettleHeight if delayed by voucher
-            if sv.min_settle_height != 0 {
-                if st.settling_at != 0 && st.settling_at < sv.min_settle_height {
-                    st.settling_at = sv.min_settle_height;
-                }
-                if st.min_settle_height < sv.min_settle_height {
-                    st.min_settle_height = sv.min_settle_height;
-                }
+    /// Releases the amount locked on a lane by an expired HTLC voucher, reducing the
+    /// lane's redeemed amount accordingly. The lane's nonce is not reset, so the voucher
+    /// can't be used again.
+    pub fn refund_htlc(rt: &impl Runtime, params: RefundHtlcParams) -> Result<(), ActorError> {
+        rt.transaction(|st: &mut State, rt| {
+            rt.validate_immediate_caller_is([st.from, st.to].iter())?;
+
+            let mut htlcs = st.load_htlcs(rt.store())?;
+            let htlc = find_htlc(&htlcs, params.lane)?
+                .ok_or_else(|| actor_error!(not_found, "no pending HTLC on lane {}", params.lane))?
+                .clone();
+
+            if rt.curr_epoch() <= htlc.expiry {
+                return Err(actor_error!(
+                    forbidden,
+                    "HTLC on lane {} can be redeemed until epoch {}",
+                    params.lane,
+                    htlc.expiry
+                ));
             }
 
-            l_states.set(lane_id, lane_state).map_err(|e| {
+            let mut l_states = Array::load(&st.lane_states, rt.store()).map_err(|e| {
+                e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load lane states")
+            })?;
+            let mut lane_state = find_lane(&l_states, params.lane)?
+                .ok_or_else(|| {
+                    actor_error!(illegal_state, "no lane state for HTLC on lane {}", params.lane)
+                })?
+                .clone();
+            lane_state.redeemed -= &htlc.amount;
+            l_states.set(params.lane, lane_state).map_err(|e| {
                 e.downcast_default(
                     ExitCode::USR_ILLEGAL_STATE,
-                    format!("failed to store lane {}", lane_id),
+                    format!("failed to store lane {}", params.lane),
+                )
+            })?;
+            htlcs.delete(params.lane).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to delete HTLC on lane {}", params.lane),
                 )
             })?;
 
             st.lane_states = l_states.flush().map_err(|e| {
                 e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save lanes")
             })?;
+            st.save_htlcs(&mut htlcs)?;
+            st.htlc_locked -= htlc.amount;
             Ok(())
         })
     }
@@ -301,6 +362,358 @@ impl Actor {
 
         Ok(())
     }
+
+    /// Returns the channel's payer and payee.
+    pub fn get_parties(rt: &impl Runtime) -> Result<GetPartiesReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(GetPartiesReturn { from: st.from, to: st.to })
+    }
+
+    /// Returns the amount redeemed by the channel's lanes, and when it settles.
+    pub fn get_settlement_state(rt: &impl Runtime) -> Result<GetSettlementStateReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        Ok(GetSettlementStateReturn {
+            to_send: st.to_send,
+            htlc_locked: st.htlc_locked,
+            settling_at: st.settling_at,
+            min_settle_height: st.min_settle_height,
+        })
+    }
+
+    /// Returns the state of a lane, and the HTLC pending on it, if any.
+    /// A lane which hasn't redeemed any voucher has no state.
+    pub fn get_lane_state(
+        rt: &impl Runtime,
+        params: GetLaneStateParams,
+    ) -> Result<GetLaneStateReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+        let lanes = Array::<LaneState, _>::load(&st.lane_states, rt.store()).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load lane states")
+        })?;
+        let htlcs = st.load_htlcs(rt.store())?;
+        Ok(GetLaneStateReturn {
+            lane_state: find_lane(&lanes, params.lane)?.cloned(),
+            htlc: find_htlc(&htlcs, params.lane)?.cloned(),
+        })
+    }
+}
+
+/// How a voucher is to be processed.
+enum VoucherMode {
+    /// Redeem the voucher, revealing the secret if it has a secret hash.
+    Redeem(Vec<u8>),
+    /// Lock the voucher as an HTLC, to be redeemed by revealing its secret later.
+    LockHtlc,
+}
+
+/// Validates a voucher and applies it to the channel's lanes, either paying out
+/// the increase in the lane's redeemed amount, or locking it in an HTLC.
+fn process_voucher(
+    rt: &impl Runtime,
+    sv: SignedVoucher,
+    mode: VoucherMode,
+) -> Result<(), ActorError> {
+    let st: State = rt.state()?;
+
+    rt.validate_immediate_caller_is([st.from, st.to].iter())?;
+    let signer = if rt.message().caller() == st.from { st.to } else { st.from };
+
+    check_not_settled(rt, &st)?;
+    validate_voucher(rt, &signer, &sv, &mode)?;
+
+    rt.transaction(|st: &mut State, rt| {
+        let mut lanes = Lanes::load(&st.lane_states, rt.store())?;
+        let mut htlcs = st.load_htlcs(rt.store())?;
+
+        apply_voucher(st, &mut lanes, &mut htlcs, sv, &mode, &rt.current_balance())?;
+
+        st.lane_states = lanes.flush()?;
+        Ok(())
+    })
+}
+
+fn check_not_settled(rt: &impl Runtime, st: &State) -> Result<(), ActorError> {
+    if st.settling_at != 0 && rt.curr_epoch() >= st.settling_at {
+        return Err(ActorError::unchecked(
+            ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED,
+            "no vouchers can be processed after settling at epoch".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks a voucher's signature by `signer`, and its validity independent of the
+/// channel's lanes.
+fn validate_voucher(
+    rt: &impl Runtime,
+    signer: &Address,
+    sv: &SignedVoucher,
+    mode: &VoucherMode,
+) -> Result<(), ActorError> {
+    // Pull signature from signed voucher
+    let sig = &sv
+        .signature
+        .as_ref()
+        .ok_or_else(|| actor_error!(illegal_argument, "voucher has no signature"))?
+        .bytes;
+
+    if let VoucherMode::Redeem(secret) = mode {
+        if secret.len() > MAX_SECRET_SIZE {
+            return Err(actor_error!(illegal_argument, "secret must be at most 256 bytes long"));
+        }
+    }
+
+    // Generate unsigned bytes
+    let sv_bz = sv.signing_bytes().map_err(|e| {
+        ActorError::serialization(format!("failed to serialized SignedVoucher: {}", e))
+    })?;
+
+    // Validate signature
+
+    if !extract_send_result(rt.send(
+        signer,
+        ext::account::AUTHENTICATE_MESSAGE_METHOD,
+        IpldBlock::serialize_cbor(&ext::account::AuthenticateMessageParams {
+            signature: sig.to_vec(),
+            message: sv_bz,
+        })?,
+        TokenAmount::zero(),
+        None,
+        SendFlags::READ_ONLY,
+    ))
+    .and_then(deserialize_block)
+    .context("proposal authentication failed")?
+    {
+        return Err(actor_error!(illegal_argument, "voucher sig authentication failed"));
+    }
+
+    let pch_addr = rt.message().receiver();
+    let svpch_id = rt.resolve_address(&sv.channel_addr).ok_or_else(|| {
+        actor_error!(
+            illegal_argument,
+            "voucher payment channel address {} does not resolve to an ID address",
+            sv.channel_addr
+        )
+    })?;
+    if pch_addr != Address::new_id(svpch_id) {
+        return Err(actor_error!(illegal_argument;
+                "voucher payment channel address {} does not match receiver {}",
+                svpch_id, pch_addr));
+    }
+
+    if rt.curr_epoch() < sv.time_lock_min {
+        return Err(actor_error!(illegal_argument; "cannot use this voucher yet"));
+    }
+
+    if sv.time_lock_max != 0 && rt.curr_epoch() > sv.time_lock_max {
+        return Err(actor_error!(illegal_argument; "this voucher has expired"));
+    }
+
+    if sv.amount.is_negative() {
+        return Err(actor_error!(illegal_argument;
+                "voucher amount must be non-negative, was {}", sv.amount));
+    }
+
+    match mode {
+        VoucherMode::Redeem(secret) => {
+            if !sv.secret_pre_image.is_empty() {
+                let hashed_secret: &[u8] = &rt.hash_blake2b(secret);
+                if hashed_secret != sv.secret_pre_image.as_slice() {
+                    return Err(actor_error!(illegal_argument; "incorrect secret"));
+                }
+            }
+        }
+        VoucherMode::LockHtlc => {
+            if sv.secret_pre_image.is_empty() || sv.time_lock_max == 0 {
+                return Err(actor_error!(illegal_argument;
+                    "HTLC voucher must have a secret hash and an expiry"));
+            }
+        }
+    }
+
+    if let Some(extra) = &sv.extra {
+        extract_send_result(rt.send_simple(
+            &extra.actor,
+            extra.method,
+            Some(IpldBlock { codec: CBOR, data: extra.data.to_vec() }),
+            TokenAmount::zero(),
+        ))
+        .map_err(|e| e.wrap("spend voucher verification failed"))?;
+    }
+    Ok(())
+}
+
+/// Applies a validated voucher to the channel state, buffering lane updates in `lanes`.
+/// Nothing is changed if the voucher can't be applied.
+fn apply_voucher<BS: Blockstore>(
+    st: &mut State,
+    lanes: &mut Lanes<BS>,
+    htlcs: &mut Array<PendingHtlc, BS>,
+    sv: SignedVoucher,
+    mode: &VoucherMode,
+    balance: &TokenAmount,
+) -> Result<(), ActorError> {
+    // Find the voucher lane, create it if necessary.
+    let lane_id = sv.lane;
+    if find_htlc(htlcs, lane_id)?.is_some() {
+        return Err(actor_error!(forbidden;
+            "lane {} has a pending HTLC, which must be redeemed or refunded first", lane_id));
+    }
+    let mut lane_state = if let Some(state) = lanes.get(lane_id)? {
+        if state.nonce >= sv.nonce {
+            return Err(actor_error!(illegal_argument;
+                "voucher has an outdated nonce, existing: {}, voucher: {}, cannot redeem",
+                state.nonce, sv.nonce));
+        }
+        state
+    } else {
+        LaneState::default()
+    };
+
+    // The next section actually calculates the payment amounts to update
+    // the payment channel state
+    // 1. (optional) sum already redeemed value of all merging lanes
+    let mut redeemed_from_others = TokenAmount::zero();
+    let mut merged: BTreeMap<u64, LaneState> = BTreeMap::new();
+    for merge in sv.merges {
+        if merge.lane == sv.lane {
+            return Err(actor_error!(illegal_argument;
+                "voucher cannot merge lanes into it's own lane"));
+        }
+        if find_htlc(htlcs, merge.lane)?.is_some() {
+            return Err(actor_error!(forbidden;
+                "merge lane {} has a pending HTLC", merge.lane));
+        }
+        let mut other_ls = match merged.get(&merge.lane) {
+            Some(ls) => ls.clone(),
+            None => lanes.get(merge.lane)?.ok_or_else(|| {
+                actor_error!(illegal_argument;
+                    "voucher specifies invalid merge lane {}", merge.lane)
+            })?,
+        };
+
+        if other_ls.nonce >= merge.nonce {
+            return Err(actor_error!(illegal_argument;
+                    "merged lane in voucher has outdated nonce, cannot redeem"));
+        }
+
+        redeemed_from_others += &other_ls.redeemed;
+        other_ls.nonce = merge.nonce;
+        merged.insert(merge.lane, other_ls);
+    }
+
+    // 2. To prevent double counting, remove already redeemed amounts (from
+    // voucher or other lanes) from the voucher amount
+    lane_state.nonce = sv.nonce;
+    let balance_delta = &sv.amount - (redeemed_from_others + &lane_state.redeemed);
+
+    // 3. set new redeemed value for merged-into lane
+    lane_state.redeemed = sv.amount;
+
+    // 4. check operation validity, and 5. add new redemption ToSend, or lock it
+    let mut min_settle_height = sv.min_settle_height;
+    match mode {
+        VoucherMode::Redeem(_) => {
+            let new_send_balance = balance_delta + &st.to_send;
+
+            if new_send_balance < TokenAmount::zero() {
+                return Err(actor_error!(illegal_argument;
+                    "voucher would leave channel balance negative"));
+            }
+
+            if &new_send_balance + &st.htlc_locked > *balance {
+                return Err(actor_error!(illegal_argument;
+                    "not enough funds in channel to cover voucher"));
+            }
+
+            st.to_send = new_send_balance;
+        }
+        VoucherMode::LockHtlc => {
+            if !balance_delta.is_positive() {
+                return Err(actor_error!(illegal_argument;
+                    "HTLC voucher must increase the amount redeemed by the lane"));
+            }
+
+            if &st.to_send + &st.htlc_locked + &balance_delta > *balance {
+                return Err(actor_error!(illegal_argument;
+                    "not enough funds in channel to cover voucher"));
+            }
+
+            htlcs
+                .set(
+                    lane_id,
+                    PendingHtlc {
+                        hash: sv.secret_pre_image,
+                        amount: balance_delta.clone(),
+                        expiry: sv.time_lock_max,
+                    },
+                )
+                .map_err(|e| {
+                    e.downcast_default(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to store HTLC on lane {}", lane_id),
+                    )
+                })?;
+            st.save_htlcs(htlcs)?;
+            st.htlc_locked += balance_delta;
+
+            // The channel can't settle until the HTLC can no longer be redeemed.
+            min_settle_height = std::cmp::max(min_settle_height, sv.time_lock_max + 1);
+        }
+    }
+
+    // update channel settlingAt and MinSettleHeight if delayed by voucher
+    if min_settle_height != 0 {
+        if st.settling_at != 0 && st.settling_at < min_settle_height {
+            st.settling_at = min_settle_height;
+        }
+        if st.min_settle_height < min_settle_height {
+            st.min_settle_height = min_settle_height;
+        }
+    }
+
+    merged.insert(lane_id, lane_state);
+    lanes.updated.extend(merged);
+    Ok(())
+}
+
+/// A channel's lane states, with updates buffered until flushed.
+struct Lanes<'bs, BS: Blockstore> {
+    states: Array<'bs, LaneState, BS>,
+    updated: BTreeMap<u64, LaneState>,
+}
+
+impl<'bs, BS: Blockstore> Lanes<'bs, BS> {
+    fn load(root: &Cid, store: &'bs BS) -> Result<Self, ActorError> {
+        let states = Array::load(root, store).map_err(|e| {
+            e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to load lane states")
+        })?;
+        Ok(Self { states, updated: BTreeMap::new() })
+    }
+
+    fn get(&self, lane: u64) -> Result<Option<LaneState>, ActorError> {
+        if let Some(state) = self.updated.get(&lane) {
+            return Ok(Some(state.clone()));
+        }
+        Ok(find_lane(&self.states, lane)?.cloned())
+    }
+
+    fn flush(mut self) -> Result<Cid, ActorError> {
+        for (lane, state) in self.updated {
+            self.states.set(lane, state).map_err(|e| {
+                e.downcast_default(
+                    ExitCode::USR_ILLEGAL_STATE,
+                    format!("failed to store lane {}", lane),
+                )
+            })?;
+        }
+        self.states
+            .flush()
+            .map_err(|e| e.downcast_default(ExitCode::USR_ILLEGAL_STATE, "failed to save lanes"))
+    }
 }
 
 #[inline]
@@ -320,6 +733,22 @@ where
     })
 }
 
+#[inline]
+fn find_htlc<'a, BS>(
+    htlcs: &'a Array<PendingHtlc, BS>,
+    lane: u64,
+) -> Result<Option<&'a PendingHtlc>, ActorError>
+where
+    BS: Blockstore,
+{
+    htlcs.get(lane).map_err(|e| {
+        e.downcast_default(
+            ExitCode::USR_ILLEGAL_STATE,
+            format!("failed to load HTLC on lane {}", lane),
+        )
+    })
+}
+
 impl ActorCode for Actor {
     type Methods = Method;
 
@@ -332,5 +761,12 @@ impl ActorCode for Actor {
         UpdateChannelState => update_channel_state,
         Settle => settle,
         Collect => collect,
+        UpdateChannelStateBatchExported => update_channel_state_batch,
+        LockHtlcExported => lock_htlc,
+        RedeemHtlcExported => redeem_htlc,
+        RefundHtlcExported => refund_htlc,
+        GetPartiesExported => get_parties,
+        GetSettlementStateExported => get_settlement_state,
+        GetLaneStateExported => get_lane_state,
     }
 }
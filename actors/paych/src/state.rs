@@ -2,16 +2,21 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use cid::Cid;
+use fil_actors_runtime::{impl_trailing_optional_tuple, ActorError, Array, AsActorError};
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::strict_bytes;
 use fvm_ipld_encoding::tuple::*;
 use fvm_shared::address::Address;
-
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+
+use crate::LANE_STATES_AMT_BITWIDTH;
 
 /// A given payment channel actor is established by `from`
 /// to enable off-chain microtransactions to `to` address
 /// to be reconciled and tallied on chain.
-#[derive(Debug, Serialize_tuple, Deserialize_tuple, Clone)]
+#[derive(Debug, Clone)]
 pub struct State {
     /// Channel owner, who has funded the actor.
     pub from: Address,
@@ -25,6 +30,16 @@ pub struct State {
     pub min_settle_height: ChainEpoch,
     /// Collections of lane states for the channel, maintained in ID order.
     pub lane_states: Cid, // AMT<LaneState>
+    /// Hash-timelocked amounts pending on lanes, keyed by lane ID.
+    /// None until the channel first locks an HTLC. Omitted from the encoding while None
+    /// (with a zero `htlc_locked`), so state written before HTLCs were added still decodes.
+    // * Added in v15
+    pub htlcs: Option<Cid>, // AMT<PendingHtlc>
+    /// Total amount locked in pending HTLCs, which is neither paid out to `to` nor
+    /// refundable to `from` until the HTLCs are redeemed or expire.
+    /// Omitted from the encoding while zero, and decoded as zero when absent.
+    // * Added in v15
+    pub htlc_locked: TokenAmount,
 }
 
 impl State {
@@ -36,10 +51,46 @@ impl State {
             settling_at: 0,
             min_settle_height: 0,
             lane_states: empty_arr_cid,
+            htlcs: None,
+            htlc_locked: Default::default(),
+        }
+    }
+
+    /// Loads the pending HTLCs, which are empty if the channel has never locked one.
+    pub fn load_htlcs<'bs, BS: Blockstore>(
+        &self,
+        store: &'bs BS,
+    ) -> Result<Array<'bs, PendingHtlc, BS>, ActorError> {
+        match &self.htlcs {
+            Some(root) => Array::load(root, store)
+                .context_code(ExitCode::USR_ILLEGAL_STATE, "failed to load HTLCs"),
+            None => Ok(Array::new_with_bit_width(store, LANE_STATES_AMT_BITWIDTH)),
         }
     }
+
+    /// Flushes the pending HTLCs and records their root.
+    pub fn save_htlcs<BS: Blockstore>(
+        &mut self,
+        htlcs: &mut Array<PendingHtlc, BS>,
+    ) -> Result<(), ActorError> {
+        let root =
+            htlcs.flush().context_code(ExitCode::USR_ILLEGAL_STATE, "failed to save HTLCs")?;
+        self.htlcs = Some(root);
+        Ok(())
+    }
 }
 
+impl_trailing_optional_tuple!(State {
+    from,
+    to,
+    to_send,
+    settling_at,
+    min_settle_height,
+    lane_states;
+    htlcs,
+    htlc_locked,
+});
+
 /// The Lane state tracks the latest (highest) voucher nonce used to merge the lane
 /// as well as the amount it has already redeemed.
 #[derive(Default, Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
@@ -48,6 +99,20 @@ pub struct LaneState {
     pub nonce: u64,
 }
 
+/// An amount locked on a lane by a hash-timelocked voucher. The amount is paid to `to`
+/// when the secret hashing to `hash` is revealed before `expiry`, and is otherwise
+/// refunded to the lane.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize_tuple, Deserialize_tuple)]
+pub struct PendingHtlc {
+    /// BLAKE2B-256 hash of the secret.
+    #[serde(with = "strict_bytes")]
+    pub hash: Vec<u8>,
+    /// The amount locked, i.e. the increase in the lane's redeemed amount by the voucher.
+    pub amount: TokenAmount,
+    /// The last epoch at which the secret may be revealed.
+    pub expiry: ChainEpoch,
+}
+
 /// Specifies which `lane`s to be merged with what `nonce` on `channel_update`
 #[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
 pub struct Merge {
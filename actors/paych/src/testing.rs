@@ -29,7 +29,7 @@ pub fn check_state_invariants<BS: Blockstore>(
         format!("to address is not ID address {}", state.to),
     );
     acc.require(
-        state.settling_at >= state.min_settle_height,
+        state.settling_at == 0 || state.settling_at >= state.min_settle_height,
         format!(
             "channel is setting at epoch {} before min settle height {}",
             state.settling_at, state.min_settle_height
@@ -39,9 +39,10 @@ pub fn check_state_invariants<BS: Blockstore>(
     match Amt::<LaneState, _>::load(&state.lane_states, store) {
         Ok(lanes) => {
             let ret = lanes.for_each(|i, lane| {
+                // A lane whose HTLC was refunded may be left with nothing redeemed.
                 acc.require(
-                    lane.redeemed.is_positive(),
-                    format!("lane {i} redeemed is not greater than zero {}", lane.redeemed),
+                    !lane.redeemed.is_negative(),
+                    format!("lane {i} redeemed is negative {}", lane.redeemed),
                 );
                 redeemed += &lane.redeemed;
                 Ok(())
@@ -51,9 +52,39 @@ pub fn check_state_invariants<BS: Blockstore>(
         Err(e) => acc.add(format!("error loading lanes: {e}")),
     }
 
+    let mut htlc_locked = TokenAmount::zero();
+    match state.load_htlcs(store) {
+        Ok(htlcs) => {
+            let ret = htlcs.for_each(|i, htlc| {
+                acc.require(
+                    htlc.amount.is_positive(),
+                    format!("HTLC on lane {i} amount is not greater than zero {}", htlc.amount),
+                );
+                acc.require(
+                    state.min_settle_height > htlc.expiry,
+                    format!(
+                        "HTLC on lane {i} expires at epoch {} after min settle height {}",
+                        htlc.expiry, state.min_settle_height
+                    ),
+                );
+                htlc_locked += &htlc.amount;
+                Ok(())
+            });
+            acc.require_no_error(ret, "error iterating HTLCs");
+        }
+        Err(e) => acc.add(format!("error loading HTLCs: {e}")),
+    }
+    acc.require(
+        htlc_locked == state.htlc_locked,
+        format!("HTLC locked {} does not match sum of HTLCs {}", state.htlc_locked, htlc_locked),
+    );
+
     acc.require(
-        balance >= &state.to_send,
-        format!("channel has insufficient funds to send ({} < {})", balance, state.to_send),
+        balance >= &(&state.to_send + &state.htlc_locked),
+        format!(
+            "channel has insufficient funds to send ({} < {} + {} locked)",
+            balance, state.to_send, state.htlc_locked
+        ),
     );
 
     (StateSummary { redeemed }, acc)
@@ -10,7 +10,7 @@ use fvm_shared::crypto::signature::Signature;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::MethodNum;
 
-use super::Merge;
+use super::{LaneState, Merge, PendingHtlc};
 
 /// Maximum number of lanes in a channel
 pub const MAX_LANE: u64 = std::i64::MAX as u64;
@@ -22,6 +22,9 @@ pub const MAX_SECRET_SIZE: usize = 256;
 
 pub const LANE_STATES_AMT_BITWIDTH: u32 = 3;
 
+/// Maximum number of vouchers that can be redeemed in a single `UpdateChannelStateBatch`.
+pub const MAX_VOUCHER_BATCH_SIZE: usize = 1024;
+
 /// Constructor parameters for payment channel actor
 #[derive(Serialize_tuple, Deserialize_tuple)]
 pub struct ConstructorParams {
@@ -126,3 +129,62 @@ impl From<SignedVoucher> for UpdateChannelStateParams {
         UpdateChannelStateParams { secret: vec![], sv }
     }
 }
+
+/// Parameters to redeem a batch of vouchers.
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct UpdateChannelStateBatchParams {
+    pub updates: Vec<UpdateChannelStateParams>,
+    /// Whether to fail the whole batch if any voucher can't be redeemed.
+    pub all_or_nothing: bool,
+}
+
+/// Parameters to lock a hash-timelocked voucher. The voucher must have a secret hash
+/// (`secret_pre_image`) and an expiry (`time_lock_max`).
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct LockHtlcParams {
+    pub sv: SignedVoucher,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+pub struct RedeemHtlcParams {
+    pub lane: u64,
+    #[serde(with = "strict_bytes")]
+    pub secret: Vec<u8>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+#[serde(transparent)]
+pub struct RefundHtlcParams {
+    pub lane: u64,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct GetPartiesReturn {
+    pub from: Address,
+    pub to: Address,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct GetSettlementStateReturn {
+    /// Amount redeemed by vouchers, to be paid to `to` when the channel is collected.
+    pub to_send: TokenAmount,
+    /// Amount locked by pending HTLC vouchers.
+    pub htlc_locked: TokenAmount,
+    /// Epoch at which the channel settles, or zero if settlement hasn't begun.
+    pub settling_at: ChainEpoch,
+    /// Epoch before which the channel can't settle.
+    pub min_settle_height: ChainEpoch,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct GetLaneStateParams {
+    pub lane: u64,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+pub struct GetLaneStateReturn {
+    pub lane_state: Option<LaneState>,
+    pub htlc: Option<PendingHtlc>,
+}
@@ -9,14 +9,17 @@ use derive_builder::Builder;
 use fil_actor_paych::ext::account::{AuthenticateMessageParams, AUTHENTICATE_MESSAGE_METHOD};
 use fil_actor_paych::testing::check_state_invariants;
 use fil_actor_paych::{
-    Actor as PaychActor, ConstructorParams, LaneState, Merge, Method, ModVerifyParams,
-    SignedVoucher, State as PState, UpdateChannelStateParams, MAX_LANE, SETTLE_DELAY,
+    Actor as PaychActor, ConstructorParams, GetLaneStateParams, GetLaneStateReturn,
+    GetPartiesReturn, GetSettlementStateReturn, LaneState, LockHtlcParams, Merge, Method,
+    ModVerifyParams, PendingHtlc, RedeemHtlcParams, RefundHtlcParams, SignedVoucher,
+    State as PState, UpdateChannelStateBatchParams, UpdateChannelStateParams, MAX_LANE,
+    MAX_VOUCHER_BATCH_SIZE, SETTLE_DELAY,
 };
 
 use fil_actors_runtime::runtime::builtins::Type;
 use fil_actors_runtime::runtime::Runtime;
 use fil_actors_runtime::test_utils::*;
-use fil_actors_runtime::INIT_ACTOR_ADDR;
+use fil_actors_runtime::{BatchReturn, INIT_ACTOR_ADDR};
 use fvm_ipld_amt::Amt;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
 use fvm_ipld_encoding::RawBytes;
@@ -463,6 +466,8 @@ mod update_channel_state_redeem {
             settling_at: state.settling_at,
             min_settle_height: state.min_settle_height,
             lane_states: construct_lane_state_amt(&rt, vec![exp_ls]),
+            htlcs: state.htlcs,
+            htlc_locked: state.htlc_locked,
         };
         verify_state(&rt, Some(1), exp_state);
     }
@@ -1022,6 +1027,720 @@ mod actor_collect {
     }
 }
 
+mod update_channel_state_batch {
+    use super::*;
+
+    // Creates a channel with lanes 0 and 1 redeemed for 1 and 2, called by the payee.
+    fn construct_runtime() -> (MockRuntime, SignedVoucher, PState) {
+        let (rt, sv) = require_create_channel_with_lanes(2);
+        let state: PState = rt.get_state();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.to);
+        (rt, sv, state)
+    }
+
+    fn voucher(sv: &SignedVoucher, lane: u64, nonce: u64, amount: u64) -> SignedVoucher {
+        SignedVoucher { lane, nonce, amount: TokenAmount::from_atto(amount), ..sv.clone() }
+    }
+
+    fn batch_params(vouchers: &[SignedVoucher], all_or_nothing: bool) -> Option<IpldBlock> {
+        IpldBlock::serialize_cbor(&UpdateChannelStateBatchParams {
+            updates: vouchers.iter().cloned().map(UpdateChannelStateParams::from).collect(),
+            all_or_nothing,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn redeem_across_lanes_with_partial_success() {
+        let (rt, sv, state) = construct_runtime();
+        let vouchers = vec![
+            // Valid, on an existing lane.
+            voucher(&sv, 0, 5, 10),
+            // Outdated, rejected without authenticating the signature.
+            voucher(&sv, 1, 2, 20),
+            // Fails authentication.
+            voucher(&sv, 2, 1, 30),
+            // Superseded by a later voucher on the same lane, which is applied instead, so it's
+            // not authenticated.
+            voucher(&sv, 3, 1, 40),
+            voucher(&sv, 3, 2, 50),
+            // Not enough funds after the others are applied.
+            voucher(&sv, 4, 1, 100_000),
+        ];
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        for (i, v) in vouchers.iter().enumerate() {
+            match i {
+                1 | 3 => {}
+                2 => expect_authenticate_message(
+                    &rt,
+                    state.from,
+                    v.clone(),
+                    ExitCode::USR_ILLEGAL_ARGUMENT,
+                ),
+                _ => expect_authenticate_message(&rt, state.from, v.clone(), ExitCode::OK),
+            }
+        }
+
+        let ret: BatchReturn = call(
+            &rt,
+            Method::UpdateChannelStateBatchExported as u64,
+            batch_params(&vouchers, false),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+        rt.verify();
+
+        assert_eq!(
+            vec![
+                ExitCode::OK,
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                ExitCode::OK,
+                ExitCode::OK,
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+            ],
+            ret.codes()
+        );
+
+        let st: PState = rt.get_state();
+        // Lane 0 goes from 1 to 10, and lane 3 from nothing to 50.
+        assert_eq!(&state.to_send + TokenAmount::from_atto(9 + 50), st.to_send);
+        assert_eq!(
+            LaneState { redeemed: TokenAmount::from_atto(10), nonce: 5 },
+            get_lane_state(&rt, &st.lane_states, 0)
+        );
+        assert_eq!(
+            LaneState { redeemed: TokenAmount::from_atto(2), nonce: 2 },
+            get_lane_state(&rt, &st.lane_states, 1)
+        );
+        assert_eq!(
+            LaneState { redeemed: TokenAmount::from_atto(50), nonce: 2 },
+            get_lane_state(&rt, &st.lane_states, 3)
+        );
+        assert_lane_states_length(&rt, &st.lane_states, 3);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn superseded_vouchers_fall_back_when_the_latest_fails() {
+        let (rt, sv, state) = construct_runtime();
+        let vouchers = vec![
+            // Superseded by the latest voucher on lane 0, which is applied.
+            voucher(&sv, 0, 5, 3),
+            // Applied after the latest voucher on lane 1 fails authentication.
+            voucher(&sv, 1, 5, 4),
+            voucher(&sv, 0, 7, 7),
+            voucher(&sv, 0, 6, 5),
+            voucher(&sv, 1, 6, 6),
+            // The latest voucher on lane 2 can't be covered by the channel, so the earlier
+            // one is applied.
+            voucher(&sv, 2, 2, 100_000),
+            voucher(&sv, 2, 1, 8),
+        ];
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        // Each lane's vouchers are authenticated from the highest nonce down, until one is
+        // applied. The vouchers superseded by the applied voucher on lane 0 are not.
+        for (i, code) in [
+            (2, ExitCode::OK),
+            (4, ExitCode::USR_ILLEGAL_ARGUMENT),
+            (1, ExitCode::OK),
+            (5, ExitCode::OK),
+            (6, ExitCode::OK),
+        ] {
+            expect_authenticate_message(&rt, state.from, vouchers[i].clone(), code);
+        }
+
+        let ret: BatchReturn = call(
+            &rt,
+            Method::UpdateChannelStateBatchExported as u64,
+            batch_params(&vouchers, false),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+        rt.verify();
+        assert_eq!(
+            vec![
+                ExitCode::OK,
+                ExitCode::OK,
+                ExitCode::OK,
+                ExitCode::OK,
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+                ExitCode::OK,
+            ],
+            ret.codes()
+        );
+
+        // Lane 0 goes from 1 to 7, lane 1 from 2 to 4, and lane 2 from nothing to 8.
+        let st: PState = rt.get_state();
+        assert_eq!(&state.to_send + TokenAmount::from_atto(6 + 2 + 8), st.to_send);
+        assert_eq!(
+            LaneState { redeemed: TokenAmount::from_atto(7), nonce: 7 },
+            get_lane_state(&rt, &st.lane_states, 0)
+        );
+        assert_eq!(
+            LaneState { redeemed: TokenAmount::from_atto(4), nonce: 5 },
+            get_lane_state(&rt, &st.lane_states, 1)
+        );
+        assert_eq!(
+            LaneState { redeemed: TokenAmount::from_atto(8), nonce: 1 },
+            get_lane_state(&rt, &st.lane_states, 2)
+        );
+        check_state(&rt);
+    }
+
+    #[test]
+    fn superseded_vouchers_fail_when_no_voucher_on_their_lane_applies() {
+        let (rt, sv, state) = construct_runtime();
+        let vouchers = vec![voucher(&sv, 0, 5, 100_001), voucher(&sv, 0, 6, 100_002)];
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        for v in vouchers.iter().rev() {
+            expect_authenticate_message(&rt, state.from, v.clone(), ExitCode::OK);
+        }
+
+        let ret: BatchReturn = call(
+            &rt,
+            Method::UpdateChannelStateBatchExported as u64,
+            batch_params(&vouchers, false),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+        rt.verify();
+        assert_eq!(vec![ExitCode::USR_ILLEGAL_ARGUMENT; 2], ret.codes());
+
+        let st: PState = rt.get_state();
+        assert_eq!(state.to_send, st.to_send);
+        assert_eq!(state.lane_states, st.lane_states);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn batch_size_is_limited() {
+        let (rt, sv, state) = construct_runtime();
+        let vouchers: Vec<_> =
+            (0..=MAX_VOUCHER_BATCH_SIZE as u64).map(|lane| voucher(&sv, lane, 1, 1)).collect();
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_abort(
+            &rt,
+            Method::UpdateChannelStateBatchExported as u64,
+            batch_params(&vouchers, false),
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+        );
+        rt.reset();
+    }
+
+    #[test]
+    fn failed_merge_leaves_lanes_unchanged() {
+        let (rt, sv, state) = construct_runtime();
+        rt.set_balance(TokenAmount::from_atto(10));
+        // The merge of lane 1 is valid, but the channel can't cover the voucher, so it
+        // fails after the merge has been checked.
+        let mut merging = voucher(&sv, 0, 5, 20);
+        merging.merges = vec![Merge { lane: 1, nonce: 5 }];
+        // A later voucher which merges lane 1 with the same nonce still succeeds.
+        let mut next = voucher(&sv, 0, 6, 10);
+        next.merges = vec![Merge { lane: 1, nonce: 5 }];
+        let vouchers = vec![merging, next];
+
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        for v in &vouchers {
+            expect_authenticate_message(&rt, state.from, v.clone(), ExitCode::OK);
+        }
+        let ret: BatchReturn = call(
+            &rt,
+            Method::UpdateChannelStateBatchExported as u64,
+            batch_params(&vouchers, false),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+        rt.verify();
+        assert_eq!(vec![ExitCode::USR_ILLEGAL_ARGUMENT, ExitCode::OK], ret.codes());
+
+        let st: PState = rt.get_state();
+        assert_eq!(&state.to_send + TokenAmount::from_atto(7), st.to_send);
+        assert_eq!(
+            LaneState { redeemed: TokenAmount::from_atto(2), nonce: 5 },
+            get_lane_state(&rt, &st.lane_states, 1)
+        );
+        check_state(&rt);
+    }
+
+    #[test]
+    fn all_or_nothing_fails_whole_batch() {
+        let (rt, sv, state) = construct_runtime();
+        let vouchers = vec![voucher(&sv, 0, 5, 10), voucher(&sv, 3, 1, 100_000)];
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        for v in &vouchers {
+            expect_authenticate_message(&rt, state.from, v.clone(), ExitCode::OK);
+        }
+        expect_abort(
+            &rt,
+            Method::UpdateChannelStateBatchExported as u64,
+            batch_params(&vouchers, true),
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+        );
+        rt.verify();
+
+        let st: PState = rt.get_state();
+        assert_eq!(state.to_send, st.to_send);
+        assert_eq!(state.lane_states, st.lane_states);
+        check_state(&rt);
+    }
+
+    #[test]
+    fn rejected_after_settling() {
+        const ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED: ExitCode = ExitCode::new(32);
+
+        let (rt, sv, state) = construct_runtime();
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        call(&rt, Method::Settle as u64, None);
+        let st: PState = rt.get_state();
+        rt.epoch.replace(st.settling_at);
+
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_abort(
+            &rt,
+            Method::UpdateChannelStateBatchExported as u64,
+            batch_params(&[voucher(&sv, 0, 5, 10)], false),
+            ERR_CHANNEL_STATE_UPDATE_AFTER_SETTLED,
+        );
+        rt.verify();
+    }
+}
+
+mod htlc_tests {
+    use fil_actors_runtime::runtime::Primitives;
+
+    use super::*;
+
+    const SECRET: &[u8] = b"the route secret";
+    const EXPIRY: ChainEpoch = 100;
+
+    // Creates a channel with lanes 0 and 1 redeemed for 1 and 2, and returns an HTLC voucher
+    // signed by the payer that increases lane 1 by 10.
+    fn construct_runtime() -> (MockRuntime, SignedVoucher) {
+        let (rt, mut sv) = require_create_channel_with_lanes(2);
+        sv.amount = TokenAmount::from_atto(12);
+        sv.time_lock_max = EXPIRY;
+        sv.secret_pre_image = rt.hash_blake2b(SECRET).to_vec();
+        (rt, sv)
+    }
+
+    fn lock(rt: &MockRuntime, sv: SignedVoucher) {
+        let state: PState = rt.get_state();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.to);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_authenticate_message(rt, state.from, sv.clone(), ExitCode::OK);
+        call(
+            rt,
+            Method::LockHtlcExported as u64,
+            IpldBlock::serialize_cbor(&LockHtlcParams { sv }).unwrap(),
+        );
+        rt.verify();
+        check_state(rt);
+    }
+
+    fn get_htlc(rt: &MockRuntime, lane: u64) -> Option<PendingHtlc> {
+        let state: PState = rt.get_state();
+        let htlcs = state.load_htlcs(&rt.store).unwrap();
+        htlcs.get(lane).unwrap().cloned()
+    }
+
+    #[test]
+    fn state_without_htlcs_is_compatible() {
+        use fvm_ipld_encoding::tuple::*;
+
+        #[derive(Serialize_tuple)]
+        struct LegacyState {
+            from: Address,
+            to: Address,
+            to_send: TokenAmount,
+            settling_at: ChainEpoch,
+            min_settle_height: ChainEpoch,
+            lane_states: Cid,
+        }
+
+        let (rt, sv) = construct_runtime();
+        let state: PState = rt.get_state();
+        let legacy = fvm_ipld_encoding::to_vec(&LegacyState {
+            from: state.from,
+            to: state.to,
+            to_send: state.to_send.clone(),
+            settling_at: state.settling_at,
+            min_settle_height: state.min_settle_height,
+            lane_states: state.lane_states,
+        })
+        .unwrap();
+
+        // A channel that has never locked an HTLC keeps the legacy encoding.
+        assert_eq!(None, state.htlcs);
+        assert_eq!(legacy, fvm_ipld_encoding::to_vec(&state).unwrap());
+        let decoded: PState = fvm_ipld_encoding::from_slice(&legacy).unwrap();
+        assert_eq!(None, decoded.htlcs);
+        assert_eq!(TokenAmount::zero(), decoded.htlc_locked);
+        assert_eq!(None, get_htlc(&rt, 1));
+
+        // Locking an HTLC adds the trailing fields.
+        lock(&rt, sv);
+        let state: PState = rt.get_state();
+        let decoded: PState =
+            fvm_ipld_encoding::from_slice(&fvm_ipld_encoding::to_vec(&state).unwrap()).unwrap();
+        assert!(decoded.htlcs.is_some());
+        assert_eq!(state.htlcs, decoded.htlcs);
+        assert_eq!(TokenAmount::from_atto(10), decoded.htlc_locked);
+    }
+
+    #[test]
+    fn lock_and_redeem() {
+        let (rt, sv) = construct_runtime();
+        let before: PState = rt.get_state();
+        lock(&rt, sv.clone());
+
+        let state: PState = rt.get_state();
+        assert_eq!(before.to_send, state.to_send);
+        assert_eq!(TokenAmount::from_atto(10), state.htlc_locked);
+        assert_eq!(EXPIRY + 1, state.min_settle_height);
+        assert_eq!(
+            Some(PendingHtlc {
+                hash: sv.secret_pre_image.clone(),
+                amount: TokenAmount::from_atto(10),
+                expiry: EXPIRY,
+            }),
+            get_htlc(&rt, 1)
+        );
+        let ls = get_lane_state(&rt, &state.lane_states, 1);
+        assert_eq!(LaneState { redeemed: sv.amount.clone(), nonce: sv.nonce }, ls);
+
+        // The wrong secret is rejected.
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_abort(
+            &rt,
+            Method::RedeemHtlcExported as u64,
+            IpldBlock::serialize_cbor(&RedeemHtlcParams { lane: 1, secret: b"wrong".to_vec() })
+                .unwrap(),
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+        );
+        rt.verify();
+
+        // The payee redeems with the secret, up to and including the expiry epoch.
+        rt.epoch.replace(EXPIRY);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        call(
+            &rt,
+            Method::RedeemHtlcExported as u64,
+            IpldBlock::serialize_cbor(&RedeemHtlcParams { lane: 1, secret: SECRET.to_vec() })
+                .unwrap(),
+        );
+        rt.verify();
+
+        let state: PState = rt.get_state();
+        assert_eq!(&before.to_send + TokenAmount::from_atto(10), state.to_send);
+        assert!(state.htlc_locked.is_zero());
+        assert_eq!(None, get_htlc(&rt, 1));
+        check_state(&rt);
+
+        // The HTLC can't be redeemed twice.
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_abort(
+            &rt,
+            Method::RedeemHtlcExported as u64,
+            IpldBlock::serialize_cbor(&RedeemHtlcParams { lane: 1, secret: SECRET.to_vec() })
+                .unwrap(),
+            ExitCode::USR_NOT_FOUND,
+        );
+        rt.verify();
+    }
+
+    #[test]
+    fn refund_after_expiry() {
+        let (rt, sv) = construct_runtime();
+        let before: PState = rt.get_state();
+        lock(&rt, sv.clone());
+        let state: PState = rt.get_state();
+
+        // Can't refund or redeem at the wrong time.
+        rt.epoch.replace(EXPIRY);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_abort(
+            &rt,
+            Method::RefundHtlcExported as u64,
+            IpldBlock::serialize_cbor(&RefundHtlcParams { lane: 1 }).unwrap(),
+            ExitCode::USR_FORBIDDEN,
+        );
+        rt.verify();
+        rt.epoch.replace(EXPIRY + 1);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_abort(
+            &rt,
+            Method::RedeemHtlcExported as u64,
+            IpldBlock::serialize_cbor(&RedeemHtlcParams { lane: 1, secret: SECRET.to_vec() })
+                .unwrap(),
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+        );
+        rt.verify();
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.from);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        call(
+            &rt,
+            Method::RefundHtlcExported as u64,
+            IpldBlock::serialize_cbor(&RefundHtlcParams { lane: 1 }).unwrap(),
+        );
+        rt.verify();
+
+        let state: PState = rt.get_state();
+        assert_eq!(before.to_send, state.to_send);
+        assert!(state.htlc_locked.is_zero());
+        assert_eq!(None, get_htlc(&rt, 1));
+        // The lane keeps the voucher's nonce, so the voucher can't be replayed.
+        let ls = get_lane_state(&rt, &state.lane_states, 1);
+        assert_eq!(LaneState { redeemed: TokenAmount::from_atto(2), nonce: sv.nonce }, ls);
+        check_state(&rt);
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.to);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_authenticate_message(&rt, state.from, sv.clone(), ExitCode::OK);
+        expect_abort(
+            &rt,
+            Method::UpdateChannelState as u64,
+            IpldBlock::serialize_cbor(&UpdateChannelStateParams { sv, secret: SECRET.to_vec() })
+                .unwrap(),
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+        );
+        rt.verify();
+    }
+
+    #[test]
+    fn pending_lane_cannot_be_updated_or_merged() {
+        let (rt, sv) = construct_runtime();
+        lock(&rt, sv.clone());
+        let state: PState = rt.get_state();
+
+        // A later voucher on the same lane.
+        let mut next = sv.clone();
+        next.nonce += 1;
+        next.amount = TokenAmount::from_atto(20);
+        next.secret_pre_image = vec![];
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_authenticate_message(&rt, state.from, next.clone(), ExitCode::OK);
+        expect_abort(
+            &rt,
+            Method::UpdateChannelState as u64,
+            IpldBlock::serialize_cbor(&UpdateChannelStateParams::from(next)).unwrap(),
+            ExitCode::USR_FORBIDDEN,
+        );
+        rt.verify();
+
+        // A voucher merging the pending lane into another.
+        let mut merging = sv;
+        merging.lane = 0;
+        merging.nonce = 10;
+        merging.amount = TokenAmount::from_atto(20);
+        merging.secret_pre_image = vec![];
+        merging.merges = vec![Merge { lane: 1, nonce: 10 }];
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_authenticate_message(&rt, state.from, merging.clone(), ExitCode::OK);
+        expect_abort(
+            &rt,
+            Method::UpdateChannelState as u64,
+            IpldBlock::serialize_cbor(&UpdateChannelStateParams::from(merging)).unwrap(),
+            ExitCode::USR_FORBIDDEN,
+        );
+        rt.verify();
+        check_state(&rt);
+    }
+
+    #[test]
+    fn locked_funds_are_reserved() {
+        let (rt, mut sv) = construct_runtime();
+        // Lanes 0 and 1 have redeemed 3, leaving 7 for the voucher.
+        rt.set_balance(TokenAmount::from_atto(10));
+        let state: PState = rt.get_state();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.to);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_authenticate_message(&rt, state.from, sv.clone(), ExitCode::OK);
+        expect_abort(
+            &rt,
+            Method::LockHtlcExported as u64,
+            IpldBlock::serialize_cbor(&LockHtlcParams { sv: sv.clone() }).unwrap(),
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+        );
+        rt.verify();
+
+        sv.amount = TokenAmount::from_atto(9);
+        lock(&rt, sv.clone());
+
+        // The locked amount can't also be paid out by a plain voucher on another lane.
+        let mut other = sv;
+        other.lane = 0;
+        other.nonce = 10;
+        other.amount = TokenAmount::from_atto(2);
+        other.secret_pre_image = vec![];
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_authenticate_message(&rt, state.from, other.clone(), ExitCode::OK);
+        expect_abort(
+            &rt,
+            Method::UpdateChannelState as u64,
+            IpldBlock::serialize_cbor(&UpdateChannelStateParams::from(other)).unwrap(),
+            ExitCode::USR_ILLEGAL_ARGUMENT,
+        );
+        rt.verify();
+        check_state(&rt);
+    }
+
+    #[test]
+    fn lock_requires_hash_and_expiry() {
+        let (rt, sv) = construct_runtime();
+        let state: PState = rt.get_state();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.to);
+
+        let mut no_hash = sv.clone();
+        no_hash.secret_pre_image = vec![];
+        let mut no_expiry = sv;
+        no_expiry.time_lock_max = 0;
+        for sv in [no_hash, no_expiry] {
+            rt.expect_validate_caller_addr(vec![state.from, state.to]);
+            expect_authenticate_message(&rt, state.from, sv.clone(), ExitCode::OK);
+            expect_abort(
+                &rt,
+                Method::LockHtlcExported as u64,
+                IpldBlock::serialize_cbor(&LockHtlcParams { sv }).unwrap(),
+                ExitCode::USR_ILLEGAL_ARGUMENT,
+            );
+            rt.verify();
+        }
+        check_state(&rt);
+    }
+
+    #[test]
+    fn collect_after_expiry_returns_locked_funds_to_payer() {
+        let (rt, sv) = construct_runtime();
+        lock(&rt, sv);
+        let state: PState = rt.get_state();
+
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.from);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        call(&rt, Method::Settle as u64, None);
+        let state: PState = rt.get_state();
+        assert!(state.settling_at > EXPIRY);
+
+        rt.epoch.replace(state.settling_at);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        rt.expect_send_simple(
+            state.to,
+            fvm_shared::METHOD_SEND,
+            None,
+            state.to_send.clone(),
+            None,
+            ExitCode::OK,
+        );
+        rt.expect_send_simple(
+            state.from,
+            fvm_shared::METHOD_SEND,
+            None,
+            rt.get_balance() - &state.to_send,
+            None,
+            ExitCode::OK,
+        );
+        rt.expect_delete_actor();
+        call(&rt, Method::Collect as u64, None);
+        rt.verify();
+    }
+}
+
+mod getters {
+    use fil_actors_runtime::runtime::Primitives;
+
+    use super::*;
+
+    fn get_lane(rt: &MockRuntime, lane: u64) -> GetLaneStateReturn {
+        rt.expect_validate_caller_any();
+        let ret = call(
+            rt,
+            Method::GetLaneStateExported as u64,
+            IpldBlock::serialize_cbor(&GetLaneStateParams { lane }).unwrap(),
+        )
+        .unwrap()
+        .deserialize()
+        .unwrap();
+        rt.verify();
+        ret
+    }
+
+    fn get_settlement_state(rt: &MockRuntime) -> GetSettlementStateReturn {
+        rt.expect_validate_caller_any();
+        let ret = call(rt, Method::GetSettlementStateExported as u64, None)
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        rt.verify();
+        ret
+    }
+
+    #[test]
+    fn query_channel() {
+        let (rt, mut sv) = require_create_channel_with_lanes(2);
+        let state: PState = rt.get_state();
+        // Any actor can query the channel.
+        rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(1000));
+
+        rt.expect_validate_caller_any();
+        let parties: GetPartiesReturn =
+            call(&rt, Method::GetPartiesExported as u64, None).unwrap().deserialize().unwrap();
+        rt.verify();
+        assert_eq!(GetPartiesReturn { from: state.from, to: state.to }, parties);
+
+        assert_eq!(
+            GetSettlementStateReturn {
+                to_send: TokenAmount::from_atto(3),
+                htlc_locked: TokenAmount::zero(),
+                settling_at: 0,
+                min_settle_height: 0,
+            },
+            get_settlement_state(&rt)
+        );
+        assert_eq!(
+            GetLaneStateReturn {
+                lane_state: Some(LaneState { redeemed: TokenAmount::from_atto(2), nonce: 2 }),
+                htlc: None,
+            },
+            get_lane(&rt, 1)
+        );
+        assert_eq!(GetLaneStateReturn { lane_state: None, htlc: None }, get_lane(&rt, 7));
+
+        // Lock an HTLC on lane 1, then settle.
+        sv.amount = TokenAmount::from_atto(12);
+        sv.time_lock_max = 100;
+        sv.secret_pre_image = rt.hash_blake2b(b"secret").to_vec();
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, state.to);
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        expect_authenticate_message(&rt, state.from, sv.clone(), ExitCode::OK);
+        call(
+            &rt,
+            Method::LockHtlcExported as u64,
+            IpldBlock::serialize_cbor(&LockHtlcParams { sv: sv.clone() }).unwrap(),
+        );
+        rt.expect_validate_caller_addr(vec![state.from, state.to]);
+        call(&rt, Method::Settle as u64, None);
+        rt.verify();
+
+        let settlement = get_settlement_state(&rt);
+        assert_eq!(TokenAmount::from_atto(3), settlement.to_send);
+        assert_eq!(TokenAmount::from_atto(10), settlement.htlc_locked);
+        assert_eq!(*rt.epoch.borrow() + SETTLE_DELAY, settlement.settling_at);
+        assert_eq!(101, settlement.min_settle_height);
+
+        let lane = get_lane(&rt, 1);
+        assert_eq!(Some(LaneState { redeemed: sv.amount, nonce: sv.nonce }), lane.lane_state);
+        assert_eq!(TokenAmount::from_atto(10), lane.htlc.unwrap().amount);
+        check_state(&rt);
+    }
+}
+
 fn require_create_channel_with_lanes(num_lanes: u64) -> (MockRuntime, SignedVoucher) {
     let paych_addr = Address::new_id(100);
     let payer_addr = Address::new_id(PAYER_ID);
@@ -56,6 +56,7 @@ pub enum Method {
     MinerRawPowerExported = frc42_dispatch::method_hash!("MinerRawPower"),
     MinerCountExported = frc42_dispatch::method_hash!("MinerCount"),
     MinerConsensusCountExported = frc42_dispatch::method_hash!("MinerConsensusCount"),
+    MinerQualityAdjPowerExported = frc42_dispatch::method_hash!("MinerQualityAdjPower"),
 }
 
 pub const ERR_TOO_MANY_PROVE_COMMITS: ExitCode = ExitCode::new(32);
@@ -300,6 +301,20 @@ impl Actor {
         Ok(MinerRawPowerReturn { raw_byte_power, meets_consensus_minimum })
     }
 
+    /// Returns the quality-adjusted power claimed by the specified miner.
+    /// Like raw power, this only includes the active (i.e. non-faulty) commitments of the miner.
+    fn miner_quality_adj_power(
+        rt: &impl Runtime,
+        params: MinerQualityAdjPowerParams,
+    ) -> Result<MinerQualityAdjPowerReturn, ActorError> {
+        rt.validate_immediate_caller_accept_any()?;
+        let st: State = rt.state()?;
+
+        let quality_adj_power = st.miner_quality_adj_power(rt.store(), params.miner)?;
+
+        Ok(MinerQualityAdjPowerReturn { quality_adj_power })
+    }
+
     /// Returns the total number of miners created, regardless of whether or not
     /// they have any pledged storage.
     fn miner_count(rt: &impl Runtime) -> Result<MinerCountReturn, ActorError> {
@@ -440,5 +455,6 @@ impl ActorCode for Actor {
         MinerRawPowerExported => miner_raw_power,
         MinerCountExported => miner_count,
         MinerConsensusCountExported => miner_consensus_count,
+        MinerQualityAdjPowerExported => miner_quality_adj_power,
     }
 }
@@ -112,6 +112,20 @@ impl State {
         self.total_pledge_collateral
     }
 
+    /// Returns the quality-adjusted power claimed by a miner.
+    pub fn miner_quality_adj_power<BS: Blockstore>(
+        &self,
+        s: &BS,
+        miner: ActorID,
+    ) -> Result<StoragePower, ActorError> {
+        let claims = self.load_claims(s)?;
+        let a = &Address::new_id(miner);
+        let claim = claims.get(a)?.with_context_code(ExitCode::USR_ILLEGAL_ARGUMENT, || {
+            format!("no claim for actor: {}", miner)
+        })?;
+        Ok(claim.quality_adj_power.clone())
+    }
+
     /// Checks power actor state for if miner meets minimum consensus power.
     pub fn miner_nominal_power_meets_consensus_minimum<BS: Blockstore>(
         &self,
@@ -92,6 +92,19 @@ pub struct MinerRawPowerReturn {
     pub meets_consensus_minimum: bool,
 }
 
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct MinerQualityAdjPowerParams {
+    pub miner: ActorID,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct MinerQualityAdjPowerReturn {
+    #[serde(with = "bigint_ser")]
+    pub quality_adj_power: StoragePower,
+}
+
 #[derive(Serialize_tuple, Deserialize_tuple, Debug, Clone, Eq, PartialEq)]
 #[serde(transparent)]
 pub struct MinerCountReturn {
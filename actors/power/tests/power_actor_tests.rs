@@ -19,8 +19,9 @@ use std::ops::Neg;
 
 use fil_actor_power::{
     consensus_miner_min_power, Actor as PowerActor, Actor, CreateMinerParams, CreateMinerReturn,
-    EnrollCronEventParams, Method, MinerRawPowerParams, MinerRawPowerReturn, NetworkRawPowerReturn,
-    State, UpdateClaimedPowerParams, CONSENSUS_MINER_MIN_MINERS,
+    EnrollCronEventParams, Method, MinerQualityAdjPowerParams, MinerQualityAdjPowerReturn,
+    MinerRawPowerParams, MinerRawPowerReturn, NetworkRawPowerReturn, State,
+    UpdateClaimedPowerParams, CONSENSUS_MINER_MIN_MINERS,
 };
 
 use fvm_ipld_encoding::ipld_block::IpldBlock;
@@ -611,6 +612,39 @@ fn get_network_and_miner_power() {
     h.check_state(&rt);
 }
 
+#[test]
+fn get_miner_quality_adj_power() {
+    let power_unit = &consensus_miner_min_power(
+        &Policy::default(),
+        RegisteredPoStProof::StackedDRGWindow32GiBV1P1,
+    )
+    .unwrap();
+
+    let (mut h, rt) = setup();
+
+    h.create_miner_basic(&rt, *OWNER, *OWNER, MINER1).unwrap();
+    h.update_claimed_power(&rt, MINER1, power_unit, &(power_unit * 2));
+
+    // set caller to not-builtin
+    rt.set_caller(*EVM_ACTOR_CODE_ID, Address::new_id(1234));
+
+    rt.expect_validate_caller_any();
+    let miner_power: MinerQualityAdjPowerReturn = rt
+        .call::<Actor>(
+            Method::MinerQualityAdjPowerExported as u64,
+            IpldBlock::serialize_cbor(&MinerQualityAdjPowerParams { miner: MINER1.id().unwrap() })
+                .unwrap(),
+        )
+        .unwrap()
+        .unwrap()
+        .deserialize()
+        .unwrap();
+
+    assert_eq!(&(power_unit * 2), &miner_power.quality_adj_power);
+
+    h.check_state(&rt);
+}
+
 #[test]
 fn given_no_miner_claim_update_pledge_total_should_abort() {
     let (mut h, rt) = setup();
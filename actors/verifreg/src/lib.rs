@@ -66,6 +66,7 @@ pub enum Method {
     GetClaims = 10,
     ExtendClaimTerms = 11,
     RemoveExpiredClaims = 12,
+    CancelClaims = 13,
     // Method numbers derived from FRC-0042 standards
     AddVerifiedClientExported = frc42_dispatch::method_hash!("AddVerifiedClient"),
     RemoveExpiredAllocationsExported = frc42_dispatch::method_hash!("RemoveExpiredAllocations"),
@@ -634,6 +635,33 @@ impl Actor {
         Ok(RemoveExpiredClaimsReturn { considered, results: batch_ret })
     }
 
+    /// Removes the claims backing verified deals that their client and provider cancelled.
+    /// Callable only by the storage market actor, which checks that each claim is for a
+    /// cancelled deal's data. The claimed space is not returned to the client as DataCap.
+    pub fn cancel_claims(rt: &impl Runtime, params: CancelClaimsParams) -> Result<(), ActorError> {
+        rt.validate_immediate_caller_is(std::iter::once(&STORAGE_MARKET_ACTOR_ADDR))?;
+        rt.transaction(|st: &mut State, rt| {
+            let mut claims = st.load_claims(rt.store())?;
+            for id in &params.claim_ids {
+                let removed = claims
+                    .remove(params.provider, *id)
+                    .context_code(
+                        ExitCode::USR_ILLEGAL_STATE,
+                        format!("failed to remove claim {}", id),
+                    )?
+                    .ok_or_else(|| {
+                        actor_error!(not_found, "no claim {} for provider {}", id, params.provider)
+                    })?;
+
+                emit::claim_removed(rt, *id, &removed)?;
+            }
+
+            st.save_claims(&mut claims)?;
+            Ok(())
+        })
+        .context("state transaction failed")
+    }
+
     // Receives data cap tokens (only) and creates allocations according to one or more
     // allocation requests specified in the transfer's operator data.
     // The token amount received must exactly correspond to the sum of the requested allocation sizes.
@@ -1110,5 +1138,6 @@ impl ActorCode for Actor {
         ExtendClaimTerms|ExtendClaimTermsExported => extend_claim_terms,
         RemoveExpiredClaims|RemoveExpiredClaimsExported => remove_expired_claims,
         UniversalReceiverHook => universal_receiver_hook,
+        CancelClaims => cancel_claims,
     }
 }
@@ -258,3 +258,11 @@ pub struct RemoveExpiredClaimsReturn {
     // Results for each processed claim.
     pub results: BatchReturn,
 }
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct CancelClaimsParams {
+    // Provider of the claims.
+    pub provider: ActorID,
+    // Claims backing the cancelled deals.
+    pub claim_ids: Vec<ClaimID>,
+}
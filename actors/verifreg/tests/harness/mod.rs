@@ -25,10 +25,11 @@ use fil_actor_verifreg::testing::check_state_invariants;
 use fil_actor_verifreg::{
     ext, Actor as VerifregActor, AddVerifiedClientParams, AddVerifierParams, Allocation,
     AllocationClaim, AllocationID, AllocationRequest, AllocationRequests, AllocationsResponse,
-    Claim, ClaimAllocationsParams, ClaimAllocationsReturn, ClaimExtensionRequest, ClaimID, DataCap,
-    ExtendClaimTermsParams, ExtendClaimTermsReturn, GetClaimsParams, GetClaimsReturn, Method,
-    RemoveExpiredAllocationsParams, RemoveExpiredAllocationsReturn, RemoveExpiredClaimsParams,
-    RemoveExpiredClaimsReturn, SectorAllocationClaims, State,
+    CancelClaimsParams, Claim, ClaimAllocationsParams, ClaimAllocationsReturn,
+    ClaimExtensionRequest, ClaimID, DataCap, ExtendClaimTermsParams, ExtendClaimTermsReturn,
+    GetClaimsParams, GetClaimsReturn, Method, RemoveExpiredAllocationsParams,
+    RemoveExpiredAllocationsReturn, RemoveExpiredClaimsParams, RemoveExpiredClaimsReturn,
+    SectorAllocationClaims, State,
 };
 use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::runtime::builtins::Type;
@@ -436,6 +437,41 @@ impl Harness {
         Ok(ret)
     }
 
+    // Invokes the CancelClaims actor method, as the market.
+    pub fn cancel_claims(
+        &self,
+        rt: &MockRuntime,
+        provider: ActorID,
+        claim_ids: Vec<ClaimID>,
+        expect_removed: Vec<(ClaimID, Claim)>,
+    ) -> Result<(), ActorError> {
+        rt.set_caller(*MARKET_ACTOR_CODE_ID, STORAGE_MARKET_ACTOR_ADDR);
+        rt.expect_validate_caller_addr(vec![STORAGE_MARKET_ACTOR_ADDR]);
+        for (id, claim) in expect_removed {
+            expect_claim_emitted(
+                rt,
+                "claim-removed",
+                id,
+                claim.client,
+                claim.provider,
+                &claim.data,
+                claim.size.0,
+                claim.sector,
+                claim.term_min,
+                claim.term_max,
+                claim.term_start,
+            )
+        }
+
+        let params = CancelClaimsParams { provider, claim_ids };
+        let ret = rt.call::<VerifregActor>(
+            Method::CancelClaims as MethodNum,
+            IpldBlock::serialize_cbor(&params).unwrap(),
+        );
+        rt.verify();
+        ret.map(|_| ())
+    }
+
     pub fn load_claim(&self, rt: &MockRuntime, provider: ActorID, id: ClaimID) -> Option<Claim> {
         let st: State = rt.get_state();
         let mut claims = st.load_claims(rt.store()).unwrap();
@@ -529,8 +529,8 @@ mod allocs_claims {
     use num_traits::Zero;
 
     use fil_actor_verifreg::{
-        Actor, AllocationID, ClaimTerm, DataCap, ExtendClaimTermsParams, GetClaimsParams, Method,
-        State,
+        Actor, AllocationID, CancelClaimsParams, ClaimTerm, DataCap, ExtendClaimTermsParams,
+        GetClaimsParams, Method, State,
     };
     use fil_actor_verifreg::{Claim, ExtendClaimTermsReturn};
     use fil_actors_runtime::runtime::policy_constants::{
@@ -540,7 +540,7 @@ mod allocs_claims {
     use fil_actors_runtime::test_utils::{
         expect_abort, expect_abort_contains_message, ACCOUNT_ACTOR_CODE_ID, EVM_ACTOR_CODE_ID,
     };
-    use fil_actors_runtime::FailCode;
+    use fil_actors_runtime::{FailCode, STORAGE_MARKET_ACTOR_ADDR};
     use harness::*;
 
     use crate::*;
@@ -1115,6 +1115,42 @@ mod allocs_claims {
         h.check_state(&rt);
     }
 
+    #[test]
+    fn cancel_claims() {
+        let (h, rt) = new_harness();
+        let term_min = MINIMUM_VERIFIED_ALLOCATION_TERM;
+        let claim1 = make_claim("1", CLIENT1, PROVIDER1, ALLOC_SIZE, term_min, term_min, 0, 0);
+        let claim2 = make_claim("2", CLIENT1, PROVIDER1, ALLOC_SIZE, term_min, term_min, 0, 0);
+        let id1 = h.create_claim(&rt, &claim1).unwrap();
+        let id2 = h.create_claim(&rt, &claim2).unwrap();
+
+        // Only the market may cancel claims, whether or not they have expired.
+        rt.set_caller(*ACCOUNT_ACTOR_CODE_ID, Address::new_id(PROVIDER1));
+        rt.expect_validate_caller_addr(vec![STORAGE_MARKET_ACTOR_ADDR]);
+        expect_abort(
+            ExitCode::USR_FORBIDDEN,
+            rt.call::<Actor>(
+                Method::CancelClaims as MethodNum,
+                IpldBlock::serialize_cbor(&CancelClaimsParams {
+                    provider: PROVIDER1,
+                    claim_ids: vec![id1],
+                })
+                .unwrap(),
+            ),
+        );
+        rt.verify();
+
+        h.cancel_claims(&rt, PROVIDER1, vec![id1], vec![(id1, claim1)]).unwrap();
+        assert!(h.load_claim(&rt, PROVIDER1, id1).is_none());
+        assert!(h.load_claim(&rt, PROVIDER1, id2).is_some());
+
+        // A missing claim fails the whole call.
+        expect_abort(ExitCode::USR_NOT_FOUND, h.cancel_claims(&rt, PROVIDER1, vec![id1], vec![]));
+        expect_abort(ExitCode::USR_NOT_FOUND, h.cancel_claims(&rt, PROVIDER2, vec![id2], vec![]));
+        assert!(h.load_claim(&rt, PROVIDER1, id2).is_some());
+        h.check_state(&rt);
+    }
+
     #[test]
     fn claims_restricted_correctly() {
         let (h, rt) = new_harness();
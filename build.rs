@@ -30,6 +30,8 @@ const ACTORS: &[(&Package, &ID)] = &[
     ("evm", "evm"),
     ("eam", "eam"),
     ("ethaccount", "ethaccount"),
+    ("passkey", "passkey"),
+    ("multikey", "multikey"),
 ];
 
 const NETWORK_ENV: &str = "BUILD_FIL_NETWORK";
@@ -139,6 +139,7 @@ fn publish_deal_params(proposals: &[DealProposal]) -> PublishStorageDealsParams
                 sig_type: SignatureType::BLS,
                 bytes: serialize(deal, "serializing deal proposal").unwrap().to_vec(),
             },
+            eip712_signature: false,
         })
         .collect();
     PublishStorageDealsParams { deals: params_deals }
@@ -0,0 +1,318 @@
+use export_macro::vm_test;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::Zero;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::signature::Signature;
+use fvm_shared::deal::DealID;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::piece::PaddedPieceSize;
+use fvm_shared::sector::{RegisteredSealProof, SectorNumber, StoragePower};
+
+use fil_actor_market::{
+    CancelDealsParams, CancelDealsReturn, ClientDealCancellationProposal, DealCancellationProposal,
+    GetBalanceReturn, Method as MarketMethod, NO_ALLOCATION_ID,
+};
+use fil_actor_miner::{
+    max_prove_commit_duration, GetSectorStatusParams, Method as MinerMethod,
+    SectorDealsCancellation, SectorDealsCancelledParams,
+};
+use fil_actor_verifreg::{CancelClaimsParams, ClaimID, Method as VerifregMethod};
+use fil_actors_runtime::cbor::serialize;
+use fil_actors_runtime::runtime::policy_constants::MARKET_DEFAULT_ALLOCATION_TERM_BUFFER;
+use fil_actors_runtime::runtime::Policy;
+use fil_actors_runtime::{
+    DealWeight, EPOCHS_IN_DAY, STORAGE_MARKET_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ID,
+    VERIFIED_REGISTRY_ACTOR_ADDR,
+};
+use vm_api::trace::ExpectInvocation;
+use vm_api::util::apply_ok;
+use vm_api::VM;
+
+use crate::expects::Expect;
+use crate::util::{
+    advance_by_deadline_to_epoch, advance_by_deadline_to_epoch_while_proving, create_accounts,
+    create_miner, cron_tick, expect_invariants, get_deal, invariant_failure_patterns,
+    make_piece_manifests_from_deal_ids, market_add_balance, market_list_deals,
+    market_list_sectors_deals, market_pending_deal_allocations, market_publish_deal, miner_power,
+    miner_precommit_one_sector_v2, miner_prove_sector, precommit_meta_data_from_deals, sector_info,
+    verifreg_add_client, verifreg_add_verifier, verifreg_list_claims,
+};
+
+/// Cancels an unverified and a verified deal by agreement of their client and provider.
+/// The deals are settled up to the cancel epoch and removed, and the collateral of both
+/// parties is returned in full.
+/// The sector's deal weight drops by the unverified deal's remaining spacetime. The verified
+/// deal's claim is removed, and the sector loses its verified weight and quality-adjusted power.
+#[vm_test]
+pub fn cancel_deal_test(v: &dyn VM) {
+    let addrs = create_accounts(v, 3, &TokenAmount::from_whole(10_000));
+    let seal_proof = RegisteredSealProof::StackedDRG32GiBV1P1;
+    let (worker, verifier, client) = (addrs[0], addrs[1], addrs[2]);
+    let sector_number: SectorNumber = 100;
+
+    let (miner_id, _) = create_miner(
+        v,
+        &worker,
+        &worker,
+        seal_proof.registered_window_post_proof().unwrap(),
+        &TokenAmount::from_whole(1_000),
+    );
+    v.set_epoch(200);
+    let datacap = StoragePower::from(32_u128 << 40);
+    verifreg_add_verifier(v, &verifier, &datacap * 2);
+    verifreg_add_client(v, &verifier, &client, datacap);
+    market_add_balance(v, &client, &client, &TokenAmount::from_whole(3));
+    market_add_balance(v, &worker, &miner_id, &TokenAmount::from_whole(64));
+
+    // Publish an unverified and a verified deal, each filling half the sector.
+    let deal_start = v.epoch() + max_prove_commit_duration(&Policy::default(), seal_proof).unwrap();
+    let deal_term = 180 * EPOCHS_IN_DAY;
+    let deal_size = 16u64 << 30;
+    let sector_expiration = deal_start + deal_term + MARKET_DEFAULT_ALLOCATION_TERM_BUFFER;
+    let mut deals = market_publish_deal(
+        v,
+        &worker,
+        &client,
+        &miner_id,
+        "deal1".to_string(),
+        PaddedPieceSize(deal_size),
+        false,
+        deal_start,
+        deal_term,
+    )
+    .ids;
+    deals.extend(
+        market_publish_deal(
+            v,
+            &worker,
+            &client,
+            &miner_id,
+            "deal2".to_string(),
+            PaddedPieceSize(deal_size),
+            true,
+            deal_start,
+            deal_term,
+        )
+        .ids,
+    );
+    let claim_id = market_pending_deal_allocations(v, &deals[1..])[0];
+    miner_precommit_one_sector_v2(
+        v,
+        &worker,
+        &miner_id,
+        seal_proof,
+        sector_number,
+        precommit_meta_data_from_deals(v, &deals, seal_proof, false),
+        true,
+        sector_expiration,
+    );
+    advance_by_deadline_to_epoch(v, &miner_id, deal_start);
+    miner_prove_sector(
+        v,
+        &worker,
+        &miner_id,
+        sector_number,
+        make_piece_manifests_from_deal_ids(v, deals.clone()),
+    );
+    cron_tick(v);
+    let sector = sector_info(v, &miner_id, sector_number);
+    let proposals: Vec<_> = deals.iter().map(|id| get_deal(v, *id)).collect();
+    let miner = miner_id.id().unwrap();
+    let claim = verifreg_list_claims(v, miner)[&claim_id].clone();
+
+    // Cancel both deals some way into their term. Verified deal space can only be removed from
+    // a sector once it is proven and active.
+    advance_by_deadline_to_epoch_while_proving(
+        v,
+        &miner_id,
+        &worker,
+        sector_number,
+        deal_start + 10 * EPOCHS_IN_DAY,
+    );
+    let power_before = miner_power(v, &miner_id);
+    let cancel_epoch = v.epoch() - 100;
+    let cancellations = vec![
+        cancellation(deals[0], cancel_epoch, NO_ALLOCATION_ID),
+        cancellation(deals[1], cancel_epoch, claim_id),
+    ];
+    let ret = cancel_deals(v, &worker, cancellations.clone());
+    assert!(ret.results.all_ok());
+    let power_after = miner_power(v, &miner_id);
+
+    let client_id = client.id().unwrap();
+    let authentications: Vec<_> = cancellations
+        .iter()
+        .map(|cancellation| {
+            Expect::frc44_authenticate(
+                STORAGE_MARKET_ACTOR_ID,
+                client,
+                serialize(&cancellation.proposal, "deal cancellation proposal").unwrap().to_vec(),
+                cancellation.client_signature.bytes.clone(),
+            )
+        })
+        .collect();
+    let claim_removal = ExpectInvocation {
+        from: STORAGE_MARKET_ACTOR_ID,
+        to: VERIFIED_REGISTRY_ACTOR_ADDR,
+        method: VerifregMethod::CancelClaims as u64,
+        params: Some(
+            IpldBlock::serialize_cbor(&CancelClaimsParams {
+                provider: miner,
+                claim_ids: vec![claim_id],
+            })
+            .unwrap(),
+        ),
+        value: Some(TokenAmount::zero()),
+        subinvocs: Some(vec![]),
+        events: Some(vec![Expect::build_verifreg_claim_event(
+            "claim-removed",
+            claim_id,
+            client_id,
+            miner,
+            &claim.data,
+            claim.size.0,
+            claim.term_min,
+            claim.term_max,
+            claim.term_start,
+            claim.sector,
+        )]),
+        ..Default::default()
+    };
+    let notification = SectorDealsCancelledParams {
+        sectors: vec![SectorDealsCancellation {
+            sector_number,
+            deal_space: deal_size,
+            verified_deal_space: deal_size,
+        }],
+    };
+    ExpectInvocation {
+        from: worker.id().unwrap(),
+        to: STORAGE_MARKET_ACTOR_ADDR,
+        method: MarketMethod::CancelDealsExported as u64,
+        subinvocs: Some(vec![
+            Expect::miner_is_controlling_address(STORAGE_MARKET_ACTOR_ID, miner_id, worker),
+            authentications[0].clone(),
+            Expect::verifreg_get_claims(STORAGE_MARKET_ACTOR_ID, miner, vec![claim_id]),
+            ExpectInvocation {
+                from: STORAGE_MARKET_ACTOR_ID,
+                to: miner_id,
+                method: MinerMethod::GetSectorStatusExported as u64,
+                params: Some(
+                    IpldBlock::serialize_cbor(&GetSectorStatusParams { sector_number }).unwrap(),
+                ),
+                value: Some(TokenAmount::zero()),
+                subinvocs: Some(vec![]),
+                ..Default::default()
+            },
+            authentications[1].clone(),
+            claim_removal,
+            ExpectInvocation {
+                from: STORAGE_MARKET_ACTOR_ID,
+                to: miner_id,
+                method: MinerMethod::SectorDealsCancelled as u64,
+                params: Some(IpldBlock::serialize_cbor(&notification).unwrap()),
+                value: Some(TokenAmount::zero()),
+                subinvocs: Some(vec![Expect::power_update_claim(
+                    miner,
+                    &power_after - &power_before,
+                )]),
+                ..Default::default()
+            },
+        ]),
+        events: Some(
+            deals
+                .iter()
+                .map(|id| Expect::build_market_event("deal-cancelled", *id, client_id, miner))
+                .collect(),
+        ),
+        ..Default::default()
+    }
+    .matches(v.take_invocations().last().unwrap());
+
+    // Both deals are gone, with payment up to the cancel epoch and no collateral slashed.
+    let market_deals = market_list_deals(v);
+    assert!(!market_deals.contains_key(&deals[0]));
+    assert!(!market_deals.contains_key(&deals[1]));
+    assert!(!market_list_sectors_deals(v, &miner_id).contains_key(&sector_number));
+    let payment = proposals
+        .iter()
+        .map(|proposal| &proposal.storage_price_per_epoch * (cancel_epoch - deal_start))
+        .sum::<TokenAmount>();
+    let client_balance = market_balance(v, &client);
+    let paid = TokenAmount::from_whole(3) - &client_balance.balance;
+    assert!(paid >= payment);
+    assert!(client_balance.locked.is_zero());
+    let provider_balance = market_balance(v, &miner_id);
+    assert_eq!(TokenAmount::from_whole(64) + &paid, provider_balance.balance);
+    assert!(provider_balance.locked.is_zero());
+
+    // The sector keeps the unverified deal's weight only up to cancellation, and none of its
+    // verified weight. Its raw power is unchanged, but its quality-adjusted power drops.
+    let updated = sector_info(v, &miner_id, sector_number);
+    let removed_weight = DealWeight::from(deal_size) * (sector.expiration - v.epoch());
+    assert_eq!(&sector.deal_weight - removed_weight, updated.deal_weight);
+    assert!(updated.verified_deal_weight.is_zero());
+    assert_eq!(power_before.raw, power_after.raw);
+    assert!(power_after.qa < power_before.qa);
+    assert!(!verifreg_list_claims(v, miner).contains_key(&claim_id));
+
+    expect_invariants(
+        v,
+        &Policy::default(),
+        &[invariant_failure_patterns::REWARD_STATE_EPOCH_MISMATCH.to_owned()],
+        None,
+    );
+}
+
+fn cancellation(
+    deal_id: DealID,
+    cancel_epoch: ChainEpoch,
+    claim_id: ClaimID,
+) -> ClientDealCancellationProposal {
+    let proposal = DealCancellationProposal {
+        deal_id,
+        cancel_epoch,
+        claim_id,
+        expiration: cancel_epoch + EPOCHS_IN_DAY,
+    };
+    // The test VM accepts a signature equal to the signed message.
+    let signature = Signature::new_secp256k1(
+        serialize(&proposal, "deal cancellation proposal").unwrap().to_vec(),
+    );
+    ClientDealCancellationProposal {
+        proposal,
+        client_signature: signature,
+        eip712_signature: false,
+    }
+}
+
+fn cancel_deals(
+    v: &dyn VM,
+    worker: &Address,
+    cancellations: Vec<ClientDealCancellationProposal>,
+) -> CancelDealsReturn {
+    apply_ok(
+        v,
+        worker,
+        &STORAGE_MARKET_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        MarketMethod::CancelDealsExported as u64,
+        Some(CancelDealsParams { cancellations }),
+    )
+    .deserialize()
+    .unwrap()
+}
+
+fn market_balance(v: &dyn VM, addr: &Address) -> GetBalanceReturn {
+    apply_ok(
+        v,
+        addr,
+        &STORAGE_MARKET_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        MarketMethod::GetBalanceExported as u64,
+        Some(*addr),
+    )
+    .deserialize()
+    .unwrap()
+}
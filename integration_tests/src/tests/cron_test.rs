@@ -0,0 +1,99 @@
+use export_macro::vm_test;
+use fil_actor_cron::{
+    AddEntryParams, AddEntryReturn, Method as CronMethod, RemoveEntryParams, SetGovernorParams,
+};
+use fil_actors_runtime::runtime::Policy;
+use fil_actors_runtime::{CRON_ACTOR_ADDR, SYSTEM_ACTOR_ADDR};
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::MethodNum;
+use num_traits::Zero;
+use vm_api::trace::InvocationTrace;
+use vm_api::util::{apply_code, apply_ok, apply_ok_implicit};
+use vm_api::VM;
+
+use crate::util::{assert_invariants, create_accounts, cron_tick};
+
+const PING_METHOD: MethodNum = frc42_dispatch::method_hash!("Ping");
+
+/// Entries added to the cron actor by its governor are called on schedule by the cron tick,
+/// until they're removed.
+#[vm_test]
+pub fn cron_scheduled_entries_test(v: &dyn VM) {
+    let addrs = create_accounts(v, 3, &TokenAmount::from_whole(10_000));
+    let (governor, receiver, other) = (addrs[0], addrs[1], addrs[2]);
+    // The cron actor was constructed without a governor, so the system actor appoints one.
+    apply_ok_implicit(
+        v,
+        &SYSTEM_ACTOR_ADDR,
+        &CRON_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        CronMethod::SetGovernor as u64,
+        Some(SetGovernorParams { governor: Some(governor) }),
+    );
+
+    let params =
+        AddEntryParams { receiver, method_num: PING_METHOD, period: 3, gas_limit: 100_000_000 };
+
+    // Only the governor can add entries.
+    apply_code(
+        v,
+        &other,
+        &CRON_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        CronMethod::AddEntryExported as u64,
+        Some(params.clone()),
+        ExitCode::USR_FORBIDDEN,
+    );
+    let ret: AddEntryReturn = apply_ok(
+        v,
+        &governor,
+        &CRON_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        CronMethod::AddEntryExported as u64,
+        Some(params),
+    )
+    .deserialize()
+    .unwrap();
+
+    // The entry is called every third epoch.
+    let start = v.epoch();
+    let mut called_at = vec![];
+    for epoch in start + 1..=start + 9 {
+        v.set_epoch(epoch);
+        if tick_calls(v, &receiver) > 0 {
+            called_at.push(epoch);
+        }
+    }
+    assert_eq!(vec![start + 3, start + 6, start + 9], called_at);
+
+    // Once removed, the entry is no longer called.
+    apply_ok(
+        v,
+        &governor,
+        &CRON_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        CronMethod::RemoveEntryExported as u64,
+        Some(RemoveEntryParams { id: ret.id }),
+    );
+    v.set_epoch(start + 12);
+    assert_eq!(0, tick_calls(v, &receiver));
+
+    v.set_epoch(start + 13);
+    assert_invariants(v, &Policy::default(), None);
+}
+
+// Runs the cron tick, returning the number of calls it made to the receiver.
+fn tick_calls(v: &dyn VM, receiver: &Address) -> usize {
+    v.take_invocations();
+    cron_tick(v);
+    let trace = v.take_invocations().pop().unwrap();
+    trace
+        .subinvocations
+        .iter()
+        .filter(|sub: &&InvocationTrace| {
+            sub.to == *receiver && sub.method == PING_METHOD && sub.exit_code.is_success()
+        })
+        .count()
+}
@@ -149,6 +149,7 @@ pub fn evm_call_test(v: &dyn VM) {
 
 #[vm_test]
 pub fn evm_create_test(v: &dyn VM) {
+    let eip6780 = v.network_version() >= fil_actor_evm::EIP_6780_NETWORK_VERSION;
     let account = create_accounts(v, 1, &TokenAmount::from_whole(10_000))[0];
 
     let address = id_to_eth(account.id().unwrap());
@@ -251,7 +252,10 @@ pub fn evm_create_test(v: &dyn VM) {
             );
         }
 
-        // It should now be dead.
+        // Before EIP-6780, it should now be dead. Since EIP-6780, a contract that self-destructs
+        // in a later transaction than the one that created it only sends away its funds. It should
+        // still be alive, and the recursive variant should have updated its value after
+        // self-destructing.
         {
             let func = factory_child.get_value();
             let call_params = func.calldata().expect("should serialize");
@@ -271,20 +275,51 @@ pub fn evm_create_test(v: &dyn VM) {
             );
             let BytesDe(return_value) =
                 call_result.ret.unwrap().deserialize().expect("failed to deserialize results");
-            assert!(return_value.is_empty());
+            if eip6780 {
+                let res: u32 = factory_child
+                    .decode_output(&func.function.name, return_value)
+                    .expect("failed to decode return");
+                assert_eq!(res, if recursive { 1234 } else { 42 });
+            } else {
+                assert!(return_value.is_empty());
+            }
         }
         child_addr_eth
     };
 
-    // Test CREATE2 twice because we should be able to deploy over an existing contract.
-    let eth_addr1 = test_func(factory.create_2([0; 32], 42), false);
-    let eth_addr2 = test_func(factory.create_2([0; 32], 42), false);
-    assert_eq!(eth_addr1, eth_addr2);
+    if !eip6780 {
+        // Test CREATE2 twice because we should be able to deploy over a self-destructed contract.
+        let eth_addr1 = test_func(factory.create_2([0; 32], 42), false);
+        let eth_addr2 = test_func(factory.create_2([0; 32], 42), false);
+        assert_eq!(eth_addr1, eth_addr2);
+
+        // Recursive self-destruct should work.
+        let eth_addr1 = test_func(factory.create_2([1; 32], 42), true);
+        let eth_addr2 = test_func(factory.create_2([1; 32], 42), false);
+        assert_eq!(eth_addr1, eth_addr2);
+    } else {
+        // Test CREATE2, then check that we can't deploy over the child, which is still alive.
+        test_func(factory.create_2([0; 32], 42), false);
+        let call_params = factory.create_2([0; 32], 42).calldata().expect("should serialize");
+        let call_result = v
+            .execute_message(
+                &account,
+                &create_return.robust_address.unwrap(),
+                &TokenAmount::zero(),
+                fil_actor_evm::Method::InvokeContract as u64,
+                Some(serialize_ok(&ContractParams(call_params.to_vec()))),
+            )
+            .unwrap();
+        assert_eq!(
+            call_result.code.value(),
+            33,
+            "expected contract revert {}",
+            call_result.message
+        );
 
-    // Recursive self-destruct should work.
-    let eth_addr1 = test_func(factory.create_2([1; 32], 42), true);
-    let eth_addr2 = test_func(factory.create_2([1; 32], 42), false);
-    assert_eq!(eth_addr1, eth_addr2);
+        // Recursive self-destruct should work.
+        test_func(factory.create_2([1; 32], 42), true);
+    }
 
     // Then test create and expect two different addrs.
     let eth_addr1 = test_func(factory.create(42), false);
@@ -753,3 +788,67 @@ pub fn evm_init_revert_data_test(v: &dyn VM) {
     expected[31] = 0x42;
     assert_eq!(revert_data, expected);
 }
+
+/// Compares the gas charged for loading storage slots, with and without an access list.
+/// Expects a network version at which warm accesses are served from memory (EIP-2929).
+#[vm_test]
+pub fn evm_access_list_test(v: &dyn VM) {
+    let account = create_accounts(v, 1, &TokenAmount::from_whole(10_000))[0];
+
+    // Creates a contract that loads two storage slots and returns their sum.
+    let create = |a: u8, b: u8| -> fil_actor_eam::CreateExternalReturn {
+        // init code:
+        // PUSH1 0x0f; PUSH1 0x0c; PUSH1 0x00; CODECOPY; PUSH1 0x0f; PUSH1 0x00; RETURN
+        // runtime code:
+        // PUSH1 a; SLOAD; PUSH1 b; SLOAD; ADD;
+        // PUSH1 0x00; MSTORE; PUSH1 0x20; PUSH1 0x00; RETURN
+        let initcode = vec![
+            0x60, 0x0f, 0x60, 0x0c, 0x60, 0x00, 0x39, 0x60, 0x0f, 0x60, 0x00, 0xf3, //
+            0x60, a, 0x54, 0x60, b, 0x54, 0x01, //
+            0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ];
+        apply_ok(
+            v,
+            &account,
+            &EAM_ACTOR_ADDR,
+            &TokenAmount::zero(),
+            fil_actor_eam::Method::CreateExternal as u64,
+            Some(fil_actor_eam::CreateExternalParams(initcode)),
+        )
+        .deserialize()
+        .unwrap()
+    };
+    // Invokes a contract, returning the gas it charged.
+    let invoke = |contract: &fil_actor_eam::CreateExternalReturn,
+                  access_list: Option<Vec<fil_actor_evm::AccessListItem>>|
+     -> i64 {
+        let BytesDe(ret) = apply_ok(
+            v,
+            &account,
+            &Address::new_id(contract.actor_id),
+            &TokenAmount::zero(),
+            fil_actor_evm::Method::InvokeContract as u64,
+            Some(fil_actor_evm::InvokeContractParams { input_data: vec![], access_list }),
+        )
+        .deserialize()
+        .unwrap();
+        assert_eq!(U256::zero(), U256::from_big_endian(&ret));
+        v.take_invocations().last().unwrap().gas_charged
+    };
+
+    let repeated = create(1, 1);
+    let distinct = create(1, 2);
+    let repeated_gas = invoke(&repeated, None);
+    let distinct_gas = invoke(&distinct, None);
+    // Cold loads only pay for the state lookup, which the test VM doesn't meter. The repeated load
+    // is warm, and charged by the actor.
+    assert_eq!(0, distinct_gas);
+    assert!(repeated_gas > 0);
+
+    // Pre-warming the slot with an access list makes both loads warm.
+    let access_list = vec![fil_actor_evm::AccessListItem {
+        address: repeated.eth_address,
+        storage_keys: vec![U256::from(1)],
+    }];
+    assert_eq!(2 * repeated_gas, invoke(&repeated, Some(access_list)));
+}
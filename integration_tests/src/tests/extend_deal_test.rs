@@ -0,0 +1,157 @@
+use export_macro::vm_test;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::Zero;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::signature::Signature;
+use fvm_shared::deal::DealID;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::piece::PaddedPieceSize;
+use fvm_shared::sector::{RegisteredSealProof, SectorNumber};
+
+use fil_actor_market::{
+    ClientDealExtensionProposal, DealExtensionProposal, DealSettlementSummary,
+    ExtendDealTermsParams, ExtendDealTermsReturn, GetDealTermParams, GetDealTermReturn,
+    Method as MarketMethod,
+};
+use fil_actor_miner::max_prove_commit_duration;
+use fil_actors_runtime::cbor::serialize;
+use fil_actors_runtime::runtime::Policy;
+use fil_actors_runtime::{EPOCHS_IN_DAY, STORAGE_MARKET_ACTOR_ADDR};
+use vm_api::util::apply_ok;
+use vm_api::VM;
+
+use crate::util::{
+    advance_by_deadline_to_epoch, create_accounts, create_miner, cron_tick, expect_invariants,
+    get_deal, invariant_failure_patterns, make_piece_manifests_from_deal_ids, market_add_balance,
+    market_publish_deal, miner_precommit_one_sector_v2, miner_prove_sector,
+    precommit_meta_data_from_deals, provider_settle_deal_payments,
+};
+
+/// Extends an activated deal in place, up to the expiration of the sector holding it.
+#[vm_test]
+pub fn extend_deal_term_test(v: &dyn VM) {
+    let addrs = create_accounts(v, 2, &TokenAmount::from_whole(10_000));
+    let seal_proof = RegisteredSealProof::StackedDRG32GiBV1P1;
+    let (worker, client) = (addrs[0], addrs[1]);
+    let sector_number: SectorNumber = 100;
+
+    let (miner_id, _) = create_miner(
+        v,
+        &worker,
+        &worker,
+        seal_proof.registered_window_post_proof().unwrap(),
+        &TokenAmount::from_whole(1_000),
+    );
+    v.set_epoch(200);
+    market_add_balance(v, &client, &client, &TokenAmount::from_whole(3));
+    market_add_balance(v, &worker, &miner_id, &TokenAmount::from_whole(64));
+
+    // Publish a deal and activate it in a sector that outlives it.
+    let deal_start = v.epoch() + max_prove_commit_duration(&Policy::default(), seal_proof).unwrap();
+    let deal_term = 180 * EPOCHS_IN_DAY;
+    let sector_expiration = deal_start + 300 * EPOCHS_IN_DAY;
+    let deals = market_publish_deal(
+        v,
+        &worker,
+        &client,
+        &miner_id,
+        "deal1".to_string(),
+        PaddedPieceSize(32u64 << 30),
+        false,
+        deal_start,
+        deal_term,
+    )
+    .ids;
+    let deal_id = deals[0];
+    miner_precommit_one_sector_v2(
+        v,
+        &worker,
+        &miner_id,
+        seal_proof,
+        sector_number,
+        precommit_meta_data_from_deals(v, &deals, seal_proof, false),
+        true,
+        sector_expiration,
+    );
+    advance_by_deadline_to_epoch(v, &miner_id, deal_start);
+    miner_prove_sector(
+        v,
+        &worker,
+        &miner_id,
+        sector_number,
+        make_piece_manifests_from_deal_ids(v, deals.clone()),
+    );
+    cron_tick(v);
+
+    // The deal cannot be extended beyond its sector's expiration.
+    let price = get_deal(v, deal_id).storage_price_per_epoch;
+    let ret = extend_deal(v, &worker, deal_id, sector_expiration + 1, &price);
+    assert_eq!(vec![ExitCode::USR_ILLEGAL_ARGUMENT], ret.results.codes());
+
+    // The extension is settled up to the current epoch and completes at its new end.
+    let settled = v.epoch();
+    let new_price = &price * 2;
+    let ret = extend_deal(v, &worker, deal_id, sector_expiration, &new_price);
+    assert!(ret.results.all_ok());
+
+    let term: GetDealTermReturn = apply_ok(
+        v,
+        &worker,
+        &STORAGE_MARKET_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        MarketMethod::GetDealTermExported as u64,
+        Some(GetDealTermParams { id: deal_id }),
+    )
+    .deserialize()
+    .unwrap();
+    assert_eq!(sector_expiration - deal_start, term.duration);
+
+    advance_by_deadline_to_epoch(v, &miner_id, settled + 100);
+    let ret = provider_settle_deal_payments(v, &miner_id, &deals);
+    assert_eq!(
+        &DealSettlementSummary { completed: false, payment: &new_price * (v.epoch() - settled) },
+        ret.settlements.first().unwrap()
+    );
+
+    expect_invariants(
+        v,
+        &Policy::default(),
+        &[invariant_failure_patterns::REWARD_STATE_EPOCH_MISMATCH.to_owned()],
+        None,
+    );
+}
+
+fn extend_deal(
+    v: &dyn VM,
+    worker: &Address,
+    deal_id: DealID,
+    end_epoch: ChainEpoch,
+    storage_price_per_epoch: &TokenAmount,
+) -> ExtendDealTermsReturn {
+    let proposal = DealExtensionProposal {
+        deal_id,
+        end_epoch,
+        storage_price_per_epoch: storage_price_per_epoch.clone(),
+        expiration: v.epoch(),
+    };
+    // The test VM accepts a signature equal to the signed message.
+    let signature =
+        Signature::new_secp256k1(serialize(&proposal, "deal extension proposal").unwrap().to_vec());
+    apply_ok(
+        v,
+        worker,
+        &STORAGE_MARKET_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        MarketMethod::ExtendDealTermsExported as u64,
+        Some(ExtendDealTermsParams {
+            extensions: vec![ClientDealExtensionProposal {
+                proposal,
+                client_signature: signature,
+                eip712_signature: false,
+            }],
+        }),
+    )
+    .deserialize()
+    .unwrap()
+}
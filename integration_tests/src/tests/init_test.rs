@@ -1,13 +1,18 @@
 use export_macro::vm_test;
-use fil_actor_init::Exec4Return;
+use fil_actor_init::{
+    Exec4Return, ExecReturn, LookupAddressesParams, LookupAddressesReturn, ResolveAddressParams,
+    ResolveAddressReturn,
+};
 use fil_actors_runtime::{
     cbor::serialize, runtime::EMPTY_ARR_CID, test_utils::MULTISIG_ACTOR_CODE_ID, EAM_ACTOR_ADDR,
     EAM_ACTOR_ID, INIT_ACTOR_ADDR,
 };
-use fvm_shared::{address::Address, econ::TokenAmount, error::ExitCode, METHOD_SEND};
+use fvm_shared::{address::Address, econ::TokenAmount, error::ExitCode, ActorID, METHOD_SEND};
 use num_traits::Zero;
-use vm_api::{builtin::Type, util::serialize_ok, VM};
+use vm_api::util::{apply_ok, pk_addrs_from, serialize_ok};
+use vm_api::{builtin::Type, VM};
 
+use crate::util::create_accounts_seeded;
 use crate::{FIRST_TEST_USER_ADDR, TEST_FAUCET_ADDR};
 
 fn assert_placeholder_actor(exp_bal: TokenAmount, v: &dyn VM, addr: Address) {
@@ -83,3 +88,94 @@ pub fn placeholder_deploy_test(v: &dyn VM) {
     let msig_ctor_res = deploy();
     assert_eq!(ExitCode::USR_FORBIDDEN, msig_ctor_res.code);
 }
+
+/// Resolves account and placeholder addresses through the init actor's exported methods, and looks
+/// up the addresses of actors by ID.
+#[vm_test]
+pub fn init_lookup_addresses_test(v: &dyn VM) {
+    let seed = 1234;
+    let ids = create_accounts_seeded(v, 5, &TokenAmount::from_whole(10), seed, &TEST_FAUCET_ADDR);
+    let pk_addrs = pk_addrs_from(seed, 5);
+    let subaddr = b"lookup";
+    let placeholder = Address::new_delegated(EAM_ACTOR_ID, subaddr).unwrap();
+    apply_ok(v, &ids[0], &placeholder, &TokenAmount::from_atto(1), METHOD_SEND, None::<()>);
+    let placeholder_id = v.resolve_id_address(&placeholder).unwrap();
+
+    let resolve = |address: Address| -> Option<ActorID> {
+        let ret: ResolveAddressReturn = apply_ok(
+            v,
+            &ids[0],
+            &INIT_ACTOR_ADDR,
+            &TokenAmount::zero(),
+            fil_actor_init::Method::ResolveAddressExported as u64,
+            Some(ResolveAddressParams { address }),
+        )
+        .deserialize()
+        .unwrap();
+        ret.id
+    };
+    assert_eq!(Some(ids[3].id().unwrap()), resolve(pk_addrs[3]));
+    assert_eq!(Some(placeholder_id.id().unwrap()), resolve(placeholder));
+    assert_eq!(None, resolve(Address::new_actor(b"unknown")));
+
+    let lookup = |id: &Address| -> LookupAddressesReturn {
+        apply_ok(
+            v,
+            &ids[0],
+            &INIT_ACTOR_ADDR,
+            &TokenAmount::zero(),
+            fil_actor_init::Method::LookupAddressesExported as u64,
+            Some(LookupAddressesParams { id: id.id().unwrap() }),
+        )
+        .deserialize()
+        .unwrap()
+    };
+
+    // An account has its key address.
+    assert_eq!(vec![pk_addrs[2]], lookup(&ids[2]).addresses);
+
+    // An actor created by Exec has no other addresses, as its robust address isn't indexed.
+    let msig_ctor_params = serialize(
+        &fil_actor_multisig::ConstructorParams {
+            signers: vec![ids[0]],
+            num_approvals_threshold: 1,
+            unlock_duration: 0,
+            start_epoch: 0,
+        },
+        "multisig ctor params",
+    )
+    .unwrap();
+    let exec_ret: ExecReturn = apply_ok(
+        v,
+        &ids[0],
+        &INIT_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        fil_actor_init::Method::Exec as u64,
+        Some(fil_actor_init::ExecParams {
+            code_cid: *MULTISIG_ACTOR_CODE_ID,
+            constructor_params: msig_ctor_params.clone(),
+        }),
+    )
+    .deserialize()
+    .unwrap();
+    assert!(lookup(&exec_ret.id_address).addresses.is_empty());
+
+    // A placeholder has its delegated address, which it keeps once an actor is deployed to it.
+    assert_eq!(vec![placeholder], lookup(&placeholder_id).addresses);
+    let exec4_ret: Exec4Return = apply_ok(
+        v,
+        &EAM_ACTOR_ADDR,
+        &INIT_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        fil_actor_init::Method::Exec4 as u64,
+        Some(fil_actor_init::Exec4Params {
+            code_cid: *MULTISIG_ACTOR_CODE_ID,
+            constructor_params: msig_ctor_params,
+            subaddress: subaddr[..].to_owned().into(),
+        }),
+    )
+    .deserialize()
+    .unwrap();
+    assert_eq!(placeholder_id, exec4_ret.id_address);
+    assert_eq!(vec![placeholder], lookup(&placeholder_id).addresses);
+}
@@ -4,24 +4,36 @@ mod batch_onboarding;
 pub use batch_onboarding::*;
 mod batch_onboarding_deals_test;
 pub use batch_onboarding_deals_test::*;
+mod cancel_deal_test;
+pub use cancel_deal_test::*;
 mod change_beneficiary_test;
 pub use change_beneficiary_test::*;
 mod change_owner_test;
 pub use change_owner_test::*;
 mod commit_post_test;
 pub use commit_post_test::*;
+mod cron_test;
+pub use cron_test::*;
 mod datacap_tests;
 pub use datacap_tests::*;
 mod evm_test;
 pub use evm_test::*;
+mod extend_deal_test;
+pub use extend_deal_test::*;
 mod extend_sectors_test;
 pub use extend_sectors_test::*;
 mod market_miner_withdrawal_test;
 pub use market_miner_withdrawal_test::*;
+mod multi_key_account_test;
+pub use multi_key_account_test::*;
+mod passkey_account_test;
+pub use passkey_account_test::*;
 mod multisig_test;
 pub use multisig_test::*;
 mod init_test;
 pub use init_test::*;
+mod paych_test;
+pub use paych_test::*;
 mod power_scenario_tests;
 pub use power_scenario_tests::*;
 mod publish_deals_test;
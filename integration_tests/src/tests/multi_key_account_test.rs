@@ -0,0 +1,217 @@
+use export_macro::vm_test;
+use fil_actor_init::ExecReturn;
+use fil_actor_market::{
+    ClientDealProposal, Method as MarketMethod, PublishStorageDealsParams,
+    PublishStorageDealsReturn,
+};
+use fil_actor_miner::{max_prove_commit_duration, Method as MinerMethod, WithdrawBalanceParams};
+use fil_actor_multikey::types::{
+    ConstructorParams as MultiKeyConstructorParams, ExecuteParams, ExecuteReturn, KeySignature,
+    MultiKeySignature,
+};
+use fil_actor_multikey::{compute_execute_digest, Method as MultiKeyMethod};
+use fil_actors_runtime::cbor::serialize;
+use fil_actors_runtime::runtime::Policy;
+use fil_actors_runtime::test_utils::MULTIKEY_ACTOR_CODE_ID;
+use fil_actors_runtime::{EPOCHS_IN_DAY, INIT_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR};
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::Zero;
+use fvm_shared::chainid::ChainID;
+use fvm_shared::crypto::signature::{Signature, SignatureType};
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::sector::RegisteredSealProof;
+use fvm_shared::MethodNum;
+use vm_api::util::{apply_code, apply_ok, pk_addrs_from};
+use vm_api::VM;
+
+use crate::util::{
+    assert_invariants, change_owner_address, create_accounts, create_miner, generate_deal_proposal,
+    market_add_balance, miner_info,
+};
+
+/// Creates a 2-of-3 multi-key account through the init actor, and shows that it can:
+/// - publish a deal as a client, authenticated by signatures from two of its keys
+/// - become a miner's owner and withdraw the miner's balance, through signed calls to `Execute`
+#[vm_test]
+pub fn multi_key_account_test(v: &dyn VM) {
+    let addrs = create_accounts(v, 1, &TokenAmount::from_whole(10_000));
+    let worker = addrs[0];
+    let keys = pk_addrs_from(4321, 3);
+    let account = create_multi_key_account(v, &worker, &keys, 2);
+
+    // Deal client.
+    let seal_proof = RegisteredSealProof::StackedDRG32GiBV1P1;
+    let miner = create_miner(
+        v,
+        &worker,
+        &worker,
+        seal_proof.registered_window_post_proof().unwrap(),
+        &TokenAmount::from_whole(1_000),
+    )
+    .0;
+    market_add_balance(v, &worker, &account, &TokenAmount::from_whole(100));
+    market_add_balance(v, &worker, &miner, &TokenAmount::from_whole(100));
+    let deal_start = v.epoch() + max_prove_commit_duration(&Policy::default(), seal_proof).unwrap();
+    let proposal = generate_deal_proposal(
+        &account,
+        &miner,
+        &TokenAmount::from_whole(1),
+        &TokenAmount::from_whole(1),
+        deal_start,
+        deal_start + 181 * EPOCHS_IN_DAY,
+    );
+    let proposal_bytes = serialize(&proposal, "deal proposal").unwrap().to_vec();
+
+    // One signature isn't enough.
+    let publish = |signers: &[Address]| PublishStorageDealsParams {
+        deals: vec![ClientDealProposal {
+            proposal: proposal.clone(),
+            client_signature: multi_key_signature(signers, &proposal_bytes),
+            eip712_signature: false,
+        }],
+    };
+    apply_code(
+        v,
+        &worker,
+        &STORAGE_MARKET_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        MarketMethod::PublishStorageDeals as u64,
+        Some(publish(&keys[..1])),
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+    );
+    let ret: PublishStorageDealsReturn = apply_ok(
+        v,
+        &worker,
+        &STORAGE_MARKET_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        MarketMethod::PublishStorageDeals as u64,
+        Some(publish(&keys[1..])),
+    )
+    .deserialize()
+    .unwrap();
+    assert_eq!(1, ret.ids.len());
+
+    // Miner owner.
+    change_owner_address(v, &worker, &miner, &account);
+    let ret = execute(
+        v,
+        &worker,
+        &account,
+        &keys[..2],
+        0,
+        &miner,
+        MinerMethod::ChangeOwnerAddress as u64,
+        RawBytes::serialize(account).unwrap(),
+    );
+    assert_eq!(ExitCode::OK, ret.code);
+    assert_eq!(account, miner_info(v, &miner).owner);
+
+    let amount = TokenAmount::from_whole(10);
+    let ret = execute(
+        v,
+        &worker,
+        &account,
+        &[keys[2], keys[0]],
+        1,
+        &miner,
+        MinerMethod::WithdrawBalance as u64,
+        RawBytes::serialize(WithdrawBalanceParams { amount_requested: amount.clone() }).unwrap(),
+    );
+    assert_eq!(ExitCode::OK, ret.code);
+    assert_eq!(amount, v.balance(&account));
+
+    // The old owner can no longer withdraw.
+    apply_code(
+        v,
+        &worker,
+        &miner,
+        &TokenAmount::zero(),
+        MinerMethod::WithdrawBalance as u64,
+        Some(WithdrawBalanceParams { amount_requested: amount }),
+        ExitCode::USR_FORBIDDEN,
+    );
+
+    assert_invariants(v, &Policy::default(), None);
+}
+
+fn create_multi_key_account(
+    v: &dyn VM,
+    creator: &Address,
+    signers: &[Address],
+    threshold: u64,
+) -> Address {
+    let ctor_params = serialize(
+        &MultiKeyConstructorParams { signers: signers.to_vec(), threshold },
+        "multi-key account ctor params",
+    )
+    .unwrap();
+    let ret: ExecReturn = apply_ok(
+        v,
+        creator,
+        &INIT_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        fil_actor_init::Method::Exec as u64,
+        Some(fil_actor_init::ExecParams {
+            code_cid: *MULTIKEY_ACTOR_CODE_ID,
+            constructor_params: ctor_params,
+        }),
+    )
+    .deserialize()
+    .unwrap();
+    ret.id_address
+}
+
+// The test VM accepts a signature equal to the signed bytes.
+fn key_signatures(signers: &[Address], plaintext: &[u8]) -> Vec<KeySignature> {
+    signers
+        .iter()
+        .map(|signer| KeySignature { signer: *signer, signature: plaintext.to_vec() })
+        .collect()
+}
+
+fn multi_key_signature(signers: &[Address], plaintext: &[u8]) -> Signature {
+    let signatures = key_signatures(signers, plaintext);
+    Signature {
+        sig_type: SignatureType::BLS,
+        bytes: serialize(&MultiKeySignature { signatures }, "multi-key signature")
+            .unwrap()
+            .to_vec(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn execute(
+    v: &dyn VM,
+    from: &Address,
+    account: &Address,
+    signers: &[Address],
+    nonce: u64,
+    to: &Address,
+    method: MethodNum,
+    params: RawBytes,
+) -> ExecuteReturn {
+    let mut params = ExecuteParams {
+        to: *to,
+        value: TokenAmount::zero(),
+        method,
+        params,
+        nonce,
+        signatures: vec![],
+    };
+    // The test VM's chain ID is 0.
+    let digest =
+        compute_execute_digest(ChainID::from(0), account, &params, v.primitives()).unwrap();
+    params.signatures = key_signatures(signers, &digest);
+    apply_ok(
+        v,
+        from,
+        account,
+        &TokenAmount::zero(),
+        MultiKeyMethod::ExecuteExported as u64,
+        Some(params),
+    )
+    .deserialize()
+    .unwrap()
+}
@@ -1,28 +1,39 @@
+use ethers::core::types::{Bytes, I256, U256 as EthU256};
+use ethers::prelude::abigen;
+use ethers::providers::Provider;
 use export_macro::vm_test;
 use fil_actor_init::ExecReturn;
 use fil_actor_multisig::{
-    compute_proposal_hash, Method as MsigMethod, PendingTxnMap, ProposeParams, RemoveSignerParams,
-    State as MsigState, SwapSignerParams, Transaction, TxnID, TxnIDParams, PENDING_TXN_CONFIG,
+    compute_proposal_hash, GetAvailableBalanceReturn, GetPendingTransactionsParams,
+    GetPendingTransactionsReturn, GetSignersReturn, Method as MsigMethod, PendingTxnMap,
+    ProposeParams, RemoveSignerParams, State as MsigState, SwapSignerParams, Transaction, TxnID,
+    TxnIDParams, PENDING_TXN_CONFIG,
 };
 use fil_actors_runtime::cbor::serialize;
 use fil_actors_runtime::runtime::Policy;
 use fil_actors_runtime::test_utils::*;
-use fil_actors_runtime::{INIT_ACTOR_ADDR, SYSTEM_ACTOR_ADDR};
-use fvm_ipld_encoding::RawBytes;
+use fil_actors_runtime::{EAM_ACTOR_ADDR, INIT_ACTOR_ADDR, SYSTEM_ACTOR_ADDR};
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::{BytesDe, RawBytes, CBOR};
 use fvm_shared::address::Address;
 use fvm_shared::bigint::Zero;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ExitCode;
+use fvm_shared::sys::SendFlags;
 use fvm_shared::METHOD_SEND;
 use std::collections::HashSet;
 use std::iter::FromIterator;
+use std::sync::Arc;
 use vm_api::trace::ExpectInvocation;
 use vm_api::util::{apply_code, apply_ok, get_state, DynBlockstore};
 use vm_api::VM;
 
 use crate::expects::Expect;
+use crate::tests::{id_to_eth, ContractParams};
 use crate::util::{assert_invariants, create_accounts};
 
+abigen!(CallActorPrecompile, "../actors/evm/tests/contracts/CallActorPrecompile.abi");
+
 #[vm_test]
 pub fn proposal_hash_test(v: &dyn VM) {
     let addrs = create_accounts(v, 3, &TokenAmount::from_whole(10_000));
@@ -40,6 +51,7 @@ pub fn proposal_hash_test(v: &dyn VM) {
         value: fil_delta.clone(),
         method: METHOD_SEND,
         params: RawBytes::default(),
+        expiration: None,
     };
     apply_ok(
         v,
@@ -55,6 +67,7 @@ pub fn proposal_hash_test(v: &dyn VM) {
         value: &fil_delta - TokenAmount::from_atto(1), // incorrect send amount not consistent with proposal
         method: METHOD_SEND,
         approved: vec![alice],
+        expiration: None,
         params: RawBytes::default(),
     };
 
@@ -76,6 +89,7 @@ pub fn proposal_hash_test(v: &dyn VM) {
         value: fil_delta.clone(),
         method: METHOD_SEND,
         approved: vec![alice],
+        expiration: None,
         params: RawBytes::default(),
     };
 
@@ -119,6 +133,7 @@ pub fn test_delete_self_inner_test(v: &dyn VM, signers: u64, threshold: usize, r
         value: TokenAmount::zero(),
         method: MsigMethod::RemoveSigner as u64,
         params: remove_param_ser,
+        expiration: None,
     };
 
     // first proposal goes ok and should have txnid = 0
@@ -181,6 +196,7 @@ pub fn swap_self_1_of_2_test(v: &dyn VM) {
         value: TokenAmount::zero(),
         method: MsigMethod::SwapSigner as u64,
         params: serialize(&swap_params, "swap params").unwrap(),
+        expiration: None,
     };
     // alice succeeds when trying to execute the tx swapping alice for chuck
     apply_ok(
@@ -210,6 +226,7 @@ pub fn swap_self_2_of_3_test(v: &dyn VM) {
         value: TokenAmount::zero(),
         method: MsigMethod::SwapSigner as u64,
         params: serialize(&swap_params, "swap params").unwrap(),
+        expiration: None,
     };
 
     // proposal from swapped addr goes ok with txnid 0
@@ -242,6 +259,7 @@ pub fn swap_self_2_of_3_test(v: &dyn VM) {
         value: TokenAmount::zero(),
         method: MsigMethod::SwapSigner as u64,
         params: serialize(&swap_params, "swap params").unwrap(),
+        expiration: None,
     };
 
     // proposal from non swapped goes ok, txnid = 1
@@ -268,6 +286,111 @@ pub fn swap_self_2_of_3_test(v: &dyn VM) {
     assert_invariants(v, &Policy::default(), None)
 }
 
+#[vm_test]
+pub fn multisig_getters_from_evm_test(v: &dyn VM) {
+    let addrs = create_accounts(v, 3, &TokenAmount::from_whole(10_000));
+    let (alice, bob, chuck) = (addrs[0], addrs[1], addrs[2]);
+    let msig_addr = create_msig(v, &[alice, bob], 2);
+
+    // leave a transaction pending
+    let propose_params = ProposeParams {
+        to: chuck,
+        value: TokenAmount::zero(),
+        method: METHOD_SEND,
+        params: RawBytes::default(),
+        expiration: None,
+    };
+    apply_ok(
+        v,
+        &alice,
+        &msig_addr,
+        &TokenAmount::zero(),
+        MsigMethod::Propose as u64,
+        Some(propose_params),
+    );
+
+    // deploy a contract that calls actors through the call_actor_id precompile
+    let bytecode =
+        hex::decode(include_str!("../../../actors/evm/tests/contracts/CallActorPrecompile.hex"))
+            .unwrap();
+    let create_return: fil_actor_eam::CreateExternalReturn = apply_ok(
+        v,
+        &alice,
+        &EAM_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        fil_actor_eam::Method::CreateExternal as u64,
+        Some(fil_actor_eam::CreateExternalParams(bytecode)),
+    )
+    .deserialize()
+    .unwrap();
+    let contract_addr = create_return.robust_address.unwrap();
+    let (client, _mock) = Provider::mocked();
+    let contract = CallActorPrecompile::new(id_to_eth(create_return.actor_id), Arc::new(client));
+
+    let call_getter = |method: MsigMethod, params: Option<IpldBlock>| -> RawBytes {
+        let (codec, params) = params.map_or((0, vec![]), |p| (p.codec, p.data));
+        let call = contract.call_actor_id(
+            method as u64,
+            EthU256::zero(),
+            SendFlags::READ_ONLY.bits(),
+            codec,
+            params.into(),
+            msig_addr.id().unwrap(),
+        );
+        let BytesDe(ret) = apply_ok(
+            v,
+            &alice,
+            &contract_addr,
+            &TokenAmount::zero(),
+            fil_actor_evm::Method::InvokeContract as u64,
+            Some(ContractParams(call.calldata().unwrap().to_vec())),
+        )
+        .deserialize()
+        .unwrap();
+        let (success, exit_code, codec, ret): (bool, I256, u64, Bytes) =
+            contract.decode_output(&call.function.name, ret).unwrap();
+        assert!(success);
+        assert_eq!(I256::zero(), exit_code);
+        assert_eq!(CBOR, codec);
+        RawBytes::new(ret.to_vec())
+    };
+
+    let signers: GetSignersReturn =
+        call_getter(MsigMethod::GetSignersExported, None).deserialize().unwrap();
+    assert_eq!(vec![alice, bob], signers.signers);
+    assert_eq!(vec![1, 1], signers.signer_weights);
+    assert_eq!(2, signers.num_approvals_threshold);
+
+    let pending: GetPendingTransactionsReturn = call_getter(
+        MsigMethod::GetPendingTransactionsExported,
+        IpldBlock::serialize_cbor(&GetPendingTransactionsParams { start: None, limit: 10 })
+            .unwrap(),
+    )
+    .deserialize()
+    .unwrap();
+    assert_eq!(
+        vec![(
+            TxnID(0),
+            Transaction {
+                to: chuck,
+                value: TokenAmount::zero(),
+                method: METHOD_SEND,
+                params: RawBytes::default(),
+                approved: vec![alice],
+                expiration: None,
+            }
+        )],
+        pending.transactions
+    );
+    assert_eq!(None, pending.next);
+
+    let available: GetAvailableBalanceReturn =
+        call_getter(MsigMethod::GetAvailableBalanceExported, None).deserialize().unwrap();
+    assert_eq!(TokenAmount::zero(), available.available_balance);
+
+    assert_invariants(v, &Policy::default(), None)
+}
+
 fn create_msig(v: &dyn VM, signers: &[Address], threshold: u64) -> Address {
     assert!(!signers.is_empty());
     let msig_ctor_params = serialize(
@@ -0,0 +1,158 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use export_macro::vm_test;
+use fil_actor_init::ExecReturn;
+use fil_actor_market::{
+    ClientDealProposal, Method as MarketMethod, PublishStorageDealsParams,
+    PublishStorageDealsReturn,
+};
+use fil_actor_miner::max_prove_commit_duration;
+use fil_actor_passkey::types::{ConstructorParams as PasskeyConstructorParams, WebAuthnAssertion};
+use fil_actors_runtime::cbor::serialize;
+use fil_actors_runtime::runtime::Policy;
+use fil_actors_runtime::test_utils::PASSKEY_ACTOR_CODE_ID;
+use fil_actors_runtime::{EPOCHS_IN_DAY, INIT_ACTOR_ADDR, STORAGE_MARKET_ACTOR_ADDR};
+use fvm_shared::address::Address;
+use fvm_shared::bigint::Zero;
+use fvm_shared::crypto::hash::SupportedHashes;
+use fvm_shared::crypto::signature::{Signature, SignatureType};
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use fvm_shared::sector::RegisteredSealProof;
+use hex_literal::hex;
+use p256::ecdsa::signature::hazmat::PrehashSigner;
+use p256::ecdsa::{Signature as EcdsaSignature, SigningKey};
+use vm_api::util::{apply_code, apply_ok};
+use vm_api::VM;
+
+use crate::util::{
+    assert_invariants, create_accounts, create_miner, generate_deal_proposal, market_add_balance,
+};
+
+const RP_ID: &str = "example.com";
+const ORIGIN: &str = "https://example.com";
+const PRIVATE_KEY: [u8; 32] =
+    hex!("1797486de8e758d651e53d67b4420a484ffb8fcc5f34052691e346ceeda89370");
+const PUBLIC_KEY: [u8; 65] = hex!(
+    "04"
+    "aac4d4b6d2f2e3f1a4775a356c12ff15756086b9ceb9a315bb5c512129383747"
+    "67d2e49dfe740c119a3ad4896cb408b41db568aa4748c0ec6a33faaa599c1e13"
+);
+
+/// Creates a passkey account through the init actor, and shows that it can publish a deal as a
+/// client, authenticated by a WebAuthn assertion over the deal proposal.
+#[vm_test]
+pub fn passkey_account_test(v: &dyn VM) {
+    let addrs = create_accounts(v, 1, &TokenAmount::from_whole(10_000));
+    let worker = addrs[0];
+    let account = create_passkey_account(v, &worker);
+
+    let seal_proof = RegisteredSealProof::StackedDRG32GiBV1P1;
+    let miner = create_miner(
+        v,
+        &worker,
+        &worker,
+        seal_proof.registered_window_post_proof().unwrap(),
+        &TokenAmount::from_whole(1_000),
+    )
+    .0;
+    market_add_balance(v, &worker, &account, &TokenAmount::from_whole(100));
+    market_add_balance(v, &worker, &miner, &TokenAmount::from_whole(100));
+    let deal_start = v.epoch() + max_prove_commit_duration(&Policy::default(), seal_proof).unwrap();
+    let proposal = generate_deal_proposal(
+        &account,
+        &miner,
+        &TokenAmount::from_whole(1),
+        &TokenAmount::from_whole(1),
+        deal_start,
+        deal_start + 181 * EPOCHS_IN_DAY,
+    );
+    let proposal_bytes = serialize(&proposal, "deal proposal").unwrap().to_vec();
+    let publish = |assertion: &WebAuthnAssertion| PublishStorageDealsParams {
+        deals: vec![ClientDealProposal {
+            proposal: proposal.clone(),
+            client_signature: Signature {
+                sig_type: SignatureType::BLS,
+                bytes: serialize(assertion, "webauthn assertion").unwrap().to_vec(),
+            },
+            eip712_signature: false,
+        }],
+    };
+
+    // An assertion from another origin is rejected.
+    apply_code(
+        v,
+        &worker,
+        &STORAGE_MARKET_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        MarketMethod::PublishStorageDeals as u64,
+        Some(publish(&assert(v, "https://example.org", &proposal_bytes))),
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+    );
+
+    let ret: PublishStorageDealsReturn = apply_ok(
+        v,
+        &worker,
+        &STORAGE_MARKET_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        MarketMethod::PublishStorageDeals as u64,
+        Some(publish(&assert(v, ORIGIN, &proposal_bytes))),
+    )
+    .deserialize()
+    .unwrap();
+    assert_eq!(1, ret.ids.len());
+
+    assert_invariants(v, &Policy::default(), None);
+}
+
+fn create_passkey_account(v: &dyn VM, creator: &Address) -> Address {
+    let ctor_params = serialize(
+        &PasskeyConstructorParams {
+            public_key: PUBLIC_KEY.to_vec(),
+            rp_id: RP_ID.to_string(),
+            origin: ORIGIN.to_string(),
+        },
+        "passkey account ctor params",
+    )
+    .unwrap();
+    let ret: ExecReturn = apply_ok(
+        v,
+        creator,
+        &INIT_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        fil_actor_init::Method::Exec as u64,
+        Some(fil_actor_init::ExecParams {
+            code_cid: *PASSKEY_ACTOR_CODE_ID,
+            constructor_params: ctor_params,
+        }),
+    )
+    .deserialize()
+    .unwrap();
+    ret.id_address
+}
+
+// Makes a WebAuthn assertion over a message, as an authenticator would from the given origin.
+fn assert(v: &dyn VM, origin: &str, message: &[u8]) -> WebAuthnAssertion {
+    let sha256 = |data: &[u8]| v.primitives().hash(SupportedHashes::Sha2_256, data);
+    // RP ID hash, User Present flag and signature counter.
+    let mut authenticator_data = sha256(RP_ID.as_bytes());
+    authenticator_data.extend_from_slice(&[0x01, 0, 0, 0, 1]);
+    let client_data_json = format!(
+        r#"{{"type":"webauthn.get","challenge":"{}","origin":"{}","crossOrigin":false}}"#,
+        URL_SAFE_NO_PAD.encode(sha256(message)),
+        origin
+    )
+    .into_bytes();
+    let mut signed = authenticator_data.clone();
+    signed.extend_from_slice(&sha256(&client_data_json));
+    let signature = sign(&sha256(&signed));
+    WebAuthnAssertion { authenticator_data, client_data_json, signature }
+}
+
+// Signs a digest as an authenticator would, normalizing s as a client must.
+fn sign(digest: &[u8]) -> Vec<u8> {
+    let key = SigningKey::from_bytes(&PRIVATE_KEY.into()).unwrap();
+    let signature: EcdsaSignature = key.sign_prehash(digest).unwrap();
+    let signature = signature.normalize_s().unwrap_or(signature);
+    signature.to_der().as_bytes().to_vec()
+}
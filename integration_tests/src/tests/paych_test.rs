@@ -0,0 +1,188 @@
+use export_macro::vm_test;
+use fil_actor_init::ExecReturn;
+use fil_actor_paych::{
+    LockHtlcParams, Method as PaychMethod, RedeemHtlcParams, RefundHtlcParams, SignedVoucher,
+    State as PaychState,
+};
+use fil_actors_runtime::cbor::serialize;
+use fil_actors_runtime::runtime::Policy;
+use fil_actors_runtime::test_utils::PAYCH_ACTOR_CODE_ID;
+use fil_actors_runtime::INIT_ACTOR_ADDR;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::Zero;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::crypto::signature::Signature;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::ExitCode;
+use vm_api::util::{apply_code, apply_ok, get_state};
+use vm_api::VM;
+
+use crate::util::{assert_invariants, create_accounts};
+
+/// Routes a payment from A to C through B over two channels, A->B and B->C, using
+/// hash-timelocked vouchers:
+/// - B locks A's voucher, then C locks B's voucher, which expires sooner
+/// - C redeems by revealing the secret, which B then uses to redeem upstream
+/// - a second payment is never completed, and A refunds it after it expires
+/// - both channels settle
+#[vm_test]
+pub fn paych_multi_hop_htlc_test(v: &dyn VM) {
+    let addrs = create_accounts(v, 3, &TokenAmount::from_whole(10_000));
+    let (a, b, c) = (addrs[0], addrs[1], addrs[2]);
+    let funds = TokenAmount::from_whole(100);
+    let ab = create_paych(v, &a, &b, &funds);
+    let bc = create_paych(v, &b, &c, &funds);
+
+    // C picks the secret and hands its hash to A.
+    let secret = b"route secret".to_vec();
+    let hash = v.primitives().hash_blake2b(&secret).to_vec();
+    let start = v.epoch();
+    let (ab_expiry, bc_expiry) = (start + 200, start + 100);
+
+    // Each hop locks the incoming voucher before forwarding, keeping 1 FIL as a fee.
+    let ab_voucher =
+        signed_voucher(&ab, 0, 1, TokenAmount::from_whole(11), hash.clone(), ab_expiry);
+    lock_htlc(v, &b, &ab, ab_voucher);
+    let bc_voucher =
+        signed_voucher(&bc, 0, 1, TokenAmount::from_whole(10), hash.clone(), bc_expiry);
+    lock_htlc(v, &c, &bc, bc_voucher);
+
+    let st: PaychState = get_state(v, &ab).unwrap();
+    assert_eq!(TokenAmount::from_whole(11), st.htlc_locked);
+    assert!(st.to_send.is_zero());
+    assert_eq!(ab_expiry + 1, st.min_settle_height);
+    assert_invariants(v, &Policy::default(), None);
+
+    // A wrong secret doesn't unlock the payment.
+    apply_code(
+        v,
+        &c,
+        &bc,
+        &TokenAmount::zero(),
+        PaychMethod::RedeemHtlcExported as u64,
+        Some(RedeemHtlcParams { lane: 0, secret: b"guess".to_vec() }),
+        ExitCode::USR_ILLEGAL_ARGUMENT,
+    );
+
+    // C reveals the secret downstream, and B uses it upstream.
+    v.set_epoch(start + 50);
+    redeem_htlc(v, &c, &bc, 0, secret.clone());
+    redeem_htlc(v, &b, &ab, 0, secret);
+
+    let st: PaychState = get_state(v, &bc).unwrap();
+    assert_eq!(TokenAmount::from_whole(10), st.to_send);
+    assert!(st.htlc_locked.is_zero());
+    let st: PaychState = get_state(v, &ab).unwrap();
+    assert_eq!(TokenAmount::from_whole(11), st.to_send);
+    assert!(st.htlc_locked.is_zero());
+
+    // A second payment on a new lane is locked by B but never completed.
+    let other_hash = v.primitives().hash_blake2b(b"unrevealed").to_vec();
+    let expiry = start + 150;
+    let voucher = signed_voucher(&ab, 1, 1, TokenAmount::from_whole(5), other_hash, expiry);
+    lock_htlc(v, &b, &ab, voucher);
+
+    // It can't be refunded before it expires, but A can reclaim it afterwards.
+    apply_code(
+        v,
+        &a,
+        &ab,
+        &TokenAmount::zero(),
+        PaychMethod::RefundHtlcExported as u64,
+        Some(RefundHtlcParams { lane: 1 }),
+        ExitCode::USR_FORBIDDEN,
+    );
+    v.set_epoch(expiry + 1);
+    apply_ok(
+        v,
+        &a,
+        &ab,
+        &TokenAmount::zero(),
+        PaychMethod::RefundHtlcExported as u64,
+        Some(RefundHtlcParams { lane: 1 }),
+    );
+    let st: PaychState = get_state(v, &ab).unwrap();
+    assert_eq!(TokenAmount::from_whole(11), st.to_send);
+    assert!(st.htlc_locked.is_zero());
+
+    // Both channels settle only once their HTLCs can no longer be redeemed, at which
+    // point collecting pays out what was redeemed and returns the rest to the payer.
+    for (from, ch, expiry) in [(a, ab, ab_expiry), (b, bc, bc_expiry)] {
+        apply_ok(v, &from, &ch, &TokenAmount::zero(), PaychMethod::Settle as u64, None::<()>);
+        let st: PaychState = get_state(v, &ch).unwrap();
+        assert!(st.settling_at > expiry);
+        assert!(st.htlc_locked.is_zero());
+        assert_eq!(funds, v.balance(&ch));
+    }
+}
+
+fn create_paych(v: &dyn VM, from: &Address, to: &Address, funds: &TokenAmount) -> Address {
+    let ctor_params = serialize(
+        &fil_actor_paych::ConstructorParams { from: *from, to: *to },
+        "paych ctor params",
+    )
+    .unwrap();
+    let ret: ExecReturn = apply_ok(
+        v,
+        from,
+        &INIT_ACTOR_ADDR,
+        funds,
+        fil_actor_init::Method::Exec as u64,
+        Some(fil_actor_init::ExecParams {
+            code_cid: *PAYCH_ACTOR_CODE_ID,
+            constructor_params: ctor_params,
+        }),
+    )
+    .deserialize()
+    .unwrap();
+    ret.id_address
+}
+
+// Vouchers are signed by the channel's payer. The test VM accepts a signature
+// equal to the signed bytes.
+fn signed_voucher(
+    ch: &Address,
+    lane: u64,
+    nonce: u64,
+    amount: TokenAmount,
+    secret_hash: Vec<u8>,
+    expiry: ChainEpoch,
+) -> SignedVoucher {
+    let mut sv = SignedVoucher {
+        channel_addr: *ch,
+        time_lock_min: 0,
+        time_lock_max: expiry,
+        secret_pre_image: secret_hash,
+        extra: None,
+        lane,
+        nonce,
+        amount,
+        min_settle_height: 0,
+        merges: vec![],
+        signature: None,
+    };
+    sv.signature = Some(Signature::new_bls(sv.signing_bytes().unwrap()));
+    sv
+}
+
+fn lock_htlc(v: &dyn VM, payee: &Address, ch: &Address, sv: SignedVoucher) {
+    apply_ok(
+        v,
+        payee,
+        ch,
+        &TokenAmount::zero(),
+        PaychMethod::LockHtlcExported as u64,
+        Some(LockHtlcParams { sv }),
+    );
+}
+
+fn redeem_htlc(v: &dyn VM, payee: &Address, ch: &Address, lane: u64, secret: Vec<u8>) {
+    apply_ok(
+        v,
+        payee,
+        ch,
+        &TokenAmount::zero(),
+        PaychMethod::RedeemHtlcExported as u64,
+        Some(RedeemHtlcParams { lane, secret }),
+    );
+}
@@ -1,7 +1,8 @@
 use fil_actor_account::types::AuthenticateMessageParams;
 use fil_actor_account::Method as AccountMethod;
 use fil_actor_market::{
-    ClientDealProposal, DealProposal, Label, Method as MarketMethod, PublishStorageDealsParams,
+    deal_proposal_eip712_message, ClientDealProposal, DealProposal, Label, Method as MarketMethod,
+    PublishStorageDealsParams, PublishStorageDealsReturn, State as MarketState,
 };
 use fil_actor_miner::max_prove_commit_duration;
 use fil_actor_verifreg::{AddVerifiedClientParams, Method as VerifregMethod};
@@ -21,15 +22,15 @@ use fvm_shared::error::ExitCode;
 use fvm_shared::piece::PaddedPieceSize;
 use fvm_shared::sector::{RegisteredSealProof, StoragePower};
 use vm_api::trace::ExpectInvocation;
-use vm_api::util::{apply_ok, serialize_ok};
+use vm_api::util::{apply_ok, get_state, serialize_ok, DynBlockstore};
 use vm_api::VM;
 
 use crate::deals::{DealBatcher, DealOptions};
 use crate::expects::Expect;
 
 use crate::util::{
-    assert_invariants, bf_all, create_accounts, create_accounts_seeded, create_miner,
-    verifreg_add_verifier,
+    assert_invariants, bf_all, create_accounts, create_accounts_seeded, create_eth_account,
+    create_miner, eth_sign, verifreg_add_verifier,
 };
 use crate::TEST_FAUCET_ADDR;
 use export_macro::vm_test;
@@ -444,6 +445,150 @@ pub fn psd_all_deals_are_bad_test(v: &dyn VM) {
     assert_invariants(v, &Policy::default(), None)
 }
 
+#[vm_test]
+pub fn psd_ethaccount_client_test(v: &dyn VM) {
+    let (a, deal_start) = setup(v);
+    let key = libsecp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+    let eth_client = create_eth_account(v, &key, &TokenAmount::from_whole(10_000));
+    apply_ok(
+        v,
+        &a.worker,
+        &STORAGE_MARKET_ACTOR_ADDR,
+        &TokenAmount::from_whole(100),
+        MarketMethod::AddBalance as u64,
+        Some(eth_client),
+    );
+
+    let DealOptions { price_per_epoch, provider_collateral, client_collateral, .. } =
+        DealOptions::default();
+    let proposals: Vec<_> = ["deal0", "deal1"]
+        .into_iter()
+        .map(|label| DealProposal {
+            piece_cid: make_piece_cid(label.as_bytes()),
+            piece_size: PaddedPieceSize(1 << 30),
+            verified_deal: false,
+            client: eth_client,
+            provider: a.maddr,
+            label: Label::String(label.to_string()),
+            start_epoch: deal_start,
+            end_epoch: deal_start + DEAL_LIFETIME,
+            storage_price_per_epoch: price_per_epoch.clone(),
+            provider_collateral: provider_collateral.clone(),
+            client_collateral: client_collateral.clone(),
+        })
+        .collect();
+
+    // The first proposal is signed by the client's Ethereum key, the second by some other key.
+    let other_key = libsecp256k1::SecretKey::parse(&[0x43; 32]).unwrap();
+    let publish_params = PublishStorageDealsParams {
+        deals: [&key, &other_key]
+            .into_iter()
+            .zip(&proposals)
+            .map(|(key, proposal)| ClientDealProposal {
+                proposal: proposal.clone(),
+                client_signature: Signature {
+                    sig_type: SignatureType::Secp256k1,
+                    bytes: eth_sign(v, key, &serialize(proposal, "deal proposal").unwrap()),
+                },
+                eip712_signature: false,
+            })
+            .collect(),
+    };
+    let ret: PublishStorageDealsReturn = apply_ok(
+        v,
+        &a.worker,
+        &STORAGE_MARKET_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        MarketMethod::PublishStorageDeals as u64,
+        Some(publish_params),
+    )
+    .deserialize()
+    .unwrap();
+    assert_eq!(vec![0], bf_all(ret.valid_deals));
+    assert_eq!(1, ret.ids.len());
+
+    let market_state: MarketState = get_state(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
+    let store = DynBlockstore::wrap(v.blockstore());
+    let published = market_state.get_proposal(&store, ret.ids[0]).unwrap();
+    assert_eq!(proposals[0], published);
+
+    assert_invariants(v, &Policy::default(), None)
+}
+
+#[vm_test]
+pub fn psd_ethaccount_client_eip712_test(v: &dyn VM) {
+    let (a, deal_start) = setup(v);
+    let key = libsecp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+    let eth_client = create_eth_account(v, &key, &TokenAmount::from_whole(10_000));
+    apply_ok(
+        v,
+        &a.worker,
+        &STORAGE_MARKET_ACTOR_ADDR,
+        &TokenAmount::from_whole(100),
+        MarketMethod::AddBalance as u64,
+        Some(eth_client),
+    );
+
+    let DealOptions { price_per_epoch, provider_collateral, client_collateral, .. } =
+        DealOptions::default();
+    let proposals: Vec<_> = ["deal0", "deal1"]
+        .into_iter()
+        .map(|label| DealProposal {
+            piece_cid: make_piece_cid(label.as_bytes()),
+            piece_size: PaddedPieceSize(1 << 30),
+            verified_deal: false,
+            client: eth_client,
+            provider: a.maddr,
+            label: Label::String(label.to_string()),
+            start_epoch: deal_start,
+            end_epoch: deal_start + DEAL_LIFETIME,
+            storage_price_per_epoch: price_per_epoch.clone(),
+            provider_collateral: provider_collateral.clone(),
+            client_collateral: client_collateral.clone(),
+        })
+        .collect();
+
+    // Both proposals are signed as typed data by the client's Ethereum key, but the second for
+    // the wrong chain (the test VM's chain ID is 0).
+    let publish_params = PublishStorageDealsParams {
+        deals: [0, 314]
+            .into_iter()
+            .zip(&proposals)
+            .map(|(chain_id, proposal)| {
+                let message =
+                    deal_proposal_eip712_message(v.primitives(), chain_id, proposal).unwrap();
+                ClientDealProposal {
+                    proposal: proposal.clone(),
+                    client_signature: Signature {
+                        sig_type: SignatureType::Secp256k1,
+                        bytes: eth_sign(v, &key, &message),
+                    },
+                    eip712_signature: true,
+                }
+            })
+            .collect(),
+    };
+    let ret: PublishStorageDealsReturn = apply_ok(
+        v,
+        &a.worker,
+        &STORAGE_MARKET_ACTOR_ADDR,
+        &TokenAmount::zero(),
+        MarketMethod::PublishStorageDeals as u64,
+        Some(publish_params),
+    )
+    .deserialize()
+    .unwrap();
+    assert_eq!(vec![0], bf_all(ret.valid_deals));
+    assert_eq!(1, ret.ids.len());
+
+    let market_state: MarketState = get_state(v, &STORAGE_MARKET_ACTOR_ADDR).unwrap();
+    let store = DynBlockstore::wrap(v.blockstore());
+    let published = market_state.get_proposal(&store, ret.ids[0]).unwrap();
+    assert_eq!(proposals[0], published);
+
+    assert_invariants(v, &Policy::default(), None)
+}
+
 #[vm_test]
 pub fn psd_bad_sig_test(v: &dyn VM) {
     let (a, deal_start) = setup(v);
@@ -474,6 +619,7 @@ pub fn psd_bad_sig_test(v: &dyn VM) {
                 sig_type: SignatureType::BLS,
                 bytes: invalid_sig_bytes.clone(),
             },
+            eip712_signature: false,
         }],
     };
     let ret = v
@@ -11,16 +11,17 @@ use fil_actor_power::State as PowerState;
 use fil_actor_reward::State as RewardState;
 use fil_actor_verifreg::{Claim, ClaimID, State as VerifregState};
 use fil_actors_runtime::cbor::serialize;
-use fil_actors_runtime::test_utils::make_piece_cid;
+use fil_actors_runtime::test_utils::{make_piece_cid, ETHACCOUNT_ACTOR_CODE_ID};
 use fil_actors_runtime::ActorError;
 use fil_actors_runtime::{
-    parse_uint_key, runtime::Policy, MessageAccumulator, REWARD_ACTOR_ADDR,
+    parse_uint_key, runtime::Policy, MessageAccumulator, EAM_ACTOR_ID, REWARD_ACTOR_ADDR,
     STORAGE_MARKET_ACTOR_ADDR, STORAGE_POWER_ACTOR_ADDR, VERIFIED_REGISTRY_ACTOR_ADDR,
 };
 use fil_builtin_actors_state::check::check_state_invariants;
 use fvm_ipld_bitfield::BitField;
 use fvm_ipld_encoding::{CborStore, RawBytes, DAG_CBOR};
 use fvm_shared::address::Address;
+use fvm_shared::crypto::hash::SupportedHashes;
 use fvm_shared::deal::DealID;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::piece::PieceInfo;
@@ -64,6 +65,39 @@ pub fn create_accounts_seeded(
     pk_addrs.iter().map(|pk_addr| v.resolve_id_address(pk_addr).unwrap()).collect()
 }
 
+/// Creates an EthAccount controlled by the given secp256k1 key.
+/// Returns the address of the account in ID format.
+pub fn create_eth_account(
+    v: &dyn VM,
+    key: &libsecp256k1::SecretKey,
+    balance: &TokenAmount,
+) -> Address {
+    let pubkey = libsecp256k1::PublicKey::from_secret_key(key).serialize();
+    let eth_address = &v.primitives().hash(SupportedHashes::Keccak256, &pubkey[1..])[12..];
+    let f410_address = Address::new_delegated(EAM_ACTOR_ID, eth_address).unwrap();
+
+    // Send funds from the faucet to the f4 address, creating a placeholder...
+    apply_ok(v, &TEST_FAUCET_ADDR, &f410_address, balance, METHOD_SEND, None::<RawBytes>);
+
+    // ...then turn it into an EthAccount, as the FVM does when the account first sends a message.
+    let id_address = v.resolve_id_address(&f410_address).unwrap();
+    let mut actor = v.actor(&id_address).unwrap();
+    actor.code = *ETHACCOUNT_ACTOR_CODE_ID;
+    v.set_actor(&id_address, actor);
+    id_address
+}
+
+/// Signs a message the way Ethereum (delegated) signers do, with a secp256k1 signature
+/// (`r || s || v`) over the keccak256 digest of the message.
+pub fn eth_sign(v: &dyn VM, key: &libsecp256k1::SecretKey, message: &[u8]) -> Vec<u8> {
+    let digest = v.primitives().hash(SupportedHashes::Keccak256, message);
+    let (signature, recovery_id) =
+        libsecp256k1::sign(&libsecp256k1::Message::parse_slice(&digest).unwrap(), key);
+    let mut bytes = signature.serialize().to_vec();
+    bytes.push(recovery_id.serialize());
+    bytes
+}
+
 pub fn check_invariants(
     vm: &dyn VM,
     policy: &Policy,
@@ -802,6 +802,7 @@ pub fn verifreg_add_verifier(v: &dyn VM, verifier: &Address, data_cap: StoragePo
         value: TokenAmount::zero(),
         method: VerifregMethod::AddVerifier as u64,
         params: serialize(&add_verifier_params, "verifreg add verifier params").unwrap(),
+        expiration: None,
     };
 
     apply_ok(
@@ -1180,6 +1181,7 @@ pub fn market_publish_deal(
         deals: vec![ClientDealProposal {
             proposal: proposal.clone(),
             client_signature: signature.clone(),
+            eip712_signature: false,
         }],
     };
     let ret: PublishStorageDealsReturn = apply_ok(
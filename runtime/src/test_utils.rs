@@ -70,6 +70,8 @@ lazy_static::lazy_static! {
     pub static ref EVM_ACTOR_CODE_ID: Cid = make_identity_cid(b"fil/test/evm");
     pub static ref EAM_ACTOR_CODE_ID: Cid = make_identity_cid(b"fil/test/eam");
     pub static ref ETHACCOUNT_ACTOR_CODE_ID: Cid = make_identity_cid(b"fil/test/ethaccount");
+    pub static ref PASSKEY_ACTOR_CODE_ID: Cid = make_identity_cid(b"fil/test/passkey");
+    pub static ref MULTIKEY_ACTOR_CODE_ID: Cid = make_identity_cid(b"fil/test/multikey");
 
     pub static ref ACTOR_TYPES: BTreeMap<Cid, Type> = {
         let mut map = BTreeMap::new();
@@ -89,6 +91,8 @@ lazy_static::lazy_static! {
         map.insert(*EVM_ACTOR_CODE_ID, Type::EVM);
         map.insert(*EAM_ACTOR_CODE_ID, Type::EAM);
         map.insert(*ETHACCOUNT_ACTOR_CODE_ID, Type::EthAccount);
+        map.insert(*PASSKEY_ACTOR_CODE_ID, Type::Passkey);
+        map.insert(*MULTIKEY_ACTOR_CODE_ID, Type::MultiKey);
         map
     };
     pub static ref ACTOR_CODES: BTreeMap<Type, Cid> = [
@@ -108,6 +112,8 @@ lazy_static::lazy_static! {
         (Type::EVM, *EVM_ACTOR_CODE_ID),
         (Type::EAM, *EAM_ACTOR_CODE_ID),
         (Type::EthAccount, *ETHACCOUNT_ACTOR_CODE_ID),
+        (Type::Passkey, *PASSKEY_ACTOR_CODE_ID),
+        (Type::MultiKey, *MULTIKEY_ACTOR_CODE_ID),
     ]
     .into_iter()
     .collect();
@@ -120,6 +126,8 @@ lazy_static::lazy_static! {
         map.insert(*PLACEHOLDER_ACTOR_CODE_ID, ());
         map.insert(*EVM_ACTOR_CODE_ID, ());
         map.insert(*ETHACCOUNT_ACTOR_CODE_ID, ());
+        map.insert(*PASSKEY_ACTOR_CODE_ID, ());
+        map.insert(*MULTIKEY_ACTOR_CODE_ID, ());
         map
     };
 }
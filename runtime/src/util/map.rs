@@ -164,18 +164,58 @@ where
                     K::from_bytes(k).context_code(ExitCode::USR_ILLEGAL_STATE, "invalid key")?;
                 f(key, v).map_err(|e| anyhow!(e))
             })
-            .map_err(|hamt_err| match hamt_err {
-                hamt::Error::Dynamic(e) => match e.downcast::<ActorError>() {
-                    Ok(ae) => ae,
-                    Err(e) => ActorError::illegal_state(format!(
-                        "error in callback traversing HAMT {}: {}",
-                        self.name, e
-                    )),
-                },
-                e => {
-                    ActorError::illegal_state(format!("error traversing HAMT {}: {}", self.name, e))
-                }
+            .map_err(|hamt_err| self.traversal_error(hamt_err))
+    }
+
+    /// Iterates over at most `max` key-value pairs in the map, starting at `starting_key`
+    /// (inclusive) if provided.
+    /// Returns the number of pairs traversed and, if iteration stopped before the end of the map,
+    /// the key at which to resume.
+    pub fn for_each_ranged<F>(
+        &self,
+        starting_key: Option<&K>,
+        max: Option<usize>,
+        mut f: F,
+    ) -> Result<(usize, Option<K>), ActorError>
+    where
+        F: FnMut(K, &V) -> Result<(), ActorError>,
+    {
+        let start = starting_key
+            .map(|key| {
+                key.to_bytes()
+                    .map(hamt::BytesKey)
+                    .with_context_code(ExitCode::USR_ASSERTION_FAILED, || {
+                        format!("invalid key {key:?}")
+                    })
             })
+            .transpose()?;
+        let (traversed, next) = self
+            .hamt
+            .for_each_ranged(start.as_ref(), max, |k, v| {
+                let key =
+                    K::from_bytes(k).context_code(ExitCode::USR_ILLEGAL_STATE, "invalid key")?;
+                f(key, v).map_err(|e| anyhow!(e))
+            })
+            .map_err(|hamt_err| self.traversal_error(hamt_err))?;
+        let next = next
+            .map(|k| K::from_bytes(&k))
+            .transpose()
+            .context_code(ExitCode::USR_ILLEGAL_STATE, "invalid key")?;
+        Ok((traversed, next))
+    }
+
+    // Extracts any ActorError propagated from a traversal callback.
+    fn traversal_error(&self, hamt_err: hamt::Error) -> ActorError {
+        match hamt_err {
+            hamt::Error::Dynamic(e) => match e.downcast::<ActorError>() {
+                Ok(ae) => ae,
+                Err(e) => ActorError::illegal_state(format!(
+                    "error in callback traversing HAMT {}: {}",
+                    self.name, e
+                )),
+            },
+            e => ActorError::illegal_state(format!("error traversing HAMT {}: {}", self.name, e)),
+        }
     }
 }
 
@@ -266,4 +306,32 @@ mod tests {
         assert!(res.is_err());
         assert_eq!(res.unwrap_err(), ActorError::forbidden("test".to_string()));
     }
+
+    #[test]
+    fn for_each_ranged_resumes_from_next_key() {
+        let bs = MemoryBlockstore::new();
+        let mut m = Map2::<_, u64, u64>::empty(bs, DEFAULT_HAMT_CONFIG, "ranged");
+        for i in 0..10 {
+            m.set(&i, i).unwrap();
+        }
+
+        let mut seen = vec![];
+        let mut start = None;
+        loop {
+            let (traversed, next) = m
+                .for_each_ranged(start.as_ref(), Some(3), |k, v| {
+                    assert_eq!(k, *v);
+                    seen.push(k);
+                    Ok(())
+                })
+                .unwrap();
+            assert!(traversed <= 3);
+            match next {
+                Some(k) => start = Some(k),
+                None => break,
+            }
+        }
+        seen.sort();
+        assert_eq!((0..10).collect::<Vec<u64>>(), seen);
+    }
 }
@@ -22,3 +22,4 @@ mod message_accumulator;
 mod multimap;
 mod set;
 mod set_multimap;
+mod tuple;
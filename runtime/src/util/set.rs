@@ -44,6 +44,12 @@ where
         self.0.set(key, ())
     }
 
+    /// Returns whether the set is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// Checks if key exists in the set.
     #[inline]
     pub fn has(&self, key: &K) -> Result<bool, ActorError> {
@@ -64,6 +70,22 @@ where
         self.0.for_each(|s, _| f(s))
     }
 
+    /// Iterates over at most `max` keys in the set, starting at `starting_key` (inclusive)
+    /// if provided.
+    /// Returns the number of keys traversed and, if iteration stopped before the end of the set,
+    /// the key at which to resume.
+    pub fn for_each_ranged<F>(
+        &self,
+        starting_key: Option<&K>,
+        max: Option<usize>,
+        mut f: F,
+    ) -> Result<(usize, Option<K>), ActorError>
+    where
+        F: FnMut(K) -> Result<(), ActorError>,
+    {
+        self.0.for_each_ranged(starting_key, max, |k, _| f(k))
+    }
+
     /// Collects all keys from the set into a vector.
     pub fn collect_keys(&self) -> Result<Vec<K>, ActorError> {
         let mut ret_keys = Vec::new();
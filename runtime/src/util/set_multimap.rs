@@ -117,6 +117,26 @@ where
         Ok(())
     }
 
+    /// Removes values from the set associated with a key, removing the key entirely
+    /// if its set becomes empty.
+    pub fn remove_many(&mut self, key: &K, values: &[V]) -> Result<(), ActorError> {
+        let mut set = match self.get(key)? {
+            Some(s) => s,
+            None => return Ok(()),
+        };
+
+        for v in values {
+            set.delete(v)?;
+        }
+        if set.is_empty() {
+            self.outer.delete(key)?;
+        } else {
+            let new_root = set.flush()?;
+            self.outer.set(key, new_root)?;
+        }
+        Ok(())
+    }
+
     /// Removes set at index.
     #[inline]
     pub fn remove_all(&mut self, key: &K) -> Result<(), ActorError> {
@@ -0,0 +1,116 @@
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+/// Implements `Serialize` and `Deserialize` for a struct encoded as a tuple whose last fields
+/// were added after the struct was first encoded.
+///
+/// The fields before the `;` are required. The fields after it are trailing fields, which are
+/// omitted from the end of the encoding while they hold their default values, and take their
+/// default values when absent. A struct that doesn't use its trailing fields is thus encoded
+/// as before they were added, and values encoded before they were added still decode.
+///
+/// The default of a trailing field is `Default::default()`, unless given as `field = expr`.
+/// The expression may refer to the fields before it.
+///
+/// ```ignore
+/// impl_trailing_optional_tuple!(State { signers, threshold; weights = vec![1; signers.len()] });
+/// ```
+#[macro_export]
+macro_rules! impl_trailing_optional_tuple {
+    ($name:ident { $($field:ident),+ ; $($optional:ident $(= $default:expr)?),+ $(,)? }) => {
+        impl ::serde::Serialize for $name {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use ::serde::ser::SerializeSeq;
+
+                fn is_default<T: PartialEq>(value: &T, default: T) -> bool {
+                    *value == default
+                }
+
+                let $name { $($field,)+ $($optional,)+ } = self;
+                let is_set = [
+                    $(!is_default($optional, $crate::__trailing_default!($($default)?)),)+
+                ];
+                let trailing = is_set.iter().rposition(|set| *set).map_or(0, |i| i + 1);
+                let required = [$(stringify!($field)),+].len();
+
+                let mut seq = serializer.serialize_seq(Some(required + trailing))?;
+                $(seq.serialize_element($field)?;)+
+                let mut remaining = 0..trailing;
+                $(
+                    if remaining.next().is_some() {
+                        seq.serialize_element($optional)?;
+                    }
+                )+
+                seq.end()
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for $name {
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct TupleVisitor;
+
+                impl<'de> ::serde::de::Visitor<'de> for TupleVisitor {
+                    type Value = $name;
+
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        write!(formatter, "a {} tuple", stringify!($name))
+                    }
+
+                    fn visit_seq<A: ::serde::de::SeqAccess<'de>>(
+                        self,
+                        mut seq: A,
+                    ) -> Result<Self::Value, A::Error> {
+                        // Deserializes the next element as the type of the given field.
+                        fn next<'de, A, T>(
+                            seq: &mut A,
+                            _field: fn(&$name) -> &T,
+                        ) -> Result<Option<T>, A::Error>
+                        where
+                            A: ::serde::de::SeqAccess<'de>,
+                            T: ::serde::Deserialize<'de>,
+                        {
+                            seq.next_element()
+                        }
+
+                        let mut len = 0;
+                        $(
+                            let $field = next(&mut seq, |s| &s.$field)?.ok_or_else(|| {
+                                <A::Error as ::serde::de::Error>::invalid_length(len, &self)
+                            })?;
+                            len += 1;
+                        )+
+                        $(
+                            let $optional = match next(&mut seq, |s| &s.$optional)? {
+                                Some(value) => {
+                                    len += 1;
+                                    value
+                                }
+                                None => $crate::__trailing_default!($($default)?),
+                            };
+                        )+
+                        if seq.next_element::<::serde::de::IgnoredAny>()?.is_some() {
+                            return Err(<A::Error as ::serde::de::Error>::invalid_length(
+                                len + 1,
+                                &self,
+                            ));
+                        }
+                        Ok($name { $($field,)+ $($optional,)+ })
+                    }
+                }
+
+                deserializer.deserialize_seq(TupleVisitor)
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __trailing_default {
+    () => {
+        Default::default()
+    };
+    ($default:expr) => {
+        $default
+    };
+}
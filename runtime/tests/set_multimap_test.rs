@@ -51,3 +51,20 @@ fn for_each() {
 
     assert_eq!(vals.len(), 3);
 }
+
+#[test]
+fn remove_many() {
+    let store = MemoryBlockstore::new();
+    let mut smm = SetMultimap::<_, ChainEpoch, u64>::empty(&store, CONFIG, "t");
+
+    let epoch: ChainEpoch = 100;
+    smm.put_many(&epoch, &[1, 2, 3]).unwrap();
+    smm.remove_many(&epoch, &[1, 3, 4]).unwrap();
+    let set = smm.get(&epoch).unwrap().unwrap();
+    assert_eq!(vec![2], set.collect_keys().unwrap());
+
+    // The key is removed along with the last of its values.
+    smm.remove_many(&epoch, &[2]).unwrap();
+    assert!(smm.get(&epoch).unwrap().is_none());
+    smm.remove_many(&epoch, &[2]).unwrap();
+}
@@ -47,3 +47,33 @@ fn delete() {
     // Test delete when doesn't exist doesn't error
     set.delete(&key).unwrap();
 }
+
+#[test]
+fn for_each_ranged() {
+    let store = fil_actors_runtime::test_blockstores::MemoryBlockstore::new();
+    let mut set = Set::empty(&store, DEFAULT_HAMT_CONFIG, "t");
+    assert!(set.is_empty());
+    for k in 0u64..10 {
+        set.put(&k).unwrap();
+    }
+    assert!(!set.is_empty());
+
+    // Pages of at most 4 keys together visit every key once.
+    let mut keys = Vec::new();
+    let mut cursor = None;
+    loop {
+        let (traversed, next) = set
+            .for_each_ranged(cursor.as_ref(), Some(4), |k| {
+                keys.push(k);
+                Ok(())
+            })
+            .unwrap();
+        assert!(traversed <= 4);
+        if next.is_none() {
+            break;
+        }
+        cursor = next;
+    }
+    keys.sort();
+    assert_eq!((0u64..10).collect::<Vec<_>>(), keys);
+}
@@ -0,0 +1,40 @@
+use fil_actors_runtime::impl_trailing_optional_tuple;
+use fvm_ipld_encoding::{from_slice, to_vec};
+
+#[derive(Debug, PartialEq, Eq)]
+struct Record {
+    items: Vec<u64>,
+    weights: Vec<u64>,
+    label: Option<String>,
+}
+
+impl_trailing_optional_tuple!(Record { items; weights = vec![1; items.len()], label });
+
+#[test]
+fn omits_trailing_defaults() {
+    let record = Record { items: vec![5, 6], weights: vec![1, 1], label: None };
+    // [[5,6]]
+    assert_eq!("81820506", hex::encode(to_vec(&record).unwrap()));
+    assert_eq!(record, from_slice(&to_vec(&record).unwrap()).unwrap());
+
+    let record = Record { items: vec![5], weights: vec![2], label: None };
+    // [[5],[2]]
+    assert_eq!("8281058102", hex::encode(to_vec(&record).unwrap()));
+    assert_eq!(record, from_slice(&to_vec(&record).unwrap()).unwrap());
+}
+
+#[test]
+fn encodes_defaults_before_a_set_field() {
+    let record = Record { items: vec![5], weights: vec![1], label: Some("a".into()) };
+    // [[5],[1],"a"]
+    assert_eq!("83810581016161", hex::encode(to_vec(&record).unwrap()));
+    assert_eq!(record, from_slice(&to_vec(&record).unwrap()).unwrap());
+}
+
+#[test]
+fn rejects_missing_and_extra_fields() {
+    // []
+    assert!(from_slice::<Record>(&hex::decode("80").unwrap()).is_err());
+    // [[5],[1],"a",0]
+    assert!(from_slice::<Record>(&hex::decode("8481058101616100").unwrap()).is_err());
+}
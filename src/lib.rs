@@ -20,6 +20,8 @@
 /// - "evm"
 /// - "eam"
 /// - "ethaccount"
+/// - "passkey"
+/// - "multikey"
 /// - "placeholder"
 ///
 /// The Filecoin client must import the contents of CAR into the blockstore, but
@@ -14,7 +14,9 @@ use fil_actor_miner::PowerPair;
 use fil_actor_miner::State as MinerState;
 use fil_actor_miner::CRON_EVENT_PROCESS_EARLY_TERMINATIONS;
 use fil_actor_miner::CRON_EVENT_PROVING_DEADLINE;
+use fil_actor_multikey::State as MultiKeyState;
 use fil_actor_multisig::State as MultisigState;
+use fil_actor_passkey::State as PasskeyState;
 use fil_actor_paych::State as PaychState;
 use fil_actor_power::testing::MinerCronEvent;
 use fil_actor_power::State as PowerState;
@@ -40,7 +42,9 @@ use fil_actor_datacap::testing as datacap;
 use fil_actor_init::testing as init;
 use fil_actor_market::testing as market;
 use fil_actor_miner::testing as miner;
+use fil_actor_multikey::testing as multikey;
 use fil_actor_multisig::testing as multisig;
+use fil_actor_passkey::testing as passkey;
 use fil_actor_paych::testing as paych;
 use fil_actor_power::testing as power;
 use fil_actor_reward::testing as reward;
@@ -166,6 +170,16 @@ pub fn check_state_invariants<BS: Blockstore>(
             Some(Type::EVM) => {}
             Some(Type::EAM) => {}
             Some(Type::EthAccount) => {}
+            Some(Type::Passkey) => {
+                let state = get_state!(store, actor, PasskeyState);
+                let msgs = passkey::check_state_invariants(&state);
+                acc.with_prefix("passkey: ").add_all(&msgs);
+            }
+            Some(Type::MultiKey) => {
+                let state = get_state!(store, actor, MultiKeyState);
+                let msgs = multikey::check_state_invariants(&state);
+                acc.with_prefix("multikey: ").add_all(&msgs);
+            }
             None => {
                 bail!("unexpected actor code CID {} for address {}", actor.code, key);
             }
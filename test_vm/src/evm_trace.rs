@@ -0,0 +1,65 @@
+use std::collections::{BTreeMap, HashMap};
+
+use fil_actor_evm::interpreter::{opcodes, tracer::StructLog};
+use fil_actors_evm_shared::uints::U256;
+use serde_json::{json, Map, Value};
+
+pub use fil_actor_evm::interpreter::tracer::{finish, start};
+
+/// Renders an EVM execution trace (see [`fil_actor_evm::interpreter::tracer`]) in the format
+/// returned by geth's `debug_traceTransaction` with the default struct logger, so it can be diffed
+/// against traces from Ethereum clients.
+///
+/// Note: gas values are FVM gas, and will not match Ethereum gas.
+pub fn struct_logs_json(gas: u64, failed: bool, return_value: &[u8], logs: &[StructLog]) -> Value {
+    // Like geth, report all storage slots accessed so far by the executing contract.
+    let mut storage: HashMap<[u8; 20], BTreeMap<String, String>> = HashMap::new();
+    let struct_logs: Vec<Value> = logs
+        .iter()
+        .map(|log| {
+            let mut entry = Map::new();
+            entry.insert("pc".into(), json!(log.pc));
+            entry.insert("op".into(), json!(op_name(log.op)));
+            entry.insert("gas".into(), json!(log.gas));
+            entry.insert("gasCost".into(), json!(log.gas_cost));
+            entry.insert("depth".into(), json!(log.depth));
+            entry.insert("memSize".into(), json!(log.memory_size));
+            entry.insert(
+                "stack".into(),
+                json!(log.stack.iter().map(|v| format!("{v:#x}")).collect::<Vec<_>>()),
+            );
+            if !log.storage.is_empty() {
+                let slots = storage.entry(log.address.0).or_default();
+                for (k, v) in &log.storage {
+                    slots.insert(word_hex(k), word_hex(v));
+                }
+                entry.insert("storage".into(), json!(slots));
+            }
+            if let Some(error) = &log.error {
+                entry.insert("error".into(), json!(error));
+            }
+            Value::Object(entry)
+        })
+        .collect();
+
+    json!({
+        "gas": gas,
+        "failed": failed,
+        "returnValue": hex::encode(return_value),
+        "structLogs": struct_logs,
+    })
+}
+
+fn op_name(op: u8) -> String {
+    match opcodes::name(op) {
+        Some(name) => name.to_owned(),
+        None => format!("opcode {op:#x} not defined"),
+    }
+}
+
+/// Formats a 256bit word as 64 hex digits, without the 0x prefix.
+fn word_hex(v: &U256) -> String {
+    let mut bytes = [0u8; 32];
+    v.to_big_endian(&mut bytes);
+    hex::encode(bytes)
+}
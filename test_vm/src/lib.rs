@@ -43,6 +43,8 @@ use vm_api::util::{get_state, serialize_ok};
 
 mod constants;
 pub use constants::*;
+#[cfg(feature = "tracing")]
+pub mod evm_trace;
 mod messaging;
 pub use messaging::*;
 
@@ -55,7 +57,7 @@ pub struct TestVM {
     actors_cache: RefCell<HashMap<Address, ActorState>>,
     invocations: RefCell<Vec<InvocationTrace>>,
     // MachineContext equivalents
-    network_version: NetworkVersion,
+    network_version: RefCell<NetworkVersion>,
     curr_epoch: RefCell<ChainEpoch>,
     circulating_supply: RefCell<TokenAmount>,
     base_fee: RefCell<TokenAmount>,
@@ -78,7 +80,7 @@ impl TestVM {
             circulating_supply: RefCell::new(TokenAmount::zero()),
             actors_dirty: RefCell::new(false),
             actors_cache: RefCell::new(HashMap::new()),
-            network_version: NetworkVersion::V16,
+            network_version: RefCell::new(NetworkVersion::V16),
             curr_epoch: RefCell::new(ChainEpoch::zero()),
             invocations: RefCell::new(vec![]),
             base_fee: RefCell::new(TokenAmount::zero()),
@@ -131,7 +133,7 @@ impl TestVM {
                 method_num: MarketMethod::CronTick as u64,
             },
         ];
-        let cron_head = v.put_store(&CronState { entries: builtin_entries });
+        let cron_head = v.put_store(&CronState { entries: builtin_entries, ..Default::default() });
         v.set_actor(
             &CRON_ACTOR_ADDR,
             new_actor(*CRON_ACTOR_CODE_ID, cron_head, 0, TokenAmount::zero(), None),
@@ -325,6 +327,7 @@ impl VM for TestVM {
             policy: &Policy::default(),
             subinvocations: RefCell::new(vec![]),
             events: RefCell::new(vec![]),
+            gas_charged: RefCell::new(0),
         };
         let res = new_ctx.invoke();
 
@@ -419,6 +422,15 @@ impl VM for TestVM {
     fn set_epoch(&self, epoch: ChainEpoch) {
         self.curr_epoch.replace(epoch);
     }
+
+    fn network_version(&self) -> NetworkVersion {
+        *self.network_version.borrow()
+    }
+
+    fn set_network_version(&self, nv: NetworkVersion) {
+        self.network_version.replace(nv);
+    }
+
     fn circulating_supply(&self) -> TokenAmount {
         self.circulating_supply.borrow().clone()
     }
@@ -10,7 +10,9 @@ use fil_actor_evm::EvmContractActor;
 use fil_actor_init::{Actor as InitActor, State as InitState};
 use fil_actor_market::Actor as MarketActor;
 use fil_actor_miner::Actor as MinerActor;
+use fil_actor_multikey::Actor as MultiKeyActor;
 use fil_actor_multisig::Actor as MultisigActor;
+use fil_actor_passkey::Actor as PasskeyActor;
 use fil_actor_paych::Actor as PaychActor;
 use fil_actor_power::Actor as PowerActor;
 use fil_actor_reward::Actor as RewardActor;
@@ -114,6 +116,7 @@ pub struct InvocationCtx<'invocation> {
     pub policy: &'invocation Policy,
     pub subinvocations: RefCell<Vec<InvocationTrace>>,
     pub events: RefCell<Vec<EmittedEvent>>,
+    pub gas_charged: RefCell<i64>,
 }
 
 impl<'invocation> InvocationCtx<'invocation> {
@@ -179,6 +182,7 @@ impl<'invocation> InvocationCtx<'invocation> {
                 policy: self.policy,
                 subinvocations: RefCell::new(vec![]),
                 events: RefCell::new(vec![]),
+                gas_charged: RefCell::new(0),
             };
             if is_account {
                 new_ctx.create_actor(*ACCOUNT_ACTOR_CODE_ID, target_id, None).unwrap();
@@ -221,6 +225,7 @@ impl<'invocation> InvocationCtx<'invocation> {
             exit_code: code,
             subinvocations: self.subinvocations.take(),
             events: self.events.take(),
+            gas_charged: self.gas_charged.take(),
         }
     }
 
@@ -291,6 +296,8 @@ impl<'invocation> InvocationCtx<'invocation> {
             Type::EVM => EvmContractActor::invoke_method(self, self.msg.method, params),
             Type::EAM => EamActor::invoke_method(self, self.msg.method, params),
             Type::EthAccount => EthAccountActor::invoke_method(self, self.msg.method, params),
+            Type::Passkey => PasskeyActor::invoke_method(self, self.msg.method, params),
+            Type::MultiKey => MultiKeyActor::invoke_method(self, self.msg.method, params),
         };
         if res.is_ok() && !*self.caller_validated.borrow() {
             res = Err(actor_error!(assertion_failed, "failed to validate caller"));
@@ -351,7 +358,7 @@ impl<'invocation> Runtime for InvocationCtx<'invocation> {
     }
 
     fn network_version(&self) -> NetworkVersion {
-        self.v.network_version
+        *self.v.network_version.borrow()
     }
 
     fn message(&self) -> &dyn MessageInfo {
@@ -517,6 +524,7 @@ impl<'invocation> Runtime for InvocationCtx<'invocation> {
             policy: self.policy,
             subinvocations: RefCell::new(vec![]),
             events: RefCell::new(vec![]),
+            gas_charged: RefCell::new(0),
         };
         let res = new_ctx.invoke();
         let invoc = new_ctx.gather_trace(res.clone());
@@ -626,7 +634,9 @@ impl<'invocation> Runtime for InvocationCtx<'invocation> {
         self.top.circ_supply.clone()
     }
 
-    fn charge_gas(&self, _name: &'static str, _compute: i64) {}
+    fn charge_gas(&self, _name: &'static str, compute: i64) {
+        *self.gas_charged.borrow_mut() += compute;
+    }
 
     fn base_fee(&self) -> TokenAmount {
         TokenAmount::zero()
@@ -0,0 +1,10 @@
+use fil_actors_integration_tests::tests::cancel_deal_test;
+use fil_actors_runtime::test_blockstores::MemoryBlockstore;
+use test_vm::TestVM;
+
+#[test]
+fn cancel_deal() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    cancel_deal_test(&v);
+}
@@ -0,0 +1,10 @@
+use fil_actors_integration_tests::tests::cron_scheduled_entries_test;
+use fil_actors_runtime::test_blockstores::MemoryBlockstore;
+use test_vm::TestVM;
+
+#[test]
+fn cron_scheduled_entries() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    cron_scheduled_entries_test(&v);
+}
@@ -1,10 +1,11 @@
 use fil_actors_integration_tests::tests::{
-    evm_call_test, evm_create_test, evm_delegatecall_test, evm_empty_initcode_test,
-    evm_eth_create_external_test, evm_init_revert_data_test, evm_staticcall_delegatecall_test,
-    evm_staticcall_test,
+    evm_access_list_test, evm_call_test, evm_create_test, evm_delegatecall_test,
+    evm_empty_initcode_test, evm_eth_create_external_test, evm_init_revert_data_test,
+    evm_staticcall_delegatecall_test, evm_staticcall_test,
 };
 use fil_actors_runtime::test_blockstores::MemoryBlockstore;
 use test_vm::TestVM;
+use vm_api::VM;
 
 #[test]
 fn evm_call() {
@@ -20,6 +21,22 @@ fn evm_create() {
     evm_create_test(&v);
 }
 
+#[test]
+fn evm_create_eip6780() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    v.set_network_version(fil_actor_evm::EIP_6780_NETWORK_VERSION);
+    evm_create_test(&v);
+}
+
+#[test]
+fn evm_access_list() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    v.set_network_version(fil_actor_evm::EIP_2929_NETWORK_VERSION);
+    evm_access_list_test(&v);
+}
+
 #[test]
 fn evm_eth_create_external() {
     let store = MemoryBlockstore::new();
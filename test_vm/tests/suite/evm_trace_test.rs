@@ -0,0 +1,77 @@
+use fil_actors_integration_tests::tests::ContractParams;
+use fil_actors_integration_tests::util::create_accounts;
+use fil_actors_runtime::test_blockstores::MemoryBlockstore;
+use fil_actors_runtime::EAM_ACTOR_ADDR;
+use fvm_ipld_encoding::BytesDe;
+use fvm_shared::econ::TokenAmount;
+use num_traits::Zero;
+use serde_json::json;
+use test_vm::{evm_trace, TestVM};
+use vm_api::util::serialize_ok;
+use vm_api::VM;
+
+#[test]
+fn evm_trace() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    let account = create_accounts(&v, 1, &TokenAmount::from_whole(10_000))[0];
+
+    // Stores 0x2a in slot 0, then loads and returns it.
+    let runtime_code = hex::decode("602a60005560005460005260206000f3").unwrap();
+    let mut initcode = hex::decode("6010600c60003960106000f3").unwrap();
+    initcode.extend_from_slice(&runtime_code);
+
+    let create_result = v
+        .execute_message(
+            &account,
+            &EAM_ACTOR_ADDR,
+            &TokenAmount::zero(),
+            fil_actor_eam::Method::CreateExternal as u64,
+            Some(serialize_ok(&fil_actor_eam::CreateExternalParams(initcode))),
+        )
+        .unwrap();
+    assert!(create_result.code.is_success(), "failed to create the contract");
+    let create_return: fil_actor_eam::CreateExternalReturn =
+        create_result.ret.unwrap().deserialize().unwrap();
+
+    evm_trace::start();
+    let call_result = v
+        .execute_message(
+            &account,
+            &create_return.robust_address.unwrap(),
+            &TokenAmount::zero(),
+            fil_actor_evm::Method::InvokeContract as u64,
+            Some(serialize_ok(&ContractParams(vec![]))),
+        )
+        .unwrap();
+    let logs = evm_trace::finish();
+    assert!(call_result.code.is_success(), "failed to call the contract");
+    let BytesDe(return_value) = call_result.ret.unwrap().deserialize().unwrap();
+
+    let trace = evm_trace::struct_logs_json(0, false, &return_value, &logs);
+    assert_eq!(trace["failed"], json!(false));
+    assert_eq!(trace["returnValue"], json!(format!("{:064x}", 0x2a)));
+
+    let struct_logs = trace["structLogs"].as_array().unwrap();
+    let ops: Vec<_> = struct_logs.iter().map(|l| l["op"].as_str().unwrap()).collect();
+    assert_eq!(
+        ops,
+        [
+            "PUSH1", "PUSH1", "SSTORE", "PUSH1", "SLOAD", "PUSH1", "MSTORE", "PUSH1", "PUSH1",
+            "RETURN"
+        ]
+    );
+    let pcs: Vec<_> = struct_logs.iter().map(|l| l["pc"].as_u64().unwrap()).collect();
+    assert_eq!(pcs, [0, 2, 4, 5, 7, 8, 10, 11, 13, 15]);
+    assert!(struct_logs.iter().all(|l| l["depth"] == json!(1)));
+    // Memory is expanded by the MSTORE.
+    let mem_sizes: Vec<_> = struct_logs.iter().map(|l| l["memSize"].as_u64().unwrap()).collect();
+    assert_eq!(mem_sizes, [0, 0, 0, 0, 0, 0, 0, 32, 32, 32]);
+
+    let slot = json!({ format!("{:064x}", 0): format!("{:064x}", 0x2a) });
+    assert_eq!(struct_logs[2]["stack"], json!(["0x2a", "0x0"]));
+    assert_eq!(struct_logs[2]["storage"], slot);
+    assert_eq!(struct_logs[4]["storage"], slot);
+    assert_eq!(struct_logs[6]["stack"], json!(["0x2a", "0x0"]));
+    assert!(struct_logs[0].get("storage").is_none());
+}
@@ -0,0 +1,10 @@
+use fil_actors_integration_tests::tests::extend_deal_term_test;
+use fil_actors_runtime::test_blockstores::MemoryBlockstore;
+use test_vm::TestVM;
+
+#[test]
+fn extend_deal_term() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    extend_deal_term_test(&v);
+}
@@ -1,4 +1,4 @@
-use fil_actors_integration_tests::tests::placeholder_deploy_test;
+use fil_actors_integration_tests::tests::{init_lookup_addresses_test, placeholder_deploy_test};
 use fil_actors_runtime::test_blockstores::MemoryBlockstore;
 use test_vm::TestVM;
 
@@ -9,3 +9,11 @@ fn placeholder_deploy() {
 
     placeholder_deploy_test(&v);
 }
+
+#[test]
+fn init_lookup_addresses() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+
+    init_lookup_addresses_test(&v);
+}
@@ -1,15 +1,24 @@
 mod authenticate_message_test;
 mod batch_onboarding;
 mod batch_onboarding_deals_test;
+mod cancel_deal_test;
 mod change_beneficiary_test;
 mod change_owner_test;
 mod commit_post_test;
+mod cron_test;
 mod datacap_tests;
 mod evm_test;
+// Run with `cargo test -p test_vm --features tracing`.
+#[cfg(feature = "tracing")]
+mod evm_trace_test;
+mod extend_deal_test;
 mod extend_sectors_test;
 mod init_test;
 mod market_miner_withdrawal_test;
+mod multi_key_account_test;
 mod multisig_test;
+mod passkey_account_test;
+mod paych_test;
 mod power_scenario_tests;
 mod prove_commit3_test;
 mod prove_commit_niporep_test;
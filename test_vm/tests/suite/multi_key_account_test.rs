@@ -0,0 +1,10 @@
+use fil_actors_integration_tests::tests::multi_key_account_test;
+use fil_actors_runtime::test_blockstores::MemoryBlockstore;
+use test_vm::TestVM;
+
+#[test]
+fn multi_key_account() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    multi_key_account_test(&v);
+}
@@ -0,0 +1,10 @@
+use fil_actors_integration_tests::tests::paych_multi_hop_htlc_test;
+use fil_actors_runtime::test_blockstores::MemoryBlockstore;
+use test_vm::TestVM;
+
+#[test]
+fn paych_multi_hop_htlc() {
+    let store = MemoryBlockstore::new();
+    let v = TestVM::new_with_singletons(store);
+    paych_multi_hop_htlc_test(&v);
+}
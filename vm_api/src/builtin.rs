@@ -24,6 +24,8 @@ pub enum Type {
     EVM = 14,
     EAM = 15,
     EthAccount = 16,
+    Passkey = 17,
+    MultiKey = 18,
 }
 
 impl Type {
@@ -45,6 +47,8 @@ impl Type {
             Type::EVM => "evm",
             Type::EAM => "eam",
             Type::EthAccount => "ethaccount",
+            Type::Passkey => "passkey",
+            Type::MultiKey => "multikey",
         }
     }
 }
@@ -22,6 +22,7 @@ use fvm_shared::{
         AggregateSealVerifyProofAndInfos, RegisteredSealProof, ReplicaUpdateInfo, SealVerifyInfo,
         WindowPoStVerifyInfo,
     },
+    version::NetworkVersion,
     MethodNum,
 };
 
@@ -96,6 +97,12 @@ pub trait VM {
     /// Sets the epoch to the specified value
     fn set_epoch(&self, epoch: ChainEpoch);
 
+    /// Get the current network version
+    fn network_version(&self) -> NetworkVersion;
+
+    /// Sets the network version to the specified value
+    fn set_network_version(&self, nv: NetworkVersion);
+
     /// Get the circulating supply constant for the network
     fn circulating_supply(&self) -> TokenAmount;
 
@@ -28,6 +28,9 @@ pub struct InvocationTrace {
     pub return_value: ReturnValue,
     pub subinvocations: Vec<InvocationTrace>,
     pub events: Vec<EmittedEvent>,
+    /// Gas charged explicitly by the actor, excluding subinvocations.
+    /// The VM may not account for other gas, such as for state access.
+    pub gas_charged: i64,
 }
 
 /// An expectation for a method invocation trace.